@@ -0,0 +1,118 @@
+//! Step-by-step mining environment for reinforcement learning
+
+use mining_sim::env::{EnvAction, MiningEnv, Observation};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+use crate::miner::{PyMiner, PythonMiner};
+
+/// Mining game played one round at a time against built-in or
+/// Python-defined opponents. See `mining_sim::env::MiningEnv`.
+///
+/// Observations are lists `[attacker, honest, withheld, fork]`, where `fork`
+/// is 0, 1, or 2 for an irrelevant, relevant, or active fork. Actions are
+/// integers: `-1` adopts the longest chain, `0` waits, and a positive number
+/// publishes that many of the oldest withheld blocks.
+#[pyclass(name = "MiningEnv", module = "mining_sim")]
+#[derive(Debug)]
+pub struct PyMiningEnv {
+    env: MiningEnv,
+    rounds: usize,
+}
+
+#[pymethods]
+impl PyMiningEnv {
+    #[new]
+    #[pyo3(signature = (
+        alpha,
+        gamma = 0.0,
+        opponents = vec![],
+        rounds = MiningEnv::DEFAULT_ROUNDS,
+        seed = None,
+    ))]
+    fn new(
+        alpha: f64,
+        gamma: f64,
+        opponents: Vec<Bound<'_, PyAny>>,
+        rounds: usize,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        if rounds == 0 {
+            return Err(PyValueError::new_err(
+                "episodes must last at least one round",
+            ));
+        }
+
+        let mut env = MiningEnv::new(alpha, gamma)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?
+            .rounds(rounds);
+        for miner in opponents.iter() {
+            let miner = match miner.downcast::<PyMiner>() {
+                Ok(miner) => miner.borrow().miner.clone(),
+                Err(_) => Box::new(PythonMiner::new(miner)?),
+            };
+            env = env.add_boxed_opponent(miner);
+        }
+        if let Some(seed) = seed {
+            env = env.seed(seed);
+        }
+
+        Ok(Self { env, rounds })
+    }
+
+    /// ID of the agent, after every opponent.
+    #[getter]
+    fn agent(&self) -> usize {
+        self.env.agent().get()
+    }
+
+    /// Starts a new episode, returning its first observation.
+    #[pyo3(signature = (seed = None))]
+    fn reset(&mut self, seed: Option<u64>) -> Vec<f64> {
+        self.env.reset(seed).to_array().to_vec()
+    }
+
+    /// Applies `action` and simulates one round, returning a tuple
+    /// `(observation, reward, done)`.
+    fn step(&mut self, action: i64) -> PyResult<(Vec<f64>, f64, bool)> {
+        let action = match action {
+            -1 => EnvAction::Adopt,
+            0 => EnvAction::Wait,
+            n if n > 0 => EnvAction::Publish(n as usize),
+            n => {
+                return Err(PyValueError::new_err(format!(
+                    "invalid action {n}"
+                )))
+            }
+        };
+
+        let done = match self.env.observation() {
+            Some(observation) => observation.round >= self.rounds,
+            None => true,
+        };
+        if done {
+            return Err(PyRuntimeError::new_err(
+                "reset() must be called before step()",
+            ));
+        }
+
+        let step = self.env.step(action);
+        Ok((step.observation.to_array().to_vec(), step.reward, step.done))
+    }
+
+    /// Fraction of the blocks on the longest chain mined by the agent.
+    fn revenue(&self) -> f64 {
+        self.env.revenue()
+    }
+
+    fn __repr__(&self) -> String {
+        match self.env.observation() {
+            Some(Observation { round, .. }) => {
+                format!("MiningEnv(round={round})")
+            }
+            None => "MiningEnv()".to_string(),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+/*!
+Python bindings for [`mining_sim`].
+
+Exposes a Python module named `mining_sim`, built with
+[maturin](https://www.maturin.rs):
+
+```text
+pip install maturin
+maturin develop --release -m mining-sim-py/Cargo.toml
+```
+
+## Example
+```python
+import mining_sim
+import pandas as pd
+
+class Honest:
+    name = "Python Honest"
+
+    def get_action(self, chain, miner_id, block_mined):
+        if block_mined is None:
+            return None
+        return (block_mined, chain.tip()[0])
+
+results = (
+    mining_sim.SimulationBuilder()
+    .add_miner(Honest())
+    .add_miner(mining_sim.Miner("ndeficit", {"i": 1}))
+    .miner_power_iter(2, [0.1, 0.2, 0.3])
+    .rounds(1000)
+    .run()
+)
+
+df = pd.DataFrame(results.to_dict())
+```
+
+Mining policies can be trained step by step with `mining_sim.MiningEnv`:
+
+```python
+env = mining_sim.MiningEnv(0.3, gamma=0.5, rounds=1000, seed=1)
+observation, done = env.reset(), False
+while not done:
+    attacker, honest, withheld, fork = observation
+    observation, reward, done = env.step(int(withheld))
+```
+*/
+
+use pyo3::prelude::*;
+
+mod env;
+mod miner;
+mod results;
+mod simulation;
+
+#[pymodule]
+#[pyo3(name = "mining_sim")]
+fn mining_sim_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<env::PyMiningEnv>()?;
+    m.add_class::<miner::Chain>()?;
+    m.add_class::<miner::PyMiner>()?;
+    m.add_class::<results::Results>()?;
+    m.add_class::<simulation::PySimulationBuilder>()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::{ffi::c_str, prelude::*};
+
+    use super::mining_sim_py;
+
+    #[test]
+    fn python_miners_and_results() {
+        pyo3::append_to_inittab!(mining_sim_py);
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            py.run(
+                c_str!(
+                    r#"
+import mining_sim
+
+class Honest:
+    name = "Python Honest"
+
+    def get_action(self, chain, miner_id, block_mined):
+        if block_mined is None:
+            return None
+        return (block_mined, chain.tip()[0])
+
+results = (
+    mining_sim.SimulationBuilder()
+    .add_miner(Honest())
+    .add_miner(mining_sim.Miner("ndeficit", {"i": 1}))
+    .miner_power_iter(2, [0.1, 0.2])
+    .rounds(100)
+    .seed(1)
+    .run()
+)
+
+table = results.to_dict()
+assert table["Miner 1 Strategy"] == ["Python Honest"] * 2, table
+assert table["Miner 2 Strategy"] == ["1-Deficit"] * 2, table
+assert len(results.to_records(average="none")) == 2
+
+try:
+    mining_sim.Miner("ndeficit", {"j": 1})
+    assert False
+except ValueError:
+    pass
+
+env = mining_sim.MiningEnv(
+    0.3, opponents=[Honest(), mining_sim.Miner("honest")], rounds=50, seed=1
+)
+assert env.agent == 3
+observation, done, total = env.reset(), False, 0.0
+while not done:
+    observation, reward, done = env.step(int(observation[2]))
+    total += reward
+assert abs(total - env.revenue()) < 1e-9, (total, env.revenue())
+
+try:
+    env.step(0)
+    assert False
+except RuntimeError:
+    pass
+"#
+                ),
+                None,
+                None,
+            )
+            .unwrap_or_else(|e| panic!("{}", e));
+        });
+    }
+}
@@ -0,0 +1,276 @@
+//! Built-in and Python-defined miners
+
+use std::{collections::HashMap, sync::Arc};
+
+use mining_sim::{
+    blockchain::{Block, BlockId, Blockchain, ChainQuery},
+    miner::{registry, Action, Miner, MinerId},
+    view::ChainView,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+/// A built-in miner, or a user strategy registered with the `mining_sim`
+/// strategy registry, created from its name and parameters.
+#[pyclass(name = "Miner", module = "mining_sim")]
+#[derive(Debug, Clone)]
+pub struct PyMiner {
+    pub(crate) miner: Box<dyn Miner>,
+}
+
+#[pymethods]
+impl PyMiner {
+    #[new]
+    #[pyo3(signature = (strategy, params = None))]
+    fn new(
+        strategy: &str,
+        params: Option<HashMap<String, f64>>,
+    ) -> PyResult<Self> {
+        let params = params.unwrap_or_default();
+        let params = params.iter().map(|(k, v)| (k.as_str(), *v));
+        let miner = registry::create(strategy, params)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        Ok(Self { miner })
+    }
+
+    /// Names of the strategies which can be passed to `Miner()`.
+    #[staticmethod]
+    fn strategies() -> Vec<String> {
+        registry::names()
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.miner.name()
+    }
+
+    #[getter]
+    fn params(&self) -> Vec<(String, f64)> {
+        self.miner.params()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Miner({:?})", self.miner.name())
+    }
+}
+
+/// A miner's view of the blockchain, passed to the `get_action` method of
+/// Python-defined miners. Block and miner IDs are integers.
+#[pyclass(name = "Chain", module = "mining_sim", frozen)]
+#[derive(Debug, Clone, Default)]
+pub struct Chain(Arc<Blockchain>);
+
+impl Chain {
+    /// Copies the blocks of `chain` which are not yet in this chain.
+    fn sync(&mut self, chain: &dyn ChainQuery) {
+        // This chain only holds blocks of `chain`, so it is up to date
+        // whenever both have the same number of blocks
+        if self.0.num_blocks() != chain.num_blocks() {
+            // Copy-on-write if Python kept a copy of the chain
+            Arc::make_mut(&mut self.0)
+                .extend_from(chain)
+                .expect("blocks seen by a miner can be mirrored");
+        }
+    }
+
+    fn get(&self, id: usize) -> PyResult<BlockId> {
+        let block_id = BlockId::from(id);
+        match self.0.contains(block_id) {
+            true => Ok(block_id),
+            false => {
+                Err(PyValueError::new_err(format!("no block with ID {id}")))
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl Chain {
+    /// IDs of the blocks at the tip of the longest chain.
+    fn tip(&self) -> Vec<usize> {
+        self.0.tip().iter().map(BlockId::get).collect()
+    }
+
+    /// Height of the longest chain.
+    fn max_height(&self) -> usize {
+        self.0.max_height()
+    }
+
+    /// ID of the genesis block.
+    fn genesis(&self) -> usize {
+        self.0.genesis().get()
+    }
+
+    /// Number of blocks, including the genesis block.
+    fn num_blocks(&self) -> usize {
+        self.0.num_blocks()
+    }
+
+    fn contains(&self, id: usize) -> bool {
+        self.0.contains(BlockId::from(id))
+    }
+
+    /// IDs of the blocks at the given height.
+    fn at_height(&self, height: usize) -> Vec<usize> {
+        let ids = self.0.at_height(height).unwrap_or_default();
+        ids.iter().map(BlockId::get).collect()
+    }
+
+    fn height(&self, id: usize) -> PyResult<usize> {
+        Ok(self.0[self.get(id)?].height)
+    }
+
+    /// ID of the block's parent, or `None` for the genesis block.
+    fn parent(&self, id: usize) -> PyResult<Option<usize>> {
+        Ok(self.0.get_parent(self.get(id)?).map(|parent| parent.get()))
+    }
+
+    /// ID of the miner which published the block.
+    fn miner(&self, id: usize) -> PyResult<usize> {
+        Ok(self.0[self.get(id)?].block.miner_id.get())
+    }
+
+    /// IDs of the blocks from the given block to the genesis block.
+    fn ancestors(&self, id: usize) -> PyResult<Vec<usize>> {
+        let ancestors = self.0.ancestors_of(self.get(id)?);
+        Ok(ancestors.map(|block_id| block_id.get()).collect())
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.num_blocks()
+    }
+}
+
+/// Miner whose strategy is defined by a Python object.
+///
+/// Each round, calls `object.get_action(chain, miner_id, block_mined)`, where
+/// `block_mined` is `None` unless the miner was selected as the proposer. The
+/// method returns `None` to wait, a `(block_id, parent_id)` tuple to publish a
+/// block, or a list of such tuples to publish a set of blocks in order.
+///
+/// Every copy of the miner shares the same Python object, so strategies which
+/// keep state should reset it in an optional `on_simulation_start()` method.
+#[derive(Debug, Clone)]
+pub(crate) struct PythonMiner {
+    chain: Chain,
+    id: MinerId,
+    name: String,
+    object: Arc<Py<PyAny>>,
+}
+
+impl PythonMiner {
+    /// Wraps `object`, which must have a `get_action` method. The miner is
+    /// named by the object's `name` attribute, if any, and otherwise by its
+    /// class name.
+    pub(crate) fn new(object: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if !object.hasattr("get_action")? {
+            return Err(PyValueError::new_err(
+                "miners must be a mining_sim.Miner or define \
+                 get_action(chain, miner_id, block_mined)",
+            ));
+        }
+
+        let name = match object.getattr("name") {
+            Ok(name) => name.str()?.to_string(),
+            Err(_) => object.get_type().name()?.to_string(),
+        };
+
+        Ok(Self {
+            chain: Chain::default(),
+            id: MinerId::default(),
+            name,
+            object: Arc::new(object.clone().unbind()),
+        })
+    }
+
+    fn to_block(&self, (id, parent): (usize, usize)) -> Block {
+        Block {
+            id: BlockId::from(id),
+            parent_id: Some(BlockId::from(parent)),
+            miner_id: self.id,
+            txns: vec![],
+        }
+    }
+
+    fn call_get_action(
+        &self,
+        py: Python<'_>,
+        block_mined: Option<BlockId>,
+    ) -> PyResult<Action> {
+        let args = (
+            self.chain.clone(),
+            self.id.get(),
+            block_mined.map(|block_id| block_id.get()),
+        );
+        let value = self.object.bind(py).call_method1("get_action", args)?;
+
+        if value.is_none() {
+            Ok(Action::Wait)
+        } else if let Ok(block) = value.extract::<(usize, usize)>() {
+            Ok(Action::Publish(self.to_block(block)))
+        } else {
+            let blocks: Vec<(usize, usize)> = value.extract()?;
+            let blocks = blocks.into_iter().map(|b| self.to_block(b));
+
+            Ok(Action::PublishSet(blocks.collect()))
+        }
+    }
+}
+
+impl Miner for PythonMiner {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn id(&self) -> MinerId {
+        self.id
+    }
+
+    fn set_id(&mut self, id: MinerId) {
+        self.id = id;
+    }
+
+    fn get_action(
+        &mut self,
+        chain: &ChainView,
+        block_mined: Option<BlockId>,
+    ) -> Action {
+        self.chain.sync(chain);
+
+        Python::with_gil(|py| {
+            self.call_get_action(py, block_mined).unwrap_or_else(|e| {
+                e.display(py);
+                panic!("get_action of Python miner {} failed: {}", self.name, e)
+            })
+        })
+    }
+
+    fn on_simulation_start(&mut self) {
+        self.chain = Chain::default();
+
+        Python::with_gil(|py| {
+            let object = self.object.bind(py);
+            let result = match object.hasattr("on_simulation_start") {
+                Ok(true) => {
+                    object.call_method0("on_simulation_start").map(drop)
+                }
+                Ok(false) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                e.display(py);
+                panic!(
+                    "on_simulation_start of Python miner {} failed",
+                    self.name
+                )
+            }
+        })
+    }
+
+    // Python objects are shared between copies, so cloning resets nothing
+    fn reset(&mut self) -> bool {
+        self.chain = Chain::default();
+
+        true
+    }
+}
@@ -0,0 +1,111 @@
+//! Simulation results as pandas-friendly Python structures
+
+use mining_sim::results::{Average, CellValue, ResultsBuilder, ResultsTable};
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyDict, PyList},
+};
+
+/// Results of a set of simulations. Every column is included, averaged over
+/// repeated simulations with the given method: `"mean"` (the default),
+/// `"median"`, `"min"`, `"max"`, `"stddev"`, or `"none"` for one row per
+/// simulation.
+#[pyclass(name = "Results", module = "mining_sim", frozen)]
+#[derive(Debug, Clone)]
+pub struct Results(ResultsBuilder);
+
+impl From<ResultsBuilder> for Results {
+    fn from(builder: ResultsBuilder) -> Self {
+        Self(builder)
+    }
+}
+
+impl Results {
+    fn table(&self, average: &str) -> PyResult<ResultsTable> {
+        let average = match average {
+            "mean" => Average::Mean,
+            "median" => Average::Median,
+            "min" => Average::Min,
+            "max" => Average::Max,
+            "stddev" => Average::StdDev,
+            "none" => Average::None,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown average {average:?}"
+                )))
+            }
+        };
+
+        Ok(self.0.clone().all().average(average).build())
+    }
+}
+
+fn cell_to_py(py: Python<'_>, cell: CellValue) -> PyResult<PyObject> {
+    let value = match cell {
+        CellValue::Text(text) => text.into_pyobject(py)?.into_any(),
+        CellValue::Integer(num) => num.into_pyobject(py)?.into_any(),
+        CellValue::Float(num) => num.into_pyobject(py)?.into_any(),
+        CellValue::Missing => py.None().into_bound(py),
+    };
+
+    Ok(value.unbind())
+}
+
+#[pymethods]
+impl Results {
+    /// Returns a dict mapping each column title to its list of values, which
+    /// can be passed directly to `pandas.DataFrame`.
+    #[pyo3(signature = (average = "mean"))]
+    fn to_dict<'py>(
+        &self,
+        py: Python<'py>,
+        average: &str,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let table = self.table(average)?;
+        let rows: Vec<_> = table.rows().collect();
+        let dict = PyDict::new(py);
+
+        for (i, title) in table.titles().enumerate() {
+            let column = rows
+                .iter()
+                .map(|row| cell_to_py(py, row[i].1.clone()))
+                .collect::<PyResult<Vec<_>>>()?;
+            dict.set_item(title, PyList::new(py, column)?)?;
+        }
+
+        Ok(dict)
+    }
+
+    /// Returns a list with one dict per row, mapping each column title to the
+    /// row's value.
+    #[pyo3(signature = (average = "mean"))]
+    fn to_records<'py>(
+        &self,
+        py: Python<'py>,
+        average: &str,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let table = self.table(average)?;
+        let titles: Vec<_> = table.titles().collect();
+        let records = PyList::empty(py);
+
+        for row in table.rows() {
+            let record = PyDict::new(py);
+            for (title, (_, cell)) in titles.iter().zip(row) {
+                record.set_item(title, cell_to_py(py, cell)?)?;
+            }
+            records.append(record)?;
+        }
+
+        Ok(records)
+    }
+
+    /// Number of distinct simulation configurations.
+    fn num_configs(&self) -> usize {
+        self.0.num_configs()
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        Ok(self.table("mean")?.to_string())
+    }
+}
@@ -0,0 +1,159 @@
+//! Configuring and running simulations
+
+use mining_sim::{miner::MinerId, simulation::SimulationBuilder};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+use crate::{
+    miner::{PyMiner, PythonMiner},
+    results::Results,
+};
+
+/// Builds up a set of simulations. Each method returns the builder, so calls
+/// can be chained. See `mining_sim::simulation::SimulationBuilder`.
+#[pyclass(name = "SimulationBuilder", module = "mining_sim")]
+#[derive(Debug, Default)]
+pub struct PySimulationBuilder {
+    /// Taken when the simulations are run.
+    builder: Option<SimulationBuilder>,
+}
+
+impl PySimulationBuilder {
+    fn update<F>(
+        mut slf: PyRefMut<'_, Self>,
+        f: F,
+    ) -> PyResult<PyRefMut<'_, Self>>
+    where
+        F: FnOnce(SimulationBuilder) -> SimulationBuilder,
+    {
+        let builder = slf.builder.take().ok_or_else(already_run)?;
+        slf.builder = Some(f(builder));
+
+        Ok(slf)
+    }
+}
+
+fn already_run() -> PyErr {
+    PyRuntimeError::new_err("simulations were already run by this builder")
+}
+
+#[pymethods]
+impl PySimulationBuilder {
+    #[new]
+    fn new() -> Self {
+        Self {
+            builder: Some(SimulationBuilder::new()),
+        }
+    }
+
+    /// Adds a `mining_sim.Miner`, or a Python object defining
+    /// `get_action(chain, miner_id, block_mined)`.
+    fn add_miner<'py>(
+        slf: PyRefMut<'py, Self>,
+        miner: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let miner = match miner.downcast::<PyMiner>() {
+            Ok(miner) => miner.borrow().miner.clone(),
+            Err(_) => Box::new(PythonMiner::new(miner)?),
+        };
+
+        Self::update(slf, |b| b.add_boxed_miner(miner))
+    }
+
+    fn next_lineup(slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.next_lineup())
+    }
+
+    fn rounds(
+        slf: PyRefMut<'_, Self>,
+        rounds: usize,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.rounds(rounds))
+    }
+
+    fn repeat_all(
+        slf: PyRefMut<'_, Self>,
+        num: usize,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.repeat_all(num))
+    }
+
+    fn seed(
+        slf: PyRefMut<'_, Self>,
+        seed: u64,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.seed(seed))
+    }
+
+    fn gamma(
+        slf: PyRefMut<'_, Self>,
+        gamma: f64,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.gamma(gamma))
+    }
+
+    fn block_subsidy(
+        slf: PyRefMut<'_, Self>,
+        subsidy: f64,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.block_subsidy(subsidy))
+    }
+
+    fn target_chain_length(
+        slf: PyRefMut<'_, Self>,
+        length: usize,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.target_chain_length(length))
+    }
+
+    fn equal_power(slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.equal_power())
+    }
+
+    fn power_values(
+        slf: PyRefMut<'_, Self>,
+        values: Vec<f64>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.power_values(values))
+    }
+
+    fn miner_power(
+        slf: PyRefMut<'_, Self>,
+        miner_id: usize,
+        value: f64,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.miner_power(MinerId::from(miner_id), value))
+    }
+
+    fn miner_power_iter(
+        slf: PyRefMut<'_, Self>,
+        miner_id: usize,
+        values: Vec<f64>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| {
+            b.miner_power_iter(MinerId::from(miner_id), values)
+        })
+    }
+
+    /// Runs every configured simulation, in parallel unless `sequential` is
+    /// true. Can only be called once per builder.
+    #[pyo3(signature = (sequential = false))]
+    fn run(&mut self, py: Python<'_>, sequential: bool) -> PyResult<Results> {
+        let builder = self.builder.take().ok_or_else(already_run)?;
+        let group = builder
+            .build()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        // Python-defined miners acquire the GIL from simulation threads
+        let results = py.allow_threads(|| match sequential {
+            true => group.run_all_sequential(),
+            false => group.run_all(),
+        });
+
+        results
+            .map(Results::from)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+}
@@ -0,0 +1,253 @@
+//! Experiment configurations accepted by the server
+
+use std::collections::BTreeMap;
+
+use mining_sim::{
+    miner::{registry, registry::RegistryError, MinerId},
+    results::{Average, ResultsBuilder, ResultsTable},
+    simulation::{SimulationBuildError, SimulationBuilder},
+};
+use serde::Deserialize;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("experiment has no miners")]
+    NoMiners,
+    #[error("{0}")]
+    Registry(#[from] RegistryError),
+    #[error("could not build simulations: {0}")]
+    Build(#[from] SimulationBuildError),
+}
+
+/// A set of simulations submitted as JSON, and the columns of the results
+/// table reported for them.
+///
+/// ## Example
+/// ```json
+/// {
+///     "miners": [
+///         { "strategy": "honest" },
+///         { "strategy": "ndeficit", "params": { "i": 2 } }
+///     ],
+///     "attacker": 2,
+///     "alpha": [0.1, 0.2, 0.3, 0.4],
+///     "gamma": 0.5,
+///     "rounds": 100000,
+///     "repeats": 10,
+///     "seed": 1,
+///     "columns": ["revenue", "reorg_count"]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentConfig {
+    /// Miners with IDs starting at 1, in order.
+    pub miners: Vec<MinerConfig>,
+    /// ID of the miner whose mining power is set to each value of `alpha`.
+    /// Defaults to the last miner.
+    #[serde(default)]
+    pub attacker: Option<usize>,
+    /// Mining powers of the attacker, each simulated as a separate
+    /// configuration. The remaining power is split equally between the other
+    /// miners.
+    #[serde(default)]
+    pub alpha: Vec<f64>,
+    /// Power values of every miner, each simulated as a separate
+    /// configuration. Used in addition to `alpha`. If both are empty, every
+    /// miner has equal power.
+    #[serde(default)]
+    pub power_values: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub gamma: Option<f64>,
+    #[serde(default = "default_rounds")]
+    pub rounds: usize,
+    #[serde(default = "default_repeats")]
+    pub repeats: usize,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Columns of the results table. Defaults to [`Column::All`].
+    #[serde(default = "default_columns")]
+    pub columns: Vec<Column>,
+    /// Averages of the repeats of each configuration reported in the results
+    /// table. If empty, every repeat is reported.
+    #[serde(default)]
+    pub averages: Vec<AverageKind>,
+}
+
+fn default_rounds() -> usize {
+    10_000
+}
+
+fn default_repeats() -> usize {
+    1
+}
+
+fn default_columns() -> Vec<Column> {
+    vec![Column::All]
+}
+
+/// A miner created by [`registry::create`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MinerConfig {
+    /// Name of a registered strategy, such as `"selfish"`.
+    pub strategy: String,
+    #[serde(default)]
+    pub params: BTreeMap<String, f64>,
+}
+
+/// A column of the results table, named after the [`ResultsBuilder`]
+/// method which adds it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    /// See [`ResultsBuilder::all`].
+    All,
+    BlocksMined,
+    BlocksOnChain,
+    BlocksPublished,
+    Forks,
+    Gini,
+    Income,
+    LongestChainLength,
+    MaxReorgDepth,
+    ReorgCount,
+    Revenue,
+    Rounds,
+    StrategyNames,
+    StrategyParams,
+}
+
+/// An [`Average`] which takes no parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AverageKind {
+    Mean,
+    Median,
+    Max,
+    Min,
+    StdDev,
+}
+
+impl From<AverageKind> for Average {
+    fn from(kind: AverageKind) -> Self {
+        match kind {
+            AverageKind::Mean => Average::Mean,
+            AverageKind::Median => Average::Median,
+            AverageKind::Max => Average::Max,
+            AverageKind::Min => Average::Min,
+            AverageKind::StdDev => Average::StdDev,
+        }
+    }
+}
+
+impl ExperimentConfig {
+    /// Returns a builder for the configured simulations.
+    pub fn builder(&self) -> Result<SimulationBuilder, ConfigError> {
+        if self.miners.is_empty() {
+            return Err(ConfigError::NoMiners);
+        }
+
+        let mut builder = SimulationBuilder::new();
+        for miner in self.miners.iter() {
+            let params = miner.params.iter().map(|(k, v)| (k.as_str(), *v));
+            builder = builder
+                .add_boxed_miner(registry::create(&miner.strategy, params)?);
+        }
+
+        if !self.alpha.is_empty() {
+            let attacker = self.attacker.unwrap_or(self.miners.len());
+            builder = builder
+                .miner_power_iter(MinerId::from(attacker), self.alpha.clone());
+        }
+        for values in self.power_values.iter() {
+            builder = builder.power_values(values.clone());
+        }
+        if let Some(gamma) = self.gamma {
+            builder = builder.gamma(gamma);
+        }
+        if let Some(seed) = self.seed {
+            builder = builder.seed(seed);
+        }
+
+        Ok(builder.rounds(self.rounds).repeat_all(self.repeats))
+    }
+
+    /// Builds the configured results table from `results`.
+    pub fn table(&self, mut results: ResultsBuilder) -> ResultsTable {
+        if !self.averages.is_empty() {
+            results = results.averages(self.averages.iter().map(|&a| a.into()));
+        }
+
+        self.columns
+            .iter()
+            .fold(results, |results, column| match column {
+                Column::All => results.all(),
+                Column::BlocksMined => results.blocks_mined(),
+                Column::BlocksOnChain => results.blocks_on_chain(),
+                Column::BlocksPublished => results.blocks_published(),
+                Column::Forks => results.forks(),
+                Column::Gini => results.gini(),
+                Column::Income => results.income(),
+                Column::LongestChainLength => results.longest_chain_length(),
+                Column::MaxReorgDepth => results.max_reorg_depth(),
+                Column::ReorgCount => results.reorg_count(),
+                Column::Revenue => results.revenue(),
+                Column::Rounds => results.rounds(),
+                Column::StrategyNames => results.strategy_names(),
+                Column::StrategyParams => results.strategy_params(),
+            })
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Column, ConfigError, ExperimentConfig};
+
+    #[test]
+    fn parse_and_build() {
+        let config: ExperimentConfig = serde_json::from_str(
+            r#"{
+                "miners": [
+                    { "strategy": "honest" },
+                    { "strategy": "ndeficit", "params": { "i": 2 } }
+                ],
+                "alpha": [0.2, 0.3],
+                "rounds": 500,
+                "repeats": 2,
+                "seed": 1,
+                "columns": ["revenue", "reorg_count"],
+                "averages": ["mean", "std_dev"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.columns, [Column::Revenue, Column::ReorgCount]);
+
+        let group = config.builder().unwrap().build().unwrap();
+        assert_eq!((group.num_configs(), group.repeats()), (2, 2));
+
+        let table = config.table(group.run_all().unwrap());
+        assert_eq!(table.rows().count(), 2);
+        assert!(table.titles().any(|title| title.contains("Revenue")));
+    }
+
+    #[test]
+    fn bad_configs() {
+        let parse =
+            |json| serde_json::from_str::<ExperimentConfig>(json).unwrap();
+
+        assert!(matches!(
+            parse(r#"{ "miners": [] }"#).builder(),
+            Err(ConfigError::NoMiners)
+        ));
+        assert!(matches!(
+            parse(r#"{ "miners": [{ "strategy": "lazy" }] }"#).builder(),
+            Err(ConfigError::Registry(_))
+        ));
+        assert!(serde_json::from_str::<ExperimentConfig>(
+            r#"{ "miners": [], "round": 10 }"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,382 @@
+//! Queue of experiments run asynchronously by worker threads
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Condvar, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use mining_sim::simulation::{events::SimulationEvent, SimulationGroup};
+use serde::Serialize;
+
+use crate::config::{ConfigError, ExperimentConfig};
+
+/// Identifies a job submitted to a [`JobQueue`]. IDs start at 1.
+pub type JobId = usize;
+
+/// Stage of a job's lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// Waiting for a free worker.
+    Queued,
+    Running,
+    Finished,
+    /// The simulations returned an error, given in [`JobStatus::error`].
+    Failed,
+    /// Cancelled before finishing. Results are kept for the configurations
+    /// which finished before the job was cancelled.
+    Cancelled,
+}
+
+impl JobState {
+    /// Returns true if the job will not change state again.
+    pub fn is_done(self) -> bool {
+        !matches!(self, Self::Queued | Self::Running)
+    }
+}
+
+/// Snapshot of a job's progress.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobStatus {
+    pub id: JobId,
+    pub state: JobState,
+    /// Number of simulations which have finished.
+    pub finished: usize,
+    /// Total number of simulations in the job.
+    pub total: usize,
+    /// Seconds spent running the job, so far.
+    pub elapsed: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A submitted experiment and its outcome.
+struct Job {
+    id: JobId,
+    config: ExperimentConfig,
+    cancel: AtomicBool,
+    finished: Arc<AtomicUsize>,
+    total: usize,
+    /// Taken by the worker which runs the job.
+    group: Mutex<Option<SimulationGroup>>,
+    outcome: Mutex<Outcome>,
+    /// Notified when the job is done.
+    done: Condvar,
+}
+
+#[derive(Debug)]
+struct Outcome {
+    state: JobState,
+    started: Option<Instant>,
+    elapsed: Duration,
+    error: Option<String>,
+    results: Option<serde_json::Value>,
+}
+
+impl Job {
+    fn status(&self) -> JobStatus {
+        let outcome = self.outcome.lock().unwrap();
+        let elapsed = match (outcome.state, outcome.started) {
+            (JobState::Running, Some(started)) => started.elapsed(),
+            _ => outcome.elapsed,
+        };
+
+        JobStatus {
+            id: self.id,
+            state: outcome.state,
+            finished: self.finished.load(Ordering::Relaxed),
+            total: self.total,
+            elapsed: elapsed.as_secs_f64(),
+            error: outcome.error.clone(),
+        }
+    }
+
+    /// Runs the job's simulations on the calling thread, unless the job was
+    /// cancelled while queued.
+    fn run(&self) {
+        let group = {
+            let mut outcome = self.outcome.lock().unwrap();
+            if outcome.state != JobState::Queued {
+                return;
+            }
+            outcome.state = JobState::Running;
+            outcome.started = Some(Instant::now());
+
+            self.group.lock().unwrap().take()
+        };
+        let Some(group) = group else {
+            return;
+        };
+
+        let result = group.run_all_cancellable(&self.cancel);
+
+        let mut outcome = self.outcome.lock().unwrap();
+        outcome.elapsed =
+            outcome.started.map_or(Duration::ZERO, |s| s.elapsed());
+        match result {
+            Ok(results) => {
+                let table = self.config.table(results);
+                outcome.results = Some(
+                    serde_json::to_value(&table)
+                        .expect("results tables serialize to JSON"),
+                );
+                outcome.state = match self.cancel.load(Ordering::Relaxed) {
+                    true => JobState::Cancelled,
+                    false => JobState::Finished,
+                };
+            }
+            Err(e) => {
+                outcome.error = Some(e.to_string());
+                outcome.state = JobState::Failed;
+            }
+        }
+        self.done.notify_all();
+    }
+
+    fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+
+        let mut outcome = self.outcome.lock().unwrap();
+        if outcome.state == JobState::Queued {
+            outcome.state = JobState::Cancelled;
+            self.group.lock().unwrap().take();
+            self.done.notify_all();
+        }
+    }
+
+    fn wait(&self) -> JobStatus {
+        let mut outcome = self.outcome.lock().unwrap();
+        while !outcome.state.is_done() {
+            outcome = self.done.wait(outcome).unwrap();
+        }
+        drop(outcome);
+
+        self.status()
+    }
+}
+
+/// Runs submitted experiments in the order they were submitted, on a fixed
+/// number of worker threads. Each job runs its simulations in parallel as
+/// usual, so one worker is enough to keep every core busy.
+///
+/// Dropping the queue cancels every job, and waits for the workers to stop.
+pub struct JobQueue {
+    jobs: Mutex<BTreeMap<JobId, Arc<Job>>>,
+    next_id: AtomicUsize,
+    sender: Option<Sender<Arc<Job>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for JobQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobQueue")
+            .field("jobs", &self.jobs.lock().unwrap().len())
+            .field("workers", &self.workers.len())
+            .finish()
+    }
+}
+
+impl JobQueue {
+    /// Creates a queue served by `workers` threads.
+    ///
+    /// # Panics
+    /// Panics if `workers` is 0.
+    pub fn new(workers: usize) -> Self {
+        assert!(workers > 0, "job queue needs at least one worker");
+
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..workers)
+            .map(|_| {
+                let receiver = receiver.clone();
+                std::thread::spawn(move || work(&receiver))
+            })
+            .collect();
+
+        Self {
+            jobs: Mutex::default(),
+            next_id: AtomicUsize::new(1),
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    /// Validates `config` and queues its simulations, returning the status
+    /// of the new job.
+    pub fn submit(
+        &self,
+        config: ExperimentConfig,
+    ) -> Result<JobStatus, ConfigError> {
+        let finished = Arc::new(AtomicUsize::new(0));
+        let counter = finished.clone();
+        let group = config
+            .builder()?
+            .subscribe(move |_, event: &SimulationEvent| {
+                if let SimulationEvent::SimulationFinished { .. } = event {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .build()?;
+
+        let job = Arc::new(Job {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            config,
+            cancel: AtomicBool::new(false),
+            finished,
+            total: group.num_configs() * group.repeats(),
+            group: Mutex::new(Some(group)),
+            outcome: Mutex::new(Outcome {
+                state: JobState::Queued,
+                started: None,
+                elapsed: Duration::ZERO,
+                error: None,
+                results: None,
+            }),
+            done: Condvar::new(),
+        });
+
+        self.jobs.lock().unwrap().insert(job.id, job.clone());
+        let status = job.status();
+        self.sender
+            .as_ref()
+            .expect("sender is present until the queue is dropped")
+            .send(job)
+            .expect("workers run until the queue is dropped");
+
+        Ok(status)
+    }
+
+    /// Returns the status of the job with ID `id`, if it exists.
+    pub fn status(&self, id: JobId) -> Option<JobStatus> {
+        self.get(id).map(|job| job.status())
+    }
+
+    /// Returns the status of every job, in the order they were submitted.
+    pub fn statuses(&self) -> Vec<JobStatus> {
+        let jobs: Vec<_> =
+            self.jobs.lock().unwrap().values().cloned().collect();
+
+        jobs.iter().map(|job| job.status()).collect()
+    }
+
+    /// Returns the results table of the job with ID `id` as JSON, once it
+    /// has finished or been cancelled. See the `Serialize` implementation of
+    /// [`ResultsTable`](mining_sim::results::ResultsTable).
+    pub fn results(&self, id: JobId) -> Option<serde_json::Value> {
+        let job = self.get(id)?;
+        let outcome = job.outcome.lock().unwrap();
+
+        outcome.results.clone()
+    }
+
+    /// Blocks until the job with ID `id` is done, and returns its final
+    /// status, if it exists.
+    pub fn wait(&self, id: JobId) -> Option<JobStatus> {
+        self.get(id).map(|job| job.wait())
+    }
+
+    /// Cancels the job with ID `id`, returning its status, if it exists.
+    /// Queued jobs will not be run, and running jobs stop early.
+    pub fn cancel(&self, id: JobId) -> Option<JobStatus> {
+        let job = self.get(id)?;
+        job.cancel();
+
+        Some(job.status())
+    }
+
+    /// Cancels the job with ID `id` and forgets it, returning its last
+    /// status, if it exists.
+    pub fn remove(&self, id: JobId) -> Option<JobStatus> {
+        let job = self.jobs.lock().unwrap().remove(&id)?;
+        job.cancel();
+
+        Some(job.status())
+    }
+
+    fn get(&self, id: JobId) -> Option<Arc<Job>> {
+        self.jobs.lock().unwrap().get(&id).cloned()
+    }
+}
+
+impl Drop for JobQueue {
+    fn drop(&mut self) {
+        for job in self.jobs.lock().unwrap().values() {
+            job.cancel();
+        }
+
+        // Workers stop once the channel is closed and empty
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Runs jobs from `receiver` until the queue is dropped.
+fn work(receiver: &Mutex<Receiver<Arc<Job>>>) {
+    loop {
+        let job = match receiver.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+
+        job.run();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JobQueue, JobState};
+    use crate::config::ExperimentConfig;
+
+    fn config(rounds: usize, repeats: usize) -> ExperimentConfig {
+        serde_json::from_value(serde_json::json!({
+            "miners": [{ "strategy": "honest" }, { "strategy": "selfish" }],
+            "alpha": [0.2, 0.4],
+            "rounds": rounds,
+            "repeats": repeats,
+            "columns": ["revenue"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn jobs_run_in_background() {
+        let queue = JobQueue::new(1);
+        let first = queue.submit(config(1000, 3)).unwrap();
+        let second = queue.submit(config(1000, 1)).unwrap();
+        assert_eq!((first.id, second.id), (1, 2));
+        assert_eq!((first.total, second.total), (6, 2));
+
+        let status = queue.wait(first.id).unwrap();
+        assert_eq!(status.state, JobState::Finished);
+        assert_eq!(status.finished, 6);
+        assert_eq!(queue.wait(second.id).unwrap().state, JobState::Finished);
+
+        let results = queue.results(first.id).unwrap();
+        assert_eq!(results["rows"].as_array().unwrap().len(), 6);
+        assert_eq!(queue.statuses().len(), 2);
+
+        assert!(queue.remove(first.id).is_some());
+        assert!(queue.status(first.id).is_none());
+    }
+
+    #[test]
+    fn jobs_can_be_cancelled() {
+        let queue = JobQueue::new(1);
+        let long = queue.submit(config(10_000_000, 100)).unwrap();
+        let queued = queue.submit(config(1000, 1)).unwrap();
+
+        assert_eq!(queue.cancel(queued.id).unwrap().state, JobState::Cancelled);
+        queue.cancel(long.id);
+        assert_eq!(queue.wait(long.id).unwrap().state, JobState::Cancelled);
+        assert_eq!(queue.wait(queued.id).unwrap().finished, 0);
+        assert!(queue.results(queued.id).is_none());
+    }
+}
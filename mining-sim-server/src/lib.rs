@@ -0,0 +1,48 @@
+/*!
+HTTP job server for [`mining_sim`] experiments.
+
+Accepts experiments described as JSON by [`ExperimentConfig`], runs them in
+the background, and serves their progress and results, so that a machine with
+many cores can run simulations submitted from elsewhere. Start the server
+with
+
+```text
+cargo run --release -p mining-sim-server -- 0.0.0.0:8080 --workers 1
+```
+
+and submit an experiment with any HTTP client:
+
+```text
+curl -X POST localhost:8080/jobs -d '{
+    "miners": [{ "strategy": "honest" }, { "strategy": "selfish" }],
+    "alpha": [0.1, 0.2, 0.3, 0.4],
+    "gamma": 0.5,
+    "rounds": 100000,
+    "repeats": 10
+}'
+```
+
+## Endpoints
+Every response body is JSON. Errors are given as `{ "error": message }`.
+
+| Request                   | Response                                           |
+|---------------------------|----------------------------------------------------|
+| `GET /strategies`         | Names accepted as a miner's `strategy`             |
+| `POST /jobs`              | `202` and the new job's [`JobStatus`]               |
+| `GET /jobs`               | The [`JobStatus`] of every job                     |
+| `GET /jobs/{id}`          | The job's [`JobStatus`], including its progress    |
+| `GET /jobs/{id}/results`  | The job's results table, or `409` until it is done |
+| `POST /jobs/{id}/cancel`  | Cancels the job, and returns its [`JobStatus`]     |
+| `DELETE /jobs/{id}`       | Cancels and forgets the job                        |
+
+Results tables are serialized as described by the `Serialize` implementation
+of [`ResultsTable`](mining_sim::results::ResultsTable).
+*/
+
+pub mod config;
+pub mod jobs;
+pub mod server;
+
+pub use config::{ExperimentConfig, MinerConfig};
+pub use jobs::{JobQueue, JobState, JobStatus};
+pub use server::Server;
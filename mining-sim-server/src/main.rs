@@ -0,0 +1,41 @@
+//! Runs a [`mining_sim_server::Server`].
+//!
+//! Usage: `mining-sim-server [ADDRESS] [--workers N]`. Listens on
+//! `127.0.0.1:8080` with one worker by default.
+
+use std::process::ExitCode;
+
+use mining_sim_server::Server;
+
+fn main() -> ExitCode {
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut workers = 1;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workers" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) if n > 0 => workers = n,
+                _ => return usage(),
+            },
+            "-h" | "--help" => return usage(),
+            _ => addr = arg,
+        }
+    }
+
+    let server = Server::new(workers);
+    eprintln!("Listening on {}", addr);
+    match server.serve(&addr) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!("Usage: mining-sim-server [ADDRESS] [--workers N]");
+
+    ExitCode::FAILURE
+}
@@ -0,0 +1,232 @@
+//! HTTP interface to a [`JobQueue`]
+
+use std::net::ToSocketAddrs;
+
+use mining_sim::miner::registry;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request};
+
+use crate::{
+    config::ExperimentConfig,
+    jobs::{JobId, JobQueue},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ServerError {
+    #[error("could not start server: {0}")]
+    Bind(Box<dyn std::error::Error + Send + Sync>),
+    #[error("could not respond to request")]
+    Io(#[from] std::io::Error),
+}
+
+/// A JSON response to a request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error<T: ToString>(status: u16, message: T) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.to_string() }),
+        }
+    }
+}
+
+/// Serves the endpoints listed in the [crate docs](crate).
+#[derive(Debug)]
+pub struct Server {
+    queue: JobQueue,
+}
+
+impl Server {
+    /// Creates a server whose jobs are run by `workers` threads. See
+    /// [`JobQueue::new`].
+    pub fn new(workers: usize) -> Self {
+        Self {
+            queue: JobQueue::new(workers),
+        }
+    }
+
+    /// Returns the queue of jobs submitted to this server.
+    pub fn queue(&self) -> &JobQueue {
+        &self.queue
+    }
+
+    /// Listens for requests on `addr`, responding to each in turn, until an
+    /// I/O error occurs.
+    pub fn serve<A: ToSocketAddrs>(&self, addr: A) -> Result<(), ServerError> {
+        let server =
+            tiny_http::Server::http(addr).map_err(ServerError::Bind)?;
+
+        for request in server.incoming_requests() {
+            self.respond(request)?;
+        }
+
+        Ok(())
+    }
+
+    fn respond(&self, mut request: Request) -> Result<(), ServerError> {
+        let mut body = String::new();
+        let response = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => self.handle(request.method(), request.url(), &body),
+            Err(e) => Response::error(400, e),
+        };
+
+        let header = Header::from_bytes("Content-Type", "application/json")
+            .expect("header is valid");
+        request.respond(
+            tiny_http::Response::from_string(response.body.to_string())
+                .with_status_code(response.status)
+                .with_header(header),
+        )?;
+
+        Ok(())
+    }
+
+    /// Responds to a request for `url` with the given method and body.
+    pub fn handle(&self, method: &Method, url: &str, body: &str) -> Response {
+        let path = url.split('?').next().unwrap_or_default();
+        let segments: Vec<_> =
+            path.split('/').filter(|s| !s.is_empty()).collect();
+
+        match (method, segments.as_slice()) {
+            (Method::Get, ["strategies"]) => {
+                Response::ok(json!(registry::names()))
+            }
+            (Method::Get, ["jobs"]) => {
+                Response::ok(json!(self.queue.statuses()))
+            }
+            (Method::Post, ["jobs"]) => self.submit(body),
+            (method, ["jobs", id, rest @ ..]) => {
+                let Ok(id) = id.parse::<JobId>() else {
+                    return Response::error(404, format!("no job {:?}", id));
+                };
+                self.job(method, id, rest).unwrap_or_else(|| {
+                    Response::error(404, format!("no job {}", id))
+                })
+            }
+            _ => {
+                Response::error(404, format!("no endpoint {} {}", method, path))
+            }
+        }
+    }
+
+    fn submit(&self, body: &str) -> Response {
+        let config: ExperimentConfig = match serde_json::from_str(body) {
+            Ok(config) => config,
+            Err(e) => return Response::error(400, e),
+        };
+
+        match self.queue.submit(config) {
+            Ok(status) => Response {
+                status: 202,
+                body: json!(status),
+            },
+            Err(e) => Response::error(400, e),
+        }
+    }
+
+    /// Handles requests for a single job, returning `None` if it does not
+    /// exist.
+    fn job(
+        &self,
+        method: &Method,
+        id: JobId,
+        rest: &[&str],
+    ) -> Option<Response> {
+        let response = match (method, rest) {
+            (Method::Get, []) => Response::ok(json!(self.queue.status(id)?)),
+            (Method::Delete, []) => {
+                Response::ok(json!(self.queue.remove(id)?))
+            }
+            (Method::Post, ["cancel"]) => {
+                Response::ok(json!(self.queue.cancel(id)?))
+            }
+            (Method::Get, ["results"]) => match self.queue.results(id) {
+                Some(results) => Response::ok(results),
+                None => {
+                    let status = self.queue.status(id)?;
+                    Response::error(
+                        409,
+                        format!("job {} has no results yet", status.id),
+                    )
+                }
+            },
+            _ => Response::error(
+                404,
+                format!("no endpoint {} for job {}", method, id),
+            ),
+        };
+
+        Some(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tiny_http::Method;
+
+    use super::Server;
+    use crate::jobs::JobState;
+
+    #[test]
+    fn endpoints() {
+        let server = Server::new(1);
+        let get = |url| server.handle(&Method::Get, url, "");
+
+        let strategies = get("/strategies");
+        assert_eq!(strategies.status, 200);
+        assert!(strategies
+            .body
+            .as_array()
+            .unwrap()
+            .contains(&json!("selfish")));
+
+        let config = json!({
+            "miners": [{ "strategy": "honest" }, { "strategy": "selfish" }],
+            "alpha": [0.3],
+            "rounds": 1000,
+        });
+        let submitted =
+            server.handle(&Method::Post, "/jobs", &config.to_string());
+        assert_eq!(submitted.status, 202);
+        assert_eq!(submitted.body["id"], 1);
+        assert_eq!(submitted.body["total"], 1);
+
+        assert_eq!(server.queue().wait(1).unwrap().state, JobState::Finished);
+        assert_eq!(get("/jobs/1").body["state"], "finished");
+        assert_eq!(get("/jobs").body.as_array().unwrap().len(), 1);
+        let results = get("/jobs/1/results?format=json");
+        assert_eq!(results.status, 200);
+        assert_eq!(results.body["rows"].as_array().unwrap().len(), 1);
+
+        assert_eq!(server.handle(&Method::Delete, "/jobs/1", "").status, 200);
+        assert_eq!(get("/jobs/1").status, 404);
+    }
+
+    #[test]
+    fn bad_requests() {
+        let server = Server::new(1);
+
+        let post = |body| server.handle(&Method::Post, "/jobs", body);
+        assert_eq!(post("not json").status, 400);
+        assert_eq!(post(r#"{ "miners": [] }"#).status, 400);
+        let unknown = post(r#"{ "miners": [{ "strategy": "lazy" }] }"#);
+        assert_eq!(unknown.status, 400);
+        assert!(unknown.body["error"].as_str().unwrap().contains("lazy"));
+
+        let get = |url| server.handle(&Method::Get, url, "").status;
+        assert_eq!(get("/jobs/7"), 404);
+        assert_eq!(get("/jobs/seven"), 404);
+        assert_eq!(get("/nothing"), 404);
+        assert_eq!(server.handle(&Method::Put, "/jobs", "").status, 404);
+    }
+}
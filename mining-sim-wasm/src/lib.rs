@@ -0,0 +1,223 @@
+/*!
+JavaScript bindings for [`mining_sim`], compiled to WebAssembly.
+
+Runs a single simulation of one attacking strategy against honest miners, and
+returns the attacker's revenue along with the block tree, so that interactive
+demos of selfish mining can run entirely in the browser. Built with
+[wasm-pack](https://rustwasm.github.io/wasm-pack/):
+
+```text
+wasm-pack build mining-sim-wasm --target web
+```
+
+## Example
+```js
+import init, { simulate, selfishRevenue } from "./pkg/mining_sim_wasm.js";
+
+await init();
+
+const result = simulate("selfish", {}, 0.35, 0.5, 10000, 1);
+console.log(result.attackerRevenue, selfishRevenue(0.35, 0.5));
+
+// [id, parent, miner] for every block except genesis
+const blocks = result.blocks();
+```
+*/
+
+use mining_sim::{
+    blockchain::Blockchain,
+    miner::{honest::Honest, registry, MinerId},
+    results,
+    simulation::{SimulationBuilder, SimulationOutput},
+};
+use wasm_bindgen::prelude::*;
+
+/// Miner ID of the attacker in [`simulate`]. The honest miners are miner 1.
+const ATTACKER: usize = 2;
+
+/// Outcome of a call to [`simulate`].
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    attacker_revenue: f64,
+    blocks: Vec<u32>,
+    fork_count: usize,
+    longest_chain: Vec<u32>,
+    longest_chain_length: usize,
+    reorg_count: usize,
+}
+
+impl From<SimulationOutput> for SimulationResult {
+    fn from(output: SimulationOutput) -> Self {
+        let chain = output.blockchain.unwrap_or_default();
+        let blocks_on_chain = |miner_id| {
+            output
+                .miner_stats
+                .get(&MinerId::from(miner_id))
+                .map_or(0, |stats| stats.blocks_on_chain)
+        };
+        let total = blocks_on_chain(1) + blocks_on_chain(ATTACKER);
+        let attacker_revenue = match total {
+            0 => 0.0,
+            total => blocks_on_chain(ATTACKER) as f64 / total as f64,
+        };
+
+        let mut longest_chain: Vec<_> =
+            chain.longest_chain().map(|id| id.get() as u32).collect();
+        longest_chain.reverse();
+
+        Self {
+            attacker_revenue,
+            blocks: block_tree(&chain),
+            fork_count: output.fork_lengths.len(),
+            longest_chain,
+            longest_chain_length: output.longest_chain_length,
+            reorg_count: output.reorg_depths.len(),
+        }
+    }
+}
+
+/// Flattens every block except genesis into `[id, parent, miner]` triples,
+/// ordered by height.
+fn block_tree(chain: &Blockchain) -> Vec<u32> {
+    let mut blocks = vec![];
+    for height in 1..=chain.max_height() {
+        for &id in chain.at_height(height).unwrap_or_default() {
+            let parent = chain.get_parent(id).unwrap_or_default();
+            let miner = chain[id].block.miner_id;
+            blocks.extend([id.get(), parent.get(), miner.get()]);
+        }
+    }
+
+    blocks.into_iter().map(|x| x as u32).collect()
+}
+
+#[wasm_bindgen]
+impl SimulationResult {
+    /// Fraction of the blocks on the longest chain mined by the attacker.
+    #[wasm_bindgen(getter, js_name = attackerRevenue)]
+    pub fn attacker_revenue(&self) -> f64 {
+        self.attacker_revenue
+    }
+
+    /// Number of blocks on the longest chain, including the genesis block.
+    #[wasm_bindgen(getter, js_name = longestChainLength)]
+    pub fn longest_chain_length(&self) -> usize {
+        self.longest_chain_length
+    }
+
+    /// Number of forks off the longest chain.
+    #[wasm_bindgen(getter, js_name = forkCount)]
+    pub fn fork_count(&self) -> usize {
+        self.fork_count
+    }
+
+    /// Number of times the tip of the longest chain was replaced by a block
+    /// which did not extend it.
+    #[wasm_bindgen(getter, js_name = reorgCount)]
+    pub fn reorg_count(&self) -> usize {
+        self.reorg_count
+    }
+
+    /// `[id, parent, miner]` for every block except genesis, ordered by
+    /// height. Miner 1 is the honest miners and miner 2 is the attacker.
+    pub fn blocks(&self) -> Vec<u32> {
+        self.blocks.clone()
+    }
+
+    /// IDs of the blocks on the longest chain, from genesis to the tip.
+    #[wasm_bindgen(js_name = longestChain)]
+    pub fn longest_chain(&self) -> Vec<u32> {
+        self.longest_chain.clone()
+    }
+}
+
+/// Runs `strategy` with mining power `alpha` against honest miners for the
+/// given number of rounds. `strategy` and `params` are passed to
+/// [`registry::create`].
+pub fn run_simulation(
+    strategy: &str,
+    params: &[(&str, f64)],
+    alpha: f64,
+    gamma: f64,
+    rounds: usize,
+    seed: u64,
+) -> Result<SimulationResult, String> {
+    let attacker = registry::create(strategy, params.iter().copied())
+        .map_err(|e| e.to_string())?;
+
+    let mut data = SimulationBuilder::new()
+        .add_miner(Honest::new())
+        .add_boxed_miner(attacker)
+        .miner_power(MinerId::from(ATTACKER), alpha)
+        .gamma(gamma)
+        .rounds(rounds)
+        .seed(seed)
+        .include_blockchain()
+        .build()
+        .map_err(|e| e.to_string())?
+        .run_all_sequential()
+        .map_err(|e| e.to_string())?
+        .data();
+
+    data.pop()
+        .map(SimulationResult::from)
+        .ok_or_else(|| "no simulation was run".to_string())
+}
+
+/// Runs `strategy` with mining power `alpha` against honest miners. `params`
+/// is an object mapping strategy parameter names to numbers, such as
+/// `{ i: 2 }` for `"ndeficit"`. See [`strategies`].
+#[wasm_bindgen]
+pub fn simulate(
+    strategy: &str,
+    params: &js_sys::Object,
+    alpha: f64,
+    gamma: f64,
+    rounds: usize,
+    seed: u32,
+) -> Result<SimulationResult, JsError> {
+    let mut names = vec![];
+    for entry in js_sys::Object::entries(params).iter() {
+        let entry = js_sys::Array::from(&entry);
+        let name = entry.get(0).as_string().unwrap_or_default();
+        let value = entry.get(1).as_f64().ok_or_else(|| {
+            JsError::new(&format!("parameter {name:?} is not a number"))
+        })?;
+        names.push((name, value));
+    }
+    let params: Vec<_> = names.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+
+    run_simulation(strategy, &params, alpha, gamma, rounds, seed.into())
+        .map_err(|e| JsError::new(&e))
+}
+
+/// Names of the strategies which can be passed to [`simulate`].
+#[wasm_bindgen]
+pub fn strategies() -> Vec<String> {
+    registry::names()
+}
+
+/// Ideal revenue of a selfish miner with mining power `alpha`, from Eyal and
+/// Sirer's paper. See [`results::selfish_revenue`].
+#[wasm_bindgen(js_name = selfishRevenue)]
+pub fn selfish_revenue(alpha: f64, gamma: f64) -> f64 {
+    results::selfish_revenue(gamma)(alpha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_simulation;
+
+    #[test]
+    fn selfish_simulation() {
+        let result = run_simulation("selfish", &[], 0.4, 0.5, 1000, 1).unwrap();
+
+        assert_eq!(result.blocks.len() % 3, 0);
+        assert_eq!(result.longest_chain.len(), result.longest_chain_length);
+        assert!(result.attacker_revenue > 0.4);
+        assert!(
+            run_simulation("selfish", &[("i", 1.0)], 0.4, 0.5, 10, 1).is_err()
+        );
+    }
+}
@@ -0,0 +1,12 @@
+/*!
+Theoretical analysis of mining strategies, for comparison with simulation
+results
+
+Submodules compute properties of the mining game itself rather than of a
+particular [`Miner`](crate::miner::Miner) implementation, such as the best
+revenue any strategy can achieve.
+*/
+
+pub mod fit;
+pub mod markov;
+pub mod mdp;
@@ -0,0 +1,300 @@
+/*!
+Least-squares curve fitting of revenue as a function of mining power
+
+A [`LinearModel`] is a weighted sum of terms, each a function of the
+attacker's mining power, such as the powers of `alpha` in a polynomial. Its
+weights are fit to points from simulation results, such as those returned by
+[`ResultsBuilder::revenue_points`](crate::results::ResultsBuilder::revenue_points),
+by least squares. The resulting [`CurveFit`] reports each term's coefficient
+and the coefficient of determination, R², of the fit.
+
+## Example
+```
+use mining_sim::{analysis::fit::LinearModel, prelude::*};
+
+let points = SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(Honest::new())
+    .power_values([0.8, 0.2])
+    .power_values([0.6, 0.4])
+    .power_values([0.4, 0.6])
+    .rounds(10_000)
+    .repeat_all(5)
+    .build()
+    .unwrap()
+    .run_all()
+    .unwrap()
+    .revenue_points(MinerId::from(2));
+
+let fit = LinearModel::polynomial(1).fit(&points).unwrap();
+println!("{}", fit);
+
+// Honest revenue is proportional to mining power
+assert!((fit.coefficients()[1] - 1.0).abs() < 0.1);
+assert!(fit.r_squared() > 0.9);
+```
+*/
+
+use std::{fmt::Display, sync::Arc};
+
+use thiserror::Error;
+
+use crate::{power_dist::PowerValue, utils::solve_linear};
+
+/// Tolerance for pivots when solving the normal equations.
+const EPSILON: f64 = 1e-12;
+
+/// Function of mining power used as a term of a [`LinearModel`].
+pub type TermFn = Arc<dyn Fn(PowerValue) -> f64 + Send + Sync>;
+
+/// Error returned by [`LinearModel::fit`].
+#[derive(Debug, Error)]
+pub enum FitError {
+    #[error("model has no terms")]
+    NoTerms,
+    #[error("{points} points are too few to fit {terms} terms")]
+    TooFewPoints { points: usize, terms: usize },
+    #[error("terms are linearly dependent at the given points")]
+    Singular,
+}
+
+/// A model of revenue as a weighted sum of named functions of mining power.
+#[derive(Clone, Default)]
+pub struct LinearModel {
+    terms: Vec<(String, TermFn)>,
+}
+
+impl std::fmt::Debug for LinearModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LinearModel")
+            .field("terms", &self.term_names().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl LinearModel {
+    /// Creates a model with no terms.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a polynomial model, with terms "1", "x", "x^2", and so on up
+    /// to `x^degree`.
+    pub fn polynomial(degree: usize) -> Self {
+        (0..=degree).fold(Self::new(), |model, k| {
+            let name = match k {
+                0 => "1".to_string(),
+                1 => "x".to_string(),
+                _ => format!("x^{}", k),
+            };
+
+            model.term(name, move |x| x.powi(k as i32))
+        })
+    }
+
+    /// Adds a term to the model, with a name used when displaying the fit.
+    pub fn term<T, F>(mut self, name: T, func: F) -> Self
+    where
+        T: Into<String>,
+        F: Fn(PowerValue) -> f64 + Send + Sync + 'static,
+    {
+        self.terms.push((name.into(), Arc::new(func)));
+
+        self
+    }
+
+    /// Names of the model's terms, in the order they were added.
+    pub fn term_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.terms.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Fits the model to `(power, revenue)` points by least squares.
+    pub fn fit(
+        &self,
+        points: &[(PowerValue, f64)],
+    ) -> Result<CurveFit, FitError> {
+        let n = self.terms.len();
+        if n == 0 {
+            return Err(FitError::NoTerms);
+        }
+        if points.len() < n {
+            return Err(FitError::TooFewPoints {
+                points: points.len(),
+                terms: n,
+            });
+        }
+
+        // Rows are the normal equations, (X^T X) c = X^T y
+        let mut matrix = vec![vec![0.0; n + 1]; n];
+        for &(x, y) in points {
+            let values: Vec<_> = self.terms.iter().map(|(_, f)| f(x)).collect();
+            for (row, vi) in matrix.iter_mut().zip(&values) {
+                for (cell, vj) in row.iter_mut().zip(&values) {
+                    *cell += vi * vj;
+                }
+                row[n] += vi * y;
+            }
+        }
+
+        let coefficients =
+            solve_linear(matrix, EPSILON).ok_or(FitError::Singular)?;
+        let r_squared = r_squared(points, |x| self.evaluate(&coefficients, x));
+
+        Ok(CurveFit {
+            model: self.clone(),
+            coefficients,
+            r_squared,
+        })
+    }
+
+    fn evaluate(&self, coefficients: &[f64], x: PowerValue) -> f64 {
+        self.terms
+            .iter()
+            .zip(coefficients)
+            .map(|((_, f), c)| c * f(x))
+            .sum()
+    }
+}
+
+/// A [`LinearModel`] fit to a set of points.
+#[derive(Debug, Clone)]
+pub struct CurveFit {
+    model: LinearModel,
+    coefficients: Vec<f64>,
+    r_squared: f64,
+}
+
+impl CurveFit {
+    /// Coefficients of the model's terms, in the order they were added.
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    /// Coefficient of determination of the fit, which is 1 for a perfect
+    /// fit, or NaN if all fitted revenues are equal.
+    pub fn r_squared(&self) -> f64 {
+        self.r_squared
+    }
+
+    /// Revenue predicted by the fit at mining power `x`.
+    pub fn predict(&self, x: PowerValue) -> f64 {
+        self.model.evaluate(&self.coefficients, x)
+    }
+
+    /// Returns [`predict`](Self::predict) as a function, which can be used
+    /// as input to
+    /// [`ResultsBuilder::mining_power_func`](crate::results::ResultsBuilder::mining_power_func).
+    pub fn predict_func(self) -> impl Fn(PowerValue) -> f64 {
+        move |x: PowerValue| -> f64 { self.predict(x) }
+    }
+}
+
+impl Display for CurveFit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "y =")?;
+        for (i, (name, c)) in
+            self.model.term_names().zip(&self.coefficients).enumerate()
+        {
+            let sign = match (i, c.is_sign_negative()) {
+                (0, false) => " ",
+                (0, true) => " -",
+                (_, false) => " + ",
+                (_, true) => " - ",
+            };
+            match name {
+                "1" => write!(f, "{}{}", sign, c.abs())?,
+                _ => write!(f, "{}{} {}", sign, c.abs(), name)?,
+            }
+        }
+
+        write!(f, " (R^2 = {})", self.r_squared)
+    }
+}
+
+/// Returns the coefficient of determination, R², of `model` on
+/// `(power, revenue)` points, such as those returned by
+/// [`ResultsBuilder::revenue_points`](crate::results::ResultsBuilder::revenue_points).
+/// Useful for scoring closed-form revenue functions like
+/// [`selfish_revenue`](crate::results::selfish_revenue) against simulation
+/// results. Returns NaN if all revenues are equal.
+pub fn r_squared<F>(points: &[(PowerValue, f64)], model: F) -> f64
+where
+    F: Fn(PowerValue) -> f64,
+{
+    let mean = points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+    let total: f64 = points.iter().map(|(_, y)| (y - mean).powi(2)).sum();
+    let residual: f64 =
+        points.iter().map(|&(x, y)| (y - model(x)).powi(2)).sum();
+
+    match total > 0.0 {
+        true => 1.0 - residual / total,
+        false => f64::NAN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{r_squared, FitError, LinearModel};
+    use crate::results::selfish_revenue;
+
+    fn sample<F: Fn(f64) -> f64>(func: F) -> Vec<(f64, f64)> {
+        (1..10)
+            .map(|i| i as f64 * 0.05)
+            .map(|x| (x, func(x)))
+            .collect()
+    }
+
+    #[test]
+    fn fit_recovers_polynomial() {
+        let points = sample(|x| 0.5 - 2.0 * x + 3.0 * x * x);
+        let fit = LinearModel::polynomial(2).fit(&points).unwrap();
+
+        for (c, expected) in fit.coefficients().iter().zip([0.5, -2.0, 3.0]) {
+            assert!((c - expected).abs() < 1e-9, "{c} != {expected}");
+        }
+        assert!((fit.r_squared() - 1.0).abs() < 1e-9);
+        assert!((fit.predict(0.3) - 0.17).abs() < 1e-9);
+        assert!(
+            fit.to_string().starts_with("y = 0.")
+                && fit.to_string().contains(" x^2 (R^2 = ")
+        );
+    }
+
+    #[test]
+    fn fit_custom_terms() {
+        let points = sample(selfish_revenue(0.5));
+        let fit = LinearModel::new()
+            .term("selfish", selfish_revenue(0.5))
+            .fit(&points)
+            .unwrap();
+        assert!((fit.coefficients()[0] - 1.0).abs() < 1e-9);
+
+        let linear = LinearModel::polynomial(1).fit(&points).unwrap();
+        assert!(linear.r_squared() < 1.0);
+        assert!(linear.r_squared() < r_squared(&points, selfish_revenue(0.5)));
+    }
+
+    #[test]
+    fn fit_errors() {
+        let points = sample(|x| x);
+        assert!(matches!(
+            LinearModel::new().fit(&points),
+            Err(FitError::NoTerms)
+        ));
+        assert!(matches!(
+            LinearModel::polynomial(3).fit(&points[..2]),
+            Err(FitError::TooFewPoints {
+                points: 2,
+                terms: 4
+            })
+        ));
+        assert!(matches!(
+            LinearModel::new()
+                .term("x", |x| x)
+                .term("2x", |x| 2.0 * x)
+                .fit(&points),
+            Err(FitError::Singular)
+        ));
+        assert!(r_squared(&[(0.1, 0.5), (0.2, 0.5)], |x| x).is_nan());
+    }
+}
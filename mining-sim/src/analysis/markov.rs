@@ -0,0 +1,304 @@
+/*!
+Steady-state revenue of strategies described as Markov chains
+
+Many strategies, such as [`Selfish`](crate::miner::selfish::Selfish) mining,
+are state machines whose transitions depend only on who mines the next block.
+A [`StrategyChain`] describes such a strategy by its states, the probability
+of each transition as a function of `alpha` and `gamma`, and the blocks each
+transition adds to the longest chain. Its stationary distribution gives the
+strategy's long-run revenue exactly, which can be used to cross-check
+simulations without sampling noise.
+
+## Example
+```
+use mining_sim::analysis::markov::StrategyChain;
+
+// Honest mining: every block found is added to the longest chain
+let honest = StrategyChain::new()
+    .transition("tip", "tip", |a, _| a, 1.0, 0.0)
+    .transition("tip", "tip", |a, _| 1.0 - a, 0.0, 1.0);
+
+let revenue = honest.revenue(0.3, 0.0).unwrap();
+assert!((revenue - 0.3).abs() < 1e-12);
+```
+*/
+
+use std::{collections::HashMap, fmt::Debug, hash::Hash, sync::Arc};
+
+use thiserror::Error;
+
+use crate::{power_dist::PowerValue, utils::solve_linear};
+
+/// Tolerance for the probabilities leaving each state to sum to 1, and for
+/// pivots when solving for the stationary distribution.
+const EPSILON: f64 = 1e-9;
+
+/// Probability of a transition, given the attacker's mining power `alpha`
+/// and `gamma`.
+pub type ProbabilityFn = Arc<dyn Fn(PowerValue, f64) -> f64 + Send + Sync>;
+
+/// Error returned when analyzing a [`StrategyChain`].
+#[derive(Debug, Error)]
+pub enum MarkovError {
+    #[error("no transitions were added")]
+    NoTransitions,
+    #[error("transitions from state {state} have total probability {sum}")]
+    BadProbabilities { state: String, sum: f64 },
+    #[error("chain does not have a unique stationary distribution")]
+    NotErgodic,
+    #[error("no blocks are added to the longest chain in the steady state")]
+    NoBlocks,
+}
+
+#[derive(Clone)]
+struct Transition {
+    from: usize,
+    to: usize,
+    probability: ProbabilityFn,
+    attacker_blocks: f64,
+    honest_blocks: f64,
+}
+
+/// A strategy's state machine, with states of type `S`.
+///
+/// Transitions are added with [`transition`](Self::transition), and states
+/// are created the first time a transition uses them. Several transitions
+/// may connect the same pair of states.
+#[derive(Clone)]
+pub struct StrategyChain<S> {
+    states: Vec<S>,
+    index: HashMap<S, usize>,
+    transitions: Vec<Transition>,
+}
+
+impl<S: Debug> Debug for StrategyChain<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StrategyChain")
+            .field("states", &self.states)
+            .field("transitions", &self.transitions.len())
+            .finish()
+    }
+}
+
+impl<S: Clone + Eq + Hash> Default for StrategyChain<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Clone + Eq + Hash> StrategyChain<S> {
+    pub fn new() -> Self {
+        Self {
+            states: vec![],
+            index: HashMap::new(),
+            transitions: vec![],
+        }
+    }
+
+    /// Adds a transition from `from` to `to`, taken with the given
+    /// probability, which adds `attacker_blocks` of the attacker's blocks and
+    /// `honest_blocks` honest blocks to the longest chain. Block counts may
+    /// be expected values.
+    pub fn transition<F>(
+        mut self,
+        from: S,
+        to: S,
+        probability: F,
+        attacker_blocks: f64,
+        honest_blocks: f64,
+    ) -> Self
+    where
+        F: Fn(PowerValue, f64) -> f64 + Send + Sync + 'static,
+    {
+        let from = self.state_index(from);
+        let to = self.state_index(to);
+        self.transitions.push(Transition {
+            from,
+            to,
+            probability: Arc::new(probability),
+            attacker_blocks,
+            honest_blocks,
+        });
+
+        self
+    }
+
+    fn state_index(&mut self, state: S) -> usize {
+        if let Some(&i) = self.index.get(&state) {
+            return i;
+        }
+        self.states.push(state.clone());
+        self.index.insert(state, self.states.len() - 1);
+
+        self.states.len() - 1
+    }
+
+    /// States of the chain, in the order they were first used.
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+}
+
+impl<S: Clone + Eq + Hash + Debug> StrategyChain<S> {
+    /// Long-run probability of being in each state, in the order of
+    /// [`states`](Self::states).
+    pub fn stationary(
+        &self,
+        alpha: PowerValue,
+        gamma: f64,
+    ) -> Result<Vec<f64>, MarkovError> {
+        let n = self.states.len();
+        if n == 0 {
+            return Err(MarkovError::NoTransitions);
+        }
+
+        let mut totals = vec![0.0; n];
+        // Rows are the balance equations, pi * (P - I) = 0
+        let mut matrix = vec![vec![0.0; n + 1]; n];
+        for (t, p) in self.probabilities(alpha, gamma) {
+            totals[t.from] += p;
+            matrix[t.to][t.from] += p;
+            matrix[t.from][t.from] -= p;
+        }
+        if let Some((state, &sum)) = totals
+            .iter()
+            .enumerate()
+            .find(|(_, sum)| (*sum - 1.0).abs() > EPSILON)
+        {
+            return Err(MarkovError::BadProbabilities {
+                state: format!("{:?}", self.states[state]),
+                sum,
+            });
+        }
+
+        // One balance equation is redundant, so replace it with sum(pi) = 1
+        matrix[n - 1] = vec![1.0; n + 1];
+
+        solve_linear(matrix, EPSILON).ok_or(MarkovError::NotErgodic)
+    }
+
+    /// Long-run fraction of the blocks on the longest chain which were mined
+    /// by the attacker.
+    pub fn revenue(
+        &self,
+        alpha: PowerValue,
+        gamma: f64,
+    ) -> Result<f64, MarkovError> {
+        let pi = self.stationary(alpha, gamma)?;
+
+        let (mut attacker, mut total) = (0.0, 0.0);
+        for (t, p) in self.probabilities(alpha, gamma) {
+            attacker += pi[t.from] * p * t.attacker_blocks;
+            total += pi[t.from] * p * (t.attacker_blocks + t.honest_blocks);
+        }
+
+        match total > EPSILON {
+            true => Ok(attacker / total),
+            false => Err(MarkovError::NoBlocks),
+        }
+    }
+
+    /// Returns [`revenue`](Self::revenue) for a given `gamma`, which can be
+    /// used as input to
+    /// [`ResultsBuilder::mining_power_func`](crate::results::ResultsBuilder::mining_power_func).
+    ///
+    /// # Panics
+    /// The returned function panics if the chain cannot be analyzed.
+    pub fn revenue_func(self, gamma: f64) -> impl Fn(PowerValue) -> f64 {
+        move |a: PowerValue| -> f64 {
+            match self.revenue(a, gamma) {
+                Ok(revenue) => revenue,
+                Err(e) => panic!("{e}"),
+            }
+        }
+    }
+
+    fn probabilities(
+        &self,
+        alpha: PowerValue,
+        gamma: f64,
+    ) -> impl Iterator<Item = (&Transition, f64)> {
+        self.transitions
+            .iter()
+            .map(move |t| (t, (t.probability)(alpha, gamma)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MarkovError, StrategyChain};
+    use crate::results::selfish_revenue;
+
+    /// Eyal and Sirer's selfish mining state machine, where state `Some(k)`
+    /// is a private lead of `k` blocks and `None` is a tie. Leads are capped
+    /// at `max_lead`, past which the attacker publishes a block for every
+    /// block it finds.
+    fn selfish_chain(max_lead: usize) -> StrategyChain<Option<usize>> {
+        let mut chain = StrategyChain::new()
+            .transition(Some(0), Some(1), |a, _| a, 0.0, 0.0)
+            .transition(Some(0), Some(0), |a, _| 1.0 - a, 0.0, 1.0)
+            .transition(None, Some(0), |a, _| a, 2.0, 0.0)
+            .transition(None, Some(0), |a, g| g * (1.0 - a), 1.0, 1.0)
+            .transition(None, Some(0), |a, g| (1.0 - g) * (1.0 - a), 0.0, 2.0)
+            .transition(Some(1), Some(2), |a, _| a, 0.0, 0.0)
+            .transition(Some(1), None, |a, _| 1.0 - a, 0.0, 0.0)
+            .transition(Some(2), Some(3), |a, _| a, 0.0, 0.0)
+            .transition(Some(2), Some(0), |a, _| 1.0 - a, 2.0, 0.0);
+        for k in 3..max_lead {
+            chain = chain
+                .transition(Some(k), Some(k + 1), |a, _| a, 0.0, 0.0)
+                .transition(Some(k), Some(k - 1), |a, _| 1.0 - a, 1.0, 0.0);
+        }
+
+        chain
+            .transition(Some(max_lead), Some(max_lead), |a, _| a, 1.0, 0.0)
+            .transition(
+                Some(max_lead),
+                Some(max_lead - 1),
+                |a, _| 1.0 - a,
+                1.0,
+                0.0,
+            )
+    }
+
+    #[test]
+    fn selfish_chain_matches_closed_form() {
+        let chain = selfish_chain(60);
+        for gamma in [0.0, 0.5, 1.0] {
+            for alpha in [0.1, 0.25, 0.4] {
+                let revenue = chain.revenue(alpha, gamma).unwrap();
+                let expected = selfish_revenue(gamma)(alpha);
+                assert!(
+                    (revenue - expected).abs() < 1e-6,
+                    "alpha {alpha}, gamma {gamma}: {revenue} != {expected}"
+                );
+            }
+        }
+
+        let pi = chain.stationary(0.3, 0.5).unwrap();
+        assert!((pi.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invalid_chains() {
+        let empty = StrategyChain::<u8>::new();
+        assert!(matches!(
+            empty.revenue(0.3, 0.0),
+            Err(MarkovError::NoTransitions)
+        ));
+
+        let leaky = StrategyChain::new().transition(0, 0, |a, _| a, 1.0, 0.0);
+        assert!(matches!(
+            leaky.revenue(0.3, 0.0),
+            Err(MarkovError::BadProbabilities { .. })
+        ));
+
+        let split = StrategyChain::new()
+            .transition(0, 0, |_, _| 1.0, 1.0, 0.0)
+            .transition(1, 1, |_, _| 1.0, 0.0, 1.0);
+        assert!(matches!(
+            split.revenue(0.3, 0.0),
+            Err(MarkovError::NotErgodic)
+        ));
+    }
+}
@@ -0,0 +1,455 @@
+/*!
+Optimal block withholding revenue via a Markov decision process
+
+Models the block withholding game of Sapirshtein, Sompolinsky and Zohar's
+paper, "Optimal Selfish Mining Strategies in Bitcoin", between an attacker
+with mining power `alpha` and honest miners, a `gamma` fraction of which mine
+on the attacker's block during a tie. Solving the game with value iteration
+gives the highest revenue any withholding strategy can earn, along with a
+policy which earns it.
+
+The attacker's private chain and the honest chain can only grow to
+[`WithholdingMdp::max_fork_len`] blocks past their common ancestor, after
+which the attacker must resolve the fork. The truncation can only remove
+strategies, so the computed revenue is a slight underestimate of the true
+optimum, which it approaches as the limit grows.
+
+## Example
+```
+use mining_sim::{analysis::mdp::WithholdingMdp, prelude::*};
+
+let solution = WithholdingMdp::new(0.35, 0.0).unwrap().solve();
+
+assert!(solution.revenue() > selfish_revenue(0.0)(0.35));
+```
+*/
+
+use thiserror::Error;
+
+use crate::power_dist::PowerValue;
+
+/// Weight of the transition probabilities in each iteration, with the
+/// remainder staying in the current state. Keeps value iteration from
+/// oscillating on periodic policies without changing the optimal policy.
+const APERIODICITY: f64 = 0.9;
+
+/// Gives up on value iteration after this many iterations, and uses the
+/// current bounds on the average reward.
+const MAX_ITERATIONS: usize = 100_000;
+
+/// Status of the race between the latest blocks of the attacker and the
+/// honest miners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fork {
+    /// The attacker mined the latest block, so the attacker cannot match the
+    /// honest chain.
+    Irrelevant,
+    /// Honest miners mined the latest block, which the attacker can match by
+    /// publishing as many blocks.
+    Relevant,
+    /// The attacker matched the honest chain, and honest miners are split
+    /// between the two.
+    Active,
+}
+
+/// State of the withholding game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MdpState {
+    /// Length of the attacker's private chain since the fork.
+    pub attacker: usize,
+    /// Length of the honest chain since the fork.
+    pub honest: usize,
+    pub fork: Fork,
+}
+
+/// Action of the attacker in a state of the withholding game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MdpAction {
+    /// Abandon the private chain and mine on the honest chain.
+    Adopt,
+    /// Publish one block more than the honest chain, replacing it.
+    Override,
+    /// Publish as many blocks as the honest chain, splitting honest miners.
+    Match,
+    /// Keep mining on the private chain.
+    Wait,
+}
+
+/// Error returned by [`WithholdingMdp::new`].
+#[derive(Debug, Error)]
+pub enum MdpError {
+    #[error("alpha value {0} is not in the range 0.0..1.0")]
+    BadAlpha(f64),
+    #[error("gamma value {0} is not in the range 0.0..=1.0")]
+    BadGamma(f64),
+}
+
+/// One of the actions available in a state, with its expected rewards and
+/// the resulting distribution over states.
+#[derive(Debug, Clone)]
+struct Choice {
+    action: MdpAction,
+    attacker_reward: f64,
+    honest_reward: f64,
+    next: Vec<(usize, f64)>,
+}
+
+/// The block withholding game for a given `alpha` and `gamma`, truncated to
+/// forks of at most [`max_fork_len`](Self::max_fork_len) blocks.
+#[derive(Debug, Clone)]
+pub struct WithholdingMdp {
+    alpha: f64,
+    gamma: f64,
+    max_fork_len: usize,
+    tolerance: f64,
+}
+
+impl WithholdingMdp {
+    pub const DEFAULT_MAX_FORK_LEN: usize = 40;
+    pub const DEFAULT_TOLERANCE: f64 = 1e-5;
+
+    /// Creates the game for an attacker with mining power `alpha`, where a
+    /// `gamma` fraction of honest miners mine on the attacker's block during
+    /// a tie.
+    pub fn new(alpha: f64, gamma: f64) -> Result<Self, MdpError> {
+        if !(0.0..1.0).contains(&alpha) {
+            return Err(MdpError::BadAlpha(alpha));
+        }
+        if !(0.0..=1.0).contains(&gamma) {
+            return Err(MdpError::BadGamma(gamma));
+        }
+
+        Ok(Self {
+            alpha,
+            gamma,
+            max_fork_len: Self::DEFAULT_MAX_FORK_LEN,
+            tolerance: Self::DEFAULT_TOLERANCE,
+        })
+    }
+
+    /// Sets the maximum length of either chain since the fork. The number of
+    /// states, and so the time to solve the game, grows with its square.
+    ///
+    /// # Panics
+    /// Panics if `len` is less than 2.
+    pub fn max_fork_len(mut self, len: usize) -> Self {
+        assert!(len >= 2, "forks must be allowed to reach 2 blocks");
+        self.max_fork_len = len;
+
+        self
+    }
+
+    /// Sets the precision of the computed revenue.
+    ///
+    /// # Panics
+    /// Panics if `tolerance` is not positive.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        assert!(tolerance > 0.0, "tolerance {tolerance} is not positive");
+        self.tolerance = tolerance;
+
+        self
+    }
+
+    /// Finds the highest revenue the attacker can earn, and a policy which
+    /// earns it.
+    ///
+    /// The revenue is found by binary search on `rho`: the attacker earns
+    /// more than `rho` exactly when some policy has a positive average
+    /// reward, where each of the attacker's blocks is worth `1 - rho` and
+    /// each honest block is worth `-rho`.
+    pub fn solve(&self) -> MdpSolution {
+        let choices: Vec<_> = self.states().map(|s| self.choices(s)).collect();
+        let mut values = vec![0.0; choices.len()];
+
+        // Honest mining earns exactly alpha
+        let (mut low, mut high) = (self.alpha, 1.0);
+        while high - low > self.tolerance {
+            let rho = (low + high) / 2.0;
+            let (min, max) =
+                average_reward(&choices, rho, &mut values, self.tolerance);
+            match min + max > 0.0 {
+                true => low = rho,
+                false => high = rho,
+            }
+        }
+
+        let revenue = (low + high) / 2.0;
+        average_reward(&choices, revenue, &mut values, self.tolerance);
+        let policy = choices
+            .iter()
+            .map(|options| {
+                let best = options.iter().max_by(|a, b| {
+                    let a = value_of(a, revenue, &values);
+                    a.total_cmp(&value_of(b, revenue, &values))
+                });
+                best.map_or(MdpAction::Adopt, |choice| choice.action)
+            })
+            .collect();
+
+        MdpSolution {
+            revenue,
+            max_fork_len: self.max_fork_len,
+            policy,
+        }
+    }
+
+    fn index(&self, state: MdpState) -> usize {
+        index(self.max_fork_len, state)
+    }
+
+    fn states(&self) -> impl Iterator<Item = MdpState> {
+        let n = self.max_fork_len;
+        (0..=n).flat_map(move |attacker| {
+            (0..=n).flat_map(move |honest| {
+                [Fork::Irrelevant, Fork::Relevant, Fork::Active].map(|fork| {
+                    MdpState {
+                        attacker,
+                        honest,
+                        fork,
+                    }
+                })
+            })
+        })
+    }
+
+    fn state(&self, attacker: usize, honest: usize, fork: Fork) -> usize {
+        self.index(MdpState {
+            attacker,
+            honest,
+            fork,
+        })
+    }
+
+    /// Actions available in `state`. Adopting is always possible, and the
+    /// attacker can only keep mining while neither chain is at the maximum
+    /// fork length.
+    fn choices(&self, state: MdpState) -> Vec<Choice> {
+        let MdpState {
+            attacker: a,
+            honest: h,
+            fork,
+        } = state;
+        let (alpha, gamma) = (self.alpha, self.gamma);
+        let mut choices = vec![Choice {
+            action: MdpAction::Adopt,
+            attacker_reward: 0.0,
+            honest_reward: h as f64,
+            next: vec![
+                (self.state(1, 0, Fork::Irrelevant), alpha),
+                (self.state(0, 1, Fork::Relevant), 1.0 - alpha),
+            ],
+        }];
+
+        if a > h {
+            choices.push(Choice {
+                action: MdpAction::Override,
+                attacker_reward: (h + 1) as f64,
+                honest_reward: 0.0,
+                next: vec![
+                    (self.state(a - h, 0, Fork::Irrelevant), alpha),
+                    (self.state(a - h - 1, 1, Fork::Relevant), 1.0 - alpha),
+                ],
+            });
+        }
+
+        if a < self.max_fork_len && h < self.max_fork_len {
+            let race = match fork {
+                Fork::Relevant if a >= h && h >= 1 => Some(MdpAction::Match),
+                Fork::Active if a >= h => Some(MdpAction::Wait),
+                _ => None,
+            };
+            if let Some(action) = race {
+                // Honest miners who mine on the attacker's chain settle it
+                choices.push(Choice {
+                    action,
+                    attacker_reward: gamma * (1.0 - alpha) * h as f64,
+                    honest_reward: 0.0,
+                    next: vec![
+                        (self.state(a + 1, h, Fork::Active), alpha),
+                        (
+                            self.state(a - h, 1, Fork::Relevant),
+                            gamma * (1.0 - alpha),
+                        ),
+                        (
+                            self.state(a, h + 1, Fork::Relevant),
+                            (1.0 - gamma) * (1.0 - alpha),
+                        ),
+                    ],
+                });
+            }
+            if fork != Fork::Active {
+                choices.push(Choice {
+                    action: MdpAction::Wait,
+                    attacker_reward: 0.0,
+                    honest_reward: 0.0,
+                    next: vec![
+                        (self.state(a + 1, h, Fork::Irrelevant), alpha),
+                        (self.state(a, h + 1, Fork::Relevant), 1.0 - alpha),
+                    ],
+                });
+            }
+        }
+
+        choices
+    }
+}
+
+fn index(max_fork_len: usize, state: MdpState) -> usize {
+    let fork = match state.fork {
+        Fork::Irrelevant => 0,
+        Fork::Relevant => 1,
+        Fork::Active => 2,
+    };
+
+    (state.attacker * (max_fork_len + 1) + state.honest) * 3 + fork
+}
+
+/// Expected value of `choice`, given the current state values.
+fn value_of(choice: &Choice, rho: f64, values: &[f64]) -> f64 {
+    let reward =
+        (1.0 - rho) * choice.attacker_reward - rho * choice.honest_reward;
+    let future: f64 = choice.next.iter().map(|&(s, p)| p * values[s]).sum();
+
+    reward + APERIODICITY * future
+}
+
+/// Runs relative value iteration from `values` until the bounds on the
+/// optimal average reward per step either share a sign or are within
+/// `tolerance` of each other, and returns the bounds.
+fn average_reward(
+    choices: &[Vec<Choice>],
+    rho: f64,
+    values: &mut Vec<f64>,
+    tolerance: f64,
+) -> (f64, f64) {
+    let mut next = vec![0.0; values.len()];
+    let (mut min, mut max) = (f64::NEG_INFINITY, f64::INFINITY);
+
+    for _ in 0..MAX_ITERATIONS {
+        for (s, options) in choices.iter().enumerate() {
+            let best = options
+                .iter()
+                .map(|choice| value_of(choice, rho, values))
+                .fold(f64::NEG_INFINITY, f64::max);
+            next[s] = best + (1.0 - APERIODICITY) * values[s];
+        }
+
+        (min, max) = next
+            .iter()
+            .zip(values.iter())
+            .map(|(next, value)| next - value)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), diff| {
+                (min.min(diff), max.max(diff))
+            });
+
+        // Keep values bounded, since only their differences matter
+        let base = next[0];
+        next.iter_mut().for_each(|value| *value -= base);
+        std::mem::swap(values, &mut next);
+
+        if min > 0.0 || max < 0.0 || max - min < tolerance {
+            break;
+        }
+    }
+
+    (min, max)
+}
+
+/// Optimal revenue and policy of a [`WithholdingMdp`].
+#[derive(Debug, Clone)]
+pub struct MdpSolution {
+    revenue: f64,
+    max_fork_len: usize,
+    policy: Vec<MdpAction>,
+}
+
+impl MdpSolution {
+    /// Highest fraction of the blocks on the longest chain the attacker can
+    /// earn.
+    pub fn revenue(&self) -> f64 {
+        self.revenue
+    }
+
+    /// Optimal action in `state`, or `None` if either chain in `state` is
+    /// longer than the maximum fork length.
+    pub fn action(&self, state: MdpState) -> Option<MdpAction> {
+        if state.attacker > self.max_fork_len
+            || state.honest > self.max_fork_len
+        {
+            return None;
+        }
+
+        Some(self.policy[index(self.max_fork_len, state)])
+    }
+}
+
+/// Returns the optimal withholding revenue for a given `gamma`, which can be
+/// used as input to
+/// [`ResultsBuilder::mining_power_func`](crate::results::ResultsBuilder::mining_power_func).
+/// Solves a new [`WithholdingMdp`] with the default settings for each value.
+///
+/// # Panics
+/// The returned function panics if `gamma` is not in `[0, 1]` or its
+/// argument is not in `[0, 1)`.
+pub fn optimal_revenue(gamma: f64) -> impl Fn(PowerValue) -> f64 {
+    move |a: PowerValue| -> f64 {
+        match WithholdingMdp::new(a, gamma) {
+            Ok(mdp) => mdp.solve().revenue(),
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fork, MdpAction, MdpState, WithholdingMdp};
+    use crate::results::selfish_revenue;
+
+    fn revenue(alpha: f64, gamma: f64) -> f64 {
+        let mdp = WithholdingMdp::new(alpha, gamma).unwrap();
+        mdp.max_fork_len(20).solve().revenue()
+    }
+
+    #[test]
+    fn optimal_withholding_revenue() {
+        // Withholding does not pay off for small miners
+        assert!((revenue(0.1, 0.0) - 0.1).abs() < 1e-4);
+
+        // From Sapirshtein, Sompolinsky and Zohar's paper
+        assert!((revenue(1.0 / 3.0, 0.0) - 0.33705).abs() < 1e-3);
+        assert!((revenue(0.35, 0.0) - 0.37077).abs() < 1e-3);
+
+        for (alpha, gamma) in [(0.3, 0.5), (0.4, 0.0), (0.4, 1.0)] {
+            let selfish = selfish_revenue(gamma)(alpha);
+            assert!(revenue(alpha, gamma) >= selfish.max(alpha) - 1e-4);
+        }
+
+        assert!(WithholdingMdp::new(1.0, 0.0).is_err());
+        assert!(WithholdingMdp::new(0.3, 1.5).is_err());
+    }
+
+    #[test]
+    fn optimal_withholding_policy() {
+        let policy = |alpha| {
+            let mdp = WithholdingMdp::new(alpha, 0.5).unwrap();
+            mdp.max_fork_len(10).solve()
+        };
+        let state = |attacker, honest, fork| MdpState {
+            attacker,
+            honest,
+            fork,
+        };
+        let (small, large) = (policy(0.1), policy(0.4));
+
+        // Small miners publish their blocks at once, like honest miners
+        let lead = state(1, 0, Fork::Irrelevant);
+        assert_eq!(small.action(lead), Some(MdpAction::Override));
+        assert_eq!(large.action(lead), Some(MdpAction::Wait));
+
+        let behind = state(0, 1, Fork::Relevant);
+        assert_eq!(small.action(behind), Some(MdpAction::Adopt));
+        assert_eq!(large.action(behind), Some(MdpAction::Adopt));
+
+        assert_eq!(large.action(state(11, 0, Fork::Irrelevant)), None);
+    }
+}
@@ -1,17 +1,37 @@
 //! Definitions for the blockchain
 
-use std::{collections::HashMap, ops::Index};
+use std::{collections::HashMap, fmt::Debug, ops::Index, sync::Mutex};
+
+#[cfg(not(feature = "fast-hash"))]
+use std::collections::hash_map::RandomState;
+
+use smallvec::{smallvec, SmallVec};
 
 use crate::{miner::MinerId, transaction::Transaction};
 
+/// Builds the hashers of the maps keyed by [`BlockId`] in a [`Blockchain`],
+/// and of [`SimulationOutput::blocks_by_miner`](crate::simulation::SimulationOutput::blocks_by_miner).
+/// The standard library's DoS-resistant SipHash by default, or the much
+/// faster [FxHash](https://docs.rs/rustc-hash/2) if the `fast-hash` feature
+/// is enabled, since IDs are small integers chosen by the simulation.
+#[cfg(feature = "fast-hash")]
+pub type BlockHasher = rustc_hash::FxBuildHasher;
+#[cfg(not(feature = "fast-hash"))]
+pub type BlockHasher = RandomState;
+
 /// Representation of a public blockchain which miners can publish to. The
 /// genesis block of this chain will always have [`BlockId`] `0`, and the
 /// genesis miner will always have [`MinerId`] `0`.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Blockchain {
     max_height: usize,
-    blocks: HashMap<BlockId, BlockData>,
-    blocks_by_height: Vec<Vec<BlockId>>,
+    blocks: HashMap<BlockId, BlockData, BlockHasher>,
+    /// Most heights hold one or two blocks, which are stored inline.
+    blocks_by_height: Vec<SmallVec<[BlockId; 2]>>,
+    track_children: bool,
+    /// Number of blocks between finalized checkpoints, if any.
+    checkpoint_interval: Option<usize>,
+    last_checkpoint: BlockId,
 }
 
 /// A block and its metadata as stored in a [`Blockchain`].
@@ -20,10 +40,10 @@ pub struct BlockData {
     pub block: Block,
     /// Length of the path from `block` to the genesis block of the blockchain.
     pub height: usize,
-    #[cfg(any(doc, feature = "block-children"))]
-    /// IDs of all blocks which point to `block` as their parent. Only
-    /// available when the `block-children` feature is enabled.
-    pub children: Vec<BlockId>,
+    /// IDs of all blocks which point to `block` as their parent, in the
+    /// order they were published. Always empty unless the blockchain
+    /// [tracks children](Blockchain::track_children).
+    pub children: SmallVec<[BlockId; 2]>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -36,6 +56,10 @@ pub enum BlockPublishingError {
     InvalidParent { child: BlockId, parent: BlockId },
     #[error("block ID {0} already exists on this chain")]
     DuplicateBlockID(BlockId),
+    #[error(
+        "block {block} does not descend from finalized checkpoint {checkpoint}"
+    )]
+    ConflictsWithCheckpoint { block: BlockId, checkpoint: BlockId },
 }
 
 impl Blockchain {
@@ -44,30 +68,136 @@ impl Blockchain {
     /// `MinerId(0)`
     pub const GENESIS_MINER: MinerId = MinerId(0);
 
-    /// Creates a new blockchain containing a genesis block.     
+    /// Creates a new blockchain containing a genesis block. The blockchain
+    /// [tracks children](Blockchain::track_children) if the `block-children`
+    /// feature is enabled.
     pub fn new() -> Self {
-        let blocks = HashMap::from([(
-            Self::GENESIS_ID,
-            BlockData {
-                block: Block {
-                    id: Self::GENESIS_ID,
-                    parent_id: None,
-                    miner_id: Self::GENESIS_MINER,
-                    txns: vec![],
-                },
-                height: 0,
-                #[cfg(feature = "block-children")]
-                children: vec![],
+        let genesis = BlockData {
+            block: Block {
+                id: Self::GENESIS_ID,
+                parent_id: None,
+                miner_id: Self::GENESIS_MINER,
+                txns: vec![],
             },
-        )]);
+            height: 0,
+            children: SmallVec::new(),
+        };
+        let mut blocks = HashMap::default();
+        blocks.insert(Self::GENESIS_ID, genesis);
 
         Blockchain {
             max_height: 0,
             blocks,
-            blocks_by_height: vec![vec![Self::GENESIS_ID]],
+            blocks_by_height: vec![smallvec![Self::GENESIS_ID]],
+            track_children: cfg!(feature = "block-children"),
+            checkpoint_interval: None,
+            last_checkpoint: Self::GENESIS_ID,
+        }
+    }
+
+    /// Returns true if [`BlockData::children`] is filled in for the blocks
+    /// of this blockchain.
+    #[inline]
+    pub fn tracks_children(&self) -> bool {
+        self.track_children
+    }
+
+    /// Sets whether [`BlockData::children`] is filled in for the blocks of
+    /// this blockchain. Tracking children increases memory usage when blocks
+    /// have more than two children, and slows down block publishing.
+    ///
+    /// Enabling tracking fills in the children of the blocks already on the
+    /// blockchain, and disabling it clears them.
+    pub fn track_children(&mut self, track: bool) {
+        if track == self.track_children {
+            return;
+        }
+        self.track_children = track;
+
+        for data in self.blocks.values_mut() {
+            data.children = SmallVec::new();
+        }
+        if track {
+            // Blocks at each height are stored in publishing order
+            for id in self.blocks_by_height.iter().flatten().skip(1) {
+                let parent_id = self.blocks[id].block.parent_id.unwrap();
+                if let Some(parent) = self.blocks.get_mut(&parent_id) {
+                    parent.children.push(*id);
+                }
+            }
         }
     }
 
+    /// Returns the number of blocks between the checkpoints finalized by this
+    /// blockchain, if it finalizes any. See [`Blockchain::finalize_every`].
+    #[inline]
+    pub fn checkpoint_interval(&self) -> Option<usize> {
+        self.checkpoint_interval
+    }
+
+    /// Sets whether this blockchain finalizes a checkpoint every `blocks`
+    /// blocks, modeling a finality gadget running on top of the longest
+    /// chain rule. Once the longest chain reaches height `(c + 1) * blocks`,
+    /// its block at height `c * blocks` becomes the new checkpoint, and
+    /// publishing a block which does not descend from the last checkpoint
+    /// fails with [`BlockPublishingError::ConflictsWithCheckpoint`], so that
+    /// blocks at or below the checkpoint can no longer be reorganized.
+    ///
+    /// Turning finality on finalizes the existing longest chain by the same
+    /// rule, and turning it off keeps the last checkpoint without enforcing
+    /// it.
+    ///
+    /// # Panics
+    /// Panics if `blocks` is `Some(0)`.
+    pub fn finalize_every(&mut self, blocks: Option<usize>) {
+        assert_ne!(blocks, Some(0), "checkpoint interval must be positive");
+        self.checkpoint_interval = blocks;
+        self.update_checkpoint();
+    }
+
+    /// Finalizes the block of the longest chain at the highest multiple of
+    /// the checkpoint interval which is buried under at least one more
+    /// interval of blocks, if it is above the last checkpoint.
+    fn update_checkpoint(&mut self) {
+        let Some(interval) = self.checkpoint_interval else {
+            return;
+        };
+
+        let height = (self.max_height / interval).saturating_sub(1) * interval;
+        if height > self[self.last_checkpoint].height {
+            self.last_checkpoint = self
+                .longest_chain()
+                .find(|&id| self[id].height == height)
+                .unwrap();
+        }
+    }
+
+    /// Returns the ID of the last checkpoint finalized by this blockchain, or
+    /// the genesis block if none has been finalized. See
+    /// [`Blockchain::finalize_every`].
+    #[inline]
+    pub fn last_checkpoint(&self) -> BlockId {
+        self.last_checkpoint
+    }
+
+    /// Sets the last checkpoint to `id`, which this blockchain contains,
+    /// without enforcing it. Used by miners' views to follow the checkpoints
+    /// of the simulation's blockchain.
+    pub(crate) fn follow_checkpoint(&mut self, id: BlockId) {
+        debug_assert!(self.contains(id));
+        self.checkpoint_interval = None;
+        self.last_checkpoint = id;
+    }
+
+    /// Returns true if the block with ID `id` is the last checkpoint or one
+    /// of its descendants, so that a child of the block may be published.
+    pub fn descends_from_checkpoint(&self, id: BlockId) -> bool {
+        let checkpoint_height = self[self.last_checkpoint].height;
+        self.ancestors_of(id)
+            .find(|&ancestor| self[ancestor].height <= checkpoint_height)
+            .is_some_and(|ancestor| ancestor == self.last_checkpoint)
+    }
+
     /// Returns the IDs of all blocks at the specified height, in the order
     /// that they were published to the blockchain.
     #[inline]
@@ -111,6 +241,44 @@ impl Blockchain {
         self.blocks.len()
     }
 
+    /// Returns an estimate of the number of bytes of memory used by the
+    /// blockchain, counting its blocks and their transactions, the blocks
+    /// stored at each height, and block children if the blockchain
+    /// [tracks them](Blockchain::track_children). Blocks are never removed, so this is also the
+    /// blockchain's peak memory usage.
+    ///
+    /// The estimate counts stored items rather than allocated capacity, so it
+    /// does not depend on how buffers grew or were reused, and it does not
+    /// include allocator overhead.
+    pub fn memory_usage(&self) -> usize {
+        use std::mem::size_of;
+
+        // Hash tables store one control byte per entry
+        let entry = size_of::<BlockId>() + size_of::<BlockData>() + 1;
+        let mut bytes = size_of::<Self>()
+            + self.blocks.len() * entry
+            + self.blocks_by_height.len() * size_of::<SmallVec<[BlockId; 2]>>();
+
+        for ids in self.blocks_by_height.iter().filter(|ids| ids.spilled()) {
+            bytes += ids.len() * size_of::<BlockId>();
+        }
+        for data in self.blocks.values() {
+            bytes += data.block.txns.len() * size_of::<Transaction>();
+            bytes += data
+                .block
+                .txns
+                .iter()
+                .filter_map(|txn| txn.tag.as_ref())
+                .map(String::len)
+                .sum::<usize>();
+            if data.children.spilled() {
+                bytes += data.children.len() * size_of::<BlockId>();
+            }
+        }
+
+        bytes
+    }
+
     /// Returns an iterator over the IDs of all blocks on the longest chain,
     /// where the tip of the longest chain is defined as the earliest block
     /// published at [`Blockchain::max_height`].
@@ -122,6 +290,44 @@ impl Blockchain {
         Ancestors::new(self, lc)
     }
 
+    /// Returns the length of each fork off of the longest chain, in the order
+    /// in which the forks were started.
+    ///
+    /// Each block which is not on the longest chain but whose parent is starts
+    /// a new fork, which contains all of that block's descendants. The length
+    /// of a fork is the number of blocks on the longest path through it.
+    pub fn fork_lengths(&self) -> Vec<usize> {
+        let longest_chain: BlockSet = self.longest_chain().collect();
+
+        // Fork index and depth within the fork of each off-chain block
+        let mut fork_of = HashMap::<_, _, BlockHasher>::default();
+        let mut forks: Vec<(BlockId, usize)> = vec![];
+
+        for blocks in self.blocks_by_height.iter().skip(1) {
+            for &block_id in blocks {
+                if longest_chain.contains(block_id) {
+                    continue;
+                }
+
+                let parent_id = self.blocks[&block_id].block.parent_id.unwrap();
+                let (fork, depth) = match fork_of.get(&parent_id) {
+                    Some(&(fork, depth)) => (fork, depth + 1),
+                    None => {
+                        forks.push((block_id, 0));
+                        (forks.len() - 1, 1)
+                    }
+                };
+
+                forks[fork].1 = forks[fork].1.max(depth);
+                fork_of.insert(block_id, (fork, depth));
+            }
+        }
+
+        // Forks are discovered in order of height, not publication
+        forks.sort_by_key(|(start, _)| *start);
+        forks.into_iter().map(|(_, length)| length).collect()
+    }
+
     /// Returns the IDs of all blocks at the tip of the longest
     /// chain. Equivalent to [`Blockchain::at_height`] called with
     /// [`Blockchain::max_height`].
@@ -140,6 +346,23 @@ impl Blockchain {
         Ancestors::new(self, id)
     }
 
+    /// Returns the ID of the highest block which is an ancestor of both `a`
+    /// and `b`, where each block counts as its own ancestor. Returns `None` if
+    /// either block is not on the blockchain.
+    pub fn common_ancestor(&self, a: BlockId, b: BlockId) -> Option<BlockId> {
+        let (mut a, mut b) = (self.get(a)?, self.get(b)?);
+
+        while a.block.id != b.block.id {
+            if a.height >= b.height {
+                a = &self.blocks[&a.block.parent_id?];
+            } else {
+                b = &self.blocks[&b.block.parent_id?];
+            }
+        }
+
+        Some(a.block.id)
+    }
+
     /// Adds the given block to the blockchain.
     pub fn publish(
         &mut self,
@@ -173,15 +396,26 @@ impl Blockchain {
             });
         }
 
-        #[cfg(feature = "block-children")]
-        parent_data.children.push(block.id);
+        if self.checkpoint_interval.is_some()
+            && !self.descends_from_checkpoint(parent_id)
+        {
+            return Err(ConflictsWithCheckpoint {
+                block: block.id,
+                checkpoint: self.last_checkpoint,
+            });
+        }
+
+        let parent_data = self.blocks.get_mut(&parent_id).unwrap();
+        if self.track_children {
+            parent_data.children.push(block.id);
+        }
 
         // Insert block
         let height = parent_data.height + 1;
         if height > self.max_height {
             debug_assert!(height == self.max_height + 1);
 
-            self.blocks_by_height.push(vec![block.id]);
+            self.blocks_by_height.push(smallvec![block.id]);
             self.max_height = height;
         } else {
             self.blocks_by_height[height].push(block.id);
@@ -192,13 +426,42 @@ impl Blockchain {
             BlockData {
                 block,
                 height,
-                #[cfg(feature = "block-children")]
-                children: vec![],
+                children: SmallVec::new(),
             },
         );
+        self.update_checkpoint();
 
         Ok(())
     }
+
+    /// Publishes each block of `other` which is not on this blockchain, such
+    /// as the blocks published to `other` since it was cloned. Blocks on this
+    /// blockchain which are not in `other` are kept. Returns the number of
+    /// blocks published, or the error of the first block which could not be
+    /// published.
+    pub fn extend_from(
+        &mut self,
+        other: &dyn ChainQuery,
+    ) -> Result<usize, BlockPublishingError> {
+        let mut new_blocks = vec![];
+        for height in 1..=other.max_height() {
+            for id in other.at_height(height).unwrap_or_default() {
+                if !self.contains(*id) {
+                    new_blocks.push(other[id].block.clone());
+                }
+            }
+        }
+
+        // Parents have smaller IDs than their children
+        new_blocks.sort_by_key(|block| block.id);
+
+        let published = new_blocks.len();
+        for block in new_blocks {
+            self.publish(block)?;
+        }
+
+        Ok(published)
+    }
 }
 
 impl Default for Blockchain {
@@ -207,6 +470,221 @@ impl Default for Blockchain {
     }
 }
 
+impl Clone for Blockchain {
+    fn clone(&self) -> Self {
+        Self {
+            max_height: self.max_height,
+            blocks: self.blocks.clone(),
+            blocks_by_height: self.blocks_by_height.clone(),
+            track_children: self.track_children,
+            checkpoint_interval: self.checkpoint_interval,
+            last_checkpoint: self.last_checkpoint,
+        }
+    }
+
+    /// Overwrites this blockchain with a copy of `source`, keeping the
+    /// allocated capacity of this blockchain.
+    fn clone_from(&mut self, source: &Self) {
+        self.max_height = source.max_height;
+        self.blocks.clear();
+        self.blocks
+            .extend(source.blocks.iter().map(|(id, data)| (*id, data.clone())));
+        self.blocks_by_height.clone_from(&source.blocks_by_height);
+        self.track_children = source.track_children;
+        self.checkpoint_interval = source.checkpoint_interval;
+        self.last_checkpoint = source.last_checkpoint;
+    }
+}
+
+/// Copies of a blockchain handed out to the simulations of a
+/// [`SimulationGroup`](crate::simulation::SimulationGroup). Blockchains
+/// returned by finished simulations are reset and handed out again, so that
+/// repeated simulations reuse their allocations.
+#[derive(Debug, Default)]
+pub(crate) struct BlockchainPool {
+    initial: Blockchain,
+    buffers: Mutex<Vec<Blockchain>>,
+}
+
+impl BlockchainPool {
+    /// Creates a pool of copies of `initial`.
+    pub(crate) fn new(initial: Blockchain) -> Self {
+        Self {
+            initial,
+            buffers: Mutex::default(),
+        }
+    }
+
+    /// Returns a copy of the initial blockchain, reusing a returned blockchain
+    /// if there is one.
+    pub(crate) fn take(&self) -> Blockchain {
+        let buffer = self.buffers.lock().unwrap().pop();
+
+        match buffer {
+            Some(mut chain) => {
+                chain.clone_from(&self.initial);
+                chain
+            }
+            None => self.initial.clone(),
+        }
+    }
+
+    /// Returns the initial blockchain which the pool copies.
+    pub(crate) fn initial(&self) -> &Blockchain {
+        &self.initial
+    }
+
+    /// Returns `chain` to the pool once a simulation is finished with it.
+    pub(crate) fn put(&self, chain: Blockchain) {
+        self.buffers.lock().unwrap().push(chain);
+    }
+}
+
+/// Read-only queries on a tree of blocks, such as a miner's
+/// [`ChainView`](crate::view::ChainView).
+///
+/// [`Miner`](crate::miner::Miner) strategies which only query the chain
+/// through this trait work with any chain structure implementing it, such as
+/// a chain whose fork-choice rule weighs subtrees (GHOST) rather than
+/// heights. [`Blockchain`] implements the longest chain rule.
+///
+/// Indexing with a [`BlockId`] which is not in the chain panics.
+pub trait ChainQuery:
+    Debug
+    + Index<BlockId, Output = BlockData>
+    + for<'a> Index<&'a BlockId, Output = BlockData>
+{
+    /// ID of the genesis block.
+    fn genesis(&self) -> BlockId;
+
+    /// Returns a reference to the [`BlockData`] associated with `id`.
+    fn get(&self, id: BlockId) -> Option<&BlockData>;
+
+    /// Returns the IDs of all blocks at the specified height, in the order
+    /// that they were published.
+    fn at_height(&self, height: usize) -> Option<&[BlockId]>;
+
+    /// Maximum height of any block in the chain.
+    fn max_height(&self) -> usize;
+
+    /// Returns the number of blocks in the chain, including the genesis
+    /// block.
+    fn num_blocks(&self) -> usize;
+
+    /// Returns the IDs of the blocks tied for the head of the chain under its
+    /// fork-choice rule, in the order that they were published. Never empty.
+    fn tip(&self) -> &[BlockId];
+
+    /// Returns an iterator over the IDs of all blocks on the path from the
+    /// given block ID to the genesis block, including the given block ID.
+    /// Implementations usually return [`Ancestors::new`].
+    fn ancestors_of(&self, id: BlockId) -> Ancestors<'_>;
+
+    /// Returns true if a block with [`BlockId`] `id` is in the chain.
+    fn contains(&self, id: BlockId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Returns the parent of the block with the given ID.
+    fn get_parent(&self, id: BlockId) -> Option<BlockId> {
+        self.get(id).and_then(|data| data.block.parent_id)
+    }
+
+    /// Returns the height of the block with the given ID.
+    fn height(&self, id: BlockId) -> Option<usize> {
+        self.get(id).map(|data| data.height)
+    }
+
+    /// Returns the ID of the highest block which is an ancestor of both `a`
+    /// and `b`, where each block counts as its own ancestor. Returns `None` if
+    /// either block is not in the chain.
+    fn common_ancestor(&self, a: BlockId, b: BlockId) -> Option<BlockId> {
+        let (mut a, mut b) = (self.get(a)?, self.get(b)?);
+
+        while a.block.id != b.block.id {
+            if a.height >= b.height {
+                a = self.get(a.block.parent_id?)?;
+            } else {
+                b = self.get(b.block.parent_id?)?;
+            }
+        }
+
+        Some(a.block.id)
+    }
+
+    /// Returns the weight given to the block with the given ID by the chain's
+    /// fork-choice rule, which prefers heavier blocks. Defaults to the
+    /// block's height, as under the longest chain rule.
+    fn weight(&self, id: BlockId) -> Option<usize> {
+        self.height(id)
+    }
+
+    /// Returns the ID of the last block finalized by the chain, which every
+    /// block published from then on must descend from. Defaults to the
+    /// genesis block, for chains without finality. See
+    /// [`Blockchain::finalize_every`].
+    fn last_checkpoint(&self) -> BlockId {
+        self.genesis()
+    }
+
+    /// Returns an iterator over the IDs of all blocks on the chain ending at
+    /// the earliest block of [`ChainQuery::tip`], in descending order of
+    /// height.
+    fn longest_chain(&self) -> Ancestors<'_> {
+        self.ancestors_of(self.tip()[0])
+    }
+}
+
+impl ChainQuery for Blockchain {
+    fn genesis(&self) -> BlockId {
+        Blockchain::genesis(self)
+    }
+
+    fn get(&self, id: BlockId) -> Option<&BlockData> {
+        Blockchain::get(self, id)
+    }
+
+    fn at_height(&self, height: usize) -> Option<&[BlockId]> {
+        Blockchain::at_height(self, height)
+    }
+
+    fn max_height(&self) -> usize {
+        Blockchain::max_height(self)
+    }
+
+    fn num_blocks(&self) -> usize {
+        Blockchain::num_blocks(self)
+    }
+
+    fn tip(&self) -> &[BlockId] {
+        Blockchain::tip(self)
+    }
+
+    fn ancestors_of(&self, id: BlockId) -> Ancestors<'_> {
+        Blockchain::ancestors_of(self, id)
+    }
+
+    fn contains(&self, id: BlockId) -> bool {
+        Blockchain::contains(self, id)
+    }
+
+    fn get_parent(&self, id: BlockId) -> Option<BlockId> {
+        Blockchain::get_parent(self, id)
+    }
+
+    fn common_ancestor(&self, a: BlockId, b: BlockId) -> Option<BlockId> {
+        Blockchain::common_ancestor(self, a, b)
+    }
+
+    fn last_checkpoint(&self) -> BlockId {
+        Blockchain::last_checkpoint(self)
+    }
+
+    fn longest_chain(&self) -> Ancestors<'_> {
+        Blockchain::longest_chain(self)
+    }
+}
+
 impl Index<BlockId> for Blockchain {
     type Output = BlockData;
 
@@ -223,20 +701,22 @@ impl Index<&BlockId> for Blockchain {
     }
 }
 
-/// Iterator over the ancestors of a block on a [`Blockchain`] in descending
-/// order of height.
+/// Iterator over the ancestors of a block on a [`Blockchain`] or other
+/// [`ChainQuery`] in descending order of height.
 ///
 /// See the [`ancestors_of`](Blockchain::ancestors_of) method of [`Blockchain`]
 /// for more information.
 pub struct Ancestors<'a> {
     curr_id: Option<BlockId>,
-    chain: &'a Blockchain,
+    chain: &'a dyn ChainQuery,
 }
 
 impl<'a> Ancestors<'a> {
-    fn new(chain: &'a Blockchain, start: BlockId) -> Self {
+    /// Creates an iterator over the ancestors of `start` in `chain`, which is
+    /// empty if `chain` does not contain `start`.
+    pub fn new(chain: &'a dyn ChainQuery, start: BlockId) -> Self {
         Self {
-            curr_id: chain.blocks.contains_key(&start).then_some(start),
+            curr_id: chain.contains(start).then_some(start),
             chain,
         }
     }
@@ -248,7 +728,7 @@ impl<'a> Iterator for Ancestors<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         match self.curr_id {
             Some(block_id) => {
-                self.curr_id = self.chain.blocks[&block_id].block.parent_id;
+                self.curr_id = self.chain.get_parent(block_id);
                 Some(block_id)
             }
             None => None,
@@ -256,6 +736,77 @@ impl<'a> Iterator for Ancestors<'a> {
     }
 }
 
+/// Set of [`BlockId`]s, such as the blocks on the longest chain, stored as a
+/// bitset indexed by ID.
+///
+/// Block IDs are the rounds in which blocks were mined, so the IDs in a
+/// simulation are dense and the set uses about one bit per round. Iteration
+/// is in ascending order of ID.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BlockSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BlockSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `id` to the set. Returns true if it was not already present.
+    pub fn insert(&mut self, id: BlockId) -> bool {
+        let (word, bit) = (id.0 / 64, 1 << (id.0 % 64));
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+
+        let inserted = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        self.len += usize::from(inserted);
+
+        inserted
+    }
+
+    /// Returns true if `id` is in the set.
+    #[inline]
+    pub fn contains(&self, id: BlockId) -> bool {
+        self.words
+            .get(id.0 / 64)
+            .is_some_and(|word| word & (1 << (id.0 % 64)) != 0)
+    }
+
+    /// Returns the number of IDs in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the IDs in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = BlockId> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| BlockId(i * 64 + bit))
+        })
+    }
+}
+
+impl FromIterator<BlockId> for BlockSet {
+    fn from_iter<T: IntoIterator<Item = BlockId>>(iter: T) -> Self {
+        let mut set = Self::new();
+        for id in iter {
+            set.insert(id);
+        }
+
+        set
+    }
+}
+
 /// Representation of a mined block of transactions.
 #[derive(Debug, Default, Clone)]
 pub struct Block {
@@ -279,13 +830,13 @@ impl Eq for Block {}
 
 impl PartialOrd for Block {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.id.cmp(&other.id))
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Block {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+        self.id.cmp(&other.id)
     }
 }
 
@@ -326,7 +877,25 @@ impl std::fmt::Display for BlockId {
 
 #[cfg(test)]
 mod tests {
-    use super::Blockchain;
+    use super::{
+        Block, BlockId, BlockPublishingError, BlockSet, Blockchain,
+        BlockchainPool,
+    };
+    use crate::{
+        miner::MinerId,
+        transaction::{Transaction, TransactionId},
+    };
+
+    fn publish(chain: &mut Blockchain, id: usize, parent: usize) {
+        chain
+            .publish(Block {
+                id: BlockId(id),
+                parent_id: Some(BlockId(parent)),
+                miner_id: MinerId(1),
+                txns: vec![],
+            })
+            .unwrap();
+    }
 
     #[test]
     fn new_instance_longest_chain() {
@@ -336,4 +905,211 @@ mod tests {
         assert_eq!(lc.len(), 1);
         assert_eq!(lc[0], chain.blocks_by_height[0][0]);
     }
+
+    #[test]
+    fn checkpoints_prevent_deep_forks() {
+        let mut chain = Blockchain::new();
+        chain.finalize_every(Some(2));
+        publish(&mut chain, 1, 0);
+        publish(&mut chain, 2, 1);
+
+        // Block 2 is not final until the longest chain buries it
+        let fork = |id, parent| Block {
+            id: BlockId(id),
+            parent_id: Some(BlockId(parent)),
+            miner_id: MinerId(2),
+            txns: vec![],
+        };
+        assert_eq!(chain.last_checkpoint(), Blockchain::GENESIS_ID);
+        chain.publish(fork(3, 1)).unwrap();
+        publish(&mut chain, 4, 2);
+        assert_eq!(chain.last_checkpoint(), Blockchain::GENESIS_ID);
+        publish(&mut chain, 5, 4);
+        assert_eq!(chain.last_checkpoint(), BlockId(2));
+        assert!(matches!(
+            chain.publish(fork(6, 3)),
+            Err(BlockPublishingError::ConflictsWithCheckpoint {
+                block: BlockId(6),
+                checkpoint: BlockId(2),
+            })
+        ));
+
+        // Forks above the checkpoint are allowed until the next one
+        publish(&mut chain, 7, 2);
+        publish(&mut chain, 8, 5);
+        assert_eq!(chain.last_checkpoint(), BlockId(2));
+        publish(&mut chain, 9, 8);
+        assert_eq!(chain.last_checkpoint(), BlockId(5));
+        assert!(chain.descends_from_checkpoint(BlockId(9)));
+        assert!(!chain.descends_from_checkpoint(BlockId(7)));
+        assert!(chain.publish(fork(10, 7)).is_err());
+
+        // Turning finality on finalizes the existing chain
+        let mut chain = Blockchain::new();
+        for id in 1..=5 {
+            publish(&mut chain, id, id - 1);
+        }
+        chain.finalize_every(Some(2));
+        assert_eq!(chain.last_checkpoint(), BlockId(2));
+        assert_eq!(chain.clone().last_checkpoint(), BlockId(2));
+    }
+
+    #[test]
+    fn extend_from_copies_new_blocks() {
+        let mut chain = Blockchain::new();
+        publish(&mut chain, 1, 0);
+        let mut copy = chain.clone();
+
+        publish(&mut chain, 2, 1);
+        publish(&mut chain, 3, 0);
+        publish(&mut chain, 4, 3);
+        publish(&mut chain, 5, 4);
+
+        assert_eq!(copy.extend_from(&chain).unwrap(), 4);
+        assert_eq!(copy.num_blocks(), chain.num_blocks());
+        assert_eq!(copy.tip(), chain.tip());
+        assert_eq!(copy.extend_from(&chain).unwrap(), 0);
+    }
+
+    #[test]
+    fn extend_from_keeps_blocks_missing_from_other() {
+        let mut chain = Blockchain::new();
+        publish(&mut chain, 1, 0);
+        let mut copy = chain.clone();
+
+        // The copy has a block which the chain lacks, so both have the same
+        // number of blocks once the chain grows
+        publish(&mut copy, 2, 1);
+        publish(&mut chain, 3, 1);
+        assert_eq!(copy.num_blocks(), chain.num_blocks());
+
+        assert_eq!(copy.extend_from(&chain).unwrap(), 1);
+        assert!(copy.contains(BlockId(2)));
+        assert!(copy.contains(BlockId(3)));
+
+        // Blocks which conflict with a checkpoint of the copy are errors
+        publish(&mut chain, 4, 0);
+        copy.finalize_every(Some(1));
+        assert!(matches!(
+            copy.extend_from(&chain),
+            Err(BlockPublishingError::ConflictsWithCheckpoint {
+                block: BlockId(4),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn pooled_blockchains_are_reset() {
+        let mut initial = Blockchain::new();
+        publish(&mut initial, 1, 0);
+        let pool = BlockchainPool::new(initial);
+
+        let mut chain = pool.take();
+        for id in 2..100 {
+            publish(&mut chain, id, id - 1);
+        }
+        let capacity = chain.blocks.capacity();
+        pool.put(chain);
+
+        let chain = pool.take();
+        assert_eq!(chain.num_blocks(), 2);
+        assert_eq!(chain.max_height(), 1);
+        assert_eq!(chain.tip(), &[BlockId(1)]);
+        assert_eq!(chain.blocks.capacity(), capacity);
+        assert!(pool.buffers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn block_set_membership() {
+        let mut set: BlockSet =
+            [3, 64, 0, 200].map(BlockId).into_iter().collect();
+
+        assert!(!set.insert(BlockId(64)));
+        assert!(set.insert(BlockId(63)));
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(BlockId(200)));
+        assert!(!set.contains(BlockId(1)));
+        assert!(!set.contains(BlockId(10_000)));
+
+        let ids: Vec<_> = set.iter().map(|id| id.get()).collect();
+        assert_eq!(ids, vec![0, 3, 63, 64, 200]);
+    }
+
+    #[test]
+    fn children_tracking_toggle() {
+        let mut chain = Blockchain::new();
+        chain.track_children(false);
+        publish(&mut chain, 1, 0);
+        publish(&mut chain, 2, 1);
+        publish(&mut chain, 3, 1);
+        assert!(chain[BlockId(1)].children.is_empty());
+
+        chain.track_children(true);
+        publish(&mut chain, 4, 1);
+        assert_eq!(
+            chain[BlockId(1)].children.as_slice(),
+            [2, 3, 4].map(BlockId)
+        );
+        assert_eq!(chain[BlockId(0)].children.as_slice(), [BlockId(1)]);
+
+        chain.track_children(false);
+        assert!(chain[BlockId(1)].children.is_empty());
+    }
+
+    #[test]
+    fn memory_usage_grows_with_blocks() {
+        let mut chain = Blockchain::new();
+        let empty = chain.memory_usage();
+        publish(&mut chain, 1, 0);
+        publish(&mut chain, 2, 1);
+        let two_blocks = chain.memory_usage();
+
+        chain
+            .publish(Block {
+                id: BlockId(3),
+                parent_id: Some(BlockId(2)),
+                miner_id: MinerId(1),
+                txns: vec![Transaction::new(TransactionId(0), 1.0); 10],
+            })
+            .unwrap();
+
+        assert!(two_blocks > empty);
+        let txns = 10 * std::mem::size_of::<Transaction>();
+        assert!(chain.memory_usage() >= two_blocks + txns);
+    }
+
+    #[test]
+    fn common_ancestor_of_forks() {
+        let mut chain = Blockchain::new();
+        publish(&mut chain, 1, 0);
+        publish(&mut chain, 2, 1);
+        publish(&mut chain, 3, 1);
+        publish(&mut chain, 4, 3);
+
+        let ancestor = |a, b| chain.common_ancestor(BlockId(a), BlockId(b));
+        assert_eq!(ancestor(2, 4), Some(BlockId(1)));
+        assert_eq!(ancestor(4, 3), Some(BlockId(3)));
+        assert_eq!(ancestor(2, 2), Some(BlockId(2)));
+        assert_eq!(ancestor(2, 5), None);
+    }
+
+    #[test]
+    fn fork_lengths_off_longest_chain() {
+        let mut chain = Blockchain::new();
+        publish(&mut chain, 1, 0);
+        publish(&mut chain, 2, 0);
+        publish(&mut chain, 3, 1);
+        publish(&mut chain, 4, 1);
+        publish(&mut chain, 5, 3);
+        publish(&mut chain, 6, 3);
+        publish(&mut chain, 7, 6);
+
+        // Longest chain is 0 <- 1 <- 3 <- 6 <- 7
+        assert_eq!(chain.fork_lengths(), vec![1, 1, 1]);
+
+        publish(&mut chain, 8, 2);
+        publish(&mut chain, 9, 8);
+        assert_eq!(chain.fork_lengths(), vec![3, 1, 1]);
+    }
 }
@@ -0,0 +1,674 @@
+/*!
+Block-DAG protocols ordered by GHOSTDAG
+
+In a block DAG, each block references every block its miner knew of which had
+no children yet, rather than a single parent, so that blocks mined in
+parallel are merged instead of orphaned. A [`BlockDag`] stores these
+references, and [`BlockDag::ghostdag`] orders its blocks with a simplified
+version of the GHOSTDAG rule from PHANTOM: the DAG is greedily partitioned
+into a *blue* set, in which each block has at most `k` blue blocks which are
+neither its ancestors nor its descendants, and a *red* set of the remaining
+blocks. Blocks are then ordered along a chain of selected parents, which
+always extends the parent with the most blue blocks in its past.
+
+Calling
+[`SimulationBuilder::ghostdag`](crate::simulation::SimulationBuilder::ghostdag)
+turns a simulation into a block-DAG simulation. Each block's
+[`parent_id`](crate::blockchain::Block::parent_id) is still published to the
+simulation's [`Blockchain`], which miners see as usual, and the block
+additionally references the blocks returned by
+[`Miner::dag_refs`](crate::miner::Miner::dag_refs) in the simulation's DAG.
+At the end of the simulation, only blue blocks are rewarded, so that revenue
+is computed over the blue part of the ordered set rather than the longest
+chain.
+
+## Example
+```
+use mining_sim::prelude::*;
+
+let data = SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(Selfish::new())
+    .power_values([0.7, 0.3])
+    .ghostdag(3)
+    .rounds(1000)
+    .build()
+    .unwrap()
+    .run_all()
+    .unwrap()
+    .data();
+
+// Every block is ordered, including the genesis block
+let order = data[0].dag_order.as_ref().unwrap();
+assert_eq!(order.len(), data[0].blocks_published);
+```
+*/
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+    blockchain::{BlockHasher, BlockId, BlockSet, Blockchain, ChainQuery},
+    miner::MinerId,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DagError {
+    #[error("block ID {0} already exists in this DAG")]
+    DuplicateBlockID(BlockId),
+    #[error("block {0} does not reference any parents")]
+    NoParentsGiven(BlockId),
+    #[error("block {child}'s parent {parent} was not found in this DAG")]
+    ParentNotFound { child: BlockId, parent: BlockId },
+    #[error("block {child} cannot have block {parent} as its parent")]
+    InvalidParent { child: BlockId, parent: BlockId },
+}
+
+/// A block as stored in a [`BlockDag`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DagBlock {
+    pub id: BlockId,
+    /// IDs of the blocks referenced by this block, in the order given when
+    /// it was inserted. Empty for the genesis block.
+    pub parents: Vec<BlockId>,
+    pub miner_id: MinerId,
+}
+
+/// Directed acyclic graph of blocks, each of which references one or more
+/// parents. Like a [`Blockchain`], a DAG starts with a genesis block with ID
+/// [`Blockchain::GENESIS_ID`], and blocks must have greater IDs than their
+/// parents. See the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct BlockDag {
+    blocks: HashMap<BlockId, DagBlock, BlockHasher>,
+    reachability: HashMap<BlockId, Reachability, BlockHasher>,
+    tips: Vec<BlockId>,
+}
+
+/// Position of a block in the tree formed by each block's parent with the
+/// greatest ID, used to answer [`BlockDag::is_ancestor`] without searching the
+/// past of a block.
+///
+/// The past of a block is the disjoint union of the blocks *merged* by each
+/// block on its path to the genesis block in the tree, where a block merges
+/// its tree parent and the blocks in its past which are not in the past of
+/// its tree parent. So a block is in the past of another exactly when one of
+/// the blocks which merged it is on the other's path in the tree.
+#[derive(Debug, Clone)]
+struct Reachability {
+    /// Parent with the greatest ID, or [`None`] for the genesis block.
+    parent: Option<BlockId>,
+    /// Number of blocks on the path to the genesis block in the tree.
+    depth: usize,
+    /// Ancestor in the tree which is skipped to when searching for the
+    /// ancestor at a given depth. Spaced so that searches take logarithmic
+    /// time (see [`BlockDag::tree_ancestor`]).
+    jump: BlockId,
+    /// Blocks which merged this block.
+    merged_by: Vec<BlockId>,
+}
+
+impl Default for BlockDag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockDag {
+    /// Creates a new DAG containing a genesis block.
+    pub fn new() -> Self {
+        let genesis = DagBlock {
+            id: Blockchain::GENESIS_ID,
+            parents: vec![],
+            miner_id: Blockchain::GENESIS_MINER,
+        };
+        let mut blocks = HashMap::default();
+        blocks.insert(genesis.id, genesis);
+        let mut reachability = HashMap::default();
+        reachability.insert(
+            Blockchain::GENESIS_ID,
+            Reachability {
+                parent: None,
+                depth: 0,
+                jump: Blockchain::GENESIS_ID,
+                merged_by: vec![],
+            },
+        );
+
+        Self {
+            blocks,
+            reachability,
+            tips: vec![Blockchain::GENESIS_ID],
+        }
+    }
+
+    /// Creates a DAG from the blocks of `chain`, each of which references
+    /// only its parent.
+    pub fn from_chain(chain: &dyn ChainQuery) -> Self {
+        let mut blocks: Vec<_> = (1..=chain.max_height())
+            .flat_map(|height| chain.at_height(height).unwrap_or_default())
+            .map(|&id| &chain[id].block)
+            .collect();
+        blocks.sort_unstable_by_key(|block| block.id);
+
+        let mut dag = Self::new();
+        for block in blocks {
+            // Blocks of a chain have exactly one parent with a smaller ID
+            let _ = dag.insert(block.id, block.miner_id, block.parent_id);
+        }
+
+        dag
+    }
+
+    /// ID of the genesis block.
+    #[inline]
+    pub fn genesis(&self) -> BlockId {
+        Blockchain::GENESIS_ID
+    }
+
+    /// Returns a reference to the block with ID `id`.
+    #[inline]
+    pub fn get(&self, id: BlockId) -> Option<&DagBlock> {
+        self.blocks.get(&id)
+    }
+
+    /// Returns true if a block with ID `id` is in the DAG.
+    #[inline]
+    pub fn contains(&self, id: BlockId) -> bool {
+        self.blocks.contains_key(&id)
+    }
+
+    /// Returns the number of blocks in the DAG, including the genesis block.
+    #[inline]
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Returns the IDs of the blocks which have no children, in the order
+    /// they were inserted.
+    #[inline]
+    pub fn tips(&self) -> &[BlockId] {
+        &self.tips
+    }
+
+    /// Adds a block with ID `id`, mined by `miner_id`, which references each
+    /// of `parents`. Repeated parents are ignored.
+    pub fn insert<I>(
+        &mut self,
+        id: BlockId,
+        miner_id: MinerId,
+        parents: I,
+    ) -> Result<(), DagError>
+    where
+        I: IntoIterator<Item = BlockId>,
+    {
+        use DagError::*;
+
+        if self.contains(id) {
+            return Err(DuplicateBlockID(id));
+        }
+
+        let mut unique = Vec::new();
+        for parent in parents {
+            if !self.contains(parent) {
+                return Err(ParentNotFound { child: id, parent });
+            }
+            if id <= parent {
+                return Err(InvalidParent { child: id, parent });
+            }
+            if !unique.contains(&parent) {
+                unique.push(parent);
+            }
+        }
+        if unique.is_empty() {
+            return Err(NoParentsGiven(id));
+        }
+
+        self.insert_reachability(id, &unique);
+        self.tips.retain(|tip| !unique.contains(tip));
+        self.tips.push(id);
+        self.blocks.insert(
+            id,
+            DagBlock {
+                id,
+                parents: unique,
+                miner_id,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Returns true if the block with ID `ancestor` is in the past of the
+    /// block with ID `id`, meaning that it can be reached from `id` by
+    /// following parent references. A block is not its own ancestor.
+    pub fn is_ancestor(&self, ancestor: BlockId, id: BlockId) -> bool {
+        let Some(ancestor) = self.reachability.get(&ancestor) else {
+            return false;
+        };
+
+        // Blocks have greater IDs than their ancestors
+        ancestor.merged_by.iter().any(|&merger| {
+            merger <= id
+                && self.tree_ancestor(id, self.reachability[&merger].depth)
+                    == Some(merger)
+        })
+    }
+
+    /// Returns the ancestor of the block `id` with the given depth in the tree
+    /// described by [`Reachability`], or [`None`] if there is none. Follows
+    /// [jump pointers](https://doi.org/10.1016/0020-0190(83)90106-0) where
+    /// possible, taking logarithmic time in the depth of `id`.
+    fn tree_ancestor(&self, mut id: BlockId, depth: usize) -> Option<BlockId> {
+        let mut reachability = self.reachability.get(&id)?;
+        while reachability.depth > depth {
+            id = match self.reachability[&reachability.jump].depth >= depth {
+                true => reachability.jump,
+                false => reachability.parent?,
+            };
+            reachability = &self.reachability[&id];
+        }
+
+        (reachability.depth == depth).then_some(id)
+    }
+
+    /// Adds the [`Reachability`] of the new block `id` which references
+    /// `parents`, and records it as merging the blocks in its past which are
+    /// not in the past of its parent with the greatest ID.
+    fn insert_reachability(&mut self, id: BlockId, parents: &[BlockId]) {
+        let parent = *parents.iter().max().expect("blocks have a parent");
+        let mut merged = vec![];
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from_iter(parents.iter().copied());
+
+        while let Some(curr) = queue.pop_front() {
+            if !visited.insert(curr) {
+                continue;
+            }
+            if curr != parent && self.is_ancestor(curr, parent) {
+                continue;
+            }
+            merged.push(curr);
+            if curr != parent {
+                queue.extend(self.blocks[&curr].parents.iter().copied());
+            }
+        }
+
+        for block in merged {
+            let reachability = self.reachability.get_mut(&block).unwrap();
+            reachability.merged_by.push(id);
+        }
+
+        // Skip to the end of two consecutive jumps which span equally many
+        // blocks, merging them into one twice as long, otherwise to the parent
+        let tree_parent = &self.reachability[&parent];
+        let jump = &self.reachability[&tree_parent.jump];
+        let jump = match tree_parent.depth - jump.depth
+            == jump.depth - self.reachability[&jump.jump].depth
+        {
+            true => jump.jump,
+            false => parent,
+        };
+        self.reachability.insert(
+            id,
+            Reachability {
+                parent: Some(parent),
+                depth: tree_parent.depth + 1,
+                jump,
+                merged_by: vec![],
+            },
+        );
+    }
+
+    /// Orders the blocks of the DAG with the GHOSTDAG rule, where `k` is the
+    /// greatest number of blue blocks which may be concurrent with each blue
+    /// block. With `k = 0`, the blue blocks form a chain.
+    pub fn ghostdag(&self, k: usize) -> Ghostdag {
+        let mut ids: Vec<_> = self.blocks.keys().copied().collect();
+        ids.sort_unstable();
+
+        // Parents have smaller IDs than their children, so sorting by ID
+        // sorts the blocks topologically
+        let mut data = HashMap::new();
+        data.insert(self.genesis(), GhostdagData::default());
+        for &id in ids.iter().skip(1) {
+            let block_data =
+                self.ghostdag_data(&self.blocks[&id].parents, &data, k);
+            data.insert(id, block_data);
+        }
+        let virtual_data = self.ghostdag_data(&self.tips, &data, k);
+
+        // Walk the chain of selected parents down from the virtual block,
+        // which points to every tip
+        let mut chain = vec![];
+        let mut curr = &virtual_data;
+        while let Some(parent) = curr.selected_parent {
+            chain.push((parent, curr));
+            curr = &data[&parent];
+        }
+
+        let mut order = vec![self.genesis()];
+        let mut blue = BlockSet::from_iter([self.genesis()]);
+        for (selected_parent, block_data) in chain.into_iter().rev() {
+            if selected_parent != self.genesis() {
+                order.push(selected_parent);
+            }
+            let mut mergeset: Vec<_> = block_data.mergeset_blues[1..]
+                .iter()
+                .chain(block_data.mergeset_reds.iter())
+                .copied()
+                .collect();
+            mergeset.sort_unstable();
+            order.extend(mergeset);
+            for &id in block_data.mergeset_blues.iter() {
+                blue.insert(id);
+            }
+        }
+
+        Ghostdag { order, blue }
+    }
+
+    /// Computes the GHOSTDAG data of a block which references `parents`,
+    /// given the data of every block in its past.
+    fn ghostdag_data(
+        &self,
+        parents: &[BlockId],
+        data: &HashMap<BlockId, GhostdagData>,
+        k: usize,
+    ) -> GhostdagData {
+        let selected_parent = *parents
+            .iter()
+            .max_by_key(|&&id| (data[&id].blue_score, block_hash(id)))
+            .expect("blocks have at least one parent");
+
+        let mut new = GhostdagData {
+            selected_parent: Some(selected_parent),
+            blue_score: 0,
+            mergeset_blues: vec![selected_parent],
+            mergeset_reds: vec![],
+            blues_anticone_sizes: HashMap::from([(selected_parent, 0)]),
+        };
+
+        for candidate in self.mergeset(selected_parent, parents) {
+            match self.blue_anticone(&new, candidate, data, k) {
+                Some(anticone) => {
+                    new.blues_anticone_sizes.insert(candidate, anticone.len());
+                    for (blue, size) in anticone {
+                        new.blues_anticone_sizes.insert(blue, size + 1);
+                    }
+                    new.mergeset_blues.push(candidate);
+                }
+                None => new.mergeset_reds.push(candidate),
+            }
+        }
+        new.blue_score =
+            data[&selected_parent].blue_score + new.mergeset_blues.len();
+
+        new
+    }
+
+    /// Returns the blocks in the past of a block which references `parents`,
+    /// but not in the past of `selected_parent`, in topological order.
+    fn mergeset(
+        &self,
+        selected_parent: BlockId,
+        parents: &[BlockId],
+    ) -> Vec<BlockId> {
+        let mut mergeset = vec![];
+        let mut visited = HashSet::new();
+        let mut queue: VecDeque<_> = parents
+            .iter()
+            .copied()
+            .filter(|&id| id != selected_parent)
+            .collect();
+
+        while let Some(id) = queue.pop_front() {
+            if id == selected_parent
+                || !visited.insert(id)
+                || self.is_ancestor(id, selected_parent)
+            {
+                continue;
+            }
+            mergeset.push(id);
+            queue.extend(self.blocks[&id].parents.iter().copied());
+        }
+        mergeset.sort_unstable();
+
+        mergeset
+    }
+
+    /// Returns the blue blocks concurrent with `candidate`, along with their
+    /// numbers of concurrent blue blocks, if `candidate` can be added to the
+    /// blue set of the block with data `new` without breaking the limit of
+    /// `k` concurrent blue blocks. Returns [`None`] otherwise.
+    fn blue_anticone(
+        &self,
+        new: &GhostdagData,
+        candidate: BlockId,
+        data: &HashMap<BlockId, GhostdagData>,
+        k: usize,
+    ) -> Option<Vec<(BlockId, usize)>> {
+        if new.mergeset_blues.len() > k {
+            return None;
+        }
+
+        let mut anticone = vec![];
+        let mut chain_block = (None, new);
+        loop {
+            let (id, block_data) = chain_block;
+            // Blue blocks below an ancestor of the candidate are in its past
+            if id.is_some_and(|id| self.is_ancestor(id, candidate)) {
+                break;
+            }
+            for &blue in block_data.mergeset_blues.iter() {
+                if self.is_ancestor(blue, candidate) {
+                    continue;
+                }
+                let size = blues_anticone_size(blue, new, data);
+                if anticone.len() == k || size == k {
+                    return None;
+                }
+                anticone.push((blue, size));
+            }
+
+            match block_data.selected_parent {
+                Some(parent) => chain_block = (Some(parent), &data[&parent]),
+                None => break,
+            }
+        }
+
+        Some(anticone)
+    }
+}
+
+/// Stands in for the hash of the block with ID `id`, which breaks ties
+/// between parents with equal blue scores, so that ties don't favor blocks
+/// mined earlier or later.
+fn block_hash(id: BlockId) -> u64 {
+    // SplitMix64 finalizer
+    let mut x = (id.0 as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    x ^ (x >> 31)
+}
+
+/// Returns the number of blue blocks concurrent with the blue block `blue`,
+/// from the perspective of the block with data `new`.
+fn blues_anticone_size(
+    blue: BlockId,
+    new: &GhostdagData,
+    data: &HashMap<BlockId, GhostdagData>,
+) -> usize {
+    let mut curr = new;
+    loop {
+        if let Some(&size) = curr.blues_anticone_sizes.get(&blue) {
+            return size;
+        }
+        match curr.selected_parent {
+            Some(parent) => curr = &data[&parent],
+            None => return 0,
+        }
+    }
+}
+
+/// Intermediate results of the GHOSTDAG rule for a single block.
+#[derive(Debug, Default)]
+struct GhostdagData {
+    /// Parent with the most blue blocks in its past, or [`None`] for the
+    /// genesis block.
+    selected_parent: Option<BlockId>,
+    /// Number of blue blocks in the past of the block.
+    blue_score: usize,
+    /// Blue blocks in the past of the block and not in the past of its
+    /// selected parent, starting with the selected parent.
+    mergeset_blues: Vec<BlockId>,
+    /// Red blocks in the past of the block and not in the past of its
+    /// selected parent.
+    mergeset_reds: Vec<BlockId>,
+    /// Number of concurrent blue blocks of each block in `mergeset_blues`,
+    /// and of earlier blue blocks whose numbers changed.
+    blues_anticone_sizes: HashMap<BlockId, usize>,
+}
+
+/// Ordering of the blocks of a [`BlockDag`]. See [`BlockDag::ghostdag`].
+#[derive(Debug, Clone)]
+pub struct Ghostdag {
+    /// Every block of the DAG, starting with the genesis block.
+    pub order: Vec<BlockId>,
+    /// Blocks in the blue set, including the genesis block.
+    pub blue: BlockSet,
+}
+
+impl Ghostdag {
+    /// Returns true if the block with ID `id` is in the blue set.
+    pub fn is_blue(&self, id: BlockId) -> bool {
+        self.blue.contains(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{blockchain::BlockId, miner::MinerId};
+
+    use super::{BlockDag, DagError};
+
+    fn insert(dag: &mut BlockDag, id: usize, parents: &[usize]) {
+        dag.insert(
+            BlockId(id),
+            MinerId(1),
+            parents.iter().map(|&parent| BlockId(parent)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn insert_tracks_tips() {
+        let mut dag = BlockDag::new();
+        insert(&mut dag, 1, &[0]);
+        insert(&mut dag, 2, &[0]);
+        assert_eq!(dag.tips(), [BlockId(1), BlockId(2)]);
+        insert(&mut dag, 3, &[1, 2, 1]);
+        assert_eq!(dag.tips(), [BlockId(3)]);
+        assert_eq!(dag.get(BlockId(3)).unwrap().parents.len(), 2);
+
+        assert!(dag.is_ancestor(BlockId(0), BlockId(3)));
+        assert!(dag.is_ancestor(BlockId(2), BlockId(3)));
+        assert!(!dag.is_ancestor(BlockId(1), BlockId(2)));
+        assert!(!dag.is_ancestor(BlockId(3), BlockId(3)));
+
+        let mine = |dag: &mut BlockDag, id, parents: Vec<usize>| {
+            dag.insert(
+                BlockId(id),
+                MinerId(1),
+                parents.into_iter().map(BlockId),
+            )
+        };
+        assert!(matches!(
+            mine(&mut dag, 3, vec![0]),
+            Err(DagError::DuplicateBlockID(_))
+        ));
+        assert!(matches!(
+            mine(&mut dag, 4, vec![]),
+            Err(DagError::NoParentsGiven(_))
+        ));
+        assert!(matches!(
+            mine(&mut dag, 4, vec![9]),
+            Err(DagError::ParentNotFound { .. })
+        ));
+        assert!(matches!(
+            mine(&mut dag, 4, vec![3, 5]),
+            Err(DagError::ParentNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn ghostdag_colors_wide_anticones_red() {
+        // Blocks 1, 2, and 3 are mined in parallel, then merged by block 4.
+        // Block 5 is withheld and mined on the genesis block after them.
+        let mut dag = BlockDag::new();
+        insert(&mut dag, 1, &[0]);
+        insert(&mut dag, 2, &[0]);
+        insert(&mut dag, 3, &[0]);
+        insert(&mut dag, 4, &[1, 2, 3]);
+        insert(&mut dag, 5, &[0]);
+        insert(&mut dag, 6, &[4, 5]);
+
+        // With k = 2, blocks 1, 2, and 3 are blue, but block 5 is concurrent
+        // with all of them. The parallel blocks are ordered after whichever
+        // of them is selected by block 4.
+        let ghostdag = dag.ghostdag(2);
+        let order: Vec<_> = ghostdag.order.iter().map(|id| id.get()).collect();
+        assert_eq!(order.len(), 7);
+        assert_eq!(order[0], 0);
+        assert_eq!(order[4..], [4, 5, 6]);
+        assert!((0..=4).all(|id| ghostdag.is_blue(BlockId(id))));
+        assert!(!ghostdag.is_blue(BlockId(5)));
+        assert!(ghostdag.is_blue(BlockId(6)));
+
+        // With k = 0, only one of the parallel blocks is blue
+        let ghostdag = dag.ghostdag(0);
+        let blue = |ids: &[usize]| {
+            ids.iter()
+                .filter(|&&id| ghostdag.is_blue(BlockId(id)))
+                .count()
+        };
+        assert_eq!(blue(&[0, 4, 6]), 3);
+        assert_eq!(blue(&[1, 2, 3]), 1);
+        assert_eq!(blue(&[5]), 0);
+        assert_eq!(ghostdag.order.len(), 7);
+    }
+
+    #[test]
+    fn ghostdag_scales_to_large_dags() {
+        // Three blocks are mined in parallel at each of 2,500 levels, each
+        // merging the level below, while a fourth miner withholds a chain
+        // mined alongside them. The withheld chain is merged at the end.
+        const LEVELS: usize = 2_500;
+        let mut dag = BlockDag::new();
+        let mut below = vec![0];
+        for level in 0..LEVELS {
+            let ids: Vec<_> = (1..=3).map(|i| 4 * level + i).collect();
+            for &id in ids.iter() {
+                insert(&mut dag, id, &below);
+            }
+            below = ids;
+        }
+        for level in 0..LEVELS {
+            let parent = level.checked_sub(1).map_or(0, |l| 4 * l + 4);
+            insert(&mut dag, 4 * level + 4, &[parent]);
+        }
+        let last = 4 * LEVELS + 1;
+        insert(&mut dag, last, &[below[0], below[1], below[2], last - 1]);
+        assert!(dag.num_blocks() > 10_000);
+
+        assert!(dag.is_ancestor(BlockId(1), BlockId(last)));
+        assert!(dag.is_ancestor(BlockId(4), BlockId(last)));
+        assert!(!dag.is_ancestor(BlockId(4), BlockId(last - 2)));
+        assert!(!dag.is_ancestor(BlockId(5), BlockId(8)));
+
+        let ghostdag = dag.ghostdag(3);
+        assert_eq!(ghostdag.order.len(), dag.num_blocks());
+        assert_eq!(ghostdag.blue.len(), 3 * LEVELS + 2);
+        assert!((0..LEVELS).all(|l| !ghostdag.is_blue(BlockId(4 * l + 4))));
+    }
+}
@@ -0,0 +1,592 @@
+/*!
+Step-by-step mining environment for reinforcement learning
+
+A [`MiningEnv`] exposes the mining game with the interface of a
+[Gym](https://gymnasium.farama.org) environment, so that external
+reinforcement learning libraries can train block withholding policies
+against the crate's built-in strategies. The environment controls an agent
+with mining power `alpha`, which plays against opponent [`Miner`]s sharing
+the remaining mining power equally.
+
+Each call to [`MiningEnv::step`] applies one of the agent's publishing
+decisions, given as an [`EnvAction`], and then simulates one round: a
+proposer is sampled by mining power, blocks mined by the agent are withheld,
+and each opponent acts on the blockchain as in a simulation. The returned
+[`Step`] holds an [`Observation`] of the race between the agent's branch and
+the longest public chain, similar to the states of
+[`WithholdingMdp`](crate::analysis::mdp::WithholdingMdp), and a reward equal
+to the change in the agent's revenue, so that the rewards of an episode sum
+to the agent's final revenue.
+
+The agent acts last in each round, after every opponent, and sees every
+block. While the agent has no withheld blocks, it mines on the tip of the
+longest chain, preferring its own blocks.
+
+## Example
+```
+use mining_sim::{
+    env::{EnvAction, MiningEnv},
+    prelude::*,
+};
+
+let mut env = MiningEnv::new(0.3, 0.0)
+    .unwrap()
+    .add_opponent(Honest::new())
+    .rounds(1000)
+    .seed(1);
+
+// Publish every block immediately, like an honest miner
+let mut observation = env.reset(None);
+let mut revenue = 0.0;
+loop {
+    let step = env.step(EnvAction::Publish(observation.withheld));
+    revenue += step.reward;
+    observation = step.observation;
+    if step.done {
+        break;
+    }
+}
+
+assert!((revenue - env.revenue()).abs() < 1e-9);
+assert!((revenue - 0.3).abs() < 0.1);
+```
+*/
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    Rng, SeedableRng,
+};
+use thiserror::Error;
+
+use crate::{
+    analysis::mdp::Fork,
+    blockchain::{Block, BlockId, Blockchain},
+    miner::{honest::Honest, Action, Miner, MinerId},
+    power_dist::PowerValue,
+    tie_breaker::TieBreaker,
+    view::ChainView,
+};
+
+/// Error returned by [`MiningEnv::new`].
+#[derive(Debug, Error)]
+pub enum EnvError {
+    #[error("alpha value {0} is not in the range 0.0..1.0")]
+    BadAlpha(f64),
+    #[error("gamma value {0} is not in the range 0.0..=1.0")]
+    BadGamma(f64),
+}
+
+/// Publishing decision of the agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnvAction {
+    /// Keep every withheld block private.
+    Wait,
+    /// Publish the given number of the oldest withheld blocks, or every
+    /// withheld block if there are fewer.
+    Publish(usize),
+    /// Abandon every withheld block, and mine on the tip of the longest
+    /// chain.
+    Adopt,
+}
+
+/// The agent's view of the race between its branch and the longest public
+/// chain, measured from their common ancestor.
+///
+/// While the agent has no withheld blocks, `attacker` and `honest` are both
+/// 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Observation {
+    /// Number of rounds simulated in the current episode.
+    pub round: usize,
+    /// Length of the agent's branch since the fork, including withheld
+    /// blocks.
+    pub attacker: usize,
+    /// Length of the longest public chain since the fork.
+    pub honest: usize,
+    /// Number of blocks mined by the agent which are not yet published.
+    pub withheld: usize,
+    pub fork: Fork,
+}
+
+impl Observation {
+    /// Returns the observation as a feature vector of `attacker`, `honest`,
+    /// `withheld`, and `fork`, with [`Fork::Irrelevant`],
+    /// [`Fork::Relevant`], and [`Fork::Active`] encoded as 0, 1, and 2.
+    pub fn to_array(&self) -> [f64; 4] {
+        let fork = match self.fork {
+            Fork::Irrelevant => 0.0,
+            Fork::Relevant => 1.0,
+            Fork::Active => 2.0,
+        };
+
+        [
+            self.attacker as f64,
+            self.honest as f64,
+            self.withheld as f64,
+            fork,
+        ]
+    }
+}
+
+/// Outcome of [`MiningEnv::step`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub observation: Observation,
+    /// Change in the agent's revenue since the previous step.
+    pub reward: f64,
+    /// Whether the episode has reached [`MiningEnv::rounds`] rounds. The
+    /// environment must be reset before stepping again.
+    pub done: bool,
+}
+
+/// Mining game played one round at a time. See the [module docs](self).
+#[derive(Debug)]
+pub struct MiningEnv {
+    alpha: PowerValue,
+    gamma: f64,
+    rounds: usize,
+    opponents: Vec<Box<dyn Miner>>,
+    seeder: StdRng,
+    episode: Option<Episode>,
+}
+
+impl MiningEnv {
+    pub const DEFAULT_ROUNDS: usize = 1000;
+
+    /// Creates an environment for an agent with mining power `alpha`, where
+    /// a `gamma` fraction of the opponents' mining power mines on the
+    /// agent's block during a tie. See
+    /// [`SimulationBuilder::gamma`](crate::simulation::SimulationBuilder::gamma).
+    ///
+    /// Unless opponents are added, the agent plays against a single
+    /// [`Honest`] miner.
+    pub fn new(alpha: PowerValue, gamma: f64) -> Result<Self, EnvError> {
+        if !(0.0..1.0).contains(&alpha) {
+            return Err(EnvError::BadAlpha(alpha));
+        }
+        if !(0.0..=1.0).contains(&gamma) {
+            return Err(EnvError::BadGamma(gamma));
+        }
+
+        Ok(Self {
+            alpha,
+            gamma,
+            rounds: Self::DEFAULT_ROUNDS,
+            opponents: vec![],
+            seeder: StdRng::from_entropy(),
+            episode: None,
+        })
+    }
+
+    /// Adds an opponent. Opponents have IDs starting at 1 in the order they
+    /// were added, and the agent has the next ID.
+    pub fn add_opponent<M: Miner + 'static>(self, miner: M) -> Self {
+        self.add_boxed_opponent(Box::new(miner))
+    }
+
+    /// Adds an opponent which is already boxed, such as one created by
+    /// [`registry::create`](crate::miner::registry::create).
+    pub fn add_boxed_opponent(mut self, miner: Box<dyn Miner>) -> Self {
+        self.opponents.push(miner);
+        self.episode = None;
+
+        self
+    }
+
+    /// Sets the number of rounds in each episode.
+    ///
+    /// # Panics
+    /// Panics if `rounds` is 0.
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        assert!(rounds > 0, "episodes must last at least one round");
+        self.rounds = rounds;
+
+        self
+    }
+
+    /// Seeds the random number generator which seeds each episode, so that a
+    /// sequence of episodes is reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seeder = StdRng::seed_from_u64(seed);
+
+        self
+    }
+
+    /// Returns the [`MinerId`] of the agent.
+    pub fn agent(&self) -> MinerId {
+        MinerId(self.opponents.len().max(1) + 1)
+    }
+
+    /// Starts a new episode, returning its first observation. If `seed` is
+    /// given, it reseeds the environment as in [`MiningEnv::seed`].
+    pub fn reset(&mut self, seed: Option<u64>) -> Observation {
+        if let Some(seed) = seed {
+            self.seeder = StdRng::seed_from_u64(seed);
+        }
+        if self.opponents.is_empty() {
+            self.opponents.push(Box::new(Honest::new()));
+        }
+
+        let agent = self.agent();
+        let mut rng = StdRng::seed_from_u64(self.seeder.gen());
+        let mut opponents = self.opponents.clone();
+        let tie_breaker = TieBreaker::FavorLatestProb(self.gamma);
+        for (miner, id) in opponents.iter_mut().zip(1..) {
+            miner.set_id(MinerId(id));
+            miner.set_default_tie_breaker(tie_breaker.clone());
+            miner.set_rng_seed(rng.gen());
+            miner.on_simulation_start();
+        }
+
+        let opponent_power = (1.0 - self.alpha) / opponents.len() as PowerValue;
+        let mut power_values = vec![opponent_power; opponents.len()];
+        power_values.push(self.alpha);
+        let proposers = WeightedIndex::new(power_values)
+            .expect("power values are valid for any alpha in 0.0..1.0");
+
+        let chain = Blockchain::new();
+        let tip = chain.tip()[0];
+        let episode = Episode {
+            agent,
+            chain,
+            opponents,
+            proposers,
+            rng,
+            round: 0,
+            withheld: vec![],
+            base: tip,
+            tip,
+            agent_blocks: 0,
+            latest_proposer: None,
+        };
+        let observation = episode.observation();
+        self.episode = Some(episode);
+
+        observation
+    }
+
+    /// Applies `action`, and simulates the next round of the episode.
+    ///
+    /// # Panics
+    /// Panics if the environment was not [reset](MiningEnv::reset) since it
+    /// was created or modified, or if the episode is done.
+    pub fn step(&mut self, action: EnvAction) -> Step {
+        let rounds = self.rounds;
+        let episode = self
+            .episode
+            .as_mut()
+            .expect("MiningEnv::reset must be called before stepping");
+        assert!(
+            episode.round < rounds,
+            "episode is done, and MiningEnv::reset must be called"
+        );
+
+        let revenue = episode.revenue();
+        episode.apply(action);
+        episode.simulate_round();
+        episode.update_revenue();
+
+        Step {
+            observation: episode.observation(),
+            reward: episode.revenue() - revenue,
+            done: episode.round >= rounds,
+        }
+    }
+
+    /// Returns the current observation, if an episode has started.
+    pub fn observation(&self) -> Option<Observation> {
+        self.episode.as_ref().map(Episode::observation)
+    }
+
+    /// Returns the fraction of the blocks on the longest chain, other than
+    /// the genesis block, which were mined by the agent. Returns 0 before the
+    /// first episode.
+    pub fn revenue(&self) -> f64 {
+        self.episode.as_ref().map_or(0.0, Episode::revenue)
+    }
+
+    /// Returns the blockchain of the current episode, if an episode has
+    /// started. Blocks withheld by the agent are not included.
+    pub fn blockchain(&self) -> Option<&Blockchain> {
+        self.episode.as_ref().map(|episode| &episode.chain)
+    }
+}
+
+/// State of an episode of a [`MiningEnv`].
+#[derive(Debug)]
+struct Episode {
+    agent: MinerId,
+    chain: Blockchain,
+    opponents: Vec<Box<dyn Miner>>,
+    proposers: WeightedIndex<PowerValue>,
+    rng: StdRng,
+    round: usize,
+    /// Blocks mined by the agent which are not yet published, oldest first.
+    withheld: Vec<Block>,
+    /// Published block which the oldest withheld block points to.
+    base: BlockId,
+    /// Tip of the longest chain as of the last revenue update.
+    tip: BlockId,
+    /// Number of the agent's blocks on the chain ending at `tip`.
+    agent_blocks: usize,
+    latest_proposer: Option<MinerId>,
+}
+
+impl Episode {
+    fn apply(&mut self, action: EnvAction) {
+        match action {
+            EnvAction::Wait => {}
+            EnvAction::Publish(count) => {
+                let count = count.min(self.withheld.len());
+                for block in self.withheld.drain(..count) {
+                    self.base = block.id;
+                    self.chain
+                        .publish(block)
+                        .expect("withheld blocks point to published blocks");
+                }
+            }
+            EnvAction::Adopt => self.withheld.clear(),
+        }
+    }
+
+    fn simulate_round(&mut self) {
+        self.round += 1;
+        let proposer = MinerId(self.proposers.sample(&mut self.rng) + 1);
+        self.latest_proposer = Some(proposer);
+
+        if proposer == self.agent {
+            let parent = match self.withheld.last() {
+                Some(block) => block.id,
+                None => {
+                    self.base = TieBreaker::FavorMiner(self.agent)
+                        .choose_with(&mut self.rng, &self.chain);
+                    self.base
+                }
+            };
+            self.withheld.push(Block {
+                id: BlockId(self.round),
+                parent_id: Some(parent),
+                miner_id: self.agent,
+                txns: vec![],
+            });
+        }
+
+        for miner in self.opponents.iter_mut() {
+            let block_mined =
+                (proposer == miner.id()).then_some(BlockId(self.round));
+            let blocks = match miner
+                .get_action(&ChainView::full(&self.chain), block_mined)
+            {
+                Action::Wait => vec![],
+                Action::Publish(block) => vec![block],
+                Action::PublishSet(blocks) => blocks,
+            };
+
+            for block in blocks {
+                assert_eq!(
+                    block.miner_id,
+                    miner.id(),
+                    "Miner {} published block with wrong MinerId",
+                    miner.id()
+                );
+                self.chain
+                    .publish(block)
+                    .expect("opponents publish valid blocks");
+            }
+        }
+    }
+
+    /// Moves `tip` to the tip of the longest chain, counting the agent's
+    /// blocks which joined or left the longest chain.
+    fn update_revenue(&mut self) {
+        let tip = self.chain.tip()[0];
+        if tip == self.tip {
+            return;
+        }
+
+        let ancestor = self.chain.common_ancestor(tip, self.tip).unwrap();
+        let agent_blocks_since = |block| {
+            self.chain
+                .ancestors_of(block)
+                .take_while(|&id| id != ancestor)
+                .filter(|&id| self.chain[id].block.miner_id == self.agent)
+                .count()
+        };
+        self.agent_blocks = self.agent_blocks + agent_blocks_since(tip)
+            - agent_blocks_since(self.tip);
+        self.tip = tip;
+    }
+
+    fn revenue(&self) -> f64 {
+        match self.chain[self.tip].height {
+            0 => 0.0,
+            height => self.agent_blocks as f64 / height as f64,
+        }
+    }
+
+    fn observation(&self) -> Observation {
+        let mut observation = Observation {
+            round: self.round,
+            attacker: 0,
+            honest: 0,
+            withheld: self.withheld.len(),
+            fork: Fork::Irrelevant,
+        };
+        if self.withheld.is_empty() {
+            return observation;
+        }
+
+        // Opponents' blocks are preferred at the tip, so that a match by the
+        // agent shows up as a tie
+        let tips = self.chain.tip();
+        let public = tips
+            .iter()
+            .copied()
+            .find(|&id| self.chain[id].block.miner_id != self.agent)
+            .unwrap_or(tips[0]);
+        let fork = self.chain.common_ancestor(self.base, public).unwrap();
+        let fork_height = self.chain[fork].height;
+        let base_height = self.chain[self.base].height;
+
+        observation.attacker = base_height + self.withheld.len() - fork_height;
+        observation.honest = self.chain[public].height - fork_height;
+        observation.fork = if fork != self.base && tips.contains(&self.base) {
+            Fork::Active
+        } else if self.latest_proposer != Some(self.agent) {
+            Fork::Relevant
+        } else {
+            Fork::Irrelevant
+        };
+
+        observation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnvAction, EnvError, MiningEnv, Observation};
+    use crate::{
+        analysis::mdp::Fork,
+        blockchain::BlockId,
+        miner::{honest::Honest, selfish::Selfish},
+    };
+
+    /// Runs one episode with `policy`, returning the sum of its rewards.
+    fn run_episode<F>(env: &mut MiningEnv, mut policy: F) -> f64
+    where
+        F: FnMut(&Observation) -> EnvAction,
+    {
+        let mut observation = env.reset(None);
+        let mut total = 0.0;
+        loop {
+            let step = env.step(policy(&observation));
+            assert!(step.observation.attacker >= step.observation.withheld);
+            total += step.reward;
+            observation = step.observation;
+            if step.done {
+                assert_eq!(observation.round, 10_000);
+                return total;
+            }
+        }
+    }
+
+    /// Selfish mining as described by Eyal and Sirer.
+    fn selfish(observation: &Observation) -> EnvAction {
+        let Observation {
+            attacker,
+            honest,
+            withheld,
+            fork,
+            ..
+        } = *observation;
+
+        match (attacker, honest) {
+            (a, h) if a < h => EnvAction::Adopt,
+            (a, h) if a == h && h > 0 && fork == Fork::Relevant => {
+                EnvAction::Publish(withheld)
+            }
+            (a, h) if a == h + 1 && h > 0 => EnvAction::Publish(withheld),
+            (a, h) if h > 0 && a > h + 1 && fork == Fork::Relevant => {
+                EnvAction::Publish(withheld - (a - h))
+            }
+            _ => EnvAction::Wait,
+        }
+    }
+
+    #[test]
+    fn policies_earn_expected_revenue() {
+        let mut env = MiningEnv::new(0.4, 0.5).unwrap().rounds(10_000).seed(1);
+
+        let honest = run_episode(&mut env, |o| EnvAction::Publish(o.withheld));
+        assert!((honest - env.revenue()).abs() < 1e-9);
+        assert!((honest - 0.4).abs() < 0.03, "{}", honest);
+        let chain = env.blockchain().unwrap();
+        assert_eq!(chain.num_blocks(), chain.max_height() + 1);
+
+        let selfish = run_episode(&mut env, selfish);
+        assert!((selfish - env.revenue()).abs() < 1e-9);
+        assert!(selfish > 0.45, "{}", selfish);
+
+        let idle = run_episode(&mut env, |_| EnvAction::Adopt);
+        assert_eq!(idle, 0.0);
+    }
+
+    #[test]
+    fn observations_track_withheld_blocks() {
+        let mut env = MiningEnv::new(0.5, 0.0)
+            .unwrap()
+            .add_opponent(Honest::new())
+            .add_opponent(Selfish::new())
+            .seed(2);
+        assert_eq!(env.agent().get(), 3);
+
+        let first = env.reset(None);
+        assert_eq!(first, env.observation().unwrap());
+        assert_eq!((first.round, first.withheld, first.attacker), (0, 0, 0));
+
+        let mut mined = 0;
+        for round in 1..=20 {
+            let step = env.step(EnvAction::Wait);
+            mined = step.observation.withheld;
+            assert_eq!(step.observation.round, round);
+            assert!(step.reward <= 0.0);
+        }
+        assert!(mined > 0);
+
+        let step = env.step(EnvAction::Publish(usize::MAX));
+        let chain = env.blockchain().unwrap();
+        let published = (1..=21)
+            .filter_map(|id| chain.get(BlockId::from(id)))
+            .filter(|data| data.block.miner_id == env.agent())
+            .count();
+        assert_eq!(published, mined);
+        assert!(step.observation.withheld <= 1);
+
+        let mut trajectory = |seed| {
+            env.reset(Some(seed));
+            (0..100)
+                .map(|_| env.step(EnvAction::Publish(1)).observation)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(trajectory(3), trajectory(3));
+    }
+
+    #[test]
+    fn bad_parameters() {
+        assert!(matches!(
+            MiningEnv::new(1.0, 0.0),
+            Err(EnvError::BadAlpha(_))
+        ));
+        assert!(matches!(
+            MiningEnv::new(0.3, f64::NAN),
+            Err(EnvError::BadGamma(_))
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "reset")]
+    fn step_before_reset() {
+        MiningEnv::new(0.3, 0.0).unwrap().step(EnvAction::Wait);
+    }
+}
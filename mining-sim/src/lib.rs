@@ -5,12 +5,56 @@ Simulator for a game theory-based model of blockchain mining.
 By default, `rayon` is enabled.
 - `rayon`: Enables the parallelization of simulation runs using
   [`rayon`](https://docs.rs/rayon/1.9), typically resulting in a signficant
-  performance boost.
-- `block-children`: Enables the tracking of the blocks which point to a
-  particular [`Block`](blockchain::Block) in a
-  [`Blockchain`](blockchain::Blockchain) via
-  [`BlockData::children`](blockchain::BlockData::children). This greatly
-  increases memory usage, and can affect runtime performance.
+  performance boost. Has no effect when compiling to WebAssembly, where
+  simulations always run on the calling thread.
+- `block-children`: Makes new [`Blockchain`](blockchain::Blockchain)s track
+  the blocks which point to a particular [`Block`](blockchain::Block) via
+  [`BlockData::children`](blockchain::BlockData::children). Tracking can
+  also be turned on or off at runtime with
+  [`Blockchain::track_children`](blockchain::Blockchain::track_children) or
+  [`SimulationBuilder::track_children`](simulation::SimulationBuilder::track_children).
+  Blocks with at most two children store them inline, but tracking can still
+  increase memory usage, and can affect runtime performance.
+- `fast-hash`: Hashes the maps keyed by block ID in a
+  [`Blockchain`](blockchain::Blockchain) and in simulation outputs with
+  [FxHash](https://docs.rs/rustc-hash/2) instead of SipHash. See
+  [`BlockHasher`](blockchain::BlockHasher). Speeds up strategies which
+  frequently walk the blockchain.
+- `arrow`: Enables the conversion of a
+  [`ResultsTable`](results::ResultsTable) into an Arrow `RecordBatch` via
+  [`ResultsTable::to_record_batch`](results::ResultsTable::to_record_batch),
+  for use with [`arrow`](https://docs.rs/arrow) and compatible dataframe
+  libraries such as Polars.
+- `serde`: Implements [`Serialize`](https://docs.rs/serde/1/serde/trait.Serialize.html)
+  for [`ResultsTable`](results::ResultsTable) and the types describing its
+  cells, implements `Serialize` and `Deserialize` for
+  [`Transaction`](transaction::Transaction), and enables loading JSON pool snapshots with
+  [`PoolSnapshot::from_json`](power_dist::PoolSnapshot::from_json).
+- `plots`: Enables [`results::plot`], which renders charts of simulation
+  results as SVG or PNG images using [`plotters`](https://docs.rs/plotters/0.3).
+- `proptest`: Enables the [`proptest`](https://docs.rs/proptest/1)
+  strategies in [`testing::generators`], which generate random blockchains
+  and power distributions for fuzzing mining strategies.
+- `scripting`: Enables [`miner::scripted`], which defines mining strategies
+  with [Rhai](https://rhai.rs) scripts.
+- `timing`: Records the time each simulation spends sampling proposers, in
+  each miner's [`get_action`](miner::Miner::get_action), publishing blocks,
+  and assembling its output, in
+  [`SimulationOutput::timings`](simulation::SimulationOutput::timings). A
+  report for a set of simulations is given by
+  [`ResultsBuilder::phase_timings`](results::ResultsBuilder::phase_timings).
+  Adds a small overhead to every round.
+- `tracing`: Emits [`tracing`](https://docs.rs/tracing/0.1) diagnostics from
+  each simulation, within a `simulation` span recording its configuration
+  index and seed. Published blocks are logged at the `TRACE` level, and
+  reorganizations of the longest chain and the capitulations of
+  block-withholding strategies at the `DEBUG` level. Install a subscriber,
+  such as one from [`tracing-subscriber`](https://docs.rs/tracing-subscriber/0.3),
+  to see them.
+- `tui`: Enables [`simulation::dashboard`], which shows the progress of a
+  running set of simulations in the terminal using
+  [`ratatui`](https://docs.rs/ratatui/0.29). Has no effect when compiling to
+  WebAssembly.
 */
 
 // ## Todo:
@@ -24,18 +68,21 @@ By default, `rayon` is enabled.
 // - try to use Average::Mean as the estimator whe possible to take advantage of
 //   the Central Limit Theorem when estimating attacker revenue
 
-// ## Important Tests (Conversation with Weinberg):
-// - Honest miners only -> alpha is roughly equal to revenue
-// - Selfish Mining revenue -> alpha matches closed form from Eyal paper
-// - NSM revenue -> alpha matches closed form from Weinberg-Ferreira
-
+pub mod analysis;
 pub mod blockchain;
+pub mod dag;
+pub mod env;
+pub mod mempool;
 pub mod miner;
 pub mod power_dist;
 pub mod prelude;
 pub mod results;
 pub mod simulation;
+pub mod stake;
+pub mod testing;
 pub mod tie_breaker;
 pub mod transaction;
+pub mod uncle;
+pub mod view;
 
 pub(crate) mod utils;
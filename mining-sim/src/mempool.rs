@@ -0,0 +1,1234 @@
+//! Describing pending transactions and the process by which they arrive
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Exp, Poisson};
+
+use crate::{
+    blockchain::{Block, BlockId, Blockchain, ChainQuery},
+    transaction::{Transaction, TransactionId},
+};
+
+/// [`Tag`](Transaction::tag) of the transactions which censoring miners
+/// refuse to include. See [`Mempool::blacklist`].
+pub const BLACKLIST_TAG: &str = "blacklisted";
+
+/// Determines how many transactions enter the [`Mempool`] in each round of a
+/// simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrivalProcess {
+    /// Add transactions at the given average rate per round, spread as evenly
+    /// as possible. A rate of `0.5` adds one transaction every other round.
+    Rate(f64),
+    /// Add a number of transactions drawn from a Poisson distribution with
+    /// the given mean in each round.
+    Poisson(f64),
+}
+
+/// Determines the fee paid by each transaction entering the [`Mempool`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeeDistribution {
+    /// Every transaction pays the same fee.
+    Constant(f64),
+    /// Fees are drawn uniformly from the range `low..=high`.
+    Uniform(f64, f64),
+    /// Fees are drawn from an exponential distribution with the given mean.
+    Exponential(f64),
+}
+
+/// Determines how the fees of the transactions in a block are paid to its
+/// miner.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum FeeMarket {
+    /// Miners receive the full fee of every transaction they include.
+    #[default]
+    FirstPrice,
+    /// Each block has a base fee per unit of transaction size, in the style
+    /// of [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559). The base fee
+    /// of each included transaction is burned, and only the remainder (the
+    /// tip) goes to the miner. Transactions which do not cover the base fee
+    /// cannot be included.
+    ///
+    /// Blocks extending the genesis block have base fee `initial`. Every other
+    /// block's base fee is its parent's, adjusted by up to 12.5% in
+    /// proportion to how far the parent's total transaction size was above or
+    /// below `target`.
+    BaseFee { initial: f64, target: usize },
+}
+
+impl FeeMarket {
+    /// Largest fraction by which the base fee changes between blocks.
+    const MAX_BASE_FEE_CHANGE: f64 = 0.125;
+}
+
+/// Order of the transactions which may be included in a block: decreasing
+/// fee, then increasing ID.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FeeOrder(f64, TransactionId);
+
+impl FeeOrder {
+    fn of(txn: &Transaction) -> Self {
+        Self(txn.fee, txn.id)
+    }
+}
+
+impl Eq for FeeOrder {}
+
+impl PartialOrd for FeeOrder {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FeeOrder {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.0.total_cmp(&self.0).then(self.1.cmp(&other.1))
+    }
+}
+
+/// Outcome of a double-spend attempt scheduled with
+/// [`Mempool::double_spend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoubleSpendOutcome {
+    /// Input spent by both the payment and the conflicting transaction.
+    pub input: usize,
+    /// Greatest number of confirmations of the payment on the longest chain
+    /// at any point during the simulation, counting the block which includes
+    /// it. `0` if the payment was never on the longest chain.
+    pub max_confirmations: usize,
+    /// True if the conflicting transaction is on the longest chain at the end
+    /// of the simulation.
+    pub reversed: bool,
+}
+
+impl DoubleSpendOutcome {
+    /// Returns true if the payment was reversed after a merchant waiting for
+    /// `confirmations` confirmations accepted it.
+    pub fn succeeded(&self, confirmations: usize) -> bool {
+        self.reversed && self.max_confirmations >= confirmations
+    }
+}
+
+/// Tracks a payment scheduled with [`Mempool::double_spend`] and the
+/// conflicting transaction withheld from the queue.
+#[derive(Debug, Clone)]
+struct DoubleSpend {
+    round: usize,
+    fee: f64,
+    /// Created along with the payment when it arrives.
+    txns: Option<(TransactionId, Transaction)>,
+    /// Block on the longest chain which includes the payment, if any.
+    payment_block: Option<BlockId>,
+    max_confirmations: usize,
+    reversed: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MempoolError {
+    #[error("arrival rate {0} is not a finite non-negative number")]
+    BadArrivalRate(f64),
+    #[error("fee {0} is not a finite non-negative number")]
+    BadFee(f64),
+    #[error("fee range {0}..={1} is empty")]
+    EmptyFeeRange(f64, f64),
+    #[error("mean fee {0} is not a finite positive number")]
+    BadMeanFee(f64),
+    #[error("whale probability {0} is not in the range 0.0..=1.0")]
+    BadWhaleProbability(f64),
+    #[error("blacklist probability {0} is not in the range 0.0..=1.0")]
+    BadBlacklistProbability(f64),
+    #[error("base fee {0} is not a finite non-negative number")]
+    BadBaseFee(f64),
+    #[error("target block size must be greater than 0")]
+    ZeroTargetSize,
+}
+
+impl ArrivalProcess {
+    /// Returns the number of transactions arriving in `round`, which starts
+    /// from 1.
+    fn count<R: Rng + ?Sized>(&self, round: usize, rng: &mut R) -> usize {
+        match *self {
+            Self::Rate(rate) => {
+                let arrived = |round: usize| (round as f64 * rate).floor();
+                (arrived(round) - arrived(round - 1)) as usize
+            }
+            Self::Poisson(0.0) => 0,
+            Self::Poisson(mean) => {
+                // Arrival rates are validated during the simulation build
+                // process
+                Poisson::new(mean).unwrap().sample(rng) as usize
+            }
+        }
+    }
+}
+
+impl FeeDistribution {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match *self {
+            Self::Constant(fee) => fee,
+            Self::Uniform(low, high) => rng.gen_range(low..=high),
+            Self::Exponential(mean) => {
+                Exp::new(mean.recip()).unwrap().sample(rng)
+            }
+        }
+    }
+}
+
+/// Configuration of the transactions generated by a [`Mempool`], recorded in
+/// [`SimulationOutput::txn_generator`](crate::simulation::SimulationOutput::txn_generator).
+/// Converting a [`GeneratorConfig`] into a [`Mempool`] creates a mempool
+/// which generates exactly the same transactions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorConfig {
+    pub arrival: ArrivalProcess,
+    pub fees: FeeDistribution,
+    pub fee_market: FeeMarket,
+    /// Probability that each generated transaction is blacklisted.
+    pub blacklist: Option<f64>,
+    /// Round and fee of each scheduled whale transaction.
+    pub whales: Vec<(usize, f64)>,
+    /// Per-round probability and fee of randomly arriving whale transactions.
+    pub random_whales: Option<(f64, f64)>,
+    /// Round, input, and fee of each scheduled double-spend attempt.
+    pub double_spends: Vec<(usize, usize, f64)>,
+    /// Seed of the random number generator used to generate transactions, if
+    /// it has been chosen. See [`Mempool::seed`].
+    pub seed: Option<u64>,
+}
+
+impl From<GeneratorConfig> for Mempool {
+    fn from(config: GeneratorConfig) -> Self {
+        let mut mempool = Mempool::new(config.arrival, config.fees)
+            .fee_market(config.fee_market);
+        if let Some(prob) = config.blacklist {
+            mempool = mempool.blacklist(prob);
+        }
+        for (round, fee) in config.whales {
+            mempool = mempool.whale(round, fee);
+        }
+        if let Some((prob, fee)) = config.random_whales {
+            mempool = mempool.random_whales(prob, fee);
+        }
+        for (round, input, fee) in config.double_spends {
+            mempool = mempool.double_spend(round, input, fee);
+        }
+        mempool.seed = config.seed;
+
+        mempool
+    }
+}
+
+/// Queue of the transactions which have arrived during a simulation but are
+/// not yet included in the longest chain.
+///
+/// When a mempool is given to
+/// [`SimulationBuilder::mempool`](crate::simulation::SimulationBuilder::mempool),
+/// new transactions arrive at the start of each round, and every block
+/// published without transactions is filled by its miner's
+/// [`Miner::select_txns`](crate::miner::Miner::select_txns), which defaults
+/// to [`Mempool::select`]. Generated transactions have size `1`.
+/// Transactions in blocks which leave the longest chain during a
+/// reorganization are returned to the queue.
+///
+/// # Example
+/// Scheduling exceptionally high-fee "whale" transactions alongside a steady
+/// stream of low-fee transactions:
+/// ```
+/// use mining_sim::prelude::*;
+///
+/// let mempool = Mempool::new(
+///     ArrivalProcess::Poisson(2.0),
+///     FeeDistribution::Uniform(0.0, 0.01),
+/// )
+/// .whale(500, 10.0)
+/// .random_whales(0.001, 5.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Mempool {
+    arrival: ArrivalProcess,
+    /// Base fee of each published block, if there is a base fee.
+    base_fees: HashMap<BlockId, f64>,
+    /// Probability that each generated transaction is blacklisted.
+    blacklist: Option<f64>,
+    /// Blacklisted transactions on the longest chain ending at `tip`.
+    blacklisted: BTreeSet<TransactionId>,
+    fee_market: FeeMarket,
+    fees: FeeDistribution,
+    /// ID of the most recently created transaction.
+    last_id: usize,
+    /// Double-spend attempts, by input.
+    double_spends: BTreeMap<usize, DoubleSpend>,
+    /// Number of rounds between the arrival of each transaction on the
+    /// longest chain and the round in which it joined the longest chain.
+    latencies: BTreeMap<TransactionId, usize>,
+    /// IDs of the blocks on the longest chain ending at `tip`, by height.
+    longest: Vec<BlockId>,
+    /// Transactions not included in the longest chain ending at `tip`.
+    pending: BTreeMap<TransactionId, Transaction>,
+    /// Keys of `pending`, in the order given by [`FeeOrder`].
+    pending_by_fee: BTreeSet<FeeOrder>,
+    /// Per-round probability and fee of randomly arriving whale transactions.
+    random_whales: Option<(f64, f64)>,
+    /// Most recent round in which transactions arrived.
+    round: usize,
+    /// Seed of the random number generator used to generate transactions.
+    seed: Option<u64>,
+    /// Inputs spent by transactions on the longest chain ending at `tip`.
+    spent: HashSet<usize>,
+    tip: BlockId,
+    /// Fees of the whale transactions scheduled to arrive in each round.
+    whales: BTreeMap<usize, Vec<f64>>,
+}
+
+impl Mempool {
+    /// Mixed into simulation seeds so that transactions are generated
+    /// independently of block proposers.
+    const SEED_SALT: u64 = 0x6d65_6d70_6f6f_6c00;
+
+    /// Create an empty [`Mempool`] whose transactions arrive according to
+    /// `arrival`, with fees drawn from `fees`.
+    pub fn new(arrival: ArrivalProcess, fees: FeeDistribution) -> Self {
+        Self {
+            arrival,
+            base_fees: HashMap::new(),
+            blacklist: None,
+            blacklisted: BTreeSet::new(),
+            fee_market: FeeMarket::default(),
+            fees,
+            double_spends: BTreeMap::new(),
+            last_id: 0,
+            latencies: BTreeMap::new(),
+            longest: vec![Blockchain::GENESIS_ID],
+            pending: BTreeMap::new(),
+            pending_by_fee: BTreeSet::new(),
+            random_whales: None,
+            round: 0,
+            seed: None,
+            spent: HashSet::new(),
+            tip: Blockchain::GENESIS_ID,
+            whales: BTreeMap::new(),
+        }
+    }
+
+    /// Set the [`FeeMarket`] which determines the fees paid to miners.
+    /// Defaults to [`FeeMarket::FirstPrice`].
+    pub fn fee_market(mut self, fee_market: FeeMarket) -> Self {
+        self.fee_market = fee_market;
+
+        self
+    }
+
+    /// Seed the random number generator used to generate transactions with
+    /// `seed` in every simulation. Otherwise, each simulation seeds it with a
+    /// value derived from its own
+    /// [`seed`](crate::simulation::SimulationOutput::seed), so that
+    /// transactions are reproducible whenever block proposers are, and do not
+    /// depend on the random choices of miners.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+
+        self
+    }
+
+    /// Tag each transaction generated by the arrival process with
+    /// [`BLACKLIST_TAG`] with probability `prob`, marking it as a transaction
+    /// which censoring miners refuse to include. See
+    /// [`Mempool::blacklisted_latencies`].
+    pub fn blacklist(mut self, prob: f64) -> Self {
+        self.blacklist = Some(prob);
+
+        self
+    }
+
+    /// Schedule a "whale" transaction paying `fee` to arrive in `round`, in
+    /// addition to the transactions generated by the arrival process. Whale
+    /// transactions are tagged `"whale"`.
+    pub fn whale(mut self, round: usize, fee: f64) -> Self {
+        self.whales.entry(round).or_default().push(fee);
+
+        self
+    }
+
+    /// Add a "whale" transaction paying `fee` in each round with probability
+    /// `prob`, in addition to the transactions generated by the arrival
+    /// process and any scheduled with [`Mempool::whale`]. These are also
+    /// tagged `"whale"`.
+    pub fn random_whales(mut self, prob: f64, fee: f64) -> Self {
+        self.random_whales = Some((prob, fee));
+
+        self
+    }
+
+    /// Schedule a payment spending `input` and paying `fee` to arrive in
+    /// `round`, along with a conflicting transaction spending the same input
+    /// which is withheld from the queue. The two transactions are tagged
+    /// `"payment"` and `"conflict"` respectively. An attacker such as
+    /// [`DoubleSpend`](crate::miner::doublespend::DoubleSpend) can include the
+    /// conflicting transaction, returned by [`Mempool::withheld`], in a
+    /// private fork to reverse the payment. The outcome of the attempt is
+    /// recorded in
+    /// [`SimulationOutput::double_spends`](crate::simulation::SimulationOutput::double_spends).
+    ///
+    /// Scheduling another payment with the same input replaces this one.
+    pub fn double_spend(
+        mut self,
+        round: usize,
+        input: usize,
+        fee: f64,
+    ) -> Self {
+        let double_spend = DoubleSpend {
+            round,
+            fee,
+            txns: None,
+            payment_block: None,
+            max_confirmations: 0,
+            reversed: false,
+        };
+        self.double_spends.insert(input, double_spend);
+
+        self
+    }
+
+    /// Returns the withheld transaction which conflicts with the payment
+    /// spending `input` scheduled with [`Mempool::double_spend`], if the
+    /// payment has arrived.
+    pub fn withheld(&self, input: usize) -> Option<&Transaction> {
+        let (_, conflict) = self.double_spends.get(&input)?.txns.as_ref()?;
+
+        Some(conflict)
+    }
+
+    /// Returns the outcome of each double-spend attempt whose payment has
+    /// arrived, in order of input.
+    pub fn double_spend_outcomes(&self) -> Vec<DoubleSpendOutcome> {
+        self.double_spends
+            .iter()
+            .filter(|(_, double_spend)| double_spend.txns.is_some())
+            .map(|(&input, double_spend)| DoubleSpendOutcome {
+                input,
+                max_confirmations: double_spend.max_confirmations,
+                reversed: double_spend.reversed,
+            })
+            .collect()
+    }
+
+    /// Returns the inclusion latency of each transaction on the longest chain,
+    /// in order of transaction ID. The inclusion latency of a transaction is
+    /// the number of rounds between its arrival and the round in which the
+    /// block including it last joined the longest chain, so a transaction
+    /// included by a block published in its arrival round has latency 0.
+    pub fn inclusion_latencies(&self) -> Vec<usize> {
+        self.latencies.values().copied().collect()
+    }
+
+    /// Returns the inclusion latency of each transaction tagged with
+    /// [`BLACKLIST_TAG`] on the longest chain, in order of transaction ID.
+    /// See [`Mempool::inclusion_latencies`].
+    pub fn blacklisted_latencies(&self) -> Vec<usize> {
+        self.blacklisted
+            .iter()
+            .map(|txn_id| self.latencies[txn_id])
+            .collect()
+    }
+
+    /// Returns the configuration of the transactions generated by this
+    /// mempool.
+    pub fn generator_config(&self) -> GeneratorConfig {
+        let whales = self
+            .whales
+            .iter()
+            .flat_map(|(&round, fees)| {
+                fees.iter().map(move |&fee| (round, fee))
+            })
+            .collect();
+        let double_spends = self
+            .double_spends
+            .iter()
+            .map(|(&input, double_spend)| {
+                (double_spend.round, input, double_spend.fee)
+            })
+            .collect();
+
+        GeneratorConfig {
+            arrival: self.arrival.clone(),
+            fees: self.fees.clone(),
+            fee_market: self.fee_market.clone(),
+            blacklist: self.blacklist,
+            whales,
+            random_whales: self.random_whales,
+            double_spends,
+            seed: self.seed,
+        }
+    }
+
+    /// Returns the process by which transactions arrive.
+    pub fn arrival(&self) -> &ArrivalProcess {
+        &self.arrival
+    }
+
+    /// Returns the distribution of transaction fees.
+    pub fn fees(&self) -> &FeeDistribution {
+        &self.fees
+    }
+
+    /// Returns the number of pending transactions.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns true if no transactions are pending.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Returns an iterator over the transactions which are not included in
+    /// the longest chain, in order of arrival.
+    pub fn pending(&self) -> impl Iterator<Item = &Transaction> {
+        self.pending.values()
+    }
+
+    /// Returns the base fee per unit of transaction size of a block whose
+    /// parent is `parent`. Always `0.0` under [`FeeMarket::FirstPrice`].
+    pub fn next_base_fee(
+        &self,
+        chain: &dyn ChainQuery,
+        parent: BlockId,
+    ) -> f64 {
+        let FeeMarket::BaseFee { initial, target } = self.fee_market else {
+            return 0.0;
+        };
+
+        match (self.base_fees.get(&parent), chain.get(parent)) {
+            (Some(&base_fee), Some(data)) => {
+                let size: usize =
+                    data.block.txns.iter().map(|txn| txn.size).sum();
+                let change = (size as f64 - target as f64) / target as f64;
+
+                base_fee * (1.0 + FeeMarket::MAX_BASE_FEE_CHANGE * change)
+            }
+            _ => initial,
+        }
+    }
+
+    /// Returns the base fee per unit of transaction size of the published
+    /// block `block_id`, or `0.0` if it was not published during the
+    /// simulation.
+    pub fn base_fee(&self, block_id: BlockId) -> f64 {
+        self.base_fees.get(&block_id).copied().unwrap_or_default()
+    }
+
+    /// Returns the total fees paid to the miner of the published block
+    /// `block`, after the block's base fee is burned.
+    pub fn miner_fees(&self, block: &Block) -> f64 {
+        let base_fee = self.base_fee(block.id);
+
+        block
+            .txns
+            .iter()
+            .map(|txn| txn.fee - base_fee * txn.size as f64)
+            .sum()
+    }
+
+    /// Returns the transactions which may be included in a block whose parent
+    /// is `parent`, in order of decreasing fee. These are the pending
+    /// transactions, plus those included only on the longest chain if
+    /// `parent` is on a fork, minus those already included in the ancestors
+    /// of `parent`, which spend an input already spent by them, or which do
+    /// not cover the block's [base fee](Mempool::next_base_fee).
+    ///
+    /// `chain` may be a miner's [`ChainView`](crate::view::ChainView), in
+    /// which case the longest chain ends at its highest block the miner can
+    /// see. Transactions in the blocks above it are treated as included.
+    pub fn available(
+        &self,
+        chain: &dyn ChainQuery,
+        parent: BlockId,
+    ) -> Vec<Transaction> {
+        self.candidates(chain, parent)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns an iterator over the transactions given by
+    /// [`Mempool::available`], which walks the pending transactions in order
+    /// of decreasing fee without sorting them. Returns [`None`] if `parent`
+    /// has no common ancestor with the longest chain.
+    fn candidates<'a>(
+        &'a self,
+        chain: &'a dyn ChainQuery,
+        parent: BlockId,
+    ) -> Option<impl Iterator<Item = &'a Transaction> + 'a> {
+        let tip = self.visible_tip(chain);
+        let ancestor = chain.common_ancestor(parent, tip)?;
+
+        let included: HashSet<_> = branch(chain, parent, ancestor)
+            .flat_map(|block| block.txns.iter().map(|txn| txn.id))
+            .collect();
+        let is_spent = self.spent_on_branch(chain, parent, ancestor);
+        let base_fee = self.next_base_fee(chain, parent);
+
+        // Transactions above the fork from `parent` are few, so they are
+        // sorted and merged into the pending transactions
+        let mut forked: Vec<_> = branch(chain, tip, ancestor)
+            .flat_map(|block| block.txns.iter())
+            .collect();
+        forked.sort_by_key(|txn| FeeOrder::of(txn));
+        let mut forked = forked.into_iter().peekable();
+        let mut pending = self
+            .pending_by_fee
+            .iter()
+            .map(|FeeOrder(_, id)| &self.pending[id])
+            .peekable();
+        let merged =
+            std::iter::from_fn(move || match (forked.peek(), pending.peek()) {
+                (Some(a), Some(b)) if FeeOrder::of(a) > FeeOrder::of(b) => {
+                    pending.next()
+                }
+                (Some(_), _) => forked.next(),
+                (None, _) => pending.next(),
+            });
+
+        Some(
+            merged
+                .filter(move |txn| !included.contains(&txn.id))
+                .filter(move |txn| !txn.input.is_some_and(&is_spent))
+                .filter(move |txn| txn.fee >= base_fee * txn.size as f64),
+        )
+    }
+
+    /// Returns true if a transaction spending `input` is included in `parent`
+    /// or its ancestors.
+    pub fn is_spent(
+        &self,
+        chain: &dyn ChainQuery,
+        parent: BlockId,
+        input: usize,
+    ) -> bool {
+        chain
+            .common_ancestor(parent, self.visible_tip(chain))
+            .is_some_and(|ancestor| {
+                self.spent_on_branch(chain, parent, ancestor)(input)
+            })
+    }
+
+    /// Returns the highest block on the longest chain which `chain` contains.
+    /// This is `tip` unless `chain` is a miner's restricted view.
+    fn visible_tip(&self, chain: &dyn ChainQuery) -> BlockId {
+        self.longest
+            .iter()
+            .rev()
+            .copied()
+            .find(|&id| chain.contains(id))
+            .unwrap_or_else(|| chain.genesis())
+    }
+
+    /// Returns a function which checks whether an input is spent on the branch
+    /// ending at `parent`, whose common ancestor with the longest chain is
+    /// `ancestor`.
+    fn spent_on_branch<'a>(
+        &'a self,
+        chain: &'a dyn ChainQuery,
+        parent: BlockId,
+        ancestor: BlockId,
+    ) -> impl Fn(usize) -> bool + 'a {
+        let inputs = |tip| -> HashSet<usize> {
+            branch(chain, tip, ancestor)
+                .flat_map(|block| block.txns.iter())
+                .filter_map(|txn| txn.input)
+                .collect()
+        };
+        let (on_branch, off_branch) =
+            (inputs(parent), inputs(self.visible_tip(chain)));
+
+        move |input| {
+            on_branch.contains(&input)
+                || (self.spent.contains(&input) && !off_branch.contains(&input))
+        }
+    }
+
+    /// Returns the transactions with the highest fees which are available to
+    /// a block whose parent is `parent` (see [`Mempool::available`]) and whose
+    /// sizes sum to at most `capacity`. Transactions are chosen greedily in
+    /// order of decreasing fee, skipping any which do not fit in the remaining
+    /// capacity or which conflict with a transaction already chosen.
+    pub fn select(
+        &self,
+        chain: &dyn ChainQuery,
+        parent: BlockId,
+        capacity: usize,
+    ) -> Vec<Transaction> {
+        let mut remaining = capacity;
+        let mut inputs = HashSet::new();
+        let mut txns = vec![];
+        for txn in self.candidates(chain, parent).into_iter().flatten() {
+            if remaining == 0 {
+                break;
+            }
+
+            let fits = txn.size <= remaining
+                && !txn.input.is_some_and(|input| inputs.contains(&input));
+            if fits {
+                remaining -= txn.size;
+                inputs.extend(txn.input);
+                txns.push(txn.clone());
+            }
+        }
+
+        txns
+    }
+
+    /// Checks that the arrival process, fee distribution, fee market, and
+    /// scheduled transactions are valid.
+    pub(crate) fn validate(&self) -> Result<(), MempoolError> {
+        use MempoolError::*;
+
+        let valid = |x: f64| x.is_finite() && x >= 0.0;
+
+        match self.arrival {
+            ArrivalProcess::Rate(rate) | ArrivalProcess::Poisson(rate)
+                if !valid(rate) =>
+            {
+                return Err(BadArrivalRate(rate));
+            }
+            _ => (),
+        }
+
+        match self.fees {
+            FeeDistribution::Constant(fee) if !valid(fee) => {
+                return Err(BadFee(fee));
+            }
+            FeeDistribution::Uniform(low, high) => {
+                if let Some(&fee) = [low, high].iter().find(|&&f| !valid(f)) {
+                    return Err(BadFee(fee));
+                } else if low > high {
+                    return Err(EmptyFeeRange(low, high));
+                }
+            }
+            FeeDistribution::Exponential(mean)
+                if !valid(mean) || mean == 0.0 =>
+            {
+                return Err(BadMeanFee(mean));
+            }
+            _ => (),
+        }
+
+        if let Some((prob, _)) = self.random_whales {
+            if !(0.0..=1.0).contains(&prob) {
+                return Err(BadWhaleProbability(prob));
+            }
+        }
+
+        if let Some(prob) = self.blacklist {
+            if !(0.0..=1.0).contains(&prob) {
+                return Err(BadBlacklistProbability(prob));
+            }
+        }
+
+        if let FeeMarket::BaseFee { initial, target } = self.fee_market {
+            if !valid(initial) {
+                return Err(BadBaseFee(initial));
+            } else if target == 0 {
+                return Err(ZeroTargetSize);
+            }
+        }
+
+        let mut scheduled_fees = self
+            .whales
+            .values()
+            .flatten()
+            .copied()
+            .chain(self.random_whales.map(|(_, fee)| fee))
+            .chain(self.double_spends.values().map(|ds| ds.fee));
+        match scheduled_fees.find(|&fee| !valid(fee)) {
+            Some(fee) => Err(BadFee(fee)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the random number generator used to generate transactions in a
+    /// simulation with seed `sim_seed`, choosing its seed if
+    /// [`Mempool::seed`] was not called.
+    pub(crate) fn generator(&mut self, sim_seed: u64) -> StdRng {
+        let seed = *self.seed.get_or_insert(sim_seed ^ Self::SEED_SALT);
+
+        StdRng::seed_from_u64(seed)
+    }
+
+    /// Adds the transactions arriving in `round` to the queue.
+    pub(crate) fn arrive<R: Rng + ?Sized>(
+        &mut self,
+        round: usize,
+        rng: &mut R,
+    ) {
+        self.round = round;
+        for _ in 0..self.arrival.count(round, rng) {
+            let fee = self.fees.sample(rng);
+            let blacklisted = self.blacklist.is_some_and(|p| rng.gen_bool(p));
+            self.push(round, fee, blacklisted.then_some(BLACKLIST_TAG));
+        }
+
+        if let Some(fees) = self.whales.get(&round).cloned() {
+            for fee in fees {
+                self.push(round, fee, Some("whale"));
+            }
+        }
+
+        if let Some((prob, fee)) = self.random_whales {
+            if rng.gen_bool(prob) {
+                self.push(round, fee, Some("whale"));
+            }
+        }
+
+        for (&input, double_spend) in self.double_spends.iter_mut() {
+            if double_spend.round != round {
+                continue;
+            }
+
+            let [payment, conflict] =
+                [(1, "payment"), (2, "conflict")].map(|(offset, tag)| {
+                    Transaction::new(
+                        TransactionId(self.last_id + offset),
+                        double_spend.fee,
+                    )
+                    .input(input)
+                    .arrival_round(round)
+                    .tag(tag)
+                });
+            self.last_id += 2;
+            self.pending_by_fee.insert(FeeOrder::of(&payment));
+            self.pending.insert(payment.id, payment.clone());
+            double_spend.txns = Some((payment.id, conflict));
+        }
+    }
+
+    /// Adds a new transaction paying `fee` which arrived in `round`, labelled
+    /// with `tag` if given.
+    fn push(&mut self, round: usize, fee: f64, tag: Option<&str>) {
+        self.last_id += 1;
+        let id = TransactionId(self.last_id);
+        let txn = Transaction::new(id, fee).arrival_round(round);
+        let txn = match tag {
+            Some(tag) => txn.tag(tag),
+            None => txn,
+        };
+        self.pending_by_fee.insert(FeeOrder::of(&txn));
+        self.pending.insert(id, txn);
+    }
+
+    /// Records the base fee of the newly published block `block_id`, then
+    /// updates the queue. Must be called after every block is published.
+    pub(crate) fn publish(&mut self, chain: &Blockchain, block_id: BlockId) {
+        if let (FeeMarket::BaseFee { .. }, Some(parent)) =
+            (&self.fee_market, chain.get_parent(block_id))
+        {
+            let base_fee = self.next_base_fee(chain, parent);
+            self.base_fees.insert(block_id, base_fee);
+        }
+
+        self.update(chain);
+    }
+
+    /// Moves transactions into or out of the queue if the longest chain
+    /// changed since the last update.
+    pub(crate) fn update(&mut self, chain: &Blockchain) {
+        let tip = chain.tip()[0];
+        if tip == self.tip {
+            return;
+        }
+
+        let ancestor = chain.common_ancestor(tip, self.tip).unwrap();
+        for block in branch(chain, self.tip, ancestor) {
+            for txn in block.txns.iter() {
+                self.pending_by_fee.insert(FeeOrder::of(txn));
+                self.pending.insert(txn.id, txn.clone());
+                self.latencies.remove(&txn.id);
+                self.blacklisted.remove(&txn.id);
+                if let Some(input) = txn.input {
+                    self.spent.remove(&input);
+                    self.track_double_spend(input, txn.id, None);
+                }
+            }
+        }
+        let fork_height = chain[ancestor].height + 1;
+        self.longest.truncate(fork_height);
+        for block in branch(chain, tip, ancestor) {
+            self.longest.push(block.id);
+            for txn in block.txns.iter() {
+                self.pending_by_fee.remove(&FeeOrder::of(txn));
+                self.pending.remove(&txn.id);
+                let latency = self.round.saturating_sub(txn.arrival_round);
+                self.latencies.insert(txn.id, latency);
+                if txn.tag.as_deref() == Some(BLACKLIST_TAG) {
+                    self.blacklisted.insert(txn.id);
+                }
+                if let Some(input) = txn.input {
+                    self.spent.insert(input);
+                    self.track_double_spend(input, txn.id, Some(block.id));
+                }
+            }
+        }
+        self.longest[fork_height..].reverse();
+        self.tip = tip;
+
+        let tip_height = chain[tip].height;
+        for double_spend in self.double_spends.values_mut() {
+            if let Some(block_id) = double_spend.payment_block {
+                let confirmations = tip_height - chain[block_id].height + 1;
+                double_spend.max_confirmations =
+                    double_spend.max_confirmations.max(confirmations);
+            }
+        }
+    }
+
+    /// Records that the transaction `txn_id` spending `input` was added to
+    /// the longest chain in `block`, or removed from it if `block` is
+    /// [`None`], if it is part of a double-spend attempt.
+    fn track_double_spend(
+        &mut self,
+        input: usize,
+        txn_id: TransactionId,
+        block: Option<BlockId>,
+    ) {
+        let Some(double_spend) = self.double_spends.get_mut(&input) else {
+            return;
+        };
+
+        match &double_spend.txns {
+            Some((payment, _)) if *payment == txn_id => {
+                double_spend.payment_block = block;
+            }
+            Some((_, conflict)) if conflict.id == txn_id => {
+                double_spend.reversed = block.is_some();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Returns an iterator over the blocks from `tip` down to, but not including,
+/// its ancestor `ancestor`.
+fn branch(
+    chain: &dyn ChainQuery,
+    tip: BlockId,
+    ancestor: BlockId,
+) -> impl Iterator<Item = &Block> {
+    std::iter::successors(Some(tip), |&id| chain.get_parent(id))
+        .take_while(move |&id| id != ancestor)
+        .map(|id| &chain[id].block)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{
+        ArrivalProcess, FeeDistribution, FeeMarket, Mempool, MempoolError,
+    };
+    use crate::{
+        blockchain::{Block, BlockId, Blockchain},
+        miner::MinerId,
+        transaction::Transaction,
+    };
+
+    #[test]
+    fn fractional_arrival_rate() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut mempool = Mempool::new(
+            ArrivalProcess::Rate(0.5),
+            FeeDistribution::Constant(1.0),
+        );
+
+        let counts: Vec<_> = (1..=6)
+            .map(|round| {
+                let before = mempool.len();
+                mempool.arrive(round, &mut rng);
+                mempool.len() - before
+            })
+            .collect();
+
+        assert_eq!(counts, vec![0, 1, 0, 1, 0, 1]);
+        assert!(mempool.pending().all(|txn| txn.fee == 1.0));
+    }
+
+    #[test]
+    fn whale_transactions() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut mempool = Mempool::new(
+            ArrivalProcess::Rate(1.0),
+            FeeDistribution::Constant(0.1),
+        )
+        .whale(2, 50.0)
+        .random_whales(1.0, 20.0);
+        for round in 1..=3 {
+            mempool.arrive(round, &mut rng);
+        }
+
+        let whales: Vec<_> = mempool
+            .pending()
+            .filter(|txn| txn.tag.as_deref() == Some("whale"))
+            .map(|txn| (txn.arrival_round, txn.fee))
+            .collect();
+
+        assert_eq!(whales, vec![(1, 20.0), (2, 50.0), (2, 20.0), (3, 20.0)]);
+        assert!(matches!(
+            mempool.random_whales(1.5, 20.0).validate(),
+            Err(MempoolError::BadWhaleProbability(_))
+        ));
+    }
+
+    #[test]
+    fn conflicting_transactions() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut chain = Blockchain::new();
+        let mut mempool = Mempool::new(
+            ArrivalProcess::Rate(0.0),
+            FeeDistribution::Constant(0.0),
+        )
+        .double_spend(1, 7, 1.0);
+        mempool.arrive(1, &mut rng);
+
+        let payment = mempool.pending().next().cloned().unwrap();
+        let conflict = mempool.withheld(7).cloned().unwrap();
+        assert_eq!(payment.input, conflict.input);
+        assert_ne!(payment.id, conflict.id);
+
+        // Blocks 1 and 2 fork from genesis, spending input 7 differently
+        for (id, txn) in [(1, payment), (2, conflict.clone())] {
+            let txns = vec![txn];
+            chain
+                .publish(Block {
+                    id: BlockId(id),
+                    parent_id: Some(chain.genesis()),
+                    miner_id: MinerId(1),
+                    txns,
+                })
+                .unwrap();
+            mempool.update(&chain);
+        }
+
+        assert!(mempool.is_spent(&chain, BlockId(1), 7));
+        assert!(mempool.is_spent(&chain, BlockId(2), 7));
+        assert!(!mempool.is_spent(&chain, chain.genesis(), 7));
+        assert!(mempool.available(&chain, BlockId(2)).is_empty());
+        assert_eq!(mempool.select(&chain, chain.genesis(), 10).len(), 1);
+
+        // Extending block 2 reverses the payment
+        chain
+            .publish(Block {
+                id: BlockId(3),
+                parent_id: Some(BlockId(2)),
+                miner_id: MinerId(1),
+                txns: vec![],
+            })
+            .unwrap();
+        mempool.update(&chain);
+
+        let outcomes = mempool.double_spend_outcomes();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].max_confirmations, 1);
+        assert!(outcomes[0].succeeded(1));
+        assert!(!outcomes[0].succeeded(2));
+    }
+
+    #[test]
+    fn select_highest_fees_within_capacity() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let chain = Blockchain::new();
+        let mut mempool = Mempool::new(
+            ArrivalProcess::Rate(5.0),
+            FeeDistribution::Uniform(0.0, 1.0),
+        );
+        mempool.arrive(1, &mut rng);
+
+        let available = mempool.available(&chain, chain.genesis());
+        let selected = mempool.select(&chain, chain.genesis(), 3);
+
+        assert_eq!(selected, available[..3]);
+        assert!(selected.windows(2).all(|w| w[0].fee >= w[1].fee));
+        assert!(mempool.select(&chain, chain.genesis(), 0).is_empty());
+    }
+
+    #[test]
+    fn base_fee_follows_block_fullness() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut chain = Blockchain::new();
+        let mut mempool = Mempool::new(
+            ArrivalProcess::Rate(4.0),
+            FeeDistribution::Constant(1.2),
+        )
+        .fee_market(FeeMarket::BaseFee {
+            initial: 1.0,
+            target: 2,
+        });
+        let publish =
+            |chain: &mut Blockchain, mempool: &mut Mempool, id, parent| {
+                let txns = mempool.available(chain, BlockId(parent));
+                chain
+                    .publish(Block {
+                        id: BlockId(id),
+                        parent_id: Some(BlockId(parent)),
+                        miner_id: MinerId(1),
+                        txns,
+                    })
+                    .unwrap();
+                mempool.publish(chain, BlockId(id));
+            };
+
+        // Full blocks raise the base fee above every pending fee
+        mempool.arrive(1, &mut rng);
+        publish(&mut chain, &mut mempool, 1, 0);
+        mempool.arrive(2, &mut rng);
+        publish(&mut chain, &mut mempool, 2, 1);
+        mempool.arrive(3, &mut rng);
+        assert_eq!(mempool.base_fee(BlockId(1)), 1.0);
+        assert_eq!(mempool.base_fee(BlockId(2)), 1.125);
+        assert!(
+            (mempool.miner_fees(&chain[BlockId(1)].block) - 0.8).abs() < 1e-9
+        );
+        assert_eq!(mempool.len(), 4);
+
+        // Empty blocks lower it again
+        publish(&mut chain, &mut mempool, 3, 2);
+        assert_eq!(mempool.base_fee(BlockId(3)), 1.265625);
+        assert_eq!(mempool.available(&chain, BlockId(3)).len(), 4);
+    }
+
+    #[test]
+    fn invalid_fee_distributions() {
+        let validate =
+            |fees| Mempool::new(ArrivalProcess::Poisson(1.0), fees).validate();
+
+        assert!(validate(FeeDistribution::Uniform(0.0, 2.0)).is_ok());
+        assert!(matches!(
+            validate(FeeDistribution::Uniform(2.0, 1.0)),
+            Err(MempoolError::EmptyFeeRange(..))
+        ));
+        assert!(matches!(
+            validate(FeeDistribution::Constant(-1.0)),
+            Err(MempoolError::BadFee(_))
+        ));
+        assert!(matches!(
+            validate(FeeDistribution::Exponential(0.0)),
+            Err(MempoolError::BadMeanFee(_))
+        ));
+
+        let validate = |fee_market| {
+            Mempool::new(
+                ArrivalProcess::Poisson(1.0),
+                FeeDistribution::Constant(1.0),
+            )
+            .fee_market(fee_market)
+            .validate()
+        };
+        assert!(matches!(
+            validate(FeeMarket::BaseFee {
+                initial: f64::NAN,
+                target: 1
+            }),
+            Err(MempoolError::BadBaseFee(_))
+        ));
+        assert!(matches!(
+            validate(FeeMarket::BaseFee {
+                initial: 1.0,
+                target: 0
+            }),
+            Err(MempoolError::ZeroTargetSize)
+        ));
+        assert!(matches!(
+            Mempool::new(
+                ArrivalProcess::Rate(1.0),
+                FeeDistribution::Constant(1.0)
+            )
+            .blacklist(-0.5)
+            .validate(),
+            Err(MempoolError::BadBlacklistProbability(_))
+        ));
+    }
+
+    #[test]
+    fn forked_transactions_merge_in_fee_order() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut chain = Blockchain::new();
+        let mut mempool = Mempool::new(
+            ArrivalProcess::Rate(1.0),
+            FeeDistribution::Constant(1.0),
+        )
+        .whale(2, 5.0);
+
+        // Block 1 confirms transaction 1, then transaction 2 and whale
+        // transaction 3 arrive
+        mempool.arrive(1, &mut rng);
+        chain
+            .publish(Block {
+                id: BlockId(1),
+                parent_id: Some(BlockId(0)),
+                miner_id: MinerId(1),
+                txns: mempool.available(&chain, chain.genesis()),
+            })
+            .unwrap();
+        mempool.update(&chain);
+        mempool.arrive(2, &mut rng);
+
+        let ids = |txns: Vec<Transaction>| {
+            txns.iter().map(|txn| txn.id.get()).collect::<Vec<_>>()
+        };
+        let genesis = chain.genesis();
+        assert_eq!(ids(mempool.available(&chain, genesis)), vec![3, 1, 2]);
+        assert_eq!(ids(mempool.select(&chain, genesis, 2)), vec![3, 1]);
+        assert_eq!(ids(mempool.select(&chain, BlockId(1), 5)), vec![3, 2]);
+    }
+
+    #[test]
+    fn transactions_follow_the_longest_chain() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut chain = Blockchain::new();
+        let mut mempool = Mempool::new(
+            ArrivalProcess::Rate(2.0),
+            FeeDistribution::Constant(1.0),
+        );
+
+        let available = |chain: &Blockchain, mempool: &Mempool, parent| {
+            mempool
+                .available(chain, BlockId(parent))
+                .iter()
+                .map(|txn| txn.id.get())
+                .collect::<Vec<_>>()
+        };
+        let publish =
+            |chain: &mut Blockchain, mempool: &mut Mempool, id, parent| {
+                chain
+                    .publish(Block {
+                        id: BlockId(id),
+                        parent_id: Some(BlockId(parent)),
+                        miner_id: MinerId(1),
+                        txns: mempool.available(chain, BlockId(parent)),
+                    })
+                    .unwrap();
+                mempool.update(chain);
+            };
+
+        // Block 1 confirms transactions 1 and 2
+        mempool.arrive(1, &mut rng);
+        publish(&mut chain, &mut mempool, 1, 0);
+        assert!(mempool.is_empty());
+
+        // A competing block may include them again
+        mempool.arrive(2, &mut rng);
+        assert_eq!(available(&chain, &mempool, 0), vec![1, 2, 3, 4]);
+        publish(&mut chain, &mut mempool, 2, 0);
+        assert_eq!(available(&chain, &mempool, 1), vec![3, 4]);
+
+        // Extending block 2 reorganizes the longest chain, but transactions 3
+        // and 4 remain available to blocks extending block 1
+        publish(&mut chain, &mut mempool, 3, 2);
+        assert!(mempool.is_empty());
+        assert!(chain[BlockId(3)].block.txns.is_empty());
+        assert_eq!(available(&chain, &mempool, 1), vec![3, 4]);
+
+        // Transactions 1 and 2 were confirmed again one round later
+        assert_eq!(mempool.inclusion_latencies(), vec![1, 1, 0, 0]);
+    }
+}
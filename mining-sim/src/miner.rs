@@ -10,10 +10,12 @@ A miner implementation which breaks longest chain ties using
 
 ```
 use mining_sim::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
 
 #[derive(Debug, Clone)]
 struct MyMiner {
     id: MinerId,
+    rng: StdRng,
     tie_breaker: TieBreaker,
 }
 
@@ -31,21 +33,35 @@ impl Miner for MyMiner {
         self.tie_breaker = TieBreaker::FavorMiner(id);
     }
 
+    fn set_rng_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
     fn get_action(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) -> Action {
         match block_mined {
-            Some(block_id) => Action::Publish(Block {
-                id: block_id,
-                parent_id: Some(self.tie_breaker.choose(chain)),
-                miner_id: self.id,
-                txns: vec![],
-            }),
+            Some(block_id) => {
+                let parent_id =
+                    self.tie_breaker.choose_with(&mut self.rng, chain);
+
+                Action::Publish(Block {
+                    id: block_id,
+                    parent_id: Some(parent_id),
+                    miner_id: self.id,
+                    txns: vec![],
+                })
+            }
             None => Action::Wait,
         }
     }
+
+    // The miner keeps no state between rounds
+    fn reset(&mut self) -> bool {
+        true
+    }
 }
 ```
 
@@ -57,19 +73,38 @@ include:
 - Honest Mining with Probabilistic Forks [`honestforking::HonestForking`]
 - Selfish Mining [`selfish::Selfish`]
 - N-Deficit Mining [`ndeficit::NDeficit`]
+- Double Spending [`doublespend::DoubleSpend`]
 - Noop [`noop::Noop`]
 */
 
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    sync::Mutex,
+};
+
+use rand::{rngs::StdRng, SeedableRng};
 
-use crate::blockchain::{Block, BlockId, Blockchain};
+use crate::{
+    blockchain::{Block, BlockId},
+    mempool::Mempool,
+    simulation::SimulationOutput,
+    tie_breaker::TieBreaker,
+    transaction::Transaction,
+    view::ChainView,
+};
 
+pub mod doublespend;
+pub mod equivocating;
 pub mod honest;
 pub mod honestforking;
 pub mod ndeficit;
 pub mod ndeficiteager;
 pub mod noise;
 pub mod noop;
+pub mod registry;
+#[cfg(feature = "scripting")]
+pub mod scripted;
 pub mod selfish;
 
 /// An action taken by a miner on the chain.
@@ -92,6 +127,17 @@ pub trait Miner: Debug + dyn_clone::DynClone + Send + Sync {
     /// column of [`ResultsTable`](crate::results::ResultsTable).
     fn name(&self) -> String;
 
+    /// Returns the titles and values of the parameters of this miner's
+    /// strategy, such as the `p` of
+    /// [`HonestForking`](crate::miner::honestforking::HonestForking).
+    ///
+    /// The return value of this method will appear in the columns added by
+    /// [`ResultsBuilder::strategy_params`](crate::results::ResultsBuilder::strategy_params).
+    /// The default implementation returns no parameters.
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![]
+    }
+
     /// Returns this miner's [`MinerId`].
     fn id(&self) -> MinerId;
 
@@ -106,27 +152,208 @@ pub trait Miner: Debug + dyn_clone::DynClone + Send + Sync {
     ///
     /// Called once in each round of each simulation.
     ///
-    /// `chain` is a reference to the simulation's blockchain. `block_mined` is
+    /// `chain` is this miner's view of the simulation's blockchain, which
+    /// contains every block unless the miner was given a restricted
+    /// [`Visibility`](crate::view::Visibility). The view implements
+    /// [`ChainQuery`](crate::blockchain::ChainQuery), so strategies should
+    /// choose blocks to extend with [`ChainQuery::tip`](crate::blockchain::ChainQuery::tip)
+    /// or a [`TieBreaker`] rather than assuming the longest chain rule.
+    /// `block_mined` is
     /// `Some(block_id)` if this miner has been selected as the proposer in the
     /// current simulation round, and `None` otherwise.
     fn get_action(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) -> Action;
+
+    /// Sets the [`TieBreaker`] used by this miner, unless one was configured
+    /// explicitly when the miner was created.
+    ///
+    /// Called by [`SimulationBuilder::build`](crate::simulation::SimulationBuilder::build)
+    /// when a network-level tie-breaking rule is configured, such as
+    /// [`SimulationBuilder::gamma`](crate::simulation::SimulationBuilder::gamma).
+    /// The default implementation does nothing, which is appropriate for
+    /// strategies that break ties in their own favor.
+    fn set_default_tie_breaker(&mut self, tie_breaker: TieBreaker) {
+        let _ = tie_breaker;
+    }
+
+    /// Seeds the random number generator used by this miner's strategy, such
+    /// as the generator passed to [`TieBreaker::choose_with`].
+    ///
+    /// Called at the start of each simulation with a seed drawn from the
+    /// simulation's own random number generator, so that simulations given a
+    /// [`seed`](crate::simulation::SimulationBuilder::seed) are reproducible.
+    /// The default implementation does nothing, which is appropriate for
+    /// deterministic strategies.
+    fn set_rng_seed(&mut self, seed: u64) {
+        let _ = seed;
+    }
+
+    /// Returns the transactions to include in a block published by this
+    /// miner which points to `parent`, whose sizes must sum to at most
+    /// `capacity`.
+    ///
+    /// Called for each block this miner publishes without transactions when
+    /// the simulation has a
+    /// [`Mempool`](crate::simulation::SimulationBuilder::mempool).
+    /// `chain` is the miner's view of the blockchain, as passed to
+    /// [`Miner::get_action`]. `capacity` is [`usize::MAX`] unless a
+    /// [`block_capacity`](crate::simulation::SimulationBuilder::block_capacity)
+    /// is set. The default implementation takes the available transactions
+    /// with the highest fees via [`Mempool::select`]. Strategies may instead
+    /// censor transactions, or leave high-fee transactions for later blocks.
+    fn select_txns(
+        &mut self,
+        mempool: &Mempool,
+        capacity: usize,
+        chain: &ChainView,
+        parent: BlockId,
+    ) -> Vec<Transaction> {
+        mempool.select(chain, parent, capacity)
+    }
+
+    /// Returns the blocks which the block with ID `block_mined` references in
+    /// addition to its parent, in a [block-DAG](crate::dag) simulation.
+    ///
+    /// Called in each round in which this miner is the proposer, after
+    /// [`Miner::get_action`], with the `tips` of the simulation's DAG which
+    /// are in `chain`. The references are added when the block is published.
+    /// The default implementation references every tip, like an honest DAG
+    /// miner. Block-withholding strategies may instead reference fewer
+    /// blocks, keeping the blocks of other miners out of their past.
+    fn dag_refs(
+        &mut self,
+        chain: &ChainView,
+        tips: &[BlockId],
+        block_mined: BlockId,
+    ) -> Vec<BlockId> {
+        let _ = (chain, block_mined);
+
+        tips.to_vec()
+    }
+
+    /// Called at the start of each simulation, after
+    /// [`Miner::set_rng_seed`] and before the first round.
+    ///
+    /// Each simulation runs on a copy of the miner as it was given to the
+    /// [`SimulationBuilder`](crate::simulation::SimulationBuilder), or on a
+    /// copy which ran an earlier simulation and was then [reset](Miner::reset).
+    /// Strategies which keep state outside of that copy, or which are reused
+    /// between simulation groups, should reset it here. The default
+    /// implementation does nothing.
+    fn on_simulation_start(&mut self) {}
+
+    /// Called at the end of each simulation which runs to completion, with
+    /// the simulation's output.
+    ///
+    /// The miner is then reset or dropped, so strategies which learn across
+    /// repeated simulations should record what they learn in state shared
+    /// between copies, such as an `Arc<Mutex<_>>`. Simulations may run in
+    /// parallel unless they are run with
+    /// [`SimulationGroup::run_all_sequential`](crate::simulation::SimulationGroup::run_all_sequential).
+    /// The default implementation does nothing.
+    fn on_simulation_end(&mut self, output: &SimulationOutput) {
+        let _ = output;
+    }
+
+    /// Returns this miner to the state it was in when it was given to the
+    /// [`SimulationBuilder`](crate::simulation::SimulationBuilder), keeping
+    /// its [`MinerId`] and tie breaker, so that it can run another simulation
+    /// without being cloned. Returns `false` if the miner cannot be reset.
+    ///
+    /// Called after each simulation. Each thread running simulations reuses
+    /// the miners of a lineup which were reset, and only clones the lineup
+    /// when none are available, which avoids deep copies of strategies with
+    /// large state. Miners which return `false` are replaced with a new copy
+    /// instead. The default implementation returns `false`.
+    fn reset(&mut self) -> bool {
+        false
+    }
+}
+
+/// Reusable copies of a lineup of miners, shared by the simulations of a
+/// [`SimulationGroup`](crate::simulation::SimulationGroup) which use that
+/// lineup. Lineups are only cloned when every copy is in use.
+#[derive(Debug)]
+pub(crate) struct MinerPool {
+    lineup: Vec<Box<dyn Miner>>,
+    idle: Mutex<Vec<Vec<Box<dyn Miner>>>>,
+}
+
+impl MinerPool {
+    pub(crate) fn new(lineup: Vec<Box<dyn Miner>>) -> Self {
+        Self {
+            lineup,
+            idle: Mutex::default(),
+        }
+    }
+
+    /// Returns a copy of the lineup which is ready to run a simulation.
+    pub(crate) fn take(&self) -> Vec<Box<dyn Miner>> {
+        let idle = self.idle.lock().unwrap().pop();
+
+        idle.unwrap_or_else(|| self.lineup.clone())
+    }
+
+    /// Resets `miners` and returns them to the pool. Miners which cannot be
+    /// reset are replaced with a new copy.
+    pub(crate) fn put(&self, mut miners: Vec<Box<dyn Miner>>) {
+        for (miner, original) in miners.iter_mut().zip(self.lineup.iter()) {
+            if !miner.reset() {
+                *miner = original.clone();
+            }
+        }
+
+        self.idle.lock().unwrap().push(miners);
+    }
 }
 
 dyn_clone::clone_trait_object!(Miner);
 
+/// Random number generator owned by a built-in [`Miner`], reseeded by
+/// [`Miner::set_rng_seed`].
+#[derive(Debug, Clone)]
+pub(crate) struct MinerRng(StdRng);
+
+impl MinerRng {
+    pub(crate) fn reseed(&mut self, seed: u64) {
+        self.0 = StdRng::seed_from_u64(seed);
+    }
+}
+
+impl Default for MinerRng {
+    fn default() -> Self {
+        Self(StdRng::seed_from_u64(0))
+    }
+}
+
+impl Deref for MinerRng {
+    type Target = StdRng;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for MinerRng {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 /// Unique identifier of a [`Miner`] implementation. Corresponds to a [`usize`].
 ///
 /// # Invariants
 ///
-/// `MinerId(0)` is reserved for [`Blockchain::GENESIS_MINER`],
+/// `MinerId(0)` is reserved for
+/// [`Blockchain::GENESIS_MINER`](crate::blockchain::Blockchain::GENESIS_MINER),
 /// and as such `MinerId(0)` cannot be instantiated outside of this crate, and
 /// [`MinerId::default`] returns `MinerId(1)`.
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MinerId(pub(crate) usize);
 
 impl MinerId {
@@ -0,0 +1,204 @@
+//! Double-spending attack implementation
+
+use crate::{
+    blockchain::{Block, BlockId},
+    mempool::Mempool,
+    miner::{Action, Miner, MinerId},
+    transaction::Transaction,
+    view::ChainView,
+};
+
+/// Attempts to reverse the payment spending a given input, scheduled with
+/// [`Mempool::double_spend`], following the attack analyzed by
+/// [Nakamoto](https://bitcoin.org/bitcoin.pdf).
+///
+/// Mines honestly until the payment is included in a block at the tip of the
+/// longest chain. Then privately extends the parent of that block with the
+/// conflicting transaction returned by [`Mempool::withheld`], and publishes
+/// the private chain as soon as it is longer than the public chain and the
+/// payment has the given number of confirmations. The attack is abandoned if
+/// the private chain falls [`max_deficit`](DoubleSpend::max_deficit) blocks
+/// behind the public chain, after which the miner mines honestly again.
+#[derive(Debug, Clone)]
+pub struct DoubleSpend {
+    confirmations: usize,
+    id: MinerId,
+    input: usize,
+    max_deficit: usize,
+    /// Parent of the first private block, once the attack has started.
+    fork_parent: Option<BlockId>,
+    /// Block containing the payment, while the attack is in progress.
+    payment_block: Option<BlockId>,
+    private_blocks: Vec<Block>,
+    /// Greatest height searched for the payment.
+    searched_height: usize,
+}
+
+impl DoubleSpend {
+    /// Creates a new miner which attempts to reverse the payment spending
+    /// `input` once it has `confirmations` confirmations. Never abandons the
+    /// attack by default.
+    pub fn new(input: usize, confirmations: usize) -> Self {
+        Self {
+            confirmations,
+            id: MinerId::default(),
+            input,
+            max_deficit: usize::MAX,
+            fork_parent: None,
+            payment_block: None,
+            private_blocks: vec![],
+            searched_height: 0,
+        }
+    }
+
+    /// Abandon the attack once the private chain is `max_deficit` blocks
+    /// shorter than the public chain.
+    pub fn max_deficit(mut self, max_deficit: usize) -> Self {
+        self.max_deficit = max_deficit;
+
+        self
+    }
+
+    /// Returns the block which includes the payment among the blocks
+    /// published at the tip since the last search, if any.
+    fn find_payment(&mut self, chain: &ChainView) -> Option<BlockId> {
+        let heights = self.searched_height + 1..=chain.max_height();
+        self.searched_height = chain.max_height();
+
+        heights
+            .flat_map(|height| chain.at_height(height).unwrap_or_default())
+            .find(|&block_id| {
+                chain[block_id]
+                    .block
+                    .txns
+                    .iter()
+                    .any(|txn| txn.input == Some(self.input))
+            })
+            .copied()
+    }
+}
+
+impl Miner for DoubleSpend {
+    fn name(&self) -> String {
+        format!("Double Spend (z={})", self.confirmations)
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![("z".to_string(), self.confirmations as f64)]
+    }
+
+    fn id(&self) -> MinerId {
+        self.id
+    }
+
+    fn set_id(&mut self, id: MinerId) {
+        self.id = id;
+    }
+
+    fn get_action(
+        &mut self,
+        chain: &ChainView,
+        block_mined: Option<BlockId>,
+    ) -> Action {
+        if self.fork_parent.is_none() {
+            if let Some(block_id) = self.find_payment(chain) {
+                self.fork_parent = chain.get_parent(block_id);
+                self.payment_block = Some(block_id);
+            }
+        }
+
+        let (Some(fork_parent), Some(payment_block)) =
+            (self.fork_parent, self.payment_block)
+        else {
+            // Mine honestly
+            return match block_mined {
+                Some(block_id) => Action::Publish(Block {
+                    id: block_id,
+                    parent_id: Some(chain.tip()[0]),
+                    miner_id: self.id,
+                    txns: vec![],
+                }),
+                None => Action::Wait,
+            };
+        };
+
+        if let Some(block_id) = block_mined {
+            let parent_id = self
+                .private_blocks
+                .last()
+                .map_or(fork_parent, |block| block.id);
+            self.private_blocks.push(Block {
+                id: block_id,
+                parent_id: Some(parent_id),
+                miner_id: self.id,
+                txns: vec![],
+            });
+        }
+
+        let public_height = chain.max_height();
+        let private_height =
+            chain[fork_parent].height + self.private_blocks.len();
+        let confirmations = public_height + 1 - chain[payment_block].height;
+
+        if private_height > public_height && confirmations >= self.confirmations
+        {
+            self.payment_block = None;
+            Action::PublishSet(std::mem::take(&mut self.private_blocks))
+        } else if public_height.saturating_sub(private_height)
+            >= self.max_deficit
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                miner = %self.id,
+                abandoned = self.private_blocks.len(),
+                "capitulated"
+            );
+            self.payment_block = None;
+            self.private_blocks.clear();
+            Action::Wait
+        } else {
+            Action::Wait
+        }
+    }
+
+    fn select_txns(
+        &mut self,
+        mempool: &Mempool,
+        capacity: usize,
+        chain: &ChainView,
+        parent: BlockId,
+    ) -> Vec<Transaction> {
+        let mut txns = vec![];
+        let mut capacity = capacity;
+
+        // Spend the payment's input on the attacker's branch
+        if let Some(conflict) = mempool.withheld(self.input) {
+            if self.fork_parent.is_some()
+                && conflict.size <= capacity
+                && !mempool.is_spent(chain, parent, self.input)
+            {
+                capacity -= conflict.size;
+                txns.push(conflict.clone());
+            }
+        }
+
+        let payment = |txn: &Transaction| txn.input == Some(self.input);
+        txns.extend(
+            mempool
+                .select(chain, parent, capacity)
+                .into_iter()
+                .filter(|txn| !payment(txn)),
+        );
+
+        txns
+    }
+
+    fn reset(&mut self) -> bool {
+        self.fork_parent = None;
+        self.payment_block = None;
+        self.private_blocks.clear();
+        self.searched_height = 0;
+
+        true
+    }
+}
@@ -0,0 +1,141 @@
+//! Nothing-at-stake mining, which extends every recent block
+
+use crate::{
+    blockchain::{Block, BlockId},
+    miner::{Action, Miner, MinerId, MinerRng},
+    stake::ProofOfStake,
+    tie_breaker::TieBreaker,
+    view::ChainView,
+};
+
+/// Publishes a block on every block which is at most `depth` blocks below the
+/// tip of the longest chain, starting with the tip chosen by its
+/// [`TieBreaker`] and continuing from the highest blocks, up to
+/// [`Equivocating::max_blocks`] blocks per slot.
+///
+/// Meant for [proof-of-stake](crate::stake) simulations, where the extra
+/// blocks use the IDs reserved for the slot. With a maximum of 1 block per
+/// slot, this strategy is the same as
+/// [`Honest`](crate::miner::honest::Honest).
+#[derive(Debug, Clone)]
+pub struct Equivocating {
+    id: MinerId,
+    depth: usize,
+    max_blocks: usize,
+    rng: MinerRng,
+    tie_breaker: Option<TieBreaker>,
+}
+
+impl Equivocating {
+    /// Creates a new equivocating miner, which extends blocks at most `depth`
+    /// blocks below the tip, publishing up to
+    /// [`ProofOfStake::DEFAULT_SLOT_CAPACITY`] blocks per slot.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            id: MinerId::default(),
+            depth,
+            max_blocks: ProofOfStake::DEFAULT_SLOT_CAPACITY,
+            rng: MinerRng::default(),
+            tie_breaker: None,
+        }
+    }
+
+    /// Publish at most `blocks` blocks per slot, which must not exceed the
+    /// [`ProofOfStake::slot_capacity`] of the simulation.
+    ///
+    /// # Panics
+    /// Panics if `blocks` is 0.
+    pub fn max_blocks(mut self, blocks: usize) -> Self {
+        assert!(
+            blocks > 0,
+            "miners must publish at least one block per slot"
+        );
+        self.max_blocks = blocks;
+
+        self
+    }
+
+    /// Returns the blocks within `depth` blocks of the tip, highest first,
+    /// and in publishing order at each height.
+    fn recent_blocks(&self, chain: &ChainView) -> Vec<BlockId> {
+        let max_height = chain.max_height();
+        let lowest = max_height.saturating_sub(self.depth);
+
+        (lowest..=max_height)
+            .rev()
+            .flat_map(|height| chain.at_height(height).unwrap_or_default())
+            .copied()
+            .collect()
+    }
+}
+
+impl Miner for Equivocating {
+    fn name(&self) -> String {
+        format!("Equivocating (depth={})", self.depth)
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            ("depth".to_string(), self.depth as f64),
+            ("max_blocks".to_string(), self.max_blocks as f64),
+        ]
+    }
+
+    fn id(&self) -> MinerId {
+        self.id
+    }
+
+    fn set_id(&mut self, id: MinerId) {
+        self.id = id;
+    }
+
+    fn set_default_tie_breaker(&mut self, tie_breaker: TieBreaker) {
+        self.tie_breaker.get_or_insert(tie_breaker);
+    }
+
+    fn set_rng_seed(&mut self, seed: u64) {
+        self.rng.reseed(seed);
+    }
+
+    fn get_action(
+        &mut self,
+        chain: &ChainView,
+        block_mined: Option<BlockId>,
+    ) -> Action {
+        let Some(block_id) = block_mined else {
+            return Action::Wait;
+        };
+
+        let tip = self
+            .tie_breaker
+            .clone()
+            .unwrap_or_default()
+            .choose_with(&mut *self.rng, chain);
+        let mut parents = vec![tip];
+        for block in self.recent_blocks(chain) {
+            if parents.len() == self.max_blocks {
+                break;
+            }
+            if block != tip {
+                parents.push(block);
+            }
+        }
+
+        let blocks = parents
+            .into_iter()
+            .zip(block_id.0..)
+            .map(|(parent, id)| Block {
+                id: BlockId(id),
+                parent_id: Some(parent),
+                miner_id: self.id,
+                txns: vec![],
+            })
+            .collect();
+
+        Action::PublishSet(blocks)
+    }
+
+    fn reset(&mut self) -> bool {
+        true
+    }
+}
@@ -1,16 +1,18 @@
 //! Honest/Frontier mining strategy
 
 use crate::{
-    blockchain::{Block, BlockId, Blockchain},
-    miner::{Action, Miner, MinerId},
+    blockchain::{Block, BlockId},
+    miner::{Action, Miner, MinerId, MinerRng},
     tie_breaker::TieBreaker,
+    view::ChainView,
 };
 
 /// Publishes all blocks as soon as possible at the tip of the longest chain.
 #[derive(Debug, Default, Clone)]
 pub struct Honest {
     id: MinerId,
-    tie_breaker: TieBreaker,
+    rng: MinerRng,
+    tie_breaker: Option<TieBreaker>,
 }
 
 impl Honest {
@@ -22,7 +24,7 @@ impl Honest {
     /// Creates a new honest miner which breaks ties using `tie_breaker`.
     pub fn with_tie_breaker(tie_breaker: TieBreaker) -> Self {
         Honest {
-            tie_breaker,
+            tie_breaker: Some(tie_breaker),
             ..Default::default()
         }
     }
@@ -41,19 +43,36 @@ impl Miner for Honest {
         self.id = id;
     }
 
+    fn set_default_tie_breaker(&mut self, tie_breaker: TieBreaker) {
+        self.tie_breaker.get_or_insert(tie_breaker);
+    }
+
+    fn set_rng_seed(&mut self, seed: u64) {
+        self.rng.reseed(seed);
+    }
+
     fn get_action(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) -> Action {
         match block_mined {
             Some(block_id) => Action::Publish(Block {
                 id: block_id,
-                parent_id: Some(self.tie_breaker.choose(chain)),
+                parent_id: Some(
+                    self.tie_breaker
+                        .as_ref()
+                        .unwrap_or(&TieBreaker::EarliestPublished)
+                        .choose_with(&mut *self.rng, chain),
+                ),
                 miner_id: self.id,
                 txns: vec![],
             }),
             None => Action::Wait,
         }
     }
+
+    fn reset(&mut self) -> bool {
+        true
+    }
 }
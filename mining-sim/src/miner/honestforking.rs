@@ -3,9 +3,10 @@
 use rand::Rng;
 
 use crate::{
-    blockchain::{Block, BlockId, Blockchain},
-    miner::{Action, Miner, MinerId},
+    blockchain::{Block, BlockId},
+    miner::{Action, Miner, MinerId, MinerRng},
     tie_breaker::TieBreaker,
+    view::ChainView,
 };
 
 /// Mines one behind the longest chain with probability `p`, following the
@@ -14,7 +15,8 @@ use crate::{
 pub struct HonestForking {
     id: MinerId,
     p: f64,
-    tie_breaker: TieBreaker,
+    rng: MinerRng,
+    tie_breaker: Option<TieBreaker>,
 }
 
 impl HonestForking {
@@ -31,7 +33,7 @@ impl HonestForking {
     pub fn with_tie_breaker(p: f64, tie_breaker: TieBreaker) -> Self {
         HonestForking {
             p,
-            tie_breaker,
+            tie_breaker: Some(tie_breaker),
             ..Default::default()
         }
     }
@@ -42,6 +44,10 @@ impl Miner for HonestForking {
         format!("Honest Forking (p={})", self.p)
     }
 
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![("p".to_string(), self.p)]
+    }
+
     fn id(&self) -> MinerId {
         self.id
     }
@@ -50,18 +56,30 @@ impl Miner for HonestForking {
         self.id = id;
     }
 
+    fn set_default_tie_breaker(&mut self, tie_breaker: TieBreaker) {
+        self.tie_breaker.get_or_insert(tie_breaker);
+    }
+
+    fn set_rng_seed(&mut self, seed: u64) {
+        self.rng.reseed(seed);
+    }
+
     fn get_action(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) -> Action {
         match block_mined {
             Some(block_id) => {
-                let lc = self.tie_breaker.choose(chain);
+                let lc = self
+                    .tie_breaker
+                    .as_ref()
+                    .unwrap_or(&TieBreaker::EarliestPublished)
+                    .choose_with(&mut *self.rng, chain);
 
                 Action::Publish(Block {
                     id: block_id,
-                    parent_id: if rand::thread_rng().gen_bool(self.p) {
+                    parent_id: if self.rng.gen_bool(self.p) {
                         chain[lc].block.parent_id.or(Some(lc))
                     } else {
                         Some(lc)
@@ -73,4 +91,8 @@ impl Miner for HonestForking {
             None => Action::Wait,
         }
     }
+
+    fn reset(&mut self) -> bool {
+        true
+    }
 }
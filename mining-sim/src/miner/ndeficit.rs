@@ -10,9 +10,10 @@
 use std::collections::{HashSet, VecDeque};
 
 use crate::{
-    blockchain::{Block, BlockId, Blockchain},
-    miner::{Action, Miner, MinerId},
+    blockchain::{Block, BlockId},
+    miner::{Action, Miner, MinerId, MinerRng},
     tie_breaker::TieBreaker,
+    view::ChainView,
 };
 
 /// An `i-Deficit` miner which belongs to the N-Deficit family of mining
@@ -21,6 +22,7 @@ use crate::{
 pub struct NDeficit {
     i: usize,
     id: MinerId,
+    rng: MinerRng,
     tie_breaker: TieBreaker,
 
     // Blockchain state tracking
@@ -57,16 +59,24 @@ impl NDeficit {
 
     /// Capitulates to B_{0, 0} with `head` as the genesis block.
     fn capitulate(&mut self, genesis: BlockId) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            miner = %self.id,
+            genesis = %genesis,
+            abandoned = self.our_blocks.len(),
+            "capitulated"
+        );
+
         self.capitulation = genesis;
         self.clear_state();
     }
 
     fn update_state(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) {
-        let tip = self.tie_breaker.choose(chain);
+        let tip = self.tie_breaker.choose_with(&mut *self.rng, chain);
         let cap_height = chain[self.capitulation].height;
 
         // Ignore states of the form [H(x), ..]
@@ -196,6 +206,12 @@ impl NDeficit {
                 let x = *x;
 
                 // Manually capitulate to B_{1, 1}
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    miner = %self.id,
+                    genesis = %self.honest_blocks[x - 1],
+                    "capitulated"
+                );
                 self.state = vec![A(1), H(1)];
                 self.capitulation = self.honest_blocks[x - 1];
                 self.our_blocks.pop_front();
@@ -216,6 +232,10 @@ impl Miner for NDeficit {
         format!("{}-Deficit", self.i)
     }
 
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![("i".to_string(), self.i as f64)]
+    }
+
     fn id(&self) -> MinerId {
         self.id
     }
@@ -225,12 +245,23 @@ impl Miner for NDeficit {
         self.tie_breaker = TieBreaker::FavorMiner(id);
     }
 
+    fn set_rng_seed(&mut self, seed: u64) {
+        self.rng.reseed(seed);
+    }
+
     fn get_action(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) -> super::Action {
         self.update_state(chain, block_mined);
         self.map_state()
     }
+
+    fn reset(&mut self) -> bool {
+        self.capitulation = BlockId::default();
+        self.clear_state();
+
+        true
+    }
 }
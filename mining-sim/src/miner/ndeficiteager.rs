@@ -20,9 +20,10 @@ though it might not be optimal according to his criteria
 use std::collections::{HashSet, VecDeque};
 
 use crate::{
-    blockchain::{Block, BlockId, Blockchain},
-    miner::{Action, Miner, MinerId},
+    blockchain::{Block, BlockId},
+    miner::{Action, Miner, MinerId, MinerRng},
     tie_breaker::TieBreaker,
+    view::ChainView,
 };
 
 /// An `i-Deficit` miner which belongs to the N-Deficit family of mining
@@ -31,6 +32,7 @@ use crate::{
 pub struct NDeficitEager {
     i: usize,
     id: MinerId,
+    rng: MinerRng,
     tie_breaker: TieBreaker,
 
     // Blockchain state tracking
@@ -67,16 +69,24 @@ impl NDeficitEager {
 
     /// Capitulates to B_{0, 0} with `head` as the genesis block.
     fn capitulate(&mut self, genesis: BlockId) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            miner = %self.id,
+            genesis = %genesis,
+            abandoned = self.our_blocks.len(),
+            "capitulated"
+        );
+
         self.capitulation = genesis;
         self.clear_state();
     }
 
     fn update_state(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) {
-        let tip = self.tie_breaker.choose(chain);
+        let tip = self.tie_breaker.choose_with(&mut *self.rng, chain);
         let cap_height = chain[self.capitulation].height;
 
         // Ignore states of the form [H(x), ..]
@@ -226,6 +236,12 @@ impl NDeficitEager {
                 let x = *x;
 
                 // Manually capitulate to B_{1, 1}
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    miner = %self.id,
+                    genesis = %self.honest_blocks[x - 1],
+                    "capitulated"
+                );
                 self.state = vec![A(1), H(1)];
                 self.capitulation = self.honest_blocks[x - 1];
                 self.our_blocks.pop_front();
@@ -246,6 +262,10 @@ impl Miner for NDeficitEager {
         format!("{}-Deficit Eager", self.i)
     }
 
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![("i".to_string(), self.i as f64)]
+    }
+
     fn id(&self) -> MinerId {
         self.id
     }
@@ -255,9 +275,13 @@ impl Miner for NDeficitEager {
         self.tie_breaker = TieBreaker::FavorMiner(id);
     }
 
+    fn set_rng_seed(&mut self, seed: u64) {
+        self.rng.reseed(seed);
+    }
+
     fn get_action(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) -> super::Action {
         let lc = chain.tip();
@@ -267,15 +291,19 @@ impl Miner for NDeficitEager {
             lc.iter().find(|&b| chain[b].block.miner_id != self.id);
 
         // Handle selfish mining fork case
-        if self.our_blocks.is_empty()
-            && block_mined.is_some()
-            && ours_at_lc.is_some()
-            && othr_at_lc.is_some()
-        {
-            println!("fork case");
-
-            let block_id = block_mined.unwrap();
-            let parent_id = ours_at_lc.unwrap();
+        if let (true, Some(block_id), Some(parent_id), Some(_)) = (
+            self.our_blocks.is_empty(),
+            block_mined,
+            ours_at_lc,
+            othr_at_lc,
+        ) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                miner = %self.id,
+                block = %block_id,
+                parent = %parent_id,
+                "forked the longest chain"
+            );
 
             self.capitulate(block_id);
 
@@ -290,4 +318,11 @@ impl Miner for NDeficitEager {
             self.map_state()
         }
     }
+
+    fn reset(&mut self) -> bool {
+        self.capitulation = BlockId::default();
+        self.clear_state();
+
+        true
+    }
 }
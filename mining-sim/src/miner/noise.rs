@@ -1,8 +1,9 @@
 use rand::Rng;
 
 use crate::{
-    blockchain::{Block, BlockId, Blockchain},
-    miner::{Action, Miner, MinerId},
+    blockchain::{Block, BlockId},
+    miner::{Action, Miner, MinerId, MinerRng},
+    view::ChainView,
 };
 
 /// Publishes blocks immediately upon mining them, selecting the parent block
@@ -10,6 +11,7 @@ use crate::{
 #[derive(Debug, Clone)]
 pub struct Noise {
     id: MinerId,
+    rng: MinerRng,
 }
 
 impl Miner for Noise {
@@ -25,9 +27,13 @@ impl Miner for Noise {
         self.id = id;
     }
 
+    fn set_rng_seed(&mut self, seed: u64) {
+        self.rng.reseed(seed);
+    }
+
     fn get_action(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) -> Action {
         match block_mined {
@@ -37,7 +43,7 @@ impl Miner for Noise {
 
                 let mut parent = block_num;
                 while !chain.contains(parent.into()) {
-                    parent = rand::thread_rng().gen_range(0..block_num);
+                    parent = self.rng.gen_range(0..block_num);
                 }
 
                 Action::Publish(Block {
@@ -49,4 +55,8 @@ impl Miner for Noise {
             }
         }
     }
+
+    fn reset(&mut self) -> bool {
+        true
+    }
 }
@@ -1,8 +1,9 @@
 //! Strategy which never publishes a block
 
 use crate::{
-    blockchain::{BlockId, Blockchain},
+    blockchain::BlockId,
     miner::{Action, Miner, MinerId},
+    view::ChainView,
 };
 
 /// [`.get_action`](Noop::get_action) always returns [`Action::Wait`].
@@ -29,7 +30,11 @@ impl Miner for Noop {
         self.0 = id;
     }
 
-    fn get_action(&mut self, _: &Blockchain, _: Option<BlockId>) -> Action {
+    fn get_action(&mut self, _: &ChainView, _: Option<BlockId>) -> Action {
         Action::Wait
     }
+
+    fn reset(&mut self) -> bool {
+        true
+    }
 }
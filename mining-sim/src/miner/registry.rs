@@ -0,0 +1,340 @@
+/*!
+Creating miners from strategy names and parameter maps
+
+The registry maps strategy names to functions which construct boxed [`Miner`]
+instances from a map of named numeric parameters, so that experiments can be
+described by configuration files instead of Rust code. Every built-in strategy
+is registered under the following name, with the following parameters:
+
+| Name             | Strategy            | Parameters                             |
+|------------------|---------------------|----------------------------------------|
+| `honest`         | [`Honest`]          |                                        |
+| `selfish`        | [`Selfish`]         |                                        |
+| `noop`           | [`Noop`]            |                                        |
+| `honest-forking` | [`HonestForking`]   | `p`                                    |
+| `ndeficit`       | [`NDeficit`]        | `i`                                    |
+| `ndeficit-eager` | [`NDeficitEager`]   | `i`                                    |
+| `double-spend`   | [`DoubleSpend`]     | `input`, `z`, `max_deficit` (optional) |
+| `equivocating`   | [`Equivocating`]    | `depth`, `max_blocks` (optional)       |
+
+User strategies can be added with [`register`].
+
+## Example
+```
+use mining_sim::miner::registry;
+use mining_sim::prelude::*;
+
+let sim = SimulationBuilder::new()
+    .add_boxed_miner(registry::create("honest", []).unwrap())
+    .add_boxed_miner(registry::create("ndeficit", [("i", 2.0)]).unwrap())
+    .build()
+    .unwrap();
+```
+*/
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, OnceLock, RwLock},
+};
+
+use crate::{
+    miner::{
+        doublespend::DoubleSpend, equivocating::Equivocating, honest::Honest,
+        honestforking::HonestForking, ndeficit::NDeficit,
+        ndeficiteager::NDeficitEager, noop::Noop, selfish::Selfish, Miner,
+    },
+    stake::ProofOfStake,
+};
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RegistryError {
+    #[error("no strategy is registered under the name {0:?}")]
+    UnknownStrategy(String),
+    #[error("a strategy is already registered under the name {0:?}")]
+    DuplicateStrategy(String),
+    #[error("strategy {strategy:?} requires parameter {param:?}")]
+    MissingParam { strategy: String, param: String },
+    #[error("strategy {strategy:?} has no parameter {param:?}")]
+    UnknownParam { strategy: String, param: String },
+    #[error("parameter {param:?} of strategy {strategy:?} cannot be {value}")]
+    BadParam {
+        strategy: String,
+        param: String,
+        value: f64,
+    },
+}
+
+/// Named parameters passed to a strategy's constructor by [`create`].
+///
+/// Constructors read each parameter they accept with [`Params::take`] or one
+/// of its variants. Parameters which are not taken are reported by [`create`]
+/// as [`RegistryError::UnknownParam`], so that misspelled parameters in
+/// configuration files are not silently ignored.
+#[derive(Debug, Clone)]
+pub struct Params {
+    strategy: String,
+    values: BTreeMap<String, f64>,
+}
+
+impl Params {
+    /// Removes and returns the parameter named `param`.
+    pub fn take(&mut self, param: &str) -> Result<f64, RegistryError> {
+        self.values
+            .remove(param)
+            .ok_or_else(|| RegistryError::MissingParam {
+                strategy: self.strategy.clone(),
+                param: param.to_string(),
+            })
+    }
+
+    /// Removes and returns the parameter named `param`, or `default` if it
+    /// was not given.
+    pub fn take_or(&mut self, param: &str, default: f64) -> f64 {
+        self.values.remove(param).unwrap_or(default)
+    }
+
+    /// Removes and returns the parameter named `param`, which must be a
+    /// non-negative integer.
+    pub fn take_usize(&mut self, param: &str) -> Result<usize, RegistryError> {
+        let value = self.take(param)?;
+        self.check_usize(param, value)
+    }
+
+    /// Removes and returns the parameter named `param`, which must be a
+    /// non-negative integer, or `default` if it was not given.
+    pub fn take_usize_or(
+        &mut self,
+        param: &str,
+        default: usize,
+    ) -> Result<usize, RegistryError> {
+        match self.values.remove(param) {
+            Some(value) => self.check_usize(param, value),
+            None => Ok(default),
+        }
+    }
+
+    /// Returns a [`RegistryError::BadParam`] for the parameter named `param`
+    /// with the given value.
+    pub fn bad_param(&self, param: &str, value: f64) -> RegistryError {
+        RegistryError::BadParam {
+            strategy: self.strategy.clone(),
+            param: param.to_string(),
+            value,
+        }
+    }
+
+    fn check_usize(
+        &self,
+        param: &str,
+        value: f64,
+    ) -> Result<usize, RegistryError> {
+        if value >= 0.0 && value.fract() == 0.0 && value <= usize::MAX as f64 {
+            Ok(value as usize)
+        } else {
+            Err(self.bad_param(param, value))
+        }
+    }
+}
+
+/// Function which constructs a strategy from its [`Params`].
+pub type Constructor = Arc<
+    dyn Fn(&mut Params) -> Result<Box<dyn Miner>, RegistryError> + Send + Sync,
+>;
+
+static REGISTRY: OnceLock<RwLock<HashMap<String, Constructor>>> =
+    OnceLock::new();
+
+fn registry() -> &'static RwLock<HashMap<String, Constructor>> {
+    REGISTRY.get_or_init(|| {
+        let mut builtins: HashMap<String, Constructor> = HashMap::new();
+        let mut add = |name: &str, constructor: Constructor| {
+            builtins.insert(name.to_string(), constructor);
+        };
+
+        add("honest", Arc::new(|_| Ok(Box::new(Honest::new()))));
+        add("selfish", Arc::new(|_| Ok(Box::new(Selfish::new()))));
+        add("noop", Arc::new(|_| Ok(Box::new(Noop::new()))));
+        add(
+            "honest-forking",
+            Arc::new(|params| {
+                let p = params.take("p")?;
+                if !(0.0..=1.0).contains(&p) {
+                    return Err(params.bad_param("p", p));
+                }
+
+                Ok(Box::new(HonestForking::new(p)))
+            }),
+        );
+        add(
+            "ndeficit",
+            Arc::new(|params| {
+                let i = params.take_usize("i")?;
+                if i == 0 {
+                    return Err(params.bad_param("i", 0.0));
+                }
+
+                Ok(Box::new(NDeficit::new(i)))
+            }),
+        );
+        add(
+            "ndeficit-eager",
+            Arc::new(|params| {
+                let i = params.take_usize("i")?;
+                if i == 0 {
+                    return Err(params.bad_param("i", 0.0));
+                }
+
+                Ok(Box::new(NDeficitEager::new(i)))
+            }),
+        );
+        add(
+            "double-spend",
+            Arc::new(|params| {
+                let input = params.take_usize("input")?;
+                let confirmations = params.take_usize("z")?;
+                let max_deficit =
+                    params.take_usize_or("max_deficit", usize::MAX)?;
+
+                Ok(Box::new(
+                    DoubleSpend::new(input, confirmations)
+                        .max_deficit(max_deficit),
+                ))
+            }),
+        );
+        add(
+            "equivocating",
+            Arc::new(|params| {
+                let depth = params.take_usize("depth")?;
+                let max_blocks = params.take_usize_or(
+                    "max_blocks",
+                    ProofOfStake::DEFAULT_SLOT_CAPACITY,
+                )?;
+                if max_blocks == 0 {
+                    return Err(params.bad_param("max_blocks", 0.0));
+                }
+
+                Ok(Box::new(Equivocating::new(depth).max_blocks(max_blocks)))
+            }),
+        );
+
+        RwLock::new(builtins)
+    })
+}
+
+/// Creates a new instance of the strategy registered under `name` with the
+/// given parameters.
+pub fn create<'a, I>(
+    name: &str,
+    params: I,
+) -> Result<Box<dyn Miner>, RegistryError>
+where
+    I: IntoIterator<Item = (&'a str, f64)>,
+{
+    let constructor = registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+        .cloned()
+        .ok_or_else(|| RegistryError::UnknownStrategy(name.to_string()))?;
+
+    let mut params = Params {
+        strategy: name.to_string(),
+        values: params
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    };
+    let miner = constructor(&mut params)?;
+
+    match params.values.into_keys().next() {
+        Some(param) => Err(RegistryError::UnknownParam {
+            strategy: params.strategy,
+            param,
+        }),
+        None => Ok(miner),
+    }
+}
+
+/// Registers a user strategy under `name`, which can then be created with
+/// [`create`]. Registered strategies are available to every thread for the
+/// rest of the program.
+pub fn register<F>(name: &str, constructor: F) -> Result<(), RegistryError>
+where
+    F: Fn(&mut Params) -> Result<Box<dyn Miner>, RegistryError>
+        + Send
+        + Sync
+        + 'static,
+{
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    if registry.contains_key(name) {
+        return Err(RegistryError::DuplicateStrategy(name.to_string()));
+    }
+    registry.insert(name.to_string(), Arc::new(constructor));
+
+    Ok(())
+}
+
+/// Returns the names of all registered strategies in alphabetical order.
+pub fn names() -> Vec<String> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    let mut names: Vec<_> = registry.keys().cloned().collect();
+    names.sort();
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create, names, register, RegistryError};
+    use crate::miner::honest::Honest;
+
+    #[test]
+    fn builtin_strategies() {
+        let miner = create("ndeficit", [("i", 2.0)]).unwrap();
+        assert_eq!(miner.name(), "2-Deficit");
+        assert_eq!(miner.params(), vec![("i".to_string(), 2.0)]);
+
+        let miner =
+            create("double-spend", [("input", 0.0), ("z", 3.0)]).unwrap();
+        assert_eq!(miner.name(), "Double Spend (z=3)");
+
+        assert_eq!(
+            create("ndeficit", [("i", 1.5)]).unwrap_err(),
+            RegistryError::BadParam {
+                strategy: "ndeficit".into(),
+                param: "i".into(),
+                value: 1.5
+            }
+        );
+        assert_eq!(
+            create("honest-forking", [("q", 0.5)]).unwrap_err(),
+            RegistryError::MissingParam {
+                strategy: "honest-forking".into(),
+                param: "p".into()
+            }
+        );
+        assert_eq!(
+            create("honest", [("p", 0.5)]).unwrap_err(),
+            RegistryError::UnknownParam {
+                strategy: "honest".into(),
+                param: "p".into()
+            }
+        );
+        assert!(matches!(
+            create("stubborn", []),
+            Err(RegistryError::UnknownStrategy(_))
+        ));
+    }
+
+    #[test]
+    fn user_strategies() {
+        register("test-honest", |_| Ok(Box::new(Honest::new()))).unwrap();
+
+        assert!(names().contains(&"test-honest".to_string()));
+        assert_eq!(create("test-honest", []).unwrap().name(), "Honest");
+        assert_eq!(
+            register("honest", |_| Ok(Box::new(Honest::new()))),
+            Err(RegistryError::DuplicateStrategy("honest".into()))
+        );
+    }
+}
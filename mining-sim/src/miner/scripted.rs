@@ -0,0 +1,378 @@
+/*!
+Defining strategies with [Rhai](https://rhai.rs) scripts
+
+Requires the `scripting` feature.
+
+A [`ScriptedStrategy`] calls the function `get_action(chain, miner_id,
+block_mined)` defined by its script once per round:
+- `chain` is the miner's view of the blockchain, which can be queried with the
+  methods listed below. Block IDs are integers.
+- `miner_id` is the miner's ID.
+- `block_mined` is the ID of the block mined by the miner this round, or `()`
+  if the miner was not selected as the proposer.
+
+The function returns `()` to wait, a map `#{ id: .., parent: .. }` to publish
+a single block, or an array of such maps to publish a set of blocks in order.
+Within the function, `this` is an object map which persists between rounds of
+a simulation, so that strategies can keep private blocks and other state.
+
+| Method                    | Returns                                           |
+|---------------------------|---------------------------------------------------|
+| `chain.tip()`             | IDs of the blocks at the tip of the longest chain |
+| `chain.max_height()`      | Height of the longest chain                       |
+| `chain.genesis()`         | ID of the genesis block                           |
+| `chain.num_blocks()`      | Number of blocks, including the genesis block     |
+| `chain.contains(id)`      | Whether the block is in the chain                 |
+| `chain.at_height(h)`      | IDs of the blocks at height `h`                   |
+| `chain.height(id)`        | Height of the block                               |
+| `chain.parent(id)`        | ID of the block's parent, or `()` for genesis     |
+| `chain.miner(id)`         | ID of the miner which published the block         |
+| `chain.last_checkpoint()` | ID of the last finalized block                    |
+
+## Example
+A simplified selfish miner, which withholds each block it mines until another
+miner publishes a block or it mines a second block:
+```
+use mining_sim::miner::scripted::ScriptedStrategy;
+use mining_sim::prelude::*;
+
+let script = r#"
+    fn get_action(chain, miner_id, block_mined) {
+        if block_mined == () {
+            if this.withheld != () {
+                let block = this.withheld;
+                this.withheld = ();
+                return block;
+            }
+            return ();
+        }
+        if this.withheld == () {
+            let block = #{ id: block_mined, parent: chain.tip()[0] };
+            this.withheld = block;
+            return ();
+        }
+        let withheld = this.withheld;
+        this.withheld = ();
+        [withheld, #{ id: block_mined, parent: withheld.id }]
+    }
+"#;
+
+let sim = SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(ScriptedStrategy::new("Scripted Selfish", script).unwrap())
+    .rounds(100)
+    .build()
+    .unwrap();
+
+sim.run_all().unwrap();
+```
+*/
+
+use std::sync::Arc;
+
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST, INT};
+
+use crate::{
+    blockchain::{Block, BlockId, Blockchain, ChainQuery},
+    miner::{Action, Miner, MinerId},
+    view::ChainView,
+};
+
+/// Name of the script function called by [`ScriptedStrategy::get_action`].
+const GET_ACTION: &str = "get_action";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScriptError {
+    #[error("could not compile script: {0}")]
+    Compile(String),
+    #[error("script does not define get_action(chain, miner_id, block_mined)")]
+    MissingGetAction,
+}
+
+/// Miner whose strategy is defined by a Rhai script. See the
+/// [module documentation](self) for the script API.
+///
+/// # Panics
+/// [`Miner::get_action`] panics if the script raises an error or returns a
+/// value which is not a valid action.
+#[derive(Debug, Clone)]
+pub struct ScriptedStrategy {
+    ast: Arc<AST>,
+    engine: Arc<Engine>,
+    id: MinerId,
+    /// Copy of the miner's view of the blockchain, shared with the script.
+    mirror: ScriptChain,
+    name: String,
+    /// Value of `this` within the script.
+    state: Dynamic,
+}
+
+impl ScriptedStrategy {
+    /// Compiles `script` into a new miner named `name`.
+    pub fn new<T: Into<String>>(
+        name: T,
+        script: &str,
+    ) -> Result<Self, ScriptError> {
+        let engine = script_engine();
+        let ast = engine
+            .compile(script)
+            .map_err(|e| ScriptError::Compile(e.to_string()))?;
+
+        let defines_get_action = ast
+            .iter_functions()
+            .any(|f| f.name == GET_ACTION && f.params.len() == 3);
+        if !defines_get_action {
+            return Err(ScriptError::MissingGetAction);
+        }
+
+        Ok(Self {
+            ast: Arc::new(ast),
+            engine: Arc::new(engine),
+            id: MinerId::default(),
+            mirror: ScriptChain::default(),
+            name: name.into(),
+            state: Dynamic::from_map(Map::new()),
+        })
+    }
+
+    /// Converts the value returned by the script into an [`Action`].
+    fn to_action(&self, value: Dynamic) -> Result<Action, String> {
+        if value.is_unit() {
+            return Ok(Action::Wait);
+        }
+        if value.is_array() {
+            let blocks = value
+                .into_array()?
+                .into_iter()
+                .map(|block| self.to_block(block))
+                .collect::<Result<_, _>>()?;
+
+            return Ok(Action::PublishSet(blocks));
+        }
+
+        self.to_block(value).map(Action::Publish)
+    }
+
+    fn to_block(&self, value: Dynamic) -> Result<Block, String> {
+        let map = value
+            .try_cast::<Map>()
+            .ok_or("expected a map #{ id: .., parent: .. }")?;
+        let field = |name: &str| {
+            map.get(name)
+                .and_then(|v| v.as_int().ok())
+                .and_then(|v| usize::try_from(v).ok())
+                .ok_or(format!("expected a block ID in field {name:?}"))
+        };
+
+        Ok(Block {
+            id: BlockId(field("id")?),
+            parent_id: Some(BlockId(field("parent")?)),
+            miner_id: self.id,
+            txns: vec![],
+        })
+    }
+}
+
+impl Miner for ScriptedStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn id(&self) -> MinerId {
+        self.id
+    }
+
+    fn set_id(&mut self, id: MinerId) {
+        self.id = id;
+    }
+
+    fn get_action(
+        &mut self,
+        chain: &ChainView,
+        block_mined: Option<BlockId>,
+    ) -> Action {
+        self.mirror.sync(chain);
+
+        let block_mined = match block_mined {
+            Some(block_id) => Dynamic::from_int(block_id.0 as INT),
+            None => Dynamic::UNIT,
+        };
+        let args = (self.mirror.clone(), self.id.0 as INT, block_mined);
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+
+        self.engine
+            .call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &self.ast,
+                GET_ACTION,
+                args,
+            )
+            .map_err(|e| e.to_string())
+            .and_then(|value| self.to_action(value))
+            .unwrap_or_else(|e| panic!("script of {} failed: {}", self.name, e))
+    }
+
+    fn on_simulation_start(&mut self) {
+        self.mirror = ScriptChain::default();
+        self.state = Dynamic::from_map(Map::new());
+    }
+
+    fn reset(&mut self) -> bool {
+        self.on_simulation_start();
+
+        true
+    }
+}
+
+/// Blockchain exposed to scripts as the `Chain` type. Cloning is cheap, so
+/// that the chain can be passed to the script in each round.
+#[derive(Debug, Clone, Default)]
+struct ScriptChain(Arc<Blockchain>);
+
+impl ScriptChain {
+    /// Copies the blocks of `chain` which are not yet in this chain.
+    fn sync(&mut self, chain: &dyn ChainQuery) {
+        // This chain only holds blocks of `chain`, so it is up to date
+        // whenever both have the same number of blocks
+        if self.0.num_blocks() != chain.num_blocks() {
+            // Copy-on-write if the script kept a copy of the chain
+            Arc::make_mut(&mut self.0)
+                .extend_from(chain)
+                .expect("blocks seen by a miner can be mirrored");
+        }
+    }
+
+    fn block_id(&self, id: INT) -> Result<BlockId, Box<rhai::EvalAltResult>> {
+        usize::try_from(id)
+            .ok()
+            .map(BlockId)
+            .filter(|&block_id| self.0.contains(block_id))
+            .ok_or_else(|| format!("no block with ID {id}").into())
+    }
+}
+
+/// Converts block IDs into a script array.
+fn id_array(ids: &[BlockId]) -> Array {
+    ids.iter()
+        .map(|block_id| Dynamic::from_int(block_id.0 as INT))
+        .collect()
+}
+
+/// Creates an [`Engine`] with the `Chain` type registered.
+fn script_engine() -> Engine {
+    type Fallible<T> = Result<T, Box<rhai::EvalAltResult>>;
+
+    let mut engine = Engine::new();
+    engine
+        .register_type_with_name::<ScriptChain>("Chain")
+        .register_fn("tip", |c: &mut ScriptChain| id_array(c.0.tip()))
+        .register_fn("max_height", |c: &mut ScriptChain| {
+            c.0.max_height() as INT
+        })
+        .register_fn("genesis", |c: &mut ScriptChain| c.0.genesis().0 as INT)
+        .register_fn("last_checkpoint", |c: &mut ScriptChain| {
+            c.0.last_checkpoint().0 as INT
+        })
+        .register_fn("num_blocks", |c: &mut ScriptChain| {
+            c.0.num_blocks() as INT
+        })
+        .register_fn("contains", |c: &mut ScriptChain, id: INT| {
+            c.block_id(id).is_ok()
+        })
+        .register_fn("at_height", |c: &mut ScriptChain, height: INT| {
+            let ids = usize::try_from(height)
+                .ok()
+                .and_then(|height| c.0.at_height(height));
+            id_array(ids.unwrap_or_default())
+        })
+        .register_fn(
+            "height",
+            |c: &mut ScriptChain, id: INT| -> Fallible<INT> {
+                let block_id = c.block_id(id)?;
+                Ok(c.0[block_id].height as INT)
+            },
+        )
+        .register_fn(
+            "parent",
+            |c: &mut ScriptChain, id: INT| -> Fallible<Dynamic> {
+                let block_id = c.block_id(id)?;
+                Ok(c.0.get_parent(block_id).map_or(Dynamic::UNIT, |parent| {
+                    Dynamic::from_int(parent.0 as INT)
+                }))
+            },
+        )
+        .register_fn(
+            "miner",
+            |c: &mut ScriptChain, id: INT| -> Fallible<INT> {
+                let block_id = c.block_id(id)?;
+                Ok(c.0[block_id].block.miner_id.0 as INT)
+            },
+        );
+
+    engine
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScriptError, ScriptedStrategy};
+    use crate::{
+        miner::{honest::Honest, MinerId},
+        simulation::SimulationBuilder,
+    };
+
+    const HONEST: &str = r#"
+        fn get_action(chain, miner_id, block_mined) {
+            if block_mined == () {
+                return ();
+            }
+            #{ id: block_mined, parent: chain.tip()[0] }
+        }
+    "#;
+
+    #[test]
+    fn scripted_honest_matches_honest() {
+        let run = |scripted: bool| {
+            let builder = SimulationBuilder::new().add_miner(Honest::new());
+            let builder = match scripted {
+                true => builder.add_miner(
+                    ScriptedStrategy::new("Scripted", HONEST).unwrap(),
+                ),
+                false => builder.add_miner(Honest::new()),
+            };
+
+            builder
+                .rounds(200)
+                .seed(3)
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .data()
+                .pop()
+                .unwrap()
+        };
+
+        let honest = run(false);
+        let scripted = run(true);
+        assert_eq!(
+            honest.miner_stats[&MinerId(2)].blocks_on_chain,
+            scripted.miner_stats[&MinerId(2)].blocks_on_chain
+        );
+        assert_eq!(scripted.longest_chain_length, 201);
+    }
+
+    #[test]
+    fn invalid_scripts() {
+        assert!(matches!(
+            ScriptedStrategy::new("Bad", "fn get_action(chain) {"),
+            Err(ScriptError::Compile(_))
+        ));
+        assert!(matches!(
+            ScriptedStrategy::new("Bad", "fn get_action(chain) { () }"),
+            Err(ScriptError::MissingGetAction)
+        ));
+    }
+}
@@ -3,9 +3,10 @@
 use std::collections::VecDeque;
 
 use crate::{
-    blockchain::{Block, BlockId, Blockchain},
-    miner::{Action, Miner, MinerId},
+    blockchain::{Block, BlockId},
+    miner::{Action, Miner, MinerId, MinerRng},
     tie_breaker::TieBreaker,
+    view::ChainView,
 };
 
 /// Follows the selfish mining strategy described by
@@ -15,6 +16,7 @@ pub struct Selfish {
     hidden_blocks: VecDeque<Block>,
     id: MinerId,
     private_height: usize,
+    rng: MinerRng,
     tie_breaker: TieBreaker,
 }
 
@@ -39,19 +41,31 @@ impl Miner for Selfish {
         self.tie_breaker = TieBreaker::FavorMiner(id);
     }
 
+    fn set_rng_seed(&mut self, seed: u64) {
+        self.rng.reseed(seed);
+    }
+
     fn get_action(
         &mut self,
-        chain: &Blockchain,
+        chain: &ChainView,
         block_mined: Option<BlockId>,
     ) -> Action {
         if self.private_height < chain.max_height() {
+            #[cfg(feature = "tracing")]
+            if !self.hidden_blocks.is_empty() {
+                tracing::debug!(
+                    miner = %self.id,
+                    abandoned = self.hidden_blocks.len(),
+                    "capitulated"
+                );
+            }
             self.hidden_blocks.clear();
         }
 
         match block_mined {
             Some(block_id) => {
                 let parent_id = if self.hidden_blocks.is_empty() {
-                    let p = self.tie_breaker.choose(chain);
+                    let p = self.tie_breaker.choose_with(&mut *self.rng, chain);
                     self.private_height = chain[p].height + 1;
                     p
                 } else {
@@ -85,4 +99,11 @@ impl Miner for Selfish {
             },
         }
     }
+
+    fn reset(&mut self) -> bool {
+        self.hidden_blocks.clear();
+        self.private_height = 0;
+
+        true
+    }
 }
@@ -1,5 +1,10 @@
 //! Describing distributions of mining power
 
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
 use crate::miner::MinerId;
 
 /// Numeric type used to represent mining power.
@@ -16,8 +21,16 @@ pub enum PowerDistribution {
     /// inclusive, with mining power distributed equally between all other
     /// miners.
     SetMiner(MinerId, PowerValue),
+    /// Set the power of each of the specified miners to some value between
+    /// `0.0` and `1.0` inclusive, with the remaining mining power distributed
+    /// equally between all other miners.
+    SetMiners(Vec<(MinerId, PowerValue)>),
     /// Set all mining power values to those in the given vector.
     SetValues(Vec<PowerValue>),
+    /// Set all mining power values proportional to the non-negative weights
+    /// in the given vector, such as the absolute hashrate of each miner. The
+    /// weights are rescaled to sum to 1.0.
+    SetValuesNormalized(Vec<PowerValue>),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -26,21 +39,94 @@ pub enum PowerDistributionError {
     BadDistributionSum(PowerValue),
     #[error("power value {0} is not in the range 0.0..=1.0")]
     BadPowerValue(PowerValue),
+    #[error("hashrate {0} is not a finite non-negative number")]
+    BadHashrate(f64),
     #[error("cannot set power for the genesis miner (MinerId 0)")]
     SetMinerGenesisMiner,
     #[error("cannot set power for invalid MinerId {0}")]
     SetMinerBadMinerID(MinerId),
     #[error("cannot set power for a single miner")]
     SetMinerSingleMiner,
+    #[error("power for MinerId {0} was set more than once")]
+    SetMinersDuplicateMinerID(MinerId),
     #[error("power distribution size {0} does not match miner count {1}")]
     WrongNumMiners(usize, usize),
     #[error("cannot create a distribution for zero miners")]
     ZeroMinersGiven,
+    #[error("hashrates sum to zero")]
+    ZeroTotalHashrate,
 }
 
 impl PowerDistribution {
     /// Allowable difference between a distribution sum and 1.0.
-    const EPSILON_POWER: PowerValue = 1e-6;
+    pub(crate) const EPSILON_POWER: PowerValue = 1e-6;
+
+    /// Create a distribution from the absolute hashrates of each miner, such
+    /// as figures in EH/s, by dividing each hashrate by their total.
+    pub fn from_hashrates(
+        hashrates: &[f64],
+    ) -> Result<Self, PowerDistributionError> {
+        use PowerDistributionError::*;
+
+        if hashrates.is_empty() {
+            return Err(ZeroMinersGiven);
+        }
+
+        if let Some(&rate) = hashrates
+            .iter()
+            .find(|&&rate| !rate.is_finite() || rate < 0.0)
+        {
+            return Err(BadHashrate(rate));
+        }
+
+        let total: f64 = hashrates.iter().sum();
+        if total == 0.0 {
+            return Err(ZeroTotalHashrate);
+        }
+
+        Ok(Self::SetValues(
+            hashrates.iter().map(|rate| rate / total).collect(),
+        ))
+    }
+
+    /// Distribute mining power between `num_miners` miners according to a
+    /// power law, such that miner `i` has power proportional to
+    /// `i.powf(-exponent)`. Miner 1 has the most power, and larger exponents
+    /// concentrate power in fewer miners. An exponent of about 1.0 roughly
+    /// matches the concentration of real mining pools.
+    ///
+    /// # Panics
+    /// Panics if `exponent` is negative or not finite.
+    pub fn pareto(num_miners: usize, exponent: f64) -> Self {
+        assert!(
+            exponent.is_finite() && exponent >= 0.0,
+            "invalid power law exponent {}",
+            exponent
+        );
+
+        let weights: Vec<_> = (1..=num_miners)
+            .map(|i| (i as PowerValue).powf(-exponent))
+            .collect();
+        let total: PowerValue = weights.iter().sum();
+
+        Self::SetValues(weights.into_iter().map(|w| w / total).collect())
+    }
+
+    /// Give the first miners the mining power in `shares`, in order, with the
+    /// remaining power distributed equally between all other miners. Useful
+    /// for modelling a few large mining pools among many small miners.
+    pub fn top_heavy<I>(shares: I) -> Self
+    where
+        I: IntoIterator<Item = PowerValue>,
+    {
+        let powers = shares
+            .into_iter()
+            .enumerate()
+            .map(|(i, share)| (MinerId::from(i + 1), share))
+            .collect();
+
+        Self::SetMiners(powers)
+    }
 
     /// Returns true if the discrete distribution described by this
     /// [`PowerDistribution`] is valid over `num_miners`.
@@ -68,8 +154,9 @@ impl PowerDistribution {
                     return Err(WrongNumMiners(dist.len(), num_miners));
                 }
 
-                if let Some(&val) =
-                    dist.iter().find(|&x| x.is_nan() || !(0.0..1.0).contains(x))
+                if let Some(&val) = dist
+                    .iter()
+                    .find(|&x| x.is_nan() || !(0.0..=1.0).contains(x))
                 {
                     return Err(BadPowerValue(val));
                 }
@@ -81,6 +168,24 @@ impl PowerDistribution {
 
                 Ok(())
             }
+            Self::SetValuesNormalized(weights) => {
+                if weights.len() != num_miners {
+                    return Err(WrongNumMiners(weights.len(), num_miners));
+                }
+
+                if let Some(&weight) = weights
+                    .iter()
+                    .find(|&&weight| !weight.is_finite() || weight < 0.0)
+                {
+                    return Err(BadHashrate(weight));
+                }
+
+                if weights.iter().sum::<PowerValue>() == 0.0 {
+                    return Err(ZeroTotalHashrate);
+                }
+
+                Ok(())
+            }
             Self::SetMiner(miner_id, power) => {
                 if num_miners == 1 {
                     return Err(SetMinerSingleMiner);
@@ -101,6 +206,38 @@ impl PowerDistribution {
                     return Err(BadPowerValue(power));
                 }
 
+                Ok(())
+            }
+            Self::SetMiners(powers) => {
+                for (i, &(miner_id, power)) in powers.iter().enumerate() {
+                    if miner_id.0 == 0 {
+                        return Err(SetMinerGenesisMiner);
+                    }
+
+                    if miner_id.0 > num_miners {
+                        return Err(SetMinerBadMinerID(miner_id));
+                    }
+
+                    if powers[..i].iter().any(|(id, _)| *id == miner_id) {
+                        return Err(SetMinersDuplicateMinerID(miner_id));
+                    }
+
+                    if power.is_nan() || !(0.0..=1.0).contains(&power) {
+                        return Err(BadPowerValue(power));
+                    }
+                }
+
+                // The remaining power must be non-negative, and must be zero
+                // if there are no other miners to share it
+                let sum: PowerValue = powers.iter().map(|(_, p)| p).sum();
+                let remainder = 1.0 - sum;
+                if remainder < -Self::EPSILON_POWER
+                    || (powers.len() == num_miners
+                        && remainder > Self::EPSILON_POWER)
+                {
+                    return Err(BadDistributionSum(sum));
+                }
+
                 Ok(())
             }
         }
@@ -133,6 +270,9 @@ impl PowerDistribution {
         match &self {
             Self::Equal => 1.0 / num_miners as PowerValue,
             Self::SetValues(dist) => dist[miner_id.0 - 1],
+            Self::SetValuesNormalized(weights) => {
+                weights[miner_id.0 - 1] / weights.iter().sum::<PowerValue>()
+            }
             Self::SetMiner(id, power) => {
                 if miner_id == *id {
                     *power
@@ -140,6 +280,12 @@ impl PowerDistribution {
                     (1.0 - power) / (num_miners - 1) as PowerValue
                 }
             }
+            Self::SetMiners(powers) => {
+                match powers.iter().find(|(id, _)| *id == miner_id) {
+                    Some((_, power)) => *power,
+                    None => Self::remainder_of(powers, num_miners),
+                }
+            }
         }
     }
 
@@ -168,16 +314,160 @@ impl PowerDistribution {
         match &self {
             Self::Equal => vec![1.0 / num_miners as PowerValue; num_miners],
             Self::SetValues(dist) => dist.clone(),
+            Self::SetValuesNormalized(weights) => {
+                let total: PowerValue = weights.iter().sum();
+
+                weights.iter().map(|weight| weight / total).collect()
+            }
             Self::SetMiner(miner_id, power) => {
                 let other = (1.0 - power) / (num_miners - 1) as PowerValue;
 
                 let mut dist = vec![other; num_miners];
                 dist[miner_id.0 - 1] = *power;
 
+                dist
+            }
+            Self::SetMiners(powers) => {
+                let other = Self::remainder_of(powers, num_miners);
+
+                let mut dist = vec![other; num_miners];
+                for (miner_id, power) in powers.iter() {
+                    dist[miner_id.0 - 1] = *power;
+                }
+
                 dist
             }
         }
     }
+
+    /// Returns the power of each miner not given a value by
+    /// [`PowerDistribution::SetMiners`].
+    #[inline]
+    fn remainder_of(
+        powers: &[(MinerId, PowerValue)],
+        num_miners: usize,
+    ) -> PowerValue {
+        let sum: PowerValue = powers.iter().map(|(_, p)| p).sum();
+
+        (1.0 - sum).max(0.0) / (num_miners - powers.len()) as PowerValue
+    }
+}
+
+/// Mining power distribution of a set of named mining pools, such as a
+/// snapshot of the real-world hashrate distribution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolSnapshot {
+    /// Name of each pool, in the order of the distribution's miners.
+    pub names: Vec<String>,
+    /// Normalized mining power of each pool.
+    pub power_dist: PowerDistribution,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PoolSnapshotError {
+    #[error("cannot infer snapshot format from file path {0:?}")]
+    UnknownExtension(PathBuf),
+    #[error("line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("invalid hashrates")]
+    PowerDistributionError(#[from] PowerDistributionError),
+    #[error("could not read snapshot")]
+    Io(#[from] io::Error),
+    #[cfg(feature = "serde")]
+    #[error("could not parse JSON snapshot")]
+    Json(#[from] serde_json::Error),
+}
+
+impl PoolSnapshot {
+    /// Load a snapshot of `(name, hashrate)` pairs from the file at `path`,
+    /// normalizing the hashrates as in
+    /// [`PowerDistribution::from_hashrates`]. The format is chosen from the
+    /// file extension:
+    /// - `csv` and `tsv`: One pool per line, with the pool name followed by
+    ///   its hashrate. A header line is skipped if present.
+    /// - `json`: An array of objects with `name` and `hashrate` fields.
+    ///   Requires the `serde` feature.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PoolSnapshotError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("csv") => {
+                Self::from_delimited(&fs::read_to_string(path)?, ',')
+            }
+            Some("tsv") => {
+                Self::from_delimited(&fs::read_to_string(path)?, '\t')
+            }
+            #[cfg(feature = "serde")]
+            Some("json") => Self::from_json(&fs::read_to_string(path)?),
+            _ => Err(PoolSnapshotError::UnknownExtension(path.to_path_buf())),
+        }
+    }
+
+    /// Parse a snapshot from lines of `(name, hashrate)` pairs separated by
+    /// `sep`. A header line is skipped if its hashrate field is not a number.
+    pub fn from_delimited(
+        contents: &str,
+        sep: char,
+    ) -> Result<Self, PoolSnapshotError> {
+        let mut names = vec![];
+        let mut hashrates = vec![];
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let parse_err = |message: String| PoolSnapshotError::Parse {
+                line: i + 1,
+                message,
+            };
+
+            // Pool names may contain the separator, hashrates never do
+            let (name, hashrate) = line
+                .rsplit_once(sep)
+                .ok_or_else(|| parse_err(format!("expected {:?}", sep)))?;
+            let name = name.trim().trim_matches('"');
+
+            match hashrate.trim().parse::<f64>() {
+                Ok(hashrate) => {
+                    names.push(name.to_string());
+                    hashrates.push(hashrate);
+                }
+                Err(_) if i == 0 => continue,
+                Err(e) => return Err(parse_err(e.to_string())),
+            }
+        }
+
+        Ok(Self {
+            names,
+            power_dist: PowerDistribution::from_hashrates(&hashrates)?,
+        })
+    }
+
+    /// Parse a snapshot from a JSON array of objects with `name` and
+    /// `hashrate` fields. Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn from_json(contents: &str) -> Result<Self, PoolSnapshotError> {
+        #[derive(serde::Deserialize)]
+        struct Pool {
+            name: String,
+            hashrate: f64,
+        }
+
+        let pools: Vec<Pool> = serde_json::from_str(contents)?;
+        let hashrates: Vec<_> =
+            pools.iter().map(|pool| pool.hashrate).collect();
+
+        Ok(Self {
+            power_dist: PowerDistribution::from_hashrates(&hashrates)?,
+            names: pools.into_iter().map(|pool| pool.name).collect(),
+        })
+    }
 }
 
 /// Helper trait for turning inclusive integer ranges into percentages.
@@ -209,9 +499,70 @@ impl Percent for std::ops::RangeInclusive<usize> {
     }
 }
 
+/// Returns an iterator over `n` evenly spaced power values from `start` to
+/// `end` inclusive.
+/// # Example
+/// ```
+/// use mining_sim::power_dist::linspace;
+///
+/// let values: Vec<_> = linspace(0.3, 0.45, 4).collect();
+/// assert_eq!(values.len(), 4);
+/// ```
+pub fn linspace(
+    start: PowerValue,
+    end: PowerValue,
+    n: usize,
+) -> impl Iterator<Item = PowerValue> {
+    let step = match n {
+        0 | 1 => 0.0,
+        _ => (end - start) / (n - 1) as PowerValue,
+    };
+
+    (0..n).map(move |i| match i + 1 == n {
+        // Avoid rounding error in the last value
+        true if n > 1 => end,
+        _ => start + step * i as PowerValue,
+    })
+}
+
+/// Returns an iterator over power values from `start` to `end` inclusive,
+/// increasing by `step`. Values are computed from `start` rather than by
+/// repeated addition, so rounding error does not accumulate.
+/// # Example
+/// ```
+/// use mining_sim::power_dist::step_range;
+///
+/// // 0.300, 0.305, ..., 0.450
+/// assert_eq!(step_range(0.3, 0.45, 0.005).count(), 31);
+/// ```
+///
+/// # Panics
+/// Panics if `step` is not positive.
+pub fn step_range(
+    start: PowerValue,
+    end: PowerValue,
+    step: PowerValue,
+) -> impl Iterator<Item = PowerValue> {
+    assert!(step > 0.0, "invalid step size {}", step);
+
+    // Tolerate rounding error when `end` is a multiple of `step` from `start`
+    let steps = ((end - start) / step + 1e-9).floor();
+    let count = match steps < 0.0 {
+        true => 0,
+        false => steps as usize + 1,
+    };
+
+    (0..count).map(move |i| start + step * i as PowerValue)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::PowerDistribution;
+    use crate::miner::MinerId;
+
+    use super::{
+        linspace, step_range, PoolSnapshot, PowerDistribution,
+        PowerDistributionError,
+    };
 
     #[test]
     fn power_dist_equal_power() {
@@ -220,4 +571,120 @@ mod tests {
             vec![0.25, 0.25, 0.25, 0.25]
         )
     }
+
+    #[test]
+    fn power_dist_presets() {
+        let values = PowerDistribution::pareto(4, 1.0).values(4).unwrap();
+        assert!(values.windows(2).all(|pair| pair[0] > pair[1]));
+        assert!((values[0] - 2.0 * values[1]).abs() < 1e-12);
+
+        let values = PowerDistribution::pareto(4, 0.0).values(4).unwrap();
+        assert_eq!(values, vec![0.25; 4]);
+
+        let dist = PowerDistribution::top_heavy([0.5, 0.25]);
+        assert_eq!(dist.values(4).unwrap(), vec![0.5, 0.25, 0.125, 0.125]);
+    }
+
+    #[test]
+    fn power_dist_from_hashrates() {
+        let dist = PowerDistribution::from_hashrates(&[300.0, 100.0, 0.0]);
+        assert_eq!(dist.unwrap().values(3).unwrap(), vec![0.75, 0.25, 0.0]);
+
+        assert!(matches!(
+            PowerDistribution::from_hashrates(&[1.0, -1.0]),
+            Err(PowerDistributionError::BadHashrate(_))
+        ));
+        assert!(matches!(
+            PowerDistribution::from_hashrates(&[0.0, 0.0]),
+            Err(PowerDistributionError::ZeroTotalHashrate)
+        ));
+    }
+
+    #[test]
+    fn power_dist_one_hot() {
+        let dist = PowerDistribution::SetValues(vec![0.0, 1.0, 0.0]);
+        assert!(dist.is_valid(3));
+        assert_eq!(dist.power_of(MinerId::from(2), 3).unwrap(), 1.0);
+        assert!(PowerDistribution::SetValues(vec![1.0]).is_valid(1));
+    }
+
+    #[test]
+    fn pool_snapshot_parsing() {
+        let csv = "Pool,Hashrate (EH/s)\n\"Foundry, USA\",300\nAntPool,100\n";
+        let snapshot = PoolSnapshot::from_delimited(csv, ',').unwrap();
+        assert_eq!(snapshot.names, vec!["Foundry, USA", "AntPool"]);
+        assert_eq!(snapshot.power_dist.values(2).unwrap(), vec![0.75, 0.25]);
+
+        assert!(PoolSnapshot::from_delimited("A,1\nB,x", ',').is_err());
+
+        #[cfg(feature = "serde")]
+        {
+            let json = r#"[{"name": "A", "hashrate": 1},
+                           {"name": "B", "hashrate": 3}]"#;
+            let snapshot = PoolSnapshot::from_json(json).unwrap();
+            assert_eq!(snapshot.names, vec!["A", "B"]);
+            assert_eq!(
+                snapshot.power_dist.values(2).unwrap(),
+                vec![0.25, 0.75]
+            );
+        }
+    }
+
+    #[test]
+    fn float_ranges() {
+        let values: Vec<_> = linspace(0.3, 0.45, 4).collect();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[0], 0.3);
+        assert!((values[1] - 0.35).abs() < 1e-12);
+        assert_eq!(values[3], 0.45);
+        assert_eq!(linspace(0.2, 0.4, 1).collect::<Vec<_>>(), vec![0.2]);
+
+        let values: Vec<_> = step_range(0.3, 0.45, 0.005).collect();
+        assert_eq!(values.len(), 31);
+        assert!((values[30] - 0.45).abs() < 1e-12);
+        assert_eq!(step_range(0.5, 0.4, 0.1).count(), 0);
+    }
+
+    #[test]
+    fn power_dist_normalized_values() {
+        let dist = PowerDistribution::SetValuesNormalized(vec![2.0, 1.0, 1.0]);
+        assert_eq!(dist.values(3).unwrap(), vec![0.5, 0.25, 0.25]);
+        assert_eq!(dist.power_of(MinerId::from(1), 3).unwrap(), 0.5);
+
+        assert!(matches!(
+            PowerDistribution::SetValuesNormalized(vec![0.0, 0.0]).validate(2),
+            Err(PowerDistributionError::ZeroTotalHashrate)
+        ));
+        assert!(matches!(
+            PowerDistribution::SetValuesNormalized(vec![1.0]).validate(2),
+            Err(PowerDistributionError::WrongNumMiners(1, 2))
+        ));
+    }
+
+    #[test]
+    fn power_dist_set_miners() {
+        let dist = PowerDistribution::SetMiners(vec![
+            (MinerId::from(2), 0.5),
+            (MinerId::from(4), 0.25),
+        ]);
+        assert_eq!(dist.values(4).unwrap(), vec![0.125, 0.5, 0.125, 0.25]);
+
+        let dist = PowerDistribution::SetMiners(vec![
+            (MinerId::from(1), 0.5),
+            (MinerId::from(2), 0.3),
+        ]);
+        assert!(matches!(
+            dist.validate(2),
+            Err(PowerDistributionError::BadDistributionSum(_))
+        ));
+
+        let dist = PowerDistribution::SetMiners(vec![
+            (MinerId::from(1), 0.5),
+            (MinerId::from(1), 0.3),
+        ]);
+        assert!(matches!(
+            dist.validate(3),
+            Err(PowerDistributionError::SetMinersDuplicateMinerID(_))
+        ));
+    }
 }
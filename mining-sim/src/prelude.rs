@@ -9,31 +9,56 @@ use mining_sim::prelude::*;
 */
 
 use crate::{
-    blockchain, miner, power_dist, results, simulation, tie_breaker,
-    transaction,
+    blockchain, dag, mempool, miner, power_dist, results, simulation, stake,
+    tie_breaker, transaction, uncle, view,
 };
 
-pub use blockchain::{Block, BlockId, BlockPublishingError, Blockchain};
+pub use blockchain::{
+    Block, BlockId, BlockPublishingError, BlockSet, Blockchain, ChainQuery,
+};
+
+pub use dag::{BlockDag, DagError, Ghostdag};
+
+pub use mempool::{
+    ArrivalProcess, DoubleSpendOutcome, FeeDistribution, FeeMarket,
+    GeneratorConfig, Mempool, MempoolError, BLACKLIST_TAG,
+};
 
 pub use miner::{
-    honest::Honest, honestforking::HonestForking, ndeficit::NDeficit,
+    doublespend::DoubleSpend, equivocating::Equivocating, honest::Honest,
+    honestforking::HonestForking, ndeficit::NDeficit,
     ndeficiteager::NDeficitEager, noop::Noop, selfish::Selfish, Action, Miner,
     MinerId,
 };
 
 pub use power_dist::{
-    Percent, PowerDistribution, PowerDistributionError, PowerValue,
+    linspace, step_range, Percent, PoolSnapshot, PoolSnapshotError,
+    PowerDistribution, PowerDistributionError, PowerValue,
 };
 
 pub use results::{
-    nsm_revenue, selfish_revenue, Average, Format, ResultsBuilder, ResultsTable,
+    honest_revenue, nsm_revenue, selfish_revenue, selfish_revenue_max,
+    selfish_revenue_min, selfish_threshold, Average, CellValue, ColumnKind,
+    Format, Histogram, Notation, ResultsBuilder, ResultsMergeError,
+    ResultsSaveError, ResultsTable,
 };
 
 pub use simulation::{
-    SimulationBuildError, SimulationBuilder, SimulationError, SimulationGroup,
-    SimulationOutput,
+    MinerStats, Parallelism, RevenueSeries, Sensitivity, SimulationBuildError,
+    SimulationBuilder, SimulationError, SimulationGroup, SimulationOutput,
 };
 
+pub use simulation::threshold::{Threshold, ThresholdError, ThresholdSearch};
+
+#[cfg(feature = "timing")]
+pub use simulation::PhaseTimings;
+
+pub use stake::ProofOfStake;
+
 pub use tie_breaker::TieBreaker;
 
-pub use transaction::Transaction;
+pub use transaction::{Transaction, TransactionId};
+
+pub use uncle::UncleRewards;
+
+pub use view::{ChainView, Visibility};
@@ -29,34 +29,73 @@ println!("{}", results);
 ```
 */
 
-use std::{collections::BTreeSet, fmt::Display, num::NonZeroUsize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+    fs::File,
+    io::{self, BufWriter, Write},
+    num::NonZeroUsize,
+    ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
+};
 
-#[cfg(feature = "rayon")]
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
 use rayon::prelude::*;
 
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "plots")]
+pub mod plot;
+
+#[cfg(feature = "timing")]
+use crate::simulation::PhaseTimings;
 use crate::{
-    miner::MinerId, power_dist::PowerValue, simulation::SimulationOutput,
-    utils::wrap, utils::WrapFunc,
+    miner::MinerId,
+    power_dist::{PowerDistribution, PowerValue},
+    simulation::{MinerStats, SimulationOutput},
+    utils::wrap,
+    utils::WrapFunc,
 };
 
-/// Floating point precision of results data.
+/// Default floating point precision of results data. See [`Notation`].
 pub const F64_DISPLAY_DIGITS: usize = 6;
 
 /// Builder for [`ResultsTable`]. Typically produced by running a
 /// [`SimulationGroup`](crate::simulation::SimulationGroup).
 #[derive(Debug, Clone)]
 pub struct ResultsBuilder {
-    average: Average,
+    averages: Vec<Average>,
+    column_notations: BTreeMap<String, Notation>,
     columns: BTreeSet<Column>,
     data: Vec<SimulationOutput>,
     format: Format,
+    group_by: Option<String>,
+    hide_power: bool,
+    include_raw: bool,
+    notation: Notation,
+    num_miners: usize,
     repeated: NonZeroUsize,
+    skipped: Vec<SkippedConfig>,
+}
+
+/// Configuration of a [`SimulationGroup`](crate::simulation::SimulationGroup)
+/// which was left out of its results. See
+/// [`ResultsBuilder::skipped_configs`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedConfig {
+    /// Index of the configuration within its simulation group, in the order
+    /// described in [`ResultsBuilder::data`].
+    pub config: usize,
+    /// Index of the configuration's lineup of miners. See
+    /// [`SimulationBuilder::next_lineup`](crate::simulation::SimulationBuilder::next_lineup).
+    pub lineup: usize,
+    /// Power distribution of the configuration.
+    pub power_dist: PowerDistribution,
 }
 
 /// Methods of extracting an average/central value from a set of repeated
 /// simulations.
-#[repr(u8)]
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Default, Debug, Clone)]
 pub enum Average {
     #[default]
     /// Include all repeated values.
@@ -69,17 +108,207 @@ pub enum Average {
     Max,
     /// Minimum of all values.
     Min,
+    /// Sample standard deviation of all values, which is NaN for a single
+    /// value.
+    StdDev,
+    /// The given percentile of all values, between `0.0` and `100.0`
+    /// inclusive. Values between ranks are linearly interpolated.
+    Percentile(f64),
+    /// Each of the given quantiles of all values, between `0.0` and `1.0`
+    /// inclusive. Each averaged column is split into one column per quantile.
+    Quantiles(Vec<f64>),
+}
+
+impl Average {
+    /// Returns a tag and the parameters of this averaging method, used to
+    /// compare and hash methods.
+    fn key(&self) -> (u8, &[f64]) {
+        match self {
+            Self::None => (0, &[]),
+            Self::Mean => (1, &[]),
+            Self::Median => (2, &[]),
+            Self::Max => (3, &[]),
+            Self::Min => (4, &[]),
+            Self::Percentile(p) => (5, std::slice::from_ref(p)),
+            Self::Quantiles(qs) => (6, qs),
+            Self::StdDev => (7, &[]),
+        }
+    }
+
+    /// Splits this method into the methods used to produce each column of an
+    /// averaged value.
+    fn split(&self) -> Vec<Average> {
+        match self {
+            Self::Quantiles(qs) => {
+                qs.iter().map(|q| Self::Percentile(q * 100.0)).collect()
+            }
+            _ => vec![self.clone()],
+        }
+    }
+
+    /// Panics if the parameters of this method are out of range.
+    fn assert_valid(&self) {
+        match self {
+            Self::Percentile(p) => {
+                assert!((0.0..=100.0).contains(p), "invalid percentile {}", p)
+            }
+            Self::Quantiles(qs) => {
+                assert!(!qs.is_empty(), "no quantiles given");
+                if let Some(q) = qs.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+                    panic!("invalid quantile {}", q);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Short description of this method used in column titles.
+    fn label(&self) -> String {
+        match self {
+            Self::None => String::new(),
+            Self::Mean => "Mean".to_string(),
+            Self::Median => "Median".to_string(),
+            Self::Max => "Max".to_string(),
+            Self::Min => "Min".to_string(),
+            Self::StdDev => "Std Dev".to_string(),
+            // Rounded to hide floating point error from Average::split
+            Self::Percentile(p) => format!("P{}", (p * 1e6).round() / 1e6),
+            Self::Quantiles(_) => "Quantiles".to_string(),
+        }
+    }
+}
+
+impl PartialEq for Average {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Average {}
+
+impl PartialOrd for Average {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Average {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let (tag, params) = self.key();
+        let (other_tag, other_params) = other.key();
+
+        tag.cmp(&other_tag).then_with(|| {
+            params
+                .iter()
+                .zip(other_params)
+                .map(|(a, b)| a.total_cmp(b))
+                .find(|ord| ord.is_ne())
+                .unwrap_or(params.len().cmp(&other_params.len()))
+        })
+    }
+}
+
+impl std::hash::Hash for Average {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let (tag, params) = self.key();
+        tag.hash(state);
+        for param in params {
+            param.to_bits().hash(state);
+        }
+    }
 }
 
 /// Describes the appearance of a [`ResultsTable`] table as given by its
 /// [`Display`] implementation.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Format {
-    /// Comma-separated, without extra whitespace.
-    CSV,
+    /// Fields separated by `sep` without extra whitespace, preceded by a row
+    /// of column titles if `header` is true. Fields containing `sep`, `quote`,
+    /// or a line break are enclosed in `quote`, with any `quote` characters
+    /// inside them doubled. See [`Format::CSV`] and [`Format::TSV`].
+    Delimited {
+        sep: char,
+        quote: char,
+        header: bool,
+    },
     /// Human-readable.
     #[default]
     PrettyPrint,
+    /// JSON array with one object per row, keyed by column title.
+    JSON,
+    /// Markdown table.
+    Markdown,
+}
+
+impl Format {
+    /// Comma-separated values with a header row.
+    pub const CSV: Self = Self::Delimited {
+        sep: ',',
+        quote: '"',
+        header: true,
+    };
+
+    /// Tab-separated values with a header row.
+    pub const TSV: Self = Self::Delimited {
+        sep: '\t',
+        quote: '"',
+        header: true,
+    };
+
+    /// Returns the format conventionally associated with the given file
+    /// extension (`csv`, `tsv`, `json`, `md`, or `txt`), ignoring case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::CSV),
+            "tsv" => Some(Self::TSV),
+            "json" => Some(Self::JSON),
+            "md" | "markdown" => Some(Self::Markdown),
+            "txt" => Some(Self::PrettyPrint),
+            _ => None,
+        }
+    }
+}
+
+/// Describes how floating point values are written in a [`ResultsTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// Fixed-point notation with the given number of digits after the decimal
+    /// point.
+    Fixed(usize),
+    /// Scientific notation (e.g. `1.25e-4`) with the given number of digits
+    /// after the decimal point.
+    Scientific(usize),
+}
+
+impl Default for Notation {
+    fn default() -> Self {
+        Self::Fixed(F64_DISPLAY_DIGITS)
+    }
+}
+
+impl Notation {
+    fn format(self, value: f64) -> String {
+        match self {
+            Self::Fixed(digits) => format!("{:.1$}", value, digits),
+            Self::Scientific(digits) => format!("{:.1$e}", value, digits),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResultsMergeError {
+    #[error("cannot merge results repeated {0} and {1} times")]
+    RepeatMismatch(NonZeroUsize, NonZeroUsize),
+    #[error("cannot merge results with {0} and {1} miners")]
+    MinerCountMismatch(usize, usize),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResultsSaveError {
+    #[error("cannot infer results format from file path {0:?}")]
+    UnknownExtension(PathBuf),
+    #[error("could not write results")]
+    Io(#[from] io::Error),
 }
 
 impl ResultsBuilder {
@@ -87,13 +316,22 @@ impl ResultsBuilder {
     pub(crate) fn new(
         data: Vec<SimulationOutput>,
         repeated: NonZeroUsize,
+        num_miners: usize,
+        skipped: Vec<SkippedConfig>,
     ) -> Self {
         Self {
             data,
+            num_miners,
             repeated,
-            average: Average::default(),
+            skipped,
+            averages: vec![Average::default()],
+            column_notations: BTreeMap::default(),
             columns: BTreeSet::default(),
             format: Format::default(),
+            group_by: None,
+            hide_power: false,
+            include_raw: false,
+            notation: Notation::default(),
         }
     }
 
@@ -117,8 +355,174 @@ impl ResultsBuilder {
     ///
     /// In the process of creating a results table, this averaging method
     /// is only applied to columns which vary between simulation runs.
+    ///
+    /// # Panics
+    /// Panics if a percentile or quantile is out of range, or if
+    /// [`Average::Quantiles`] is given no quantiles.
     pub fn average(mut self, average: Average) -> Self {
-        self.average = average;
+        average.assert_valid();
+        self.averages = vec![average];
+
+        self
+    }
+
+    /// Average the results of repeated simulations using each of the given
+    /// [`Average`] types at once. Each averaged column is split into one
+    /// column per averaging method, so that e.g. [`Average::Mean`] and
+    /// [`Average::StdDev`] can be reported side by side.
+    ///
+    /// # Panics
+    /// Panics if no averaging methods are given, if [`Average::None`] is
+    /// given along with other methods, or under the conditions of
+    /// [`ResultsBuilder::average`].
+    pub fn averages<I>(mut self, averages: I) -> Self
+    where
+        I: IntoIterator<Item = Average>,
+    {
+        let averages: Vec<_> = averages.into_iter().collect();
+        assert!(!averages.is_empty(), "no averaging methods given");
+        if averages.len() > 1 {
+            assert!(
+                !averages.contains(&Average::None),
+                "Average::None cannot be combined with other methods"
+            );
+        }
+
+        for average in averages.iter() {
+            average.assert_valid();
+        }
+        self.averages = averages;
+
+        self
+    }
+
+    /// Include a "Miner `X` Blocks Mined" column in the results table for each
+    /// miner `X`, counting every block the miner mined, whether or not it was
+    /// published.
+    pub fn blocks_mined(mut self) -> Self {
+        let num_miners = self.num_miners();
+        for miner_id in 1..=num_miners {
+            self.columns
+                .insert(Column::MinerBlocksMined(miner_id.into()));
+        }
+
+        self
+    }
+
+    /// Include the "Miner `X` Blocks Mined" column in the results table for
+    /// the miner with ID `miner_id`. See [`ResultsBuilder::blocks_mined`].
+    ///
+    /// # Panics
+    /// Panics if no miner has ID `miner_id`.
+    pub fn blocks_mined_for(mut self, miner_id: MinerId) -> Self {
+        self.assert_valid_miner(miner_id);
+        self.columns.insert(Column::MinerBlocksMined(miner_id));
+
+        self
+    }
+
+    /// Include a "Miner `X` Blocks On Chain" column in the results table for
+    /// each miner `X`, counting the miner's blocks in the longest chain.
+    pub fn blocks_on_chain(mut self) -> Self {
+        let num_miners = self.num_miners();
+        for miner_id in 1..=num_miners {
+            self.columns
+                .insert(Column::MinerBlocksOnChain(miner_id.into()));
+        }
+
+        self
+    }
+
+    /// Include the "Miner `X` Blocks On Chain" column in the results table for
+    /// the miner with ID `miner_id`. See [`ResultsBuilder::blocks_on_chain`].
+    ///
+    /// # Panics
+    /// Panics if no miner has ID `miner_id`.
+    pub fn blocks_on_chain_for(mut self, miner_id: MinerId) -> Self {
+        self.assert_valid_miner(miner_id);
+        self.columns.insert(Column::MinerBlocksOnChain(miner_id));
+
+        self
+    }
+
+    /// Include "Miner `X` Subsidy Income" and "Miner `X` Fee Income" columns
+    /// in the results table for each miner `X`, splitting the rewards of the
+    /// miner's blocks on the longest chain into
+    /// [block subsidies](crate::simulation::SimulationBuilder::block_subsidy)
+    /// and transaction fees.
+    pub fn income(mut self) -> Self {
+        let num_miners = self.num_miners();
+        for miner_id in 1..=num_miners {
+            self = self.income_for(miner_id.into());
+        }
+
+        self
+    }
+
+    /// Include the "Miner `X` Subsidy Income" and "Miner `X` Fee Income"
+    /// columns in the results table for the miner with ID `miner_id`. See
+    /// [`ResultsBuilder::income`].
+    ///
+    /// # Panics
+    /// Panics if no miner has ID `miner_id`.
+    pub fn income_for(mut self, miner_id: MinerId) -> Self {
+        self.assert_valid_miner(miner_id);
+        self.columns.insert(Column::MinerSubsidyIncome(miner_id));
+        self.columns.insert(Column::MinerFeeIncome(miner_id));
+
+        self
+    }
+
+    /// Include "Miner `X` Uncle Income" and "Miner `X` Nephew Income" columns
+    /// in the results table for each miner `X`, which give the rewards paid
+    /// for the miner's blocks referenced as uncles and for the uncles its
+    /// blocks referenced. Both are 0 unless the simulations pay
+    /// [uncle rewards](crate::simulation::SimulationBuilder::uncle_rewards).
+    pub fn uncle_income(mut self) -> Self {
+        let num_miners = self.num_miners();
+        for miner_id in 1..=num_miners {
+            self = self.uncle_income_for(miner_id.into());
+        }
+
+        self
+    }
+
+    /// Include the "Miner `X` Uncle Income" and "Miner `X` Nephew Income"
+    /// columns in the results table for the miner with ID `miner_id`. See
+    /// [`ResultsBuilder::uncle_income`].
+    ///
+    /// # Panics
+    /// Panics if no miner has ID `miner_id`.
+    pub fn uncle_income_for(mut self, miner_id: MinerId) -> Self {
+        self.assert_valid_miner(miner_id);
+        self.columns.insert(Column::MinerUncleIncome(miner_id));
+        self.columns.insert(Column::MinerNephewIncome(miner_id));
+
+        self
+    }
+
+    /// Include a "Miner `X` Reward Share" column in the results table for
+    /// each miner `X`, giving the miner's fraction of the total subsidy, fee,
+    /// uncle, and nephew income paid to all miners. Unlike
+    /// [`ResultsBuilder::revenue`], this accounts for
+    /// [uncle rewards](crate::simulation::SimulationBuilder::uncle_rewards).
+    pub fn reward_share(mut self) -> Self {
+        let num_miners = self.num_miners();
+        for miner_id in 1..=num_miners {
+            self = self.reward_share_for(miner_id.into());
+        }
+
+        self
+    }
+
+    /// Include the "Miner `X` Reward Share" column in the results table for
+    /// the miner with ID `miner_id`. See [`ResultsBuilder::reward_share`].
+    ///
+    /// # Panics
+    /// Panics if no miner has ID `miner_id`.
+    pub fn reward_share_for(mut self, miner_id: MinerId) -> Self {
+        self.assert_valid_miner(miner_id);
+        self.columns.insert(Column::MinerRewardShare(miner_id));
 
         self
     }
@@ -148,12 +552,168 @@ impl ResultsBuilder {
     /// # Ordering
     /// Simulations are run in the same order they are specified using
     /// [`SimulationBuilder`](crate::simulation::SimulationBuilder), with
-    /// repeated runs being grouped together. The output data from this method
-    /// follows this ordering as well.
+    /// repeated runs being grouped together. When multiple lineups of miners
+    /// are given, all simulations of the first lineup come first. The output
+    /// data from this method follows this ordering as well.
     pub fn data(self) -> Vec<SimulationOutput> {
         self.data
     }
 
+    /// Total time spent in each phase of every simulation, for diagnosing
+    /// slow strategies. See [`PhaseTimings`].
+    ///
+    /// Requires the `timing` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let results = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Selfish::new())
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all()
+    ///     .unwrap();
+    ///
+    /// println!("{}", results.phase_timings());
+    /// ```
+    #[cfg(feature = "timing")]
+    pub fn phase_timings(&self) -> PhaseTimings {
+        self.data.iter().map(|output| &output.timings).sum()
+    }
+
+    /// Combine the simulations of `other` with those of this builder, so that
+    /// results from separately run
+    /// [`SimulationGroup`](crate::simulation::SimulationGroup)s can be
+    /// presented in a single table. The simulations of `other` come after
+    /// those of this builder, and the columns of both builders are included.
+    /// All other settings are taken from this builder.
+    ///
+    /// Both builders must have the same number of repeats per configuration
+    /// and the same number of miners. Use [`ResultsTable::concat`] to combine
+    /// results which do not.
+    pub fn merge(mut self, other: Self) -> Result<Self, ResultsMergeError> {
+        use ResultsMergeError::*;
+
+        if !self.data.is_empty() && !other.data.is_empty() {
+            if self.repeated != other.repeated {
+                return Err(RepeatMismatch(self.repeated, other.repeated));
+            }
+            if self.num_miners() != other.num_miners() {
+                return Err(MinerCountMismatch(
+                    self.num_miners(),
+                    other.num_miners(),
+                ));
+            }
+        } else if self.data.is_empty() {
+            self.num_miners = other.num_miners;
+            self.repeated = other.repeated;
+        }
+
+        self.columns.extend(other.columns);
+        self.data.extend(other.data);
+        self.skipped.extend(other.skipped);
+
+        Ok(self)
+    }
+
+    /// Returns the configurations which were left out of these results
+    /// because not all of their repeats completed, either because the
+    /// [`time_budget`](crate::simulation::SimulationBuilder::time_budget) of
+    /// their simulation group ran out or because the run was cancelled with
+    /// [`SimulationGroup::run_all_cancellable`](crate::simulation::SimulationGroup::run_all_cancellable).
+    /// Configurations are listed in the order they were configured, and those
+    /// of [merged](ResultsBuilder::merge) results come after these results'.
+    pub fn skipped_configs(&self) -> &[SkippedConfig] {
+        &self.skipped
+    }
+
+    /// Include the "Fork Count", "Mean Fork Length", and "Max Fork Length"
+    /// columns in the results table, describing the forks off of the longest
+    /// chain in each simulation as given by
+    /// [`Blockchain::fork_lengths`](crate::blockchain::Blockchain::fork_lengths).
+    /// Fork lengths are 0 in simulations without forks.
+    pub fn forks(mut self) -> Self {
+        self.columns.insert(Column::ForkCount);
+        self.columns.insert(Column::MeanForkLength);
+        self.columns.insert(Column::MaxForkLength);
+
+        self
+    }
+
+    /// Include the "Power Gini Coefficient" and "Revenue Gini Coefficient"
+    /// columns in the results table, measuring the inequality of the mining
+    /// power and revenue of all miners in each simulation. A strategy which
+    /// distorts proportional rewards makes the revenue coefficient differ from
+    /// the power coefficient.
+    pub fn gini(mut self) -> Self {
+        self.columns.insert(Column::PowerGini);
+        self.columns.insert(Column::RevenueGini);
+
+        self
+    }
+
+    /// Include the "Double-Spend Success (z Confirmations)" column in the
+    /// results table, giving the fraction of the double-spend attempts
+    /// scheduled with
+    /// [`Mempool::double_spend`](crate::mempool::Mempool::double_spend) which
+    /// reversed a payment after it reached `confirmations` confirmations. When
+    /// averaged with [`Average::Mean`], this estimates the probability of a
+    /// successful double spend. NaN in simulations without double-spend
+    /// attempts. See
+    /// [`SimulationOutput::double_spends`](crate::simulation::SimulationOutput::double_spends).
+    pub fn double_spend_success(mut self, confirmations: usize) -> Self {
+        self.columns
+            .insert(Column::DoubleSpendSuccess(confirmations));
+
+        self
+    }
+
+    /// Include the "Mean Inclusion Latency" and "Median Inclusion Latency"
+    /// columns in the results table, summarizing the number of rounds each
+    /// transaction on the longest chain waited to be included. NaN in
+    /// simulations without confirmed transactions. See
+    /// [`Mempool::inclusion_latencies`](crate::mempool::Mempool::inclusion_latencies).
+    pub fn inclusion_latency(mut self) -> Self {
+        self.columns.insert(Column::MeanInclusionLatency);
+        self.columns.insert(Column::MedianInclusionLatency);
+
+        self
+    }
+
+    /// Include the "Blacklisted Txns Confirmed" and "Blacklisted Txn Mean
+    /// Latency" columns in the results table, giving the number of
+    /// [blacklisted](crate::mempool::Mempool::blacklist) transactions on the
+    /// longest chain and their mean inclusion latency, which measure how
+    /// effectively they are censored. The latency is NaN in simulations
+    /// without confirmed blacklisted transactions. See
+    /// [`Mempool::blacklisted_latencies`](crate::mempool::Mempool::blacklisted_latencies).
+    pub fn censorship(mut self) -> Self {
+        self.columns.insert(Column::BlacklistedConfirmed);
+        self.columns.insert(Column::BlacklistedMeanLatency);
+
+        self
+    }
+
+    /// Include the "Max Reorg Depth" column in the results table, giving the
+    /// greatest number of blocks removed from the longest chain by a single
+    /// reorganization in each simulation, or 0 if there were none. See
+    /// [`SimulationOutput::reorg_depths`].
+    pub fn max_reorg_depth(mut self) -> Self {
+        self.columns.insert(Column::MaxReorgDepth);
+
+        self
+    }
+
+    /// Include the "Reorg Count" column in the results table, giving the
+    /// number of reorganizations of the longest chain in each simulation.
+    pub fn reorg_count(mut self) -> Self {
+        self.columns.insert(Column::ReorgCount);
+
+        self
+    }
+
     /// Include the "Longest Chain Length" column in the results table.
     pub fn longest_chain_length(mut self) -> Self {
         self.columns.insert(Column::LongestChainLength);
@@ -161,6 +721,16 @@ impl ResultsBuilder {
         self
     }
 
+    /// Include the "Blockchain Memory (MiB)" column in the results table,
+    /// giving the estimated peak memory used by the blockchain of each
+    /// simulation. See
+    /// [`Blockchain::memory_usage`](crate::blockchain::Blockchain::memory_usage).
+    pub fn blockchain_memory(mut self) -> Self {
+        self.columns.insert(Column::BlockchainMemory);
+
+        self
+    }
+
     /// Use the mining power of the miner with ID `miner_id` as input to `func`,
     /// and present the output in a table column with the given title.
     pub fn mining_power_func<T, F>(
@@ -179,10 +749,61 @@ impl ResultsBuilder {
         self
     }
 
+    /// Compare the simulated revenue of the miner with ID `miner_id` against
+    /// the revenue predicted by `ideal` from the miner's mining power, such as
+    /// [`selfish_revenue`]. Includes a "Miner `X` Revenue - `title`" column
+    /// with the difference between simulated and ideal revenue, and a
+    /// "Miner `X` Revenue Relative Error (`title`)" column with that
+    /// difference divided by the ideal revenue.
+    pub fn revenue_vs<T, F>(
+        mut self,
+        miner_id: MinerId,
+        title: T,
+        ideal: F,
+    ) -> Self
+    where
+        T: Into<String>,
+        F: Fn(PowerValue) -> f64 + Send + Sync + 'static,
+    {
+        let ideal = wrap!(title, ideal);
+        self.columns
+            .insert(Column::RevenueDifference(miner_id, ideal.clone()));
+        self.columns
+            .insert(Column::RevenueRelativeError(miner_id, ideal));
+
+        self
+    }
+
+    /// Test whether the simulated revenue of the miner with ID `miner_id`
+    /// matches the revenue predicted by `ideal`, such as [`nsm_revenue`].
+    /// Includes a "Miner `X` Revenue p-value (`title`)" column with the
+    /// two-sided p-value of a one-sample t-test of the differences between
+    /// simulated and ideal revenue in each averaged row. Small p-values are
+    /// evidence that the simulation does not match the prediction.
+    ///
+    /// The test is computed over the simulations averaged into each row, so
+    /// the column is NaN unless at least two simulations are averaged. See
+    /// [`ResultsBuilder::average`].
+    pub fn revenue_t_test<T, F>(
+        mut self,
+        miner_id: MinerId,
+        title: T,
+        ideal: F,
+    ) -> Self
+    where
+        T: Into<String>,
+        F: Fn(PowerValue) -> f64 + Send + Sync + 'static,
+    {
+        self.columns
+            .insert(Column::RevenuePValue(miner_id, wrap!(title, ideal)));
+
+        self
+    }
+
     /// Include a "Miner `X` Strategy Name" column in the results table for each
     /// miner `X`.
     pub fn strategy_names(mut self) -> Self {
-        let num_miners = self.data[0].miners.len();
+        let num_miners = self.num_miners();
         for miner_id in 1..=num_miners {
             self.columns
                 .insert(Column::MinerStrategyName(miner_id.into()));
@@ -191,10 +812,55 @@ impl ResultsBuilder {
         self
     }
 
+    /// Include the "Miner `X` Strategy Name" column in the results table for
+    /// the miner with ID `miner_id`.
+    ///
+    /// # Panics
+    /// Panics if no miner has ID `miner_id`.
+    pub fn strategy_name_for(mut self, miner_id: MinerId) -> Self {
+        self.assert_valid_miner(miner_id);
+        self.columns.insert(Column::MinerStrategyName(miner_id));
+
+        self
+    }
+
+    /// Include a "Miner `X` `param`" column in the results table for each
+    /// strategy parameter `param` returned by
+    /// [`Miner::params`](crate::miner::Miner::params) for each miner `X`.
+    /// Useful for comparing parameters of a strategy across lineups added with
+    /// [`SimulationBuilder::next_lineup`](crate::simulation::SimulationBuilder::next_lineup).
+    pub fn strategy_params(mut self) -> Self {
+        for output in self.data.iter() {
+            for (miner_id, params) in output.miner_params.iter() {
+                for (param, _) in params {
+                    self.columns
+                        .insert(Column::MinerParam(*miner_id, param.clone()));
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Include the "Miner `X` Power" column in the results table for the miner
+    /// with ID `miner_id`. By default, the table includes a power column for
+    /// every miner. Once this method is called, only the power columns of the
+    /// miners given to it are included. See also
+    /// [`ResultsBuilder::hide_power`].
+    ///
+    /// # Panics
+    /// Panics if no miner has ID `miner_id`.
+    pub fn mining_power_for(mut self, miner_id: MinerId) -> Self {
+        self.assert_valid_miner(miner_id);
+        self.columns.insert(Column::MiningPower(miner_id));
+
+        self
+    }
+
     /// Include a "Miner `X` Revenue" column in the results table for each
     /// miner `X`.
     pub fn revenue(mut self) -> Self {
-        let num_miners = self.data[0].miners.len();
+        let num_miners = self.num_miners();
         for miner_id in 1..=num_miners {
             self.columns.insert(Column::MinerRevenue(miner_id.into()));
         }
@@ -202,94 +868,556 @@ impl ResultsBuilder {
         self
     }
 
-    /// Include the "Simulated Rounds" column in the results table.
-    pub fn rounds(mut self) -> Self {
-        self.columns.insert(Column::Rounds);
+    /// Include the "Miner `X` Revenue" column in the results table for the
+    /// miner with ID `miner_id`.
+    ///
+    /// # Panics
+    /// Panics if no miner has ID `miner_id`.
+    pub fn revenue_for(mut self, miner_id: MinerId) -> Self {
+        self.assert_valid_miner(miner_id);
+        self.columns.insert(Column::MinerRevenue(miner_id));
 
         self
     }
 
-    /// Specify the [`Format`] of the results table.
-    pub fn format(mut self, format: Format) -> Self {
-        self.format = format;
+    /// Returns the number of distinct simulation configurations in these
+    /// results, each of which was run once per repeat.
+    pub fn num_configs(&self) -> usize {
+        self.data.len() / self.repeated.get()
+    }
 
-        self
+    /// Bin the revenue of the miner with ID `miner_id` across the repeated
+    /// runs of configuration `config` into a [`Histogram`] with `bins` bins.
+    /// Configurations are indexed in the order described in
+    /// [`ResultsBuilder::data`]. Returns [`None`] if `config` is out of range.
+    ///
+    /// # Panics
+    /// Panics if `bins` is 0.
+    pub fn revenue_histogram(
+        &self,
+        miner_id: MinerId,
+        config: usize,
+        bins: usize,
+    ) -> Option<Histogram> {
+        let repeated = self.repeated.get();
+        let outputs = self.data.chunks(repeated).nth(config)?;
+        let samples: Vec<_> = outputs
+            .iter()
+            .map(|output| revenue_of(&miner_id, output))
+            .collect();
+
+        Some(Histogram::new(&samples, bins))
     }
 
-    /// Create new [`ResultsTable`].
-    pub fn build(self) -> ResultsTable {
-        let ResultsBuilder {
-            average,
-            mut columns,
-            data,
-            format,
-            repeated,
-        } = self;
+    /// Returns the mining power and revenue of the miner with ID `miner_id`
+    /// in each simulation, in the order described in
+    /// [`ResultsBuilder::data`]. Useful for fitting a curve to revenue as a
+    /// function of mining power with
+    /// [`LinearModel`](crate::analysis::fit::LinearModel).
+    ///
+    /// # Panics
+    /// Panics if no miner has ID `miner_id`.
+    pub fn revenue_points(&self, miner_id: MinerId) -> Vec<(PowerValue, f64)> {
+        self.assert_valid_miner(miner_id);
 
-        let num_miners = data[0].miners.len();
-        for miner_id in 1..=num_miners {
-            columns.insert(Column::MiningPower(miner_id.into()));
-        }
+        self.data
+            .iter()
+            .map(|output| {
+                // Safety: power distributions are validated during the build
+                // step of the simulation pipeline
+                let power = unsafe {
+                    output
+                        .power_dist
+                        .power_of_unchecked(miner_id, output.miners.len())
+                };
 
-        match average {
-            Average::None => (),
-            _ => {
-                columns.insert(Column::AverageOf(average));
-            }
-        }
+                (power, revenue_of(&miner_id, output))
+            })
+            .collect()
+    }
 
-        let columns = Vec::from_iter(columns);
-        let rows = match average {
-            Average::None => data
+    /// Build an empirical CDF of the column with the given title, such as
+    /// "Miner 2 Revenue", across the repeated runs of each configuration.
+    /// The table has one row per distinct value of the column in each
+    /// configuration, giving the value and the fraction of runs with values
+    /// at most as large in a "Cumulative Probability" column. Rows are
+    /// identified by the columns of the results table which do not vary
+    /// between runs, such as strategy names and mining power, so that the
+    /// distributions of different strategies at the same mining power can be
+    /// compared. The table can be written in any [`Format`].
+    ///
+    /// Configurations appear in the order described in
+    /// [`ResultsBuilder::data`], and NaN values are left out.
+    ///
+    /// # Panics
+    /// Panics if the results table has no column with the given title, or if
+    /// that column does not vary between runs.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let results = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Selfish::new())
+    ///     .next_lineup()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(NDeficit::new(1))
+    ///     .miner_power(MinerId::from(2), 0.35)
+    ///     .repeat_all(10)
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all()
+    ///     .unwrap()
+    ///     .revenue();
+    ///
+    /// let ecdf = results.ecdf("Miner 2 Revenue");
+    /// println!("{}", ecdf);
+    /// ```
+    pub fn ecdf<T: AsRef<str>>(&self, title: T) -> ResultsTable {
+        let title = title.as_ref();
+        let labels = Self::miner_labels(&self.data);
+        let notations = (self.notation, &self.column_notations);
+        let columns = Self::table_columns(
+            self.all_columns(),
+            &[Average::None],
+            &labels,
+            notations,
+        );
+
+        let sampled = columns
+            .iter()
+            .find(|col| col.title == title)
+            .unwrap_or_else(|| panic!("no column titled {:?}", title))
+            .clone();
+        assert!(
+            sampled.column.is_averaged(),
+            "column {:?} does not vary between runs",
+            title
+        );
+
+        // Columns which identify a configuration
+        let mut columns: Vec<_> = columns
+            .into_iter()
+            .filter(|col| match col.column {
+                Column::RevenuePValue(_, _) => false,
+                _ => !col.column.is_averaged(),
+            })
+            .collect();
+        let keys = columns.len();
+        columns.push(sampled);
+        columns.push(TableColumn {
+            column: Column::CumulativeProbability,
+            average: Average::None,
+            notation: self.notation,
+            title: Column::CumulativeProbability.to_string(),
+        });
+
+        let mut rows = vec![];
+        for outputs in self.data.chunks(self.repeated.get()) {
+            let key: Vec<_> = columns[..keys]
                 .iter()
-                .map(|sim_output| {
-                    #[cfg(feature = "rayon")]
+                .map(|col| col.column.get_value(&outputs[0]))
+                .collect();
+
+            let mut values: Vec<_> = outputs
+                .iter()
+                .map(|output| columns[keys].column.get_value(output))
+                .filter(|value| !value.as_f64().is_nan())
+                .collect();
+            values.sort_by(|a, b| a.as_f64().total_cmp(&b.as_f64()));
+
+            let n = values.len() as f64;
+            for (i, value) in values.iter().enumerate() {
+                // Only the last of a run of equal values is a step of the CDF
+                let next = values.get(i + 1).map(ColumnValue::as_f64);
+                if next == Some(value.as_f64()) {
+                    continue;
+                }
+
+                let mut row = key.clone();
+                row.push(value.clone());
+                row.push(ColumnValue::CumulativeProbability(
+                    (i + 1) as f64 / n,
+                ));
+                rows.push(row);
+            }
+        }
+
+        ResultsTable {
+            columns,
+            format: self.format,
+            rows,
+            raw: None,
+            skipped: self.skipped.clone(),
+        }
+    }
+
+    /// Include the "Simulated Rounds" column in the results table.
+    pub fn rounds(mut self) -> Self {
+        self.columns.insert(Column::Rounds);
+
+        self
+    }
+
+    /// Aggregate simulations which share the same value in the column with
+    /// the given title, instead of aggregating the repeats of each
+    /// configuration separately. For example, grouping by "Miner 2 Power"
+    /// combines the runs of every power distribution which gives miner 2 the
+    /// same mining power. Groups appear in the order of their first
+    /// simulation, and the [`Average`] method is applied within each group.
+    ///
+    /// Columns which are not averaged, such as strategy names, take their
+    /// values from the first simulation in each group.
+    ///
+    /// # Panics
+    /// [`ResultsBuilder::build`] panics if the table has no column with the
+    /// given title, or if that column is the "Average Of" column.
+    pub fn group_by<T: Into<String>>(mut self, title: T) -> Self {
+        self.group_by = Some(title.into());
+
+        self
+    }
+
+    /// Leave out the "Miner `X` Power" columns which are included in the
+    /// results table by default. Power columns selected with
+    /// [`ResultsBuilder::mining_power_for`] are still included.
+    pub fn hide_power(mut self) -> Self {
+        self.hide_power = true;
+
+        self
+    }
+
+    /// Also build a raw companion table, available through
+    /// [`ResultsTable::raw`], with the same columns as the results table but
+    /// one row per simulation run and no averaging. The raw table begins with
+    /// a "Repeat" column giving the index of each run among the repeats of
+    /// its configuration.
+    pub fn include_raw(mut self) -> Self {
+        self.include_raw = true;
+
+        self
+    }
+
+    /// Specify the [`Notation`] of floating point values in the results table.
+    /// Defaults to fixed-point notation with [`F64_DISPLAY_DIGITS`] digits.
+    pub fn notation(mut self, notation: Notation) -> Self {
+        self.notation = notation;
+
+        self
+    }
+
+    /// Specify the [`Notation`] of floating point values in the column with
+    /// the given title, overriding [`ResultsBuilder::notation`]. When a column
+    /// is split into several by [`Average::Quantiles`], the title of the
+    /// original column applies to all of them.
+    pub fn column_notation<T>(mut self, title: T, notation: Notation) -> Self
+    where
+        T: Into<String>,
+    {
+        self.column_notations.insert(title.into(), notation);
+
+        self
+    }
+
+    /// Specify the [`Format`] of the results table.
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+
+        self
+    }
+
+    /// Create new [`ResultsTable`].
+    pub fn build(self) -> ResultsTable {
+        let mut columns = self.all_columns();
+        let ResultsBuilder {
+            averages,
+            column_notations,
+            mut data,
+            format,
+            group_by,
+            include_raw,
+            notation,
+            repeated,
+            skipped,
+            ..
+        } = self;
+
+        let labels = Self::miner_labels(&data);
+        let notations = (notation, &column_notations);
+
+        let raw = include_raw.then(|| {
+            let mut columns = columns.clone();
+            columns.insert(Column::Repeat);
+
+            let columns = Self::table_columns(
+                columns,
+                &[Average::None],
+                &labels,
+                notations,
+            );
+            let rows = Self::table_rows(&columns, false, &data, &[], repeated);
+
+            Box::new(ResultsTable {
+                columns,
+                format,
+                rows,
+                raw: None,
+                skipped: skipped.clone(),
+            })
+        });
+
+        let averaged = averages != [Average::None];
+        if averaged {
+            columns.insert(Column::AverageOf(averages.clone()));
+        }
+
+        let columns =
+            Self::table_columns(columns, &averages, &labels, notations);
+        let groups = match group_by {
+            Some(title) => {
+                let key = columns
+                    .iter()
+                    .find(|col| col.title == title)
+                    .map(|col| col.column.clone())
+                    .unwrap_or_else(|| panic!("no column titled {:?}", title));
+
+                Self::group_data(&key, &mut data)
+            }
+            None => (0..data.len())
+                .step_by(repeated.get())
+                .map(|start| start..(start + repeated.get()).min(data.len()))
+                .collect(),
+        };
+        let rows =
+            Self::table_rows(&columns, averaged, &data, &groups, repeated);
+
+        ResultsTable {
+            columns,
+            format,
+            rows,
+            raw,
+            skipped,
+        }
+    }
+
+    /// Selected columns, along with the columns which are included based on
+    /// the simulation data, such as the "Miner `X` Power" columns.
+    fn all_columns(&self) -> BTreeSet<Column> {
+        let mut columns = self.columns.clone();
+        let data = &self.data;
+
+        if data.iter().any(|output| output.power_dist_label.is_some()) {
+            columns.insert(Column::PowerDistLabel);
+        }
+        for (title, _) in
+            data.iter().filter_map(|output| output.power_param.as_ref())
+        {
+            columns.insert(Column::PowerParam(title.clone()));
+        }
+        for sensitivity in data.iter().flat_map(|output| &output.sensitivities)
+        {
+            columns.insert(Column::RevenueSensitivity(
+                sensitivity.miner,
+                sensitivity.param.clone(),
+            ));
+        }
+
+        let power_selected = columns
+            .iter()
+            .any(|column| matches!(column, Column::MiningPower(_)));
+        if !power_selected && !self.hide_power {
+            for miner_id in 1..=self.num_miners() {
+                columns.insert(Column::MiningPower(miner_id.into()));
+            }
+        }
+
+        columns
+    }
+
+    /// Label of each miner which has the same label in every simulation.
+    fn miner_labels(data: &[SimulationOutput]) -> BTreeMap<MinerId, String> {
+        let Some((first, rest)) = data.split_first() else {
+            return BTreeMap::new();
+        };
+
+        first
+            .miner_labels
+            .iter()
+            .filter(|(miner_id, label)| {
+                rest.iter().all(|output| {
+                    output.miner_labels.get(miner_id) == Some(label)
+                })
+            })
+            .map(|(miner_id, label)| (*miner_id, label.clone()))
+            .collect()
+    }
+
+    /// Split averaged columns into one column per averaging method, with the
+    /// method in the title if there are several. Miners are titled by their
+    /// `labels`, if any.
+    fn table_columns(
+        columns: BTreeSet<Column>,
+        averages: &[Average],
+        labels: &BTreeMap<MinerId, String>,
+        (notation, column_notations): (Notation, &BTreeMap<String, Notation>),
+    ) -> Vec<TableColumn> {
+        let averages: Vec<_> =
+            averages.iter().flat_map(Average::split).collect();
+
+        columns
+            .into_iter()
+            .flat_map(|column| {
+                let title = column.title(labels);
+                let notation =
+                    column_notations.get(&title).copied().unwrap_or(notation);
+
+                match column.is_averaged() {
+                    true if averages.len() > 1 => averages
+                        .iter()
+                        .map(|avg| TableColumn {
+                            title: format!("{} ({})", title, avg.label()),
+                            column: column.clone(),
+                            average: avg.clone(),
+                            notation,
+                        })
+                        .collect(),
+                    _ => vec![TableColumn {
+                        title,
+                        column,
+                        average: averages[0].clone(),
+                        notation,
+                    }],
+                }
+            })
+            .collect()
+    }
+
+    /// Stably reorder `data` so that simulations with the same value in the
+    /// `key` column are adjacent, and return the range of each group.
+    fn group_data(
+        key: &Column,
+        data: &mut Vec<SimulationOutput>,
+    ) -> Vec<Range<usize>> {
+        if let Column::AverageOf(_) = key {
+            panic!("cannot group simulations by the \"{}\" column", key);
+        }
+
+        let mut group_of = HashMap::new();
+        let mut sizes = vec![];
+        let mut grouped: Vec<_> = data
+            .drain(..)
+            .map(|sim_output| {
+                let value = key.get_value(&sim_output);
+                let value = value.format(Notation::default());
+                let group = *group_of.entry(value).or_insert_with(|| {
+                    sizes.push(0);
+                    sizes.len() - 1
+                });
+                sizes[group] += 1;
+
+                (group, sim_output)
+            })
+            .collect();
+
+        grouped.sort_by_key(|(group, _)| *group);
+        data.extend(grouped.into_iter().map(|(_, sim_output)| sim_output));
+
+        let mut start = 0;
+        sizes
+            .into_iter()
+            .map(|size| {
+                start += size;
+                (start - size)..start
+            })
+            .collect()
+    }
+
+    /// Compute the rows of a table. Averaged rows are computed over each range
+    /// of `data` in `groups`, and `groups` is ignored otherwise.
+    fn table_rows(
+        columns: &[TableColumn],
+        averaged: bool,
+        data: &[SimulationOutput],
+        groups: &[Range<usize>],
+        repeated: NonZeroUsize,
+    ) -> Vec<Vec<ColumnValue>> {
+        let raw_value = |col: &TableColumn, i: usize, output| match col.column {
+            Column::Repeat => ColumnValue::Repeat(i % repeated.get()),
+            _ => col.column.get_value(output),
+        };
+
+        match averaged {
+            false => data
+                .iter()
+                .enumerate()
+                .map(|(i, sim_output)| {
+                    #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
                     {
                         columns
                             .par_iter()
-                            .map(|col_type| col_type.get_value(sim_output))
+                            .map(|col| raw_value(col, i, sim_output))
                             .collect()
                     }
-                    #[cfg(not(feature = "rayon"))]
+                    #[cfg(any(not(feature = "rayon"), target_arch = "wasm32"))]
                     {
                         columns
                             .iter()
-                            .map(|col_type| col_type.get_value(sim_output))
+                            .map(|col| raw_value(col, i, sim_output))
                             .collect()
                     }
                 })
                 .collect(),
-            _ => data
-                .chunks(repeated.get())
-                .map(|sim_outputs| {
-                    #[cfg(feature = "rayon")]
+            true => groups
+                .iter()
+                .map(|range| {
+                    let sim_outputs = &data[range.clone()];
+
+                    #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
                     {
                         columns
                             .par_iter()
-                            .map(|col_type| {
-                                col_type.get_average_value(average, sim_outputs)
+                            .map(|col| {
+                                col.column.get_average_value(
+                                    &col.average,
+                                    sim_outputs,
+                                )
                             })
                             .collect()
                     }
-                    #[cfg(not(feature = "rayon"))]
+                    #[cfg(any(not(feature = "rayon"), target_arch = "wasm32"))]
                     {
                         columns
                             .iter()
-                            .map(|col_type| {
-                                col_type.get_average_value(average, sim_outputs)
+                            .map(|col| {
+                                col.column.get_average_value(
+                                    &col.average,
+                                    sim_outputs,
+                                )
                             })
                             .collect()
                     }
                 })
                 .collect(),
-        };
-
-        ResultsTable {
-            columns,
-            format,
-            rows,
         }
     }
+
+    /// Number of miners in each simulation, even if every configuration was
+    /// skipped.
+    fn num_miners(&self) -> usize {
+        self.num_miners
+    }
+
+    /// Panics if `miner_id` does not belong to a miner in the simulations.
+    fn assert_valid_miner(&self, miner_id: MinerId) {
+        let num_miners = self.num_miners();
+        assert!(
+            (1..=num_miners).contains(&miner_id.get()),
+            "invalid MinerId {} for {} miners",
+            miner_id,
+            num_miners
+        );
+    }
 }
 
 /// Formatted results from the completion of a
@@ -297,9 +1425,11 @@ impl ResultsBuilder {
 /// is given by the struct's [`Display`] implementation, as specified by
 /// its [`Format`].
 pub struct ResultsTable {
-    columns: Vec<Column>,
+    columns: Vec<TableColumn>,
     format: Format,
     rows: Vec<Vec<ColumnValue>>,
+    raw: Option<Box<ResultsTable>>,
+    skipped: Vec<SkippedConfig>,
 }
 
 impl ResultsTable {
@@ -312,37 +1442,186 @@ impl ResultsTable {
 
     pub fn set_format(&mut self, format: Format) {
         self.format = format;
+        if let Some(raw) = self.raw.as_mut() {
+            raw.set_format(format);
+        }
     }
-}
 
-impl Display for ResultsTable {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let titles: Vec<_> = self
+    /// Append the rows of `other` to the rows of this table. Columns are
+    /// matched by title, and any columns of `other` which are not in this
+    /// table are added after this table's columns. Cells of columns which a
+    /// row's original table did not have are left empty, e.g. the strategy
+    /// names of extra miners when combining lineups of different sizes.
+    ///
+    /// The combined table keeps this table's [`Format`]. Raw companion tables
+    /// are combined only if both tables have one.
+    pub fn concat(mut self, other: ResultsTable) -> Self {
+        let ResultsTable {
+            columns,
+            rows,
+            raw,
+            skipped,
+            ..
+        } = other;
+
+        // Index of each column of `other` in the combined table
+        let mut positions = Vec::with_capacity(columns.len());
+        for col in columns {
+            match self.columns.iter().position(|c| c.title == col.title) {
+                Some(i) => positions.push(i),
+                None => {
+                    self.columns.push(col);
+                    for row in self.rows.iter_mut() {
+                        row.push(ColumnValue::Missing);
+                    }
+                    positions.push(self.columns.len() - 1);
+                }
+            }
+        }
+
+        for row in rows {
+            let mut combined = vec![ColumnValue::Missing; self.columns.len()];
+            for (val, &i) in row.into_iter().zip(positions.iter()) {
+                combined[i] = val;
+            }
+            self.rows.push(combined);
+        }
+
+        self.raw = match (self.raw, raw) {
+            (Some(raw), Some(other_raw)) => {
+                Some(Box::new(raw.concat(*other_raw)))
+            }
+            _ => None,
+        };
+        self.skipped.extend(skipped);
+
+        self
+    }
+
+    /// Returns the kind of each column in this table, in order.
+    pub fn column_kinds(&self) -> impl Iterator<Item = ColumnKind> + '_ {
+        self.columns.iter().map(|col| col.column.kind())
+    }
+
+    /// Returns the title of each column in this table, in order.
+    pub fn titles(&self) -> impl Iterator<Item = &str> + '_ {
+        self.columns.iter().map(|col| col.title.as_str())
+    }
+
+    /// Returns the averaging method applied to each column in this table, in
+    /// order. Columns which are not averaged give [`Average::None`].
+    pub fn column_averages(&self) -> impl Iterator<Item = &Average> + '_ {
+        let averaged = self
             .columns
             .iter()
-            .map(|col_type| col_type.to_string())
+            .any(|col| matches!(col.column, Column::AverageOf(_)));
+
+        self.columns
+            .iter()
+            .map(move |col| match col.column.is_averaged() {
+                true if averaged => &col.average,
+                _ => &Average::None,
+            })
+    }
+
+    /// Returns an iterator over the rows of this table, with each cell paired
+    /// with the kind of its column.
+    pub fn rows(
+        &self,
+    ) -> impl Iterator<Item = Vec<(ColumnKind, CellValue)>> + '_ {
+        self.rows.iter().map(|row| {
+            row.iter()
+                .zip(self.columns.iter())
+                .map(|(val, col)| (col.column.kind(), val.to_cell()))
+                .collect()
+        })
+    }
+
+    /// Returns the raw companion table of this table, with one row per
+    /// simulation run. Only present if [`ResultsBuilder::include_raw`] was
+    /// called.
+    pub fn raw(&self) -> Option<&ResultsTable> {
+        self.raw.as_deref()
+    }
+
+    /// Returns the configurations left out of this table. See
+    /// [`ResultsBuilder::skipped_configs`].
+    pub fn skipped_configs(&self) -> &[SkippedConfig] {
+        &self.skipped
+    }
+
+    /// Write this table to `writer` in its current [`Format`], followed by a
+    /// newline.
+    pub fn write_to<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{}", self)
+    }
+
+    /// Write this table to the file at `path`, creating or truncating it. The
+    /// [`Format`] is chosen from the file extension as in
+    /// [`Format::from_extension`], regardless of the table's current format.
+    pub fn save<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), ResultsSaveError> {
+        let path = path.as_ref();
+        let format = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Format::from_extension)
+            .ok_or_else(|| {
+                ResultsSaveError::UnknownExtension(path.to_path_buf())
+            })?;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", FormattedTable(self, format))?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    fn write_formatted(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        format: Format,
+    ) -> std::fmt::Result {
+        let titles: Vec<_> =
+            self.columns.iter().map(|col| col.title.clone()).collect();
+        let values: Vec<Vec<_>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(self.columns.iter())
+                    .map(|(val, col)| val.format(col.notation))
+                    .collect()
+            })
             .collect();
 
-        match self.format {
-            Format::CSV => {
-                write!(f, "{}", titles.join(","))?;
+        match format {
+            Format::Delimited { sep, quote, header } => {
+                let field = |text: &str| delimited_field(text, sep, quote);
+                let sep = sep.to_string();
 
-                for row in self.rows.iter() {
-                    writeln!(f)?;
+                let header: Option<Vec<_>> = header
+                    .then(|| titles.iter().map(|title| field(title)).collect());
+                let rows = values
+                    .iter()
+                    .map(|row| row.iter().map(|val| field(val)).collect());
 
-                    let row: Vec<_> =
-                        row.iter().map(|val| val.to_string()).collect();
+                for (i, line) in header.into_iter().chain(rows).enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
 
-                    write!(f, "{}", row.join(","))?;
+                    write!(f, "{}", line.join(&sep))?;
                 }
             }
             Format::PrettyPrint => {
                 let mut text_widths: Vec<_> =
                     titles.iter().map(|title| title.len()).collect();
 
-                for row in self.rows.iter() {
+                for row in values.iter() {
                     for (i, val) in row.iter().enumerate() {
-                        let val = val.to_string();
                         text_widths[i] = text_widths[i].max(val.len());
                     }
                 }
@@ -363,73 +1642,773 @@ impl Display for ResultsTable {
                     write!(f, "{}", Self::SEPARATOR_HORIZONTAL)?;
                 }
 
-                for row in self.rows.iter() {
+                for row in values.iter() {
                     writeln!(f)?;
 
                     for (i, val) in row.iter().enumerate() {
                         write!(
                             f,
                             " {:1$} {2}",
-                            val.to_string(),
+                            val,
                             text_widths[i],
                             Self::SEPARATOR_VERTICAL
                         )?;
                     }
                 }
             }
+            Format::JSON => {
+                write!(f, "[")?;
+
+                for (i, (row, row_values)) in
+                    self.rows.iter().zip(values.iter()).enumerate()
+                {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "\n  {{")?;
+
+                    let cells = titles.iter().zip(row).zip(row_values);
+                    for (j, ((title, val), text)) in cells.enumerate() {
+                        if j > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}: ", json_string(title))?;
+
+                        match val {
+                            ColumnValue::MinerStrategyName(name)
+                            | ColumnValue::PowerDistLabel(name) => {
+                                write!(f, "{}", json_string(name))?
+                            }
+                            // JSON has no representation of NaN or infinity
+                            _ if !val.as_f64().is_finite() => {
+                                write!(f, "null")?
+                            }
+                            _ => write!(f, "{}", text)?,
+                        }
+                    }
+
+                    write!(f, "}}")?;
+                }
+
+                if !self.rows.is_empty() {
+                    writeln!(f)?;
+                }
+                write!(f, "]")?;
+            }
+            Format::Markdown => {
+                let escape = |text: &str| text.replace('|', "\\|");
+
+                write!(f, "|")?;
+                for title in titles.iter() {
+                    write!(f, " {} |", escape(title))?;
+                }
+                writeln!(f)?;
+
+                write!(f, "|")?;
+                for _ in titles.iter() {
+                    write!(f, " --- |")?;
+                }
+
+                for row in values.iter() {
+                    writeln!(f)?;
+
+                    write!(f, "|")?;
+                    for val in row.iter() {
+                        write!(f, " {} |", escape(val))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ResultsTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_formatted(f, self.format)
+    }
+}
+
+/// Serializes as a struct with a `columns` field, containing the title and
+/// [`ColumnKind`] of each column, and a `rows` field, containing the
+/// [`CellValue`]s of each row. Requires the `serde` feature.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ResultsTable {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        #[derive(serde::Serialize)]
+        struct SerializedColumn<'a> {
+            title: &'a str,
+            kind: ColumnKind,
+        }
+
+        let columns: Vec<_> = self
+            .columns
+            .iter()
+            .map(|col| SerializedColumn {
+                title: &col.title,
+                kind: col.column.kind(),
+            })
+            .collect();
+        let rows: Vec<Vec<_>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|val| val.to_cell()).collect())
+            .collect();
+
+        let mut table = serializer.serialize_struct("ResultsTable", 2)?;
+        table.serialize_field("columns", &columns)?;
+        table.serialize_field("rows", &rows)?;
+        table.end()
+    }
+}
+
+/// Displays a [`ResultsTable`] in a format other than its own.
+struct FormattedTable<'a>(&'a ResultsTable, Format);
+
+impl Display for FormattedTable<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.write_formatted(f, self.1)
+    }
+}
+
+/// Counts of samples falling into equal-width bins. Produced by
+/// [`ResultsBuilder::revenue_histogram`].
+///
+/// Bin `i` covers the half-open range `edges[i]..edges[i + 1]`, except for the
+/// last bin, which also includes its upper edge. The bins span the range from
+/// the smallest sample to the largest. The [`Display`] implementation
+/// produces a CSV table with one row per bin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Number of samples in each bin.
+    pub counts: Vec<usize>,
+    /// Boundaries of the bins, of which there is one more than the number of
+    /// bins.
+    pub edges: Vec<f64>,
+}
+
+impl Histogram {
+    /// Bin `samples` into `bins` equal-width bins. NaN samples are ignored.
+    ///
+    /// # Panics
+    /// Panics if `bins` is 0.
+    pub fn new(samples: &[f64], bins: usize) -> Self {
+        assert!(bins > 0, "histogram must have at least 1 bin");
+
+        let samples: Vec<_> =
+            samples.iter().copied().filter(|x| !x.is_nan()).collect();
+        let min = samples.iter().copied().reduce(f64::min).unwrap_or(0.0);
+        let max = samples.iter().copied().reduce(f64::max).unwrap_or(0.0);
+        let width = (max - min) / bins as f64;
+
+        let edges = (0..=bins)
+            .map(|i| match i {
+                i if i == bins => max,
+                i => min + width * i as f64,
+            })
+            .collect();
+
+        let mut counts = vec![0; bins];
+        for sample in samples {
+            let bin = match width > 0.0 {
+                true => ((sample - min) / width) as usize,
+                false => 0,
+            };
+            counts[bin.min(bins - 1)] += 1;
+        }
+
+        Histogram { counts, edges }
+    }
+}
+
+impl Display for Histogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Bin Start,Bin End,Count")?;
+
+        for (i, count) in self.counts.iter().enumerate() {
+            write!(
+                f,
+                "\n{:.3$},{:.3$},{}",
+                self.edges[i],
+                self.edges[i + 1],
+                count,
+                F64_DISPLAY_DIGITS
+            )?;
         }
 
         Ok(())
     }
 }
 
+/// Quote `text` as a field of a [`Format::Delimited`] table if necessary.
+fn delimited_field(text: &str, sep: char, quote: char) -> String {
+    let needs_quotes = text
+        .chars()
+        .any(|c| c == sep || c == quote || c == '\n' || c == '\r');
+
+    if needs_quotes {
+        let doubled = text.replace(quote, &format!("{0}{0}", quote));
+        format!("{0}{1}{0}", quote, doubled)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Quote and escape `text` as a JSON string.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                quoted.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
 /// Type of column that can appear in a [`ResultsTable`].
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Column {
     // Variant order determines the order of columns:
     // https://doc.rust-lang.org/stable/std/cmp/trait.PartialOrd.html#derivable
+    Repeat,
+    PowerDistLabel,
+    PowerParam(String),
     MinerStrategyName(MinerId),
+    MinerParam(MinerId, String),
     MiningPower(MinerId),
     MinerRevenue(MinerId),
+    MinerBlocksMined(MinerId),
+    MinerBlocksOnChain(MinerId),
+    MinerSubsidyIncome(MinerId),
+    MinerFeeIncome(MinerId),
+    MinerUncleIncome(MinerId),
+    MinerNephewIncome(MinerId),
+    MinerRewardShare(MinerId),
     MiningPowerFunction(MinerId, WrapFunc<PowerValue, f64>),
+    RevenueDifference(MinerId, WrapFunc<PowerValue, f64>),
+    RevenueRelativeError(MinerId, WrapFunc<PowerValue, f64>),
+    RevenuePValue(MinerId, WrapFunc<PowerValue, f64>),
+    RevenueSensitivity(MinerId, String),
     Constant(WrapFunc<(), f64>),
     Rounds,
-    AverageOf(Average),
+    AverageOf(Vec<Average>),
+    BlocksPublished,
+    LongestChainLength,
+    BlockchainMemory,
+    ForkCount,
+    MeanForkLength,
+    MaxForkLength,
+    ReorgCount,
+    MaxReorgDepth,
+    PowerGini,
+    RevenueGini,
+    DoubleSpendSuccess(usize),
+    MeanInclusionLatency,
+    MedianInclusionLatency,
+    BlacklistedConfirmed,
+    BlacklistedMeanLatency,
+    CumulativeProbability,
+}
+
+/// Public description of the type of a column in a [`ResultsTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ColumnKind {
+    /// Index of a simulation among the repeats of its configuration.
+    Repeat,
+    /// Label of a simulation's power distribution.
+    PowerDistLabel,
+    /// Parameter which produced a simulation's power distribution.
+    PowerParam,
+    MinerStrategyName(MinerId),
+    /// Parameter of a miner's strategy. See
+    /// [`Miner::params`](crate::miner::Miner::params).
+    MinerParam(MinerId),
+    MiningPower(MinerId),
+    MinerRevenue(MinerId),
+    MinerBlocksMined(MinerId),
+    MinerBlocksOnChain(MinerId),
+    MinerSubsidyIncome(MinerId),
+    MinerFeeIncome(MinerId),
+    MinerUncleIncome(MinerId),
+    MinerNephewIncome(MinerId),
+    /// Fraction of all income paid to a miner. See
+    /// [`ResultsBuilder::reward_share`].
+    MinerRewardShare(MinerId),
+    MiningPowerFunction(MinerId),
+    RevenueDifference(MinerId),
+    RevenueRelativeError(MinerId),
+    RevenuePValue(MinerId),
+    /// Estimated derivative of a miner's revenue. See
+    /// [`SimulationBuilder::revenue_sensitivity`](crate::simulation::SimulationBuilder::revenue_sensitivity).
+    RevenueSensitivity(MinerId),
+    Constant,
+    Rounds,
+    /// Number of simulations averaged into each row.
+    AverageOf,
     BlocksPublished,
     LongestChainLength,
+    BlockchainMemory,
+    ForkCount,
+    MeanForkLength,
+    MaxForkLength,
+    ReorgCount,
+    MaxReorgDepth,
+    PowerGini,
+    RevenueGini,
+    /// Fraction of double-spend attempts which succeeded against a merchant
+    /// waiting for the given number of confirmations.
+    DoubleSpendSuccess(usize),
+    MeanInclusionLatency,
+    MedianInclusionLatency,
+    BlacklistedConfirmed,
+    BlacklistedMeanLatency,
+    /// Fraction of runs with values at most as large as a row's value. See
+    /// [`ResultsBuilder::ecdf`].
+    CumulativeProbability,
+}
+
+/// Value of a single cell of a [`ResultsTable`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum CellValue {
+    Text(String),
+    Integer(usize),
+    Float(f64),
+    /// The cell's column was not part of the cell's table before it was
+    /// combined with [`ResultsTable::concat`].
+    Missing,
+}
+
+impl CellValue {
+    /// Returns the numeric value of this cell, or [`None`] if it contains
+    /// text.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Text(_) | Self::Missing => None,
+            Self::Integer(num) => Some(*num as f64),
+            Self::Float(num) => Some(*num),
+        }
+    }
+}
+
+/// A [`Column`] as it appears in a [`ResultsTable`].
+#[derive(Debug, Clone)]
+struct TableColumn {
+    column: Column,
+    /// Method used to average the column's values across repeated
+    /// simulations. Ignored if the table is not averaged.
+    average: Average,
+    notation: Notation,
+    title: String,
 }
 
 /// Value which corresponds to a [`Column`].
 #[derive(Debug, Clone)]
 enum ColumnValue {
+    /// Placeholder for cells of columns which a row's table did not have
+    /// before being combined with [`ResultsTable::concat`].
+    Missing,
+    Repeat(usize),
+    PowerDistLabel(String),
+    PowerParam(f64),
     MinerStrategyName(String),
+    MinerParam(f64),
     MiningPower(PowerValue),
     MinerRevenue(f64),
+    MinerBlocksMined(f64),
+    MinerBlocksOnChain(f64),
+    MinerSubsidyIncome(f64),
+    MinerFeeIncome(f64),
+    MinerUncleIncome(f64),
+    MinerNephewIncome(f64),
+    MinerRewardShare(f64),
     MiningPowerFunction(f64),
+    RevenueDifference(f64),
+    RevenueRelativeError(f64),
+    RevenuePValue(f64),
+    RevenueSensitivity(f64),
     Constant(f64),
-    Rounds(usize),
+    Rounds(f64),
     AverageOf(usize),
     BlocksPublished(f64),
     LongestChainLength(f64),
+    BlockchainMemory(f64),
+    ForkCount(f64),
+    MeanForkLength(f64),
+    MaxForkLength(f64),
+    ReorgCount(f64),
+    MaxReorgDepth(f64),
+    PowerGini(f64),
+    RevenueGini(f64),
+    DoubleSpendSuccess(f64),
+    MeanInclusionLatency(f64),
+    MedianInclusionLatency(f64),
+    BlacklistedConfirmed(f64),
+    BlacklistedMeanLatency(f64),
+    CumulativeProbability(f64),
+}
+
+#[inline]
+pub(crate) fn revenue_of(miner_id: &MinerId, data: &SimulationOutput) -> f64 {
+    let blocks = data
+        .miner_stats
+        .get(miner_id)
+        .map(|stats| stats.blocks_on_chain as f64)
+        .unwrap_or_default();
+
+    blocks / data.longest_chain_length as f64
+}
+
+/// Returns the estimated derivative of the revenue of `miner_id` with respect
+/// to `param`, or NaN if it was not estimated.
+#[inline]
+fn sensitivity_of(
+    miner_id: &MinerId,
+    param: &str,
+    data: &SimulationOutput,
+) -> f64 {
+    data.sensitivities
+        .iter()
+        .find(|s| s.miner == *miner_id && s.param == param)
+        .map_or(f64::NAN, |s| s.derivative)
+}
+
+/// Returns the difference between the simulated revenue of `miner_id` and
+/// the revenue predicted by `ideal`, along with the relative error of the
+/// simulated revenue.
+#[inline]
+fn revenue_error_of(
+    miner_id: &MinerId,
+    ideal: &WrapFunc<PowerValue, f64>,
+    data: &SimulationOutput,
+) -> (f64, f64) {
+    // Safety: power distributions are validated during the build step of the
+    // simulation pipeline
+    let power = unsafe {
+        data.power_dist
+            .power_of_unchecked(*miner_id, data.miners.len())
+    };
+    let ideal = ideal.call(power);
+    let difference = revenue_of(miner_id, data) - ideal;
+
+    (difference, difference / ideal)
+}
+
+/// Returns the p-value of a one-sample t-test of the hypothesis that the
+/// simulated revenue of `miner_id` in `data` matches the revenue predicted by
+/// `ideal`.
+fn revenue_p_value_of(
+    miner_id: &MinerId,
+    ideal: &WrapFunc<PowerValue, f64>,
+    data: &[SimulationOutput],
+) -> f64 {
+    let differences: Vec<_> = data
+        .iter()
+        .map(|sim_output| revenue_error_of(miner_id, ideal, sim_output).0)
+        .collect();
+
+    crate::utils::t_test_p_value(&differences, 0.0)
+}
+
+/// Returns the Gini coefficients of the mining power and revenue of all miners
+/// in a simulation.
+#[inline]
+fn gini_of(data: &SimulationOutput) -> (f64, f64) {
+    let num_miners = data.miners.len();
+    let (mut powers, mut revenues): (Vec<_>, Vec<_>) = (1..=num_miners)
+        .map(|id| {
+            let miner_id = MinerId::from(id);
+            // Safety: power distributions are validated during the build
+            // step of the simulation pipeline
+            let power = unsafe {
+                data.power_dist.power_of_unchecked(miner_id, num_miners)
+            };
+
+            (power, revenue_of(&miner_id, data))
+        })
+        .unzip();
+
+    (
+        crate::utils::gini_of_floats(&mut powers),
+        crate::utils::gini_of_floats(&mut revenues),
+    )
+}
+
+/// Converts a number of bytes to mebibytes.
+#[inline]
+fn mebibytes(bytes: usize) -> f64 {
+    bytes as f64 / (1 << 20) as f64
+}
+
+/// Returns the number of forks, mean fork length, and maximum fork length of
+/// a simulation. Fork lengths are 0 if there are no forks.
+#[inline]
+fn fork_stats_of(data: &SimulationOutput) -> (f64, f64, f64) {
+    let count = data.fork_lengths.len();
+    let total: usize = data.fork_lengths.iter().sum();
+    let max = data.fork_lengths.iter().copied().max().unwrap_or_default();
+    let mean = match count {
+        0 => 0.0,
+        _ => total as f64 / count as f64,
+    };
+
+    (count as f64, mean, max as f64)
+}
+
+/// Returns the fraction of the double-spend attempts in a simulation which
+/// succeeded against a merchant waiting for `confirmations` confirmations, or
+/// NaN if there were none.
+#[inline]
+fn double_spend_success_of(
+    confirmations: usize,
+    data: &SimulationOutput,
+) -> f64 {
+    let successes = data
+        .double_spends
+        .iter()
+        .filter(|outcome| outcome.succeeded(confirmations))
+        .count();
+
+    successes as f64 / data.double_spends.len() as f64
+}
+
+/// Returns the mean and median inclusion latency of the transactions on the
+/// longest chain of a simulation, or NaN if there were none.
+#[inline]
+fn inclusion_latency_of(data: &SimulationOutput) -> (f64, f64) {
+    if data.inclusion_latencies.is_empty() {
+        return (f64::NAN, f64::NAN);
+    }
+
+    let mut latencies: Vec<_> = data
+        .inclusion_latencies
+        .iter()
+        .map(|&latency| latency as f64)
+        .collect();
+    let mean = latencies.iter().sum::<f64>() / latencies.len() as f64;
+    let median = crate::utils::median_of_floats(&mut latencies);
+
+    (mean, median)
+}
+
+/// Returns the number of blacklisted transactions on the longest chain of a
+/// simulation and their mean inclusion latency, or NaN if there were none.
+#[inline]
+fn blacklisted_stats_of(data: &SimulationOutput) -> (f64, f64) {
+    let count = data.blacklisted_latencies.len() as f64;
+    let total: usize = data.blacklisted_latencies.iter().sum();
+
+    (count, total as f64 / count)
+}
+
+/// Returns the number of reorganizations and the maximum reorganization depth
+/// of a simulation. The maximum depth is 0 if there are no reorganizations.
+#[inline]
+fn reorg_stats_of(data: &SimulationOutput) -> (f64, f64) {
+    let count = data.reorg_depths.len();
+    let max = data.reorg_depths.iter().copied().max().unwrap_or_default();
+
+    (count as f64, max as f64)
+}
+
+#[inline]
+fn blocks_mined_by(miner_id: &MinerId, data: &SimulationOutput) -> f64 {
+    data.miner_stats
+        .get(miner_id)
+        .map(|stats| stats.blocks_mined as f64)
+        .unwrap_or_default()
+}
+
+#[inline]
+fn blocks_on_chain_by(miner_id: &MinerId, data: &SimulationOutput) -> f64 {
+    data.miner_stats
+        .get(miner_id)
+        .map(|stats| stats.blocks_on_chain as f64)
+        .unwrap_or_default()
+}
+
+/// Returns the subsidy income and fee income of `miner_id`.
+#[inline]
+fn income_of(miner_id: &MinerId, data: &SimulationOutput) -> (f64, f64) {
+    data.miner_stats
+        .get(miner_id)
+        .map(|stats| (stats.subsidy_income, stats.fee_income))
+        .unwrap_or_default()
 }
 
+/// Returns the uncle income and nephew income of `miner_id`.
 #[inline]
-fn revenue_of(miner_id: &MinerId, data: &SimulationOutput) -> f64 {
-    let blocks = data
-        .blocks_by_miner
+fn uncle_income_of(miner_id: &MinerId, data: &SimulationOutput) -> (f64, f64) {
+    data.miner_stats
         .get(miner_id)
-        .map(|block_ids| {
-            block_ids
-                .iter()
-                .filter(|&block_id| data.longest_chain.contains(block_id))
-                .count() as f64
-        })
-        .unwrap_or_default();
+        .map(|stats| (stats.uncle_income, stats.nephew_income))
+        .unwrap_or_default()
+}
 
-    blocks / data.longest_chain.len() as f64
+/// Returns the fraction of the total income of all miners which was paid to
+/// `miner_id`, or NaN if no income was paid.
+fn reward_share_of(miner_id: &MinerId, data: &SimulationOutput) -> f64 {
+    let income = |stats: &MinerStats| {
+        stats.subsidy_income
+            + stats.fee_income
+            + stats.uncle_income
+            + stats.nephew_income
+    };
+    let total: f64 = data.miner_stats.values().map(income).sum();
+
+    data.miner_stats.get(miner_id).map_or(0.0, income) / total
 }
 
 impl Column {
+    fn kind(&self) -> ColumnKind {
+        match self {
+            Self::Repeat => ColumnKind::Repeat,
+            Self::PowerDistLabel => ColumnKind::PowerDistLabel,
+            Self::PowerParam(_) => ColumnKind::PowerParam,
+            Self::MinerStrategyName(id) => ColumnKind::MinerStrategyName(*id),
+            Self::MinerParam(id, _) => ColumnKind::MinerParam(*id),
+            Self::MiningPower(id) => ColumnKind::MiningPower(*id),
+            Self::MinerRevenue(id) => ColumnKind::MinerRevenue(*id),
+            Self::MinerBlocksMined(id) => ColumnKind::MinerBlocksMined(*id),
+            Self::MinerBlocksOnChain(id) => ColumnKind::MinerBlocksOnChain(*id),
+            Self::MinerSubsidyIncome(id) => ColumnKind::MinerSubsidyIncome(*id),
+            Self::MinerFeeIncome(id) => ColumnKind::MinerFeeIncome(*id),
+            Self::MinerUncleIncome(id) => ColumnKind::MinerUncleIncome(*id),
+            Self::MinerNephewIncome(id) => ColumnKind::MinerNephewIncome(*id),
+            Self::MinerRewardShare(id) => ColumnKind::MinerRewardShare(*id),
+            Self::MiningPowerFunction(id, _) => {
+                ColumnKind::MiningPowerFunction(*id)
+            }
+            Self::RevenueDifference(id, _) => {
+                ColumnKind::RevenueDifference(*id)
+            }
+            Self::RevenueRelativeError(id, _) => {
+                ColumnKind::RevenueRelativeError(*id)
+            }
+            Self::RevenuePValue(id, _) => ColumnKind::RevenuePValue(*id),
+            Self::RevenueSensitivity(id, _) => {
+                ColumnKind::RevenueSensitivity(*id)
+            }
+            Self::Constant(_) => ColumnKind::Constant,
+            Self::Rounds => ColumnKind::Rounds,
+            Self::AverageOf(_) => ColumnKind::AverageOf,
+            Self::BlocksPublished => ColumnKind::BlocksPublished,
+            Self::LongestChainLength => ColumnKind::LongestChainLength,
+            Self::BlockchainMemory => ColumnKind::BlockchainMemory,
+            Self::ForkCount => ColumnKind::ForkCount,
+            Self::MeanForkLength => ColumnKind::MeanForkLength,
+            Self::MaxForkLength => ColumnKind::MaxForkLength,
+            Self::ReorgCount => ColumnKind::ReorgCount,
+            Self::MaxReorgDepth => ColumnKind::MaxReorgDepth,
+            Self::PowerGini => ColumnKind::PowerGini,
+            Self::RevenueGini => ColumnKind::RevenueGini,
+            Self::DoubleSpendSuccess(confirmations) => {
+                ColumnKind::DoubleSpendSuccess(*confirmations)
+            }
+            Self::MeanInclusionLatency => ColumnKind::MeanInclusionLatency,
+            Self::MedianInclusionLatency => ColumnKind::MedianInclusionLatency,
+            Self::BlacklistedConfirmed => ColumnKind::BlacklistedConfirmed,
+            Self::BlacklistedMeanLatency => ColumnKind::BlacklistedMeanLatency,
+            Self::CumulativeProbability => ColumnKind::CumulativeProbability,
+        }
+    }
+
+    /// Returns true if the values of this column vary between repeated
+    /// simulations, and so must be averaged.
+    fn is_averaged(&self) -> bool {
+        match &self {
+            // Summarizes the simulations of a row without an average
+            Self::RevenuePValue(_, _) => false,
+            Self::AverageOf(_)
+            | Self::Repeat
+            | Self::Constant(_)
+            | Self::PowerDistLabel
+            | Self::PowerParam(_)
+            | Self::MinerStrategyName(_)
+            | Self::MinerParam(_, _)
+            | Self::MiningPower(_)
+            | Self::MiningPowerFunction(_, _)
+            | Self::PowerGini
+            | Self::CumulativeProbability => false,
+            Self::BlocksPublished
+            | Self::MinerRevenue(_)
+            | Self::MinerBlocksMined(_)
+            | Self::MinerBlocksOnChain(_)
+            | Self::MinerSubsidyIncome(_)
+            | Self::MinerFeeIncome(_)
+            | Self::MinerUncleIncome(_)
+            | Self::MinerNephewIncome(_)
+            | Self::MinerRewardShare(_)
+            | Self::RevenueDifference(_, _)
+            | Self::RevenueRelativeError(_, _)
+            | Self::RevenueSensitivity(_, _)
+            | Self::LongestChainLength
+            | Self::BlockchainMemory
+            | Self::ForkCount
+            | Self::MeanForkLength
+            | Self::MaxForkLength
+            | Self::ReorgCount
+            | Self::MaxReorgDepth
+            | Self::RevenueGini
+            | Self::DoubleSpendSuccess(_)
+            | Self::MeanInclusionLatency
+            | Self::MedianInclusionLatency
+            | Self::BlacklistedConfirmed
+            | Self::BlacklistedMeanLatency
+            | Self::Rounds => true,
+        }
+    }
+
+    /// Returns the title of this column, with "Miner `X`" replaced by the
+    /// miner's label if it has one.
+    fn title(&self, labels: &BTreeMap<MinerId, String>) -> String {
+        let title = self.to_string();
+        let miner_id = match self {
+            Self::MinerStrategyName(miner_id)
+            | Self::MinerParam(miner_id, _)
+            | Self::MiningPower(miner_id)
+            | Self::MinerRevenue(miner_id)
+            | Self::MinerBlocksMined(miner_id)
+            | Self::MinerBlocksOnChain(miner_id)
+            | Self::MinerSubsidyIncome(miner_id)
+            | Self::MinerFeeIncome(miner_id)
+            | Self::MinerUncleIncome(miner_id)
+            | Self::MinerNephewIncome(miner_id)
+            | Self::MinerRewardShare(miner_id)
+            | Self::RevenueDifference(miner_id, _)
+            | Self::RevenueRelativeError(miner_id, _)
+            | Self::RevenuePValue(miner_id, _)
+            | Self::RevenueSensitivity(miner_id, _) => miner_id,
+            _ => return title,
+        };
+
+        match labels.get(miner_id) {
+            Some(label) => {
+                title.replacen(&format!("Miner {}", miner_id), label, 1)
+            }
+            None => title,
+        }
+    }
+
     fn get_value(&self, output: &SimulationOutput) -> ColumnValue {
         match &self {
             Self::BlocksPublished => {
@@ -447,6 +2426,28 @@ impl Column {
 
                 ColumnValue::MinerStrategyName(name)
             }
+            Self::MinerParam(miner_id, param) => {
+                let value = output
+                    .miner_params
+                    .get(miner_id)
+                    .and_then(|params| params.iter().find(|(p, _)| p == param))
+                    .map_or(f64::NAN, |(_, value)| *value);
+
+                ColumnValue::MinerParam(value)
+            }
+            Self::PowerDistLabel => {
+                let label = output.power_dist_label.clone();
+
+                ColumnValue::PowerDistLabel(label.unwrap_or_default())
+            }
+            Self::PowerParam(title) => {
+                let value = match &output.power_param {
+                    Some((param, value)) if param == title => *value,
+                    _ => f64::NAN,
+                };
+
+                ColumnValue::PowerParam(value)
+            }
             Self::MiningPower(miner_id) => {
                 // Safety: power distributions are validated during the build
                 // step of the simulation pipeline
@@ -470,42 +2471,177 @@ impl Column {
 
                 ColumnValue::MiningPowerFunction(value)
             }
+            Self::RevenueDifference(miner_id, ideal) => {
+                let (difference, _) = revenue_error_of(miner_id, ideal, output);
+
+                ColumnValue::RevenueDifference(difference)
+            }
+            Self::RevenueRelativeError(miner_id, ideal) => {
+                let (_, error) = revenue_error_of(miner_id, ideal, output);
+
+                ColumnValue::RevenueRelativeError(error)
+            }
+            Self::RevenuePValue(miner_id, ideal) => {
+                let p_value = revenue_p_value_of(
+                    miner_id,
+                    ideal,
+                    std::slice::from_ref(output),
+                );
+
+                ColumnValue::RevenuePValue(p_value)
+            }
+            Self::RevenueSensitivity(miner_id, param) => {
+                let derivative = sensitivity_of(miner_id, param, output);
+
+                ColumnValue::RevenueSensitivity(derivative)
+            }
             Self::MinerRevenue(miner_id) => {
                 let revenue = revenue_of(miner_id, output);
 
                 ColumnValue::MinerRevenue(revenue)
             }
+            Self::MinerBlocksMined(miner_id) => {
+                let num = blocks_mined_by(miner_id, output);
+
+                ColumnValue::MinerBlocksMined(num)
+            }
+            Self::MinerBlocksOnChain(miner_id) => {
+                let num = blocks_on_chain_by(miner_id, output);
+
+                ColumnValue::MinerBlocksOnChain(num)
+            }
+            Self::MinerSubsidyIncome(miner_id) => {
+                let (income, _) = income_of(miner_id, output);
+
+                ColumnValue::MinerSubsidyIncome(income)
+            }
+            Self::MinerFeeIncome(miner_id) => {
+                let (_, income) = income_of(miner_id, output);
+
+                ColumnValue::MinerFeeIncome(income)
+            }
+            Self::MinerUncleIncome(miner_id) => {
+                let (income, _) = uncle_income_of(miner_id, output);
+
+                ColumnValue::MinerUncleIncome(income)
+            }
+            Self::MinerNephewIncome(miner_id) => {
+                let (_, income) = uncle_income_of(miner_id, output);
+
+                ColumnValue::MinerNephewIncome(income)
+            }
+            Self::MinerRewardShare(miner_id) => {
+                let share = reward_share_of(miner_id, output);
+
+                ColumnValue::MinerRewardShare(share)
+            }
             Self::Rounds => {
-                let rounds = output.rounds;
+                let rounds = output.rounds as f64;
 
                 ColumnValue::Rounds(rounds)
             }
             Self::LongestChainLength => {
-                let length = output.longest_chain.len() as f64;
+                let length = output.longest_chain_length as f64;
 
                 ColumnValue::LongestChainLength(length)
             }
+            Self::BlockchainMemory => {
+                let mib = mebibytes(output.blockchain_memory);
+
+                ColumnValue::BlockchainMemory(mib)
+            }
+            Self::ForkCount => {
+                let (count, _, _) = fork_stats_of(output);
+
+                ColumnValue::ForkCount(count)
+            }
+            Self::MeanForkLength => {
+                let (_, mean, _) = fork_stats_of(output);
+
+                ColumnValue::MeanForkLength(mean)
+            }
+            Self::MaxForkLength => {
+                let (_, _, max) = fork_stats_of(output);
+
+                ColumnValue::MaxForkLength(max)
+            }
+            Self::ReorgCount => {
+                let (count, _) = reorg_stats_of(output);
+
+                ColumnValue::ReorgCount(count)
+            }
+            Self::MaxReorgDepth => {
+                let (_, max) = reorg_stats_of(output);
+
+                ColumnValue::MaxReorgDepth(max)
+            }
+            Self::PowerGini => {
+                let (gini, _) = gini_of(output);
+
+                ColumnValue::PowerGini(gini)
+            }
+            Self::RevenueGini => {
+                let (_, gini) = gini_of(output);
+
+                ColumnValue::RevenueGini(gini)
+            }
+            Self::DoubleSpendSuccess(confirmations) => {
+                let rate = double_spend_success_of(*confirmations, output);
+
+                ColumnValue::DoubleSpendSuccess(rate)
+            }
+            Self::MeanInclusionLatency => {
+                let (mean, _) = inclusion_latency_of(output);
+
+                ColumnValue::MeanInclusionLatency(mean)
+            }
+            Self::MedianInclusionLatency => {
+                let (_, median) = inclusion_latency_of(output);
+
+                ColumnValue::MedianInclusionLatency(median)
+            }
+            Self::BlacklistedConfirmed => {
+                let (count, _) = blacklisted_stats_of(output);
+
+                ColumnValue::BlacklistedConfirmed(count)
+            }
+            Self::BlacklistedMeanLatency => {
+                let (_, mean) = blacklisted_stats_of(output);
+
+                ColumnValue::BlacklistedMeanLatency(mean)
+            }
             Self::AverageOf(_) => unreachable!(
                 "never need the single value of the average descriptor column"
             ),
+            Self::Repeat => {
+                unreachable!("repeat index is not stored in simulation output")
+            }
+            Self::CumulativeProbability => {
+                unreachable!("cumulative probability is computed over runs")
+            }
         }
     }
 
     fn get_average_value(
         &self,
-        method: Average,
+        method: &Average,
         data: &[SimulationOutput],
     ) -> ColumnValue {
         match &self {
             Self::AverageOf(_) => return ColumnValue::AverageOf(data.len()),
-            Self::Constant(_)
-            | Self::MinerStrategyName(_)
-            | Self::MiningPower(_)
-            | Self::MiningPowerFunction(_, _)
-            | Self::Rounds => return self.get_value(&data[0]),
-            Self::BlocksPublished => (),
-            Self::MinerRevenue(_) => (),
-            Self::LongestChainLength => (),
+            Self::RevenuePValue(miner_id, ideal) => {
+                let p_value = revenue_p_value_of(miner_id, ideal, data);
+
+                return ColumnValue::RevenuePValue(p_value);
+            }
+            Self::Repeat => unreachable!("repeated runs are never averaged"),
+            Self::CumulativeProbability => {
+                unreachable!("cumulative probability is computed over runs")
+            }
+            _ => (),
+        }
+        if !self.is_averaged() {
+            return self.get_value(&data[0]);
         }
 
         let mut vls: Vec<_> = match &self {
@@ -517,9 +2653,107 @@ impl Column {
                 .iter()
                 .map(|sim_output| revenue_of(miner_id, sim_output))
                 .collect(),
+            Self::MinerBlocksMined(miner_id) => data
+                .iter()
+                .map(|sim_output| blocks_mined_by(miner_id, sim_output))
+                .collect(),
+            Self::MinerBlocksOnChain(miner_id) => data
+                .iter()
+                .map(|sim_output| blocks_on_chain_by(miner_id, sim_output))
+                .collect(),
+            Self::MinerSubsidyIncome(miner_id) => data
+                .iter()
+                .map(|sim_output| income_of(miner_id, sim_output).0)
+                .collect(),
+            Self::MinerFeeIncome(miner_id) => data
+                .iter()
+                .map(|sim_output| income_of(miner_id, sim_output).1)
+                .collect(),
+            Self::MinerUncleIncome(miner_id) => data
+                .iter()
+                .map(|sim_output| uncle_income_of(miner_id, sim_output).0)
+                .collect(),
+            Self::MinerNephewIncome(miner_id) => data
+                .iter()
+                .map(|sim_output| uncle_income_of(miner_id, sim_output).1)
+                .collect(),
+            Self::MinerRewardShare(miner_id) => data
+                .iter()
+                .map(|sim_output| reward_share_of(miner_id, sim_output))
+                .collect(),
+            Self::RevenueDifference(miner_id, ideal) => data
+                .iter()
+                .map(|sim_output| {
+                    revenue_error_of(miner_id, ideal, sim_output).0
+                })
+                .collect(),
+            Self::RevenueRelativeError(miner_id, ideal) => data
+                .iter()
+                .map(|sim_output| {
+                    revenue_error_of(miner_id, ideal, sim_output).1
+                })
+                .collect(),
+            Self::RevenueSensitivity(miner_id, param) => data
+                .iter()
+                .map(|sim_output| sensitivity_of(miner_id, param, sim_output))
+                .collect(),
             Self::LongestChainLength => data
                 .iter()
-                .map(|sim_output| sim_output.longest_chain.len() as f64)
+                .map(|sim_output| sim_output.longest_chain_length as f64)
+                .collect(),
+            Self::BlockchainMemory => data
+                .iter()
+                .map(|sim_output| mebibytes(sim_output.blockchain_memory))
+                .collect(),
+            Self::Rounds => data
+                .iter()
+                .map(|sim_output| sim_output.rounds as f64)
+                .collect(),
+            Self::ForkCount => data
+                .iter()
+                .map(|sim_output| fork_stats_of(sim_output).0)
+                .collect(),
+            Self::MeanForkLength => data
+                .iter()
+                .map(|sim_output| fork_stats_of(sim_output).1)
+                .collect(),
+            Self::MaxForkLength => data
+                .iter()
+                .map(|sim_output| fork_stats_of(sim_output).2)
+                .collect(),
+            Self::ReorgCount => data
+                .iter()
+                .map(|sim_output| reorg_stats_of(sim_output).0)
+                .collect(),
+            Self::MaxReorgDepth => data
+                .iter()
+                .map(|sim_output| reorg_stats_of(sim_output).1)
+                .collect(),
+            Self::RevenueGini => data
+                .iter()
+                .map(|sim_output| gini_of(sim_output).1)
+                .collect(),
+            Self::DoubleSpendSuccess(confirmations) => data
+                .iter()
+                .map(|sim_output| {
+                    double_spend_success_of(*confirmations, sim_output)
+                })
+                .collect(),
+            Self::MeanInclusionLatency => data
+                .iter()
+                .map(|sim_output| inclusion_latency_of(sim_output).0)
+                .collect(),
+            Self::MedianInclusionLatency => data
+                .iter()
+                .map(|sim_output| inclusion_latency_of(sim_output).1)
+                .collect(),
+            Self::BlacklistedConfirmed => data
+                .iter()
+                .map(|sim_output| blacklisted_stats_of(sim_output).0)
+                .collect(),
+            Self::BlacklistedMeanLatency => data
+                .iter()
+                .map(|sim_output| blacklisted_stats_of(sim_output).1)
                 .collect(),
             _ => unreachable!(),
         };
@@ -529,13 +2763,60 @@ impl Column {
             Average::Median => crate::utils::median_of_floats(&mut vls),
             Average::Max => vls.into_iter().reduce(|a, b| a.max(b)).unwrap(),
             Average::Min => vls.into_iter().reduce(|a, b| a.min(b)).unwrap(),
-            Average::None => unreachable!(),
+            Average::StdDev => {
+                let n = vls.len() as f64;
+                let mean = vls.iter().sum::<f64>() / n;
+                let sum_sq: f64 = vls.iter().map(|v| (v - mean).powi(2)).sum();
+
+                (sum_sq / (n - 1.0)).sqrt()
+            }
+            Average::Percentile(p) => {
+                crate::utils::quantile_of_floats(&mut vls, p / 100.0)
+            }
+            Average::None | Average::Quantiles(_) => unreachable!(),
         };
 
         match &self {
             Self::BlocksPublished => ColumnValue::BlocksPublished(avg),
             Self::MinerRevenue(_) => ColumnValue::MinerRevenue(avg),
+            Self::MinerBlocksMined(_) => ColumnValue::MinerBlocksMined(avg),
+            Self::MinerBlocksOnChain(_) => ColumnValue::MinerBlocksOnChain(avg),
+            Self::MinerSubsidyIncome(_) => ColumnValue::MinerSubsidyIncome(avg),
+            Self::MinerFeeIncome(_) => ColumnValue::MinerFeeIncome(avg),
+            Self::MinerUncleIncome(_) => ColumnValue::MinerUncleIncome(avg),
+            Self::MinerNephewIncome(_) => ColumnValue::MinerNephewIncome(avg),
+            Self::MinerRewardShare(_) => ColumnValue::MinerRewardShare(avg),
+            Self::RevenueDifference(_, _) => {
+                ColumnValue::RevenueDifference(avg)
+            }
+            Self::RevenueRelativeError(_, _) => {
+                ColumnValue::RevenueRelativeError(avg)
+            }
+            Self::RevenueSensitivity(_, _) => {
+                ColumnValue::RevenueSensitivity(avg)
+            }
             Self::LongestChainLength => ColumnValue::LongestChainLength(avg),
+            Self::BlockchainMemory => ColumnValue::BlockchainMemory(avg),
+            Self::Rounds => ColumnValue::Rounds(avg),
+            Self::ForkCount => ColumnValue::ForkCount(avg),
+            Self::MeanForkLength => ColumnValue::MeanForkLength(avg),
+            Self::MaxForkLength => ColumnValue::MaxForkLength(avg),
+            Self::ReorgCount => ColumnValue::ReorgCount(avg),
+            Self::MaxReorgDepth => ColumnValue::MaxReorgDepth(avg),
+            Self::RevenueGini => ColumnValue::RevenueGini(avg),
+            Self::DoubleSpendSuccess(_) => ColumnValue::DoubleSpendSuccess(avg),
+            Self::MeanInclusionLatency => {
+                ColumnValue::MeanInclusionLatency(avg)
+            }
+            Self::MedianInclusionLatency => {
+                ColumnValue::MedianInclusionLatency(avg)
+            }
+            Self::BlacklistedConfirmed => {
+                ColumnValue::BlacklistedConfirmed(avg)
+            }
+            Self::BlacklistedMeanLatency => {
+                ColumnValue::BlacklistedMeanLatency(avg)
+            }
             _ => unreachable!(),
         }
     }
@@ -544,13 +2825,20 @@ impl Column {
 impl Display for Column {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
-            Self::AverageOf(method) => match method {
-                Average::Mean => write!(f, "Mean Of"),
-                Average::Median => write!(f, "Median Of"),
-                Average::Max => write!(f, "Max Of"),
-                Average::Min => write!(f, "Min Of"),
-                Average::None => unreachable!(),
-            },
+            Self::AverageOf(methods) => {
+                let labels: Vec<_> =
+                    methods.iter().map(|method| method.label()).collect();
+                write!(f, "{} Of", labels.join("/"))
+            }
+            Self::Repeat => {
+                write!(f, "Repeat")
+            }
+            Self::PowerDistLabel => {
+                write!(f, "Power Distribution")
+            }
+            Self::PowerParam(title) => {
+                write!(f, "{}", title)
+            }
             Self::BlocksPublished => {
                 write!(f, "Blocks Published")
             }
@@ -560,55 +2848,230 @@ impl Display for Column {
             Self::MinerStrategyName(miner_id) => {
                 write!(f, "Miner {} Strategy", miner_id)
             }
+            Self::MinerParam(miner_id, param) => {
+                write!(f, "Miner {} {}", miner_id, param)
+            }
             Self::MiningPower(miner_id) => {
                 write!(f, "Miner {} Power", miner_id)
             }
             Self::MiningPowerFunction(_, func) => {
                 write!(f, "{}", func.name())
             }
+            Self::RevenueDifference(miner_id, ideal) => {
+                write!(f, "Miner {} Revenue - {}", miner_id, ideal.name())
+            }
+            Self::RevenueRelativeError(miner_id, ideal) => {
+                write!(
+                    f,
+                    "Miner {} Revenue Relative Error ({})",
+                    miner_id,
+                    ideal.name()
+                )
+            }
+            Self::RevenuePValue(miner_id, ideal) => {
+                write!(
+                    f,
+                    "Miner {} Revenue p-value ({})",
+                    miner_id,
+                    ideal.name()
+                )
+            }
+            Self::RevenueSensitivity(miner_id, param) => {
+                write!(f, "Miner {} Revenue Sensitivity ({})", miner_id, param)
+            }
             Self::MinerRevenue(miner_id) => {
                 write!(f, "Miner {} Revenue", miner_id)
             }
+            Self::MinerBlocksMined(miner_id) => {
+                write!(f, "Miner {} Blocks Mined", miner_id)
+            }
+            Self::MinerBlocksOnChain(miner_id) => {
+                write!(f, "Miner {} Blocks On Chain", miner_id)
+            }
+            Self::MinerSubsidyIncome(miner_id) => {
+                write!(f, "Miner {} Subsidy Income", miner_id)
+            }
+            Self::MinerFeeIncome(miner_id) => {
+                write!(f, "Miner {} Fee Income", miner_id)
+            }
+            Self::MinerUncleIncome(miner_id) => {
+                write!(f, "Miner {} Uncle Income", miner_id)
+            }
+            Self::MinerNephewIncome(miner_id) => {
+                write!(f, "Miner {} Nephew Income", miner_id)
+            }
+            Self::MinerRewardShare(miner_id) => {
+                write!(f, "Miner {} Reward Share", miner_id)
+            }
             Self::Rounds => {
                 write!(f, "Simulated Rounds")
             }
             Self::LongestChainLength => {
                 write!(f, "Longest Chain Length")
             }
-        }
-    }
-}
-
-impl Display for ColumnValue {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self {
-            Self::AverageOf(repeats) => {
-                write!(f, "{}", repeats)
+            Self::BlockchainMemory => {
+                write!(f, "Blockchain Memory (MiB)")
+            }
+            Self::ForkCount => {
+                write!(f, "Fork Count")
+            }
+            Self::MeanForkLength => {
+                write!(f, "Mean Fork Length")
+            }
+            Self::MaxForkLength => {
+                write!(f, "Max Fork Length")
             }
-            Self::BlocksPublished(num) => {
-                write!(f, "{:.1$}", num, F64_DISPLAY_DIGITS)
+            Self::ReorgCount => {
+                write!(f, "Reorg Count")
             }
-            Self::Constant(value) => {
-                write!(f, "{:.1$}", value, F64_DISPLAY_DIGITS)
+            Self::MaxReorgDepth => {
+                write!(f, "Max Reorg Depth")
             }
-            Self::MinerStrategyName(name) => {
-                write!(f, "{}", name)
+            Self::PowerGini => {
+                write!(f, "Power Gini Coefficient")
             }
-            Self::MiningPower(power) => {
-                write!(f, "{:.1$}", power, F64_DISPLAY_DIGITS)
+            Self::RevenueGini => {
+                write!(f, "Revenue Gini Coefficient")
             }
-            Self::MiningPowerFunction(value) => {
-                write!(f, "{:.1$}", value, F64_DISPLAY_DIGITS)
+            Self::DoubleSpendSuccess(confirmations) => {
+                write!(
+                    f,
+                    "Double-Spend Success ({} Confirmations)",
+                    confirmations
+                )
             }
-            Self::MinerRevenue(revenue) => {
-                write!(f, "{:.1$}", revenue, F64_DISPLAY_DIGITS)
+            Self::MeanInclusionLatency => {
+                write!(f, "Mean Inclusion Latency")
+            }
+            Self::MedianInclusionLatency => {
+                write!(f, "Median Inclusion Latency")
+            }
+            Self::BlacklistedConfirmed => {
+                write!(f, "Blacklisted Txns Confirmed")
+            }
+            Self::BlacklistedMeanLatency => {
+                write!(f, "Blacklisted Txn Mean Latency")
+            }
+            Self::CumulativeProbability => {
+                write!(f, "Cumulative Probability")
+            }
+        }
+    }
+}
+
+impl ColumnValue {
+    /// Returns the numeric value of this column value, or NaN if it is not
+    /// numeric.
+    fn as_f64(&self) -> f64 {
+        match self {
+            Self::MinerStrategyName(_)
+            | Self::PowerDistLabel(_)
+            | Self::Missing => f64::NAN,
+            Self::AverageOf(repeats) | Self::Repeat(repeats) => *repeats as f64,
+            Self::MiningPower(value) => *value,
+            Self::MinerRevenue(value)
+            | Self::MinerBlocksMined(value)
+            | Self::MinerBlocksOnChain(value)
+            | Self::MinerSubsidyIncome(value)
+            | Self::MinerFeeIncome(value)
+            | Self::MinerUncleIncome(value)
+            | Self::MinerNephewIncome(value)
+            | Self::MinerRewardShare(value)
+            | Self::MiningPowerFunction(value)
+            | Self::RevenueDifference(value)
+            | Self::RevenueRelativeError(value)
+            | Self::RevenuePValue(value)
+            | Self::RevenueSensitivity(value)
+            | Self::Constant(value)
+            | Self::Rounds(value)
+            | Self::BlocksPublished(value)
+            | Self::ForkCount(value)
+            | Self::MeanForkLength(value)
+            | Self::MaxForkLength(value)
+            | Self::ReorgCount(value)
+            | Self::MaxReorgDepth(value)
+            | Self::PowerParam(value)
+            | Self::MinerParam(value)
+            | Self::PowerGini(value)
+            | Self::RevenueGini(value)
+            | Self::DoubleSpendSuccess(value)
+            | Self::MeanInclusionLatency(value)
+            | Self::MedianInclusionLatency(value)
+            | Self::BlacklistedConfirmed(value)
+            | Self::BlacklistedMeanLatency(value)
+            | Self::BlockchainMemory(value)
+            | Self::CumulativeProbability(value)
+            | Self::LongestChainLength(value) => *value,
+        }
+    }
+
+    fn to_cell(&self) -> CellValue {
+        match self {
+            Self::MinerStrategyName(name) | Self::PowerDistLabel(name) => {
+                CellValue::Text(name.clone())
+            }
+            Self::Missing => CellValue::Missing,
+            Self::AverageOf(num) | Self::Repeat(num) => {
+                CellValue::Integer(*num)
+            }
+            _ => CellValue::Float(self.as_f64()),
+        }
+    }
+}
+
+impl ColumnValue {
+    /// Returns this value as it is displayed in a results table, with floating
+    /// point values written in the given [`Notation`].
+    fn format(&self, notation: Notation) -> String {
+        match &self {
+            Self::AverageOf(repeats) | Self::Repeat(repeats) => {
+                repeats.to_string()
             }
-            Self::Rounds(rounds) => {
-                write!(f, "{}", rounds)
+            Self::MinerStrategyName(name) | Self::PowerDistLabel(name) => {
+                name.clone()
             }
-            Self::LongestChainLength(length) => {
-                write!(f, "{:.1$}", length, F64_DISPLAY_DIGITS)
+            Self::Missing => String::new(),
+            Self::MinerBlocksMined(num)
+            | Self::MinerBlocksOnChain(num)
+            | Self::ForkCount(num)
+            | Self::MaxForkLength(num)
+            | Self::ReorgCount(num)
+            | Self::MaxReorgDepth(num)
+            | Self::BlacklistedConfirmed(num)
+            | Self::Rounds(num) => {
+                // Counts are only fractional when averaged
+                if num.fract() == 0.0 {
+                    num.to_string()
+                } else {
+                    notation.format(*num)
+                }
             }
+            Self::BlocksPublished(value)
+            | Self::Constant(value)
+            | Self::MiningPower(value)
+            | Self::MiningPowerFunction(value)
+            | Self::RevenueDifference(value)
+            | Self::RevenueRelativeError(value)
+            | Self::RevenuePValue(value)
+            | Self::RevenueSensitivity(value)
+            | Self::MinerRevenue(value)
+            | Self::MinerSubsidyIncome(value)
+            | Self::MinerFeeIncome(value)
+            | Self::MinerUncleIncome(value)
+            | Self::MinerNephewIncome(value)
+            | Self::MinerRewardShare(value)
+            | Self::MeanForkLength(value)
+            | Self::PowerParam(value)
+            | Self::MinerParam(value)
+            | Self::PowerGini(value)
+            | Self::RevenueGini(value)
+            | Self::DoubleSpendSuccess(value)
+            | Self::MeanInclusionLatency(value)
+            | Self::MedianInclusionLatency(value)
+            | Self::BlacklistedMeanLatency(value)
+            | Self::BlockchainMemory(value)
+            | Self::CumulativeProbability(value)
+            | Self::LongestChainLength(value) => notation.format(*value),
         }
     }
 }
@@ -624,6 +3087,62 @@ pub fn selfish_revenue(gamma: f64) -> impl Fn(PowerValue) -> f64 {
     }
 }
 
+/// Lower bound of [`selfish_revenue`] when `gamma` is only known to lie in
+/// the given range, such as `0.0..=1.0`. Can be used as input to
+/// [`ResultsBuilder::mining_power_func`].
+///
+/// # Panics
+/// Panics if the range is empty or not within `[0, 1]`.
+pub fn selfish_revenue_min(
+    gamma: RangeInclusive<f64>,
+) -> impl Fn(PowerValue) -> f64 {
+    let (low, high) = selfish_revenue_ends(gamma);
+
+    move |a: PowerValue| -> f64 { low(a).min(high(a)) }
+}
+
+/// Upper bound of [`selfish_revenue`] when `gamma` is only known to lie in
+/// the given range, such as `0.0..=1.0`. Can be used as input to
+/// [`ResultsBuilder::mining_power_func`].
+///
+/// # Panics
+/// Panics if the range is empty or not within `[0, 1]`.
+pub fn selfish_revenue_max(
+    gamma: RangeInclusive<f64>,
+) -> impl Fn(PowerValue) -> f64 {
+    let (low, high) = selfish_revenue_ends(gamma);
+
+    move |a: PowerValue| -> f64 { low(a).max(high(a)) }
+}
+
+/// Returns [`selfish_revenue`] at each end of a range of `gamma`. Revenue is
+/// linear in `gamma`, so its bounds over the range are at the ends.
+fn selfish_revenue_ends(
+    gamma: RangeInclusive<f64>,
+) -> (impl Fn(PowerValue) -> f64, impl Fn(PowerValue) -> f64) {
+    let (&low, &high) = (gamma.start(), gamma.end());
+    assert!(
+        0.0 <= low && low <= high && high <= 1.0,
+        "gamma range {low}..={high} is not within [0, 1]"
+    );
+
+    (selfish_revenue(low), selfish_revenue(high))
+}
+
+/// Smallest mining power for which [`selfish_revenue`] exceeds the revenue
+/// of honest mining, `(1 - gamma) / (3 - 2 * gamma)`, from Eyal and Sirer's
+/// paper.
+pub fn selfish_threshold(gamma: f64) -> f64 {
+    (1.0 - gamma) / (3.0 - 2.0 * gamma)
+}
+
+/// Revenue of a miner which follows the protocol, which is equal to its
+/// mining power. Can be used as input to
+/// [`ResultsBuilder::mining_power_func`].
+pub fn honest_revenue(a: PowerValue) -> f64 {
+    a
+}
+
 /// Ideal Nothing-At-Stake miner revenue function from Weinberg and Ferrera's
 /// paper. Can be used as input to
 /// [`ResultsBuilder::mining_power_func`].
@@ -633,3 +3152,379 @@ pub fn nsm_revenue(a: PowerValue) -> f64 {
         / (1.0 - a - 2.0 * a.powi(2) + 3.0 * a.powi(4) - 3.0 * a.powi(5)
             + a.powi(6))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, num::NonZeroUsize};
+
+    use crate::{
+        miner::MinerId,
+        power_dist::PowerDistribution,
+        simulation::{MinerStats, SimulationOutput},
+    };
+
+    use super::{
+        Average, ColumnKind, Format, Notation, ResultsBuilder,
+        ResultsMergeError, ResultsSaveError,
+    };
+
+    /// Returns the output of a simulation of honest miners with the given
+    /// mining power values, in which miner `i` has `blocks_on_chain[i - 1]`
+    /// blocks on the longest chain and no blocks anywhere else.
+    fn output(power: &[f64], blocks_on_chain: &[usize]) -> SimulationOutput {
+        let miner_ids = (1..=power.len()).map(MinerId::from);
+        let blocks = blocks_on_chain.iter().sum::<usize>();
+
+        SimulationOutput {
+            blockchain: None,
+            blockchain_memory: 0,
+            blacklisted_latencies: vec![],
+            blocks_by_miner: None,
+            blocks_published: blocks,
+            dag_order: None,
+            double_spends: vec![],
+            fork_lengths: vec![],
+            inclusion_latencies: vec![],
+            longest_chain: None,
+            longest_chain_length: blocks + 1,
+            miner_stats: miner_ids
+                .clone()
+                .zip(blocks_on_chain)
+                .map(|(miner_id, &blocks)| {
+                    let stats = MinerStats {
+                        blocks_mined: blocks,
+                        blocks_published: blocks,
+                        blocks_on_chain: blocks,
+                        ..Default::default()
+                    };
+                    (miner_id, stats)
+                })
+                .collect(),
+            miners: miner_ids.map(|id| (id, "Honest".to_string())).collect(),
+            miner_labels: HashMap::new(),
+            miner_params: HashMap::new(),
+            power_dist: PowerDistribution::SetValues(power.to_vec()),
+            power_dist_label: None,
+            power_param: None,
+            proposers: None,
+            reorg_depths: vec![],
+            revenue_series: None,
+            rounds: blocks,
+            seed: 0,
+            sensitivities: vec![],
+            #[cfg(feature = "timing")]
+            timings: Default::default(),
+            txn_generator: None,
+        }
+    }
+
+    /// Returns a builder over `data`, in which each configuration was run
+    /// `repeated` times.
+    fn results(data: Vec<SimulationOutput>, repeated: usize) -> ResultsBuilder {
+        let num_miners = data[0].miners.len();
+        let repeated = NonZeroUsize::new(repeated).unwrap();
+        ResultsBuilder::new(data, repeated, num_miners, vec![])
+    }
+
+    /// Returns the outputs of four runs of a single miner which last 1, 2, 3,
+    /// and 4 rounds.
+    fn single_miner_runs() -> Vec<SimulationOutput> {
+        (1..=4).map(|blocks| output(&[1.0], &[blocks])).collect()
+    }
+
+    #[test]
+    fn quantiles_split_averaged_columns() {
+        let table = results(single_miner_runs(), 4)
+            .rounds()
+            .average(Average::Quantiles(vec![0.25, 0.5, 1.0]))
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        assert_eq!(
+            table,
+            "Miner 1 Power,Simulated Rounds (P25),Simulated Rounds (P50),\
+             Simulated Rounds (P100),Quantiles Of\n\
+             1.000000,1.750000,2.500000,4,4"
+        );
+    }
+
+    #[test]
+    fn multiple_aggregations_per_column() {
+        let table = results(single_miner_runs(), 4)
+            .averages([Average::Mean, Average::Median, Average::StdDev])
+            .rounds()
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        assert_eq!(
+            table,
+            "Miner 1 Power,Simulated Rounds (Mean),Simulated Rounds (Median),\
+             Simulated Rounds (Std Dev),Mean/Median/Std Dev Of\n\
+             1.000000,2.500000,2.500000,1.290994,4"
+        );
+    }
+
+    #[test]
+    fn delimited_format_quotes_fields() {
+        let mut table = results(vec![output(&[1.0], &[1])], 1)
+            .constant("Gamma, \"Tie\" Rate", 0.5)
+            .format(Format::CSV)
+            .build();
+
+        assert_eq!(
+            table.to_string(),
+            "Miner 1 Power,\"Gamma, \"\"Tie\"\" Rate\"\n1.000000,0.500000"
+        );
+
+        table.set_format(Format::TSV);
+        assert_eq!(
+            table.to_string(),
+            "Miner 1 Power\t\"Gamma, \"\"Tie\"\" Rate\"\n1.000000\t0.500000"
+        );
+
+        table.set_format(Format::Delimited {
+            sep: ';',
+            quote: '\'',
+            header: false,
+        });
+        assert_eq!(table.to_string(), "1.000000;0.500000");
+    }
+
+    #[test]
+    fn column_notation_overrides_table_notation() {
+        let table = results(vec![output(&[1.0], &[1])], 1)
+            .constant("Small", 0.000125)
+            .constant("Large", 2.5)
+            .notation(Notation::Fixed(2))
+            .column_notation("Small", Notation::Scientific(2))
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        assert_eq!(table, "Miner 1 Power,Large,Small\n1.00,2.50,1.25e-4");
+    }
+
+    #[test]
+    fn group_by_aggregates_across_power_dists() {
+        let data = vec![
+            output(&[0.1, 0.4, 0.5], &[2, 1, 1]),
+            output(&[0.1, 0.4, 0.5], &[1, 1, 2]),
+            output(&[0.2, 0.2, 0.6], &[1, 1, 2]),
+            output(&[0.2, 0.2, 0.6], &[1, 0, 3]),
+            output(&[0.4, 0.1, 0.5], &[1, 1, 2]),
+            output(&[0.4, 0.1, 0.5], &[0, 1, 3]),
+        ];
+        let table = results(data, 2)
+            .average(Average::Mean)
+            .mining_power_for(MinerId::from(3))
+            .revenue_for(MinerId::from(3))
+            .group_by("Miner 3 Power")
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        assert_eq!(
+            table,
+            "Miner 3 Power,Miner 3 Revenue,Mean Of\n\
+             0.500000,0.400000,4\n\
+             0.600000,0.500000,2"
+        );
+    }
+
+    #[test]
+    fn save_results_by_extension() {
+        let table = results(vec![output(&[1.0], &[10])], 1)
+            .strategy_names()
+            .rounds()
+            .build();
+
+        let dir = std::env::temp_dir().join(format!(
+            "mining_sim_{}_save_results_by_extension",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let json_path = dir.join("results.json");
+        let md_path = dir.join("results.md");
+
+        table.save(&json_path).unwrap();
+        table.save(&md_path).unwrap();
+        let json = std::fs::read_to_string(&json_path).unwrap();
+        let md = std::fs::read_to_string(&md_path).unwrap();
+        let unknown = table.save(dir.join("results.xyz"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            json,
+            "[\n  {\"Miner 1 Strategy\": \"Honest\", \"Miner 1 Power\": 1.000000, \
+             \"Simulated Rounds\": 10}\n]\n"
+        );
+        assert_eq!(
+            md,
+            "| Miner 1 Strategy | Miner 1 Power | Simulated Rounds |\n\
+             | --- | --- | --- |\n\
+             | Honest | 1.000000 | 10 |\n"
+        );
+        assert!(matches!(
+            unknown,
+            Err(ResultsSaveError::UnknownExtension(_))
+        ));
+    }
+
+    #[test]
+    fn merge_and_concat_results() {
+        let two_miners = || results(vec![output(&[0.5, 0.5], &[1, 3])], 1);
+        let three_miners = results(vec![output(&[0.2, 0.3, 0.5], &[1; 3])], 1);
+
+        assert!(matches!(
+            two_miners().merge(three_miners).unwrap_err(),
+            ResultsMergeError::MinerCountMismatch(2, 3)
+        ));
+
+        let repeated = results(vec![output(&[0.5, 0.5], &[1, 3]); 2], 2);
+        assert!(matches!(
+            two_miners().merge(repeated).unwrap_err(),
+            ResultsMergeError::RepeatMismatch(..)
+        ));
+
+        let other = results(vec![output(&[0.25, 0.75], &[0, 4])], 1).revenue();
+        let merged = two_miners()
+            .merge(other)
+            .unwrap()
+            .format(Format::CSV)
+            .build()
+            .to_string();
+        assert_eq!(
+            merged,
+            "Miner 1 Power,Miner 2 Power,Miner 1 Revenue,Miner 2 Revenue\n\
+             0.500000,0.500000,0.200000,0.600000\n\
+             0.250000,0.750000,0.000000,0.800000"
+        );
+
+        let one_miner = results(vec![output(&[1.0], &[2])], 1);
+        let table = two_miners()
+            .format(Format::CSV)
+            .build()
+            .concat(one_miner.strategy_names().build())
+            .to_string();
+        assert_eq!(
+            table,
+            "Miner 1 Power,Miner 2 Power,Miner 1 Strategy\n\
+             0.500000,0.500000,\n\
+             1.000000,,Honest"
+        );
+    }
+
+    #[test]
+    fn revenue_histogram_counts_repeats() {
+        let mut data: Vec<_> = [[0, 3], [1, 2], [2, 1], [3, 0]]
+            .iter()
+            .map(|blocks| output(&[0.5, 0.5], blocks))
+            .collect();
+        data.extend(vec![output(&[0.2, 0.8], &[1, 3]); 4]);
+        let results = results(data, 4);
+
+        assert_eq!(results.num_configs(), 2);
+        assert!(results.revenue_histogram(MinerId::from(1), 2, 3).is_none());
+
+        let histogram =
+            results.revenue_histogram(MinerId::from(1), 0, 3).unwrap();
+        assert_eq!(histogram.counts, [1, 1, 2]);
+        assert_eq!(histogram.edges, [0.0, 0.25, 0.5, 0.75]);
+
+        let histogram =
+            results.revenue_histogram(MinerId::from(1), 1, 3).unwrap();
+        assert_eq!(histogram.counts, [4, 0, 0]);
+        assert_eq!(histogram.edges, [0.2; 4]);
+    }
+
+    #[test]
+    fn revenue_ecdf_per_strategy() {
+        let selfish = |blocks: &[usize]| {
+            let mut output = output(&[0.5, 0.5], blocks);
+            output
+                .miners
+                .insert(MinerId::from(2), "Selfish".to_string());
+            output
+        };
+        let data = vec![
+            selfish(&[2, 2]),
+            selfish(&[1, 3]),
+            selfish(&[3, 1]),
+            output(&[0.5, 0.5], &[2, 2]),
+            output(&[0.5, 0.5], &[3, 1]),
+            output(&[0.5, 0.5], &[2, 2]),
+        ];
+        let mut ecdf = results(data, 3)
+            .strategy_names()
+            .revenue()
+            .format(Format::CSV)
+            .ecdf("Miner 2 Revenue");
+
+        assert_eq!(
+            ecdf.to_string(),
+            "Miner 1 Strategy,Miner 2 Strategy,Miner 1 Power,Miner 2 Power,\
+             Miner 2 Revenue,Cumulative Probability\n\
+             Honest,Selfish,0.500000,0.500000,0.200000,0.333333\n\
+             Honest,Selfish,0.500000,0.500000,0.400000,0.666667\n\
+             Honest,Selfish,0.500000,0.500000,0.600000,1.000000\n\
+             Honest,Honest,0.500000,0.500000,0.200000,0.333333\n\
+             Honest,Honest,0.500000,0.500000,0.400000,1.000000"
+        );
+
+        ecdf.set_format(Format::JSON);
+        assert!(ecdf.to_string().contains("\"Cumulative Probability\""));
+    }
+
+    #[test]
+    fn revenue_t_test_p_values() {
+        let data = vec![
+            output(&[0.6, 0.4], &[2, 2]),
+            output(&[0.6, 0.4], &[3, 1]),
+            output(&[0.6, 0.4], &[4, 0]),
+        ];
+        let results = results(data, 3)
+            .format(Format::CSV)
+            .revenue_t_test(MinerId::from(1), "Power", |a| a)
+            .revenue_t_test(MinerId::from(1), "Ninety", |_| 0.9);
+
+        let table = results.clone().average(Average::Mean).build();
+        let row = table.rows().next().unwrap();
+        let p_values: Vec<_> = row
+            .into_iter()
+            .filter(|(kind, _)| {
+                *kind == ColumnKind::RevenuePValue(MinerId::from(1))
+            })
+            .map(|(_, cell)| cell.as_f64().unwrap())
+            .collect();
+
+        // Columns are ordered by title. Revenues of 0.4, 0.6, and 0.8 have
+        // mean 0.6 and standard error 0.2 / sqrt(3), so the test against 0.9
+        // has t = -1.5 * sqrt(3) with 2 degrees of freedom, for which
+        // p = 1 - |t| / sqrt(2 + t^2).
+        let t = 1.5 * 3f64.sqrt();
+        assert_eq!(p_values.len(), 2);
+        assert!((p_values[0] - (1.0 - t / (2.0 + t * t).sqrt())).abs() < 1e-9);
+        assert!((p_values[1] - 1.0).abs() < 1e-9);
+
+        let raw = results.build().to_string();
+        assert!(raw.lines().nth(1).unwrap().ends_with("NaN,NaN"));
+    }
+
+    #[test]
+    fn gini_coefficients() {
+        let table = results(vec![output(&[0.2, 0.8], &[3, 1])], 1)
+            .gini()
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        assert_eq!(
+            table,
+            "Miner 1 Power,Miner 2 Power,Power Gini Coefficient,\
+             Revenue Gini Coefficient\n\
+             0.200000,0.800000,0.300000,0.250000"
+        );
+    }
+}
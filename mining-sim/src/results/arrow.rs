@@ -0,0 +1,71 @@
+//! Conversion of results tables into Arrow record batches
+
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, Float64Array, RecordBatch, StringArray, UInt64Array,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+
+use crate::results::{Column, ColumnValue, ResultsTable};
+
+impl ResultsTable {
+    /// Convert this table into an Arrow [`RecordBatch`] with one field per
+    /// column, named by the column's title. Strategy names and power
+    /// distribution labels are stored as UTF-8 strings, repeat indices and the
+    /// number of averaged simulations as unsigned integers, and all other
+    /// values as 64-bit floats. Cells missing from a table made by
+    /// [`ResultsTable::concat`] are null.
+    ///
+    /// Requires the `arrow` feature.
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let mut fields = Vec::with_capacity(self.columns.len());
+        let mut arrays: Vec<ArrayRef> = Vec::with_capacity(self.columns.len());
+
+        for (i, col) in self.columns.iter().enumerate() {
+            let values = self.rows.iter().map(|row| &row[i]);
+
+            let (data_type, array): (_, ArrayRef) = match col.column {
+                Column::MinerStrategyName(_) | Column::PowerDistLabel => {
+                    let names = values.map(|val| match val {
+                        ColumnValue::MinerStrategyName(name)
+                        | ColumnValue::PowerDistLabel(name) => {
+                            Some(name.as_str())
+                        }
+                        ColumnValue::Missing => None,
+                        _ => unreachable!(),
+                    });
+
+                    (DataType::Utf8, Arc::new(StringArray::from_iter(names)))
+                }
+                Column::AverageOf(_) | Column::Repeat => {
+                    let repeats = values.map(|val| match val {
+                        ColumnValue::AverageOf(repeats)
+                        | ColumnValue::Repeat(repeats) => Some(*repeats as u64),
+                        ColumnValue::Missing => None,
+                        _ => unreachable!(),
+                    });
+
+                    (
+                        DataType::UInt64,
+                        Arc::new(UInt64Array::from_iter(repeats)),
+                    )
+                }
+                _ => {
+                    let nums = values.map(|val| match val {
+                        ColumnValue::Missing => None,
+                        _ => Some(val.as_f64()),
+                    });
+
+                    (DataType::Float64, Arc::new(Float64Array::from_iter(nums)))
+                }
+            };
+
+            let nullable = array.null_count() > 0;
+            fields.push(Field::new(col.title.clone(), data_type, nullable));
+            arrays.push(array);
+        }
+
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+    }
+}
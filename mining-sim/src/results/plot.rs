@@ -0,0 +1,300 @@
+/*!
+Render charts of simulation results
+
+Requires the `plots` feature.
+
+## Examples
+
+Plotting the revenue of a selfish miner against its mining power, with the
+ideal revenue curve overlaid:
+
+```no_run
+use mining_sim::prelude::*;
+use mining_sim::results::plot::RevenuePlot;
+
+let sim = SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(Selfish::new())
+    .miner_power_iter(MinerId::from(2), (0..=50).percent())
+    .repeat_all(10)
+    .build()
+    .unwrap();
+
+let results_builder = sim.run_all().unwrap();
+
+RevenuePlot::new(MinerId::from(2))
+    .caption("Selfish Mining")
+    .curve("Ideal Revenue", selfish_revenue(0.0))
+    .save(&results_builder, "selfish_revenue.svg")
+    .unwrap();
+```
+*/
+
+use std::path::{Path, PathBuf};
+
+use plotters::{
+    coord::Shift,
+    prelude::{
+        BitMapBackend, ChartBuilder, Circle, DrawingArea, DrawingBackend,
+        IntoDrawingArea, LineSeries, Palette, Palette99, PathElement,
+        SVGBackend, SeriesLabelPosition, BLACK, WHITE,
+    },
+    style::Color,
+};
+
+use crate::{
+    miner::MinerId,
+    power_dist::PowerValue,
+    results::{Average, Column, ResultsBuilder},
+    utils::{wrap, WrapFunc},
+};
+
+/// Number of points used to draw each closed-form curve.
+const CURVE_POINTS: usize = 200;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PlotError {
+    #[error("cannot infer image format from file path {0:?}")]
+    UnknownExtension(PathBuf),
+    #[error("no simulation results to plot")]
+    NoData,
+    #[error("could not draw plot: {0}")]
+    DrawingError(String),
+}
+
+/// Line chart of a miner's revenue against its mining power. Each lineup of
+/// miners given to the
+/// [`SimulationBuilder`](crate::simulation::SimulationBuilder) is drawn as a
+/// separate series, labelled with the plotted miner's strategy name.
+#[derive(Debug, Clone)]
+pub struct RevenuePlot {
+    average: Average,
+    caption: String,
+    curves: Vec<WrapFunc<PowerValue, f64>>,
+    miner_id: MinerId,
+    size: (u32, u32),
+}
+
+impl RevenuePlot {
+    /// Create a new [`RevenuePlot`] for the miner with ID `miner_id`.
+    pub fn new(miner_id: MinerId) -> Self {
+        Self {
+            miner_id,
+            average: Average::Mean,
+            caption: String::new(),
+            curves: vec![],
+            size: (800, 600),
+        }
+    }
+
+    /// Average the revenue of repeated simulations using the given
+    /// [`Average`]. [`Average::None`] draws every repeat as a separate point,
+    /// and [`Average::Quantiles`] draws one line per quantile. Defaults to
+    /// [`Average::Mean`].
+    pub fn average(mut self, average: Average) -> Self {
+        self.average = average;
+
+        self
+    }
+
+    /// Set the caption drawn above the chart.
+    pub fn caption<T: Into<String>>(mut self, caption: T) -> Self {
+        self.caption = caption.into();
+
+        self
+    }
+
+    /// Overlay the curve of `func` over the range of plotted mining power
+    /// values, such as [`selfish_revenue`](crate::results::selfish_revenue).
+    pub fn curve<T, F>(mut self, title: T, func: F) -> Self
+    where
+        T: Into<String>,
+        F: Fn(PowerValue) -> f64 + Send + Sync + 'static,
+    {
+        self.curves.push(wrap!(title, func));
+
+        self
+    }
+
+    /// Set the size of the rendered image in pixels. Defaults to 800x600.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = (width, height);
+
+        self
+    }
+
+    /// Render the chart to the file at `path`. The image format is chosen
+    /// from the file extension, which must be `svg` or `png`.
+    pub fn save<P: AsRef<Path>>(
+        &self,
+        results: &ResultsBuilder,
+        path: P,
+    ) -> Result<(), PlotError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("svg") => {
+                let root = SVGBackend::new(path, self.size).into_drawing_area();
+                self.draw(results, &root)
+            }
+            Some("png") => {
+                let root =
+                    BitMapBackend::new(path, self.size).into_drawing_area();
+                self.draw(results, &root)
+            }
+            _ => Err(PlotError::UnknownExtension(path.to_path_buf())),
+        }
+    }
+
+    /// Returns the labelled series of (mining power, revenue) points to plot.
+    fn series(
+        &self,
+        results: &ResultsBuilder,
+    ) -> Vec<(String, Vec<(f64, f64)>)> {
+        let column = Column::MinerRevenue(self.miner_id);
+        let averages = self.average.split();
+        let mut series: Vec<(String, Vec<(f64, f64)>)> = vec![];
+
+        for config in results.data.chunks(results.repeated.get()) {
+            let first = &config[0];
+            let num_miners = first.miners.len();
+            let Ok(power) =
+                first.power_dist.power_of(self.miner_id, num_miners)
+            else {
+                continue;
+            };
+            let name = first.miners.get(&self.miner_id).cloned();
+            let name =
+                name.unwrap_or_else(|| format!("Miner {}", self.miner_id));
+
+            for average in averages.iter() {
+                let label = match averages.len() {
+                    1 => name.clone(),
+                    _ => format!("{} ({})", name, average.label()),
+                };
+                let points: Vec<_> = match average {
+                    Average::None => config
+                        .iter()
+                        .map(|output| {
+                            let revenue = column.get_value(output).as_f64();
+                            (power, revenue)
+                        })
+                        .collect(),
+                    _ => {
+                        let revenue =
+                            column.get_average_value(average, config).as_f64();
+                        vec![(power, revenue)]
+                    }
+                };
+
+                // Lineups are contiguous in the results data
+                match series.iter_mut().rev().find(|(l, _)| *l == label) {
+                    Some((_, existing)) => existing.extend(points),
+                    None => series.push((label, points)),
+                }
+            }
+        }
+
+        for (_, points) in series.iter_mut() {
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+
+        series
+    }
+
+    fn draw<DB: DrawingBackend>(
+        &self,
+        results: &ResultsBuilder,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<(), PlotError> {
+        let series = self.series(results);
+        let powers = series.iter().flat_map(|(_, points)| points);
+        let min_power = powers.clone().map(|p| p.0).reduce(f64::min);
+        let max_power = powers.map(|p| p.0).reduce(f64::max);
+        let (Some(min_power), Some(max_power)) = (min_power, max_power) else {
+            return Err(PlotError::NoData);
+        };
+
+        let err =
+            |e: &dyn std::fmt::Display| PlotError::DrawingError(e.to_string());
+
+        root.fill(&WHITE).map_err(|e| err(&e))?;
+
+        let mut chart = ChartBuilder::on(root)
+            .caption(&self.caption, ("sans-serif", 24))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(
+                min_power..max_power.max(min_power + 1e-9),
+                0.0..1.0,
+            )
+            .map_err(|e| err(&e))?;
+
+        let miner = ResultsBuilder::miner_labels(&results.data)
+            .remove(&self.miner_id)
+            .unwrap_or_else(|| format!("Miner {}", self.miner_id));
+        chart
+            .configure_mesh()
+            .x_desc(format!("{} Power", miner))
+            .y_desc(format!("{} Revenue", miner))
+            .draw()
+            .map_err(|e| err(&e))?;
+
+        let mut color_index = 0;
+        let mut next_color = || {
+            color_index += 1;
+            Palette99::pick(color_index - 1).to_rgba()
+        };
+
+        for (label, points) in series {
+            let color = next_color();
+            match self.average {
+                Average::None => chart.draw_series(
+                    points
+                        .into_iter()
+                        .map(|point| Circle::new(point, 2, color.filled())),
+                ),
+                _ => chart.draw_series(LineSeries::new(points, color)),
+            }
+            .map_err(|e| err(&e))?
+            .label(label)
+            .legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color)
+            });
+        }
+
+        for curve in self.curves.iter() {
+            let color = next_color();
+            let step = (max_power - min_power) / (CURVE_POINTS - 1) as f64;
+            let points = (0..CURVE_POINTS).map(|i| {
+                let power = min_power + step * i as f64;
+                (power, curve.call(power))
+            });
+
+            chart
+                .draw_series(LineSeries::new(points, color.stroke_width(2)))
+                .map_err(|e| err(&e))?
+                .label(curve.name())
+                .legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], color)
+                });
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(|e| err(&e))?;
+
+        root.present().map_err(|e| err(&e))?;
+
+        Ok(())
+    }
+}
@@ -3,43 +3,152 @@ Building simulations, running them, and analyzing the resulting data
 */
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
-use rand::distributions::{Distribution, WeightedError, WeightedIndex};
-#[cfg(feature = "rayon")]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use web_time::Instant;
+
+use rand::{
+    distributions::{Distribution, WeightedError, WeightedIndex},
+    rngs::StdRng,
+    Rng, RngCore, SeedableRng,
+};
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
 use rayon::prelude::*;
 
+#[cfg(all(feature = "tui", not(target_arch = "wasm32")))]
+pub mod dashboard;
+pub mod events;
+pub mod merge;
+pub mod snapshot;
+pub mod threshold;
+
 use crate::{
-    blockchain::{BlockId, BlockPublishingError, Blockchain},
-    miner::{Action, Miner, MinerId},
+    blockchain::{
+        Block, BlockHasher, BlockId, BlockPublishingError, BlockSet,
+        Blockchain, BlockchainPool,
+    },
+    dag::{BlockDag, DagError},
+    mempool::{DoubleSpendOutcome, GeneratorConfig, Mempool, MempoolError},
+    miner::{Action, Miner, MinerId, MinerPool},
     power_dist::{PowerDistribution, PowerDistributionError, PowerValue},
-    results::ResultsBuilder,
+    results::{revenue_of, ResultsBuilder, SkippedConfig},
+    stake::{self, ProofOfStake},
+    tie_breaker::TieBreaker,
+    uncle::UncleRewards,
+    utils::{wrap, WrapFunc},
+    view::{ViewTracker, Visibility},
 };
 
+use events::{EventBus, EventSource, SimulationEvent, Subscriber};
+
+/// Creates the random number generator used by a simulation from its seed.
+type RngFactory = WrapFunc<u64, Box<dyn RngCore + Send>>;
+
+/// Called with the index of a simulation's configuration and its output when
+/// the simulation finishes. See [`SimulationGroup::run_all_observed`].
+type Observer<'a> = dyn Fn(usize, &SimulationOutput) + Sync + 'a;
+
 /// Builds up a set of simulations based on the configuration parameters.
 #[derive(Debug, Default)]
 pub struct SimulationBuilder {
+    block_capacity: Option<usize>,
+    block_subsidy: Option<f64>,
     blockchain: Option<Blockchain>,
+    common_random_numbers: bool,
+    default_tie_breaker: Option<TieBreaker>,
+    events: EventBus,
+    finality: Option<usize>,
+    gamma: Option<f64>,
+    ghostdag: Option<usize>,
     include_blockchain: bool,
+    lightweight_output: bool,
+    mempool: Option<Mempool>,
+    power_dist_labels: BTreeMap<usize, String>,
+    power_params: BTreeMap<usize, (String, f64)>,
     power_dists: Vec<PowerDistribution>,
+    proof_of_stake: Option<ProofOfStake>,
+    record_proposers: bool,
     repeat_all: Option<NonZeroUsize>,
+    replay_proposers: Option<Vec<MinerId>>,
+    revenue_window: Option<(usize, usize)>,
+    rng: Option<RngFactory>,
     rounds: Option<NonZeroUsize>,
-    miners: Vec<Box<dyn Miner>>,
+    seed: Option<u64>,
+    sensitivities: Vec<SensitivityConfig>,
+    target_chain_length: Option<NonZeroUsize>,
+    track_children: Option<bool>,
+    uncle_rewards: Option<UncleRewards>,
+    visibility: BTreeMap<MinerId, Visibility>,
+    lineups: Vec<Vec<Box<dyn Miner>>>,
+    /// Label of each miner added with [`SimulationBuilder::add_miner_named`],
+    /// by lineup.
+    miner_labels: Vec<BTreeMap<MinerId, String>>,
     curr_miner_id: MinerId,
+    time_budget: Option<Duration>,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum SimulationBuildError {
     #[error("no miners were added")]
     NoMinersGiven,
+    #[error("no miners were added to lineup {0}")]
+    EmptyLineup(usize),
+    #[error("lineup {lineup} has {found} miners, expected {expected}")]
+    LineupSizeMismatch {
+        lineup: usize,
+        expected: usize,
+        found: usize,
+    },
     #[error("number of simulation rounds must be greater than 0")]
     ZeroRounds,
+    #[error("target chain length requires a number of rounds as a cap")]
+    UncappedTargetChainLength,
     #[error("cannot repeat simulations 0 times")]
     ZeroRepeats,
+    #[error("gamma value {0} is not in the range 0.0..=1.0")]
+    BadGamma(f64),
+    #[error("block subsidy {0} is not a finite non-negative number")]
+    BadBlockSubsidy(f64),
+    #[error("replayed proposer {0} is not a miner in this simulation")]
+    ReplayBadMinerId(MinerId),
+    #[error("cannot set the visibility of {0}, which is not a miner in this simulation")]
+    VisibilityBadMinerId(MinerId),
+    #[error("more than one miner in lineup {lineup} is labeled {label:?}")]
+    DuplicateMinerLabel { lineup: usize, label: String },
+    #[error("revenue window size and step must be greater than 0")]
+    ZeroRevenueWindow,
+    #[error("cannot estimate the sensitivity of {0}, which is not a miner in this simulation")]
+    SensitivityBadMinerId(MinerId),
+    #[error("sensitivity step {0} is not a finite positive number")]
+    BadSensitivityStep(f64),
+    #[error("miner {miner} in lineup {lineup} has no parameter {param:?}")]
+    SensitivityUnknownParam {
+        lineup: usize,
+        miner: MinerId,
+        param: String,
+    },
     #[error("invalid mining power distribution")]
     PowerDistributionError(#[from] PowerDistributionError),
+    #[error("invalid transaction arrival process or fee distribution")]
+    MempoolError(#[from] MempoolError),
+    #[error("proof-of-stake slot capacity must be greater than 0")]
+    ZeroSlotCapacity,
+    #[error("slashing fraction {0} is not in the range 0.0..=1.0")]
+    BadSlashing(f64),
+    #[error("uncle reward fraction {0} is not a finite non-negative number")]
+    BadUncleReward(f64),
+    #[error("finality checkpoint interval must be greater than 0")]
+    ZeroFinalityInterval,
 }
 
 impl SimulationBuilder {
@@ -49,7 +158,52 @@ impl SimulationBuilder {
     }
 
     /// Add `miner` to the simulation.
-    pub fn add_miner<M: Miner + 'static>(mut self, mut miner: M) -> Self {
+    pub fn add_miner<M: Miner + 'static>(self, miner: M) -> Self {
+        self.add_boxed_miner(Box::new(miner))
+    }
+
+    /// Add `miner` to the simulation, labeled `label` in place of "Miner `X`"
+    /// in the titles of [`ResultsBuilder`] columns, e.g. "AttackerPool
+    /// Revenue". The label is recorded in
+    /// [`SimulationOutput::miner_labels`].
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let table = SimulationBuilder::new()
+    ///     .add_miner_named("HonestPool", Honest::new())
+    ///     .add_miner_named("AttackerPool", Selfish::new())
+    ///     .rounds(100)
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all()
+    ///     .unwrap()
+    ///     .all()
+    ///     .build();
+    ///
+    /// assert!(table.titles().any(|title| title == "AttackerPool Revenue"));
+    /// ```
+    pub fn add_miner_named<T, M>(self, label: T, miner: M) -> Self
+    where
+        T: Into<String>,
+        M: Miner + 'static,
+    {
+        let miner_id = self.curr_miner_id;
+        let mut builder = self.add_miner(miner);
+
+        let lineup = builder.lineups.len() - 1;
+        if builder.miner_labels.len() <= lineup {
+            builder.miner_labels.resize_with(lineup + 1, BTreeMap::new);
+        }
+        builder.miner_labels[lineup].insert(miner_id, label.into());
+
+        builder
+    }
+
+    /// Add a boxed `miner` to the simulation, such as a miner created by
+    /// [`registry::create`](crate::miner::registry::create).
+    pub fn add_boxed_miner(mut self, mut miner: Box<dyn Miner>) -> Self {
         miner.set_id(self.curr_miner_id);
 
         assert_eq!(
@@ -59,12 +213,47 @@ impl SimulationBuilder {
             miner.name(),
         );
 
-        self.miners.push(Box::new(miner));
+        match self.lineups.last_mut() {
+            Some(lineup) => lineup.push(miner),
+            None => self.lineups.push(vec![miner]),
+        }
         self.curr_miner_id.0 += 1;
 
         self
     }
 
+    /// Start a new lineup of miners, so that a single [`SimulationGroup`] can
+    /// compare different sets of strategies.
+    ///
+    /// Miners added after this call belong to the new lineup, and are assigned
+    /// [`MinerId`]s starting again from 1. Every configured simulation is run
+    /// once for each lineup, and all lineups must contain the same number of
+    /// miners.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let sim = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Selfish::new())
+    ///     .next_lineup()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(NDeficit::new(1))
+    ///     .miner_power(MinerId::from(2), 0.3)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn next_lineup(mut self) -> Self {
+        if self.lineups.is_empty() {
+            self.lineups.push(vec![]);
+        }
+        self.lineups.push(vec![]);
+        self.curr_miner_id = MinerId::default();
+
+        self
+    }
+
     /// Include each simulation's full [`Blockchain`] in its corresponding
     /// [`SimulationOutput`].
     ///
@@ -82,6 +271,97 @@ impl SimulationBuilder {
         self
     }
 
+    /// Include the sequence of block proposers chosen in each round of each
+    /// simulation in its corresponding [`SimulationOutput`], so that it can
+    /// be replayed later using [`SimulationBuilder::replay_proposers`].
+    pub fn record_proposers(mut self) -> Self {
+        self.record_proposers = true;
+
+        self
+    }
+
+    /// Deliver the [`SimulationEvent`](events::SimulationEvent)s of every
+    /// simulation to `subscriber`, which may be any thread-safe closure
+    /// taking an [`EventSource`](events::EventSource) and an event. See the
+    /// [`events`] module.
+    pub fn subscribe<S: Subscriber + 'static>(mut self, subscriber: S) -> Self {
+        self.events.subscribe(Arc::new(subscriber));
+
+        self
+    }
+
+    /// Use `proposers` as the block proposer of each round, instead of
+    /// sampling proposers from the configured mining power distributions.
+    /// The number of simulation rounds is set to the length of `proposers`.
+    ///
+    /// Replaying a recorded sequence against a different lineup of miners
+    /// gives a paired comparison of strategies, with the same luck in each
+    /// run. Mining power distributions are still reported in the results.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let recorded = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Selfish::new())
+    ///     .miner_power(MinerId::from(2), 0.3)
+    ///     .rounds(1000)
+    ///     .record_proposers()
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all()
+    ///     .unwrap()
+    ///     .data();
+    ///
+    /// let proposers = recorded[0].proposers.clone().unwrap();
+    ///
+    /// let replayed = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(NDeficit::new(1))
+    ///     .miner_power(MinerId::from(2), 0.3)
+    ///     .replay_proposers(proposers)
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all()
+    ///     .unwrap();
+    /// ```
+    pub fn replay_proposers<I>(mut self, proposers: I) -> Self
+    where
+        I: IntoIterator<Item = MinerId>,
+    {
+        self.replay_proposers = Some(proposers.into_iter().collect());
+
+        self
+    }
+
+    /// Compute the revenue of each miner over sliding windows of `window`
+    /// rounds, with consecutive windows starting `step` rounds apart, and
+    /// include the resulting [`RevenueSeries`] in each [`SimulationOutput`].
+    ///
+    /// Useful for analyzing the convergence and stationarity of strategies.
+    pub fn revenue_series(mut self, window: usize, step: usize) -> Self {
+        self.revenue_window = Some((window, step));
+
+        self
+    }
+
+    /// Only keep derived statistics in each [`SimulationOutput`], such as
+    /// [`SimulationOutput::miner_stats`], and leave out the per-block data in
+    /// [`SimulationOutput::blocks_by_miner`] and
+    /// [`SimulationOutput::longest_chain`].
+    ///
+    /// # Usage
+    /// For long simulations, per-block data dominates the memory usage of
+    /// simulation outputs. All columns of
+    /// [`ResultsTable`](crate::results::ResultsTable) are computed from derived
+    /// statistics, so this option does not affect results tables.
+    pub fn lightweight_output(mut self) -> Self {
+        self.lightweight_output = true;
+
+        self
+    }
+
     /// Run each configured simulation `num` times.
     pub fn repeat_all(mut self, num: usize) -> Self {
         self.repeat_all = NonZeroUsize::new(num);
@@ -89,6 +369,40 @@ impl SimulationBuilder {
         self
     }
 
+    /// Limit the total [`size`](crate::transaction::Transaction::size) of the
+    /// transactions in each published block to `capacity`, which is passed to
+    /// [`Miner::select_txns`] when filling blocks from a [`Mempool`].
+    /// Publishing a block over capacity ends the simulation with
+    /// [`SimulationError::BlockOverCapacity`]. Blocks are unlimited by
+    /// default.
+    pub fn block_capacity(mut self, capacity: usize) -> Self {
+        self.block_capacity = Some(capacity);
+
+        self
+    }
+
+    /// Set the subsidy paid to the miner of each block on the longest chain,
+    /// in the same units as transaction fees. Defaults to 1.0, so that fees
+    /// are measured in block subsidies. See
+    /// [`MinerStats::subsidy_income`] and [`MinerStats::fee_income`].
+    pub fn block_subsidy(mut self, subsidy: f64) -> Self {
+        self.block_subsidy = Some(subsidy);
+
+        self
+    }
+
+    /// Restrict the blocks seen by the miner with ID `miner_id` according to
+    /// `visibility`. Miners see every block by default. See [`Visibility`].
+    pub fn visibility(
+        mut self,
+        miner_id: MinerId,
+        visibility: Visibility,
+    ) -> Self {
+        self.visibility.insert(miner_id, visibility);
+
+        self
+    }
+
     /// Set the initial blockchain state used in the simulation.
     /// [`Blockchain::default`] is used otherwise.
     pub fn blockchain(mut self, chain: Blockchain) -> Self {
@@ -97,6 +411,287 @@ impl SimulationBuilder {
         self
     }
 
+    /// Set whether simulation blockchains fill in
+    /// [`BlockData::children`](crate::blockchain::BlockData::children). See
+    /// [`Blockchain::track_children`]. By default, children are tracked if
+    /// the initial [blockchain](SimulationBuilder::blockchain) tracks them,
+    /// which new blockchains do if the `block-children` feature is enabled.
+    pub fn track_children(mut self, track: bool) -> Self {
+        self.track_children = Some(track);
+
+        self
+    }
+
+    /// Set the propagation advantage `gamma` described by
+    /// [Eyal and Sirer](https://doi.org/10.48550/arXiv.1311.0243): the
+    /// fraction of honest mining power which mines on the attacker's block
+    /// during a race at the tip of the longest chain. When more than two
+    /// blocks are tied, `gamma` is the fraction which mines on any block other
+    /// than the one published first.
+    ///
+    /// This is applied at the network level, by giving every miner which was
+    /// not created with an explicit [`TieBreaker`] (such as
+    /// [`Honest::new`](crate::miner::honest::Honest::new)) the tie breaker
+    /// [`TieBreaker::FavorLatestProb`]`(gamma)`. See
+    /// [`Miner::set_default_tie_breaker`] for details.
+    pub fn gamma(mut self, gamma: f64) -> Self {
+        self.gamma = Some(gamma);
+
+        self
+    }
+
+    /// Give every miner which was not created with an explicit
+    /// [`TieBreaker`] the tie breaker `tie_breaker`, without constructing
+    /// each honest miner with
+    /// [`Honest::with_tie_breaker`](crate::miner::honest::Honest::with_tie_breaker).
+    /// See [`Miner::set_default_tie_breaker`] for details.
+    ///
+    /// Takes precedence over [`SimulationBuilder::gamma`] if both are set.
+    pub fn default_tie_breaker(mut self, tie_breaker: TieBreaker) -> Self {
+        self.default_tie_breaker = Some(tie_breaker);
+
+        self
+    }
+
+    /// Generate transactions during each simulation using a copy of
+    /// `mempool`, so that published blocks contain transactions. See
+    /// [`Mempool`] for details.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let mempool = Mempool::new(
+    ///     ArrivalProcess::Poisson(3.0),
+    ///     FeeDistribution::Exponential(0.1),
+    /// );
+    /// let sim = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Selfish::new())
+    ///     .mempool(mempool)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn mempool(mut self, mempool: Mempool) -> Self {
+        self.mempool = Some(mempool);
+
+        self
+    }
+
+    /// Treat each round as a proof-of-stake slot, where the proposer is
+    /// selected by stake and may equivocate by publishing several blocks for
+    /// the slot. Mining power distributions give each miner's stake. See the
+    /// [`stake`](crate::stake) module.
+    pub fn proof_of_stake(mut self, proof_of_stake: ProofOfStake) -> Self {
+        self.proof_of_stake = Some(proof_of_stake);
+
+        self
+    }
+
+    /// Finalize the longest chain every `blocks` blocks, once another `blocks`
+    /// blocks bury each checkpoint, so that blocks which do not descend from
+    /// the last checkpoint are rejected. Rejected blocks
+    /// are counted in [`MinerStats::blocks_rejected`], and blocks which
+    /// extend them are rejected as well. See [`Blockchain::finalize_every`].
+    /// Strategies can query the last checkpoint with
+    /// [`ChainQuery::last_checkpoint`](crate::blockchain::ChainQuery::last_checkpoint).
+    pub fn finality(mut self, blocks: usize) -> Self {
+        self.finality = Some(blocks);
+
+        self
+    }
+
+    /// Run block-DAG simulations, in which each block also references the
+    /// blocks returned by [`Miner::dag_refs`], and the blocks are ordered by
+    /// the GHOSTDAG rule with parameter `k`. Only blue blocks earn rewards,
+    /// and they take the place of the longest chain in
+    /// [`MinerStats::blocks_on_chain`] and
+    /// [`SimulationOutput::longest_chain_length`]. See the
+    /// [`dag`](crate::dag) module.
+    pub fn ghostdag(mut self, k: usize) -> Self {
+        self.ghostdag = Some(k);
+
+        self
+    }
+
+    /// Pay Ethereum-style rewards for uncles, which are blocks off the longest
+    /// chain referenced by blocks on it. See the [`uncle`](crate::uncle)
+    /// module.
+    pub fn uncle_rewards(mut self, uncle_rewards: UncleRewards) -> Self {
+        self.uncle_rewards = Some(uncle_rewards);
+
+        self
+    }
+
+    /// Seed the random number generators of all simulations, so that the
+    /// sequence of block proposers in each simulation is reproducible. Each
+    /// simulation receives its own seed, which is derived from `seed` and
+    /// recorded in [`SimulationOutput::seed`].
+    ///
+    /// Simulations are not seeded deterministically otherwise.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+
+        self
+    }
+
+    /// Give the `n`th repeat of every configuration the same seed, so that
+    /// simulations with different power distributions or lineups share the
+    /// same sequence of uniform random draws (common random numbers).
+    ///
+    /// # Usage
+    /// Each block proposer is chosen by comparing a single uniform draw
+    /// against the miners' cumulative mining power, so a small change in
+    /// mining power only changes the proposers of a small fraction of
+    /// rounds. Differences between the configurations of a sweep, such as
+    /// [`miner_power_iter`](Self::miner_power_iter), are then measured with
+    /// far less noise for a given number of repeats. Repeats of the same
+    /// configuration are still independent.
+    pub fn common_random_numbers(mut self) -> Self {
+        self.common_random_numbers = true;
+
+        self
+    }
+
+    /// Estimate the derivative of the revenue of the miner with ID
+    /// `miner_id` with respect to its mining power.
+    ///
+    /// Each simulation is paired with two more, in which the miner's mining
+    /// power is `step` lower and `step` higher, clamped to `[0, 1]`, and the
+    /// power of the other miners is scaled to compensate. The paired
+    /// simulations share the simulation's seed, so that they use common
+    /// random numbers as with
+    /// [`common_random_numbers`](Self::common_random_numbers). The difference
+    /// in the miner's revenue between them, divided by the difference in
+    /// power, is recorded in [`SimulationOutput::sensitivities`] and reported
+    /// in a "Miner `X` Revenue Sensitivity (Power)" column of results tables.
+    ///
+    /// # Usage
+    /// Useful for locating profitability thresholds precisely, where the
+    /// revenue of a strategy starts to grow faster than its mining power.
+    /// Each sensitivity triples the number of simulations which are run.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let table = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Selfish::new())
+    ///     .miner_power_iter(MinerId::from(2), [0.2, 0.3, 0.4])
+    ///     .revenue_sensitivity(MinerId::from(2), 0.02)
+    ///     .rounds(1000)
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all()
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// assert!(table
+    ///     .titles()
+    ///     .any(|title| title == "Miner 2 Revenue Sensitivity (Power)"));
+    /// ```
+    pub fn revenue_sensitivity(
+        mut self,
+        miner_id: MinerId,
+        step: PowerValue,
+    ) -> Self {
+        self.sensitivities.push(SensitivityConfig {
+            miner: miner_id,
+            param: SensitivityParam::Power,
+            step,
+        });
+
+        self
+    }
+
+    /// Estimate the derivative of the revenue of the miner with ID
+    /// `miner_id` with respect to its strategy parameter `param`, as given by
+    /// [`Miner::params`]. Works like
+    /// [`revenue_sensitivity`](Self::revenue_sensitivity), except that the
+    /// paired simulations replace the miner with `func(value - step)` and
+    /// `func(value + step)`, where `value` is the parameter's value in each
+    /// lineup. Reported in a "Miner `X` Revenue Sensitivity (`param`)"
+    /// column of results tables.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let sim = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(HonestForking::new(0.5))
+    ///     .param_sensitivity(MinerId::from(2), "p", 0.1, HonestForking::new)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn param_sensitivity<T, F, M>(
+        mut self,
+        miner_id: MinerId,
+        param: T,
+        step: f64,
+        func: F,
+    ) -> Self
+    where
+        T: Into<String>,
+        F: Fn(f64) -> M + Send + Sync + 'static,
+        M: Miner + 'static,
+    {
+        let param = param.into();
+        let func = wrap!(param.clone(), move |value| {
+            Box::new(func(value)) as Box<dyn Miner>
+        });
+        self.sensitivities.push(SensitivityConfig {
+            miner: miner_id,
+            param: SensitivityParam::Strategy(param, func),
+            step,
+        });
+
+        self
+    }
+
+    /// Use a random number generator of type `R` in each simulation, created
+    /// using [`SeedableRng::seed_from_u64`]. [`StdRng`] is used otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    /// use rand::rngs::StdRng;
+    ///
+    /// let sim = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .rng::<StdRng>()
+    ///     .seed(42)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn rng<R>(mut self) -> Self
+    where
+        R: RngCore + SeedableRng + Send + 'static,
+    {
+        self.rng = Some(wrap!(std::any::type_name::<R>(), |seed| {
+            Box::new(R::seed_from_u64(seed)) as Box<dyn RngCore + Send>
+        }));
+
+        self
+    }
+
+    /// Use the random number generator returned by `func` in each simulation,
+    /// where `func` is called with the simulation's seed. Useful for
+    /// generators which do not implement [`SeedableRng`], such as mock
+    /// generators used in tests.
+    pub fn rng_fn<F, R>(mut self, func: F) -> Self
+    where
+        F: Fn(u64) -> R + Send + Sync + 'static,
+        R: RngCore + Send + 'static,
+    {
+        self.rng = Some(wrap!(std::any::type_name::<F>(), move |seed| {
+            Box::new(func(seed)) as Box<dyn RngCore + Send>
+        }));
+
+        self
+    }
+
     /// Set the number of rounds the simulation will last for (default 1).
     pub fn rounds(mut self, rounds: usize) -> Self {
         self.rounds = NonZeroUsize::new(rounds);
@@ -104,6 +699,34 @@ impl SimulationBuilder {
         self
     }
 
+    /// Stop starting new simulations once `budget` has elapsed since the call
+    /// to [`SimulationGroup::run_all`]. Simulations which are already running
+    /// are allowed to finish.
+    ///
+    /// Only configurations for which every repeat completed are reported.
+    /// The configurations left out can be read using
+    /// [`ResultsBuilder::skipped_configs`].
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+
+        self
+    }
+
+    /// Stop each simulation once its longest chain contains `length` blocks
+    /// (not counting the genesis block), instead of after a fixed number of
+    /// rounds. Passing 0 removes this stopping criterion.
+    ///
+    /// [`SimulationBuilder::rounds`] must also be called, unless proposers
+    /// are replayed, so that simulations in which no miner publishes blocks
+    /// still end. Simulations stop after that many rounds even if the target
+    /// length is not reached. The number of rounds actually run is given by
+    /// [`SimulationOutput::rounds`].
+    pub fn target_chain_length(mut self, length: usize) -> Self {
+        self.target_chain_length = NonZeroUsize::new(length);
+
+        self
+    }
+
     /// Run the simulation using the specified mining power distribution.
     pub fn power_dist(mut self, dist: PowerDistribution) -> Self {
         self.power_dists.push(dist);
@@ -111,6 +734,61 @@ impl SimulationBuilder {
         self
     }
 
+    /// Run the simulation using the specified mining power distribution, and
+    /// label the results of the distribution with `label`. Labels appear in
+    /// the "Power Distribution" column of results tables.
+    pub fn labeled_power_dist<T: Into<String>>(
+        mut self,
+        label: T,
+        dist: PowerDistribution,
+    ) -> Self {
+        self.power_dist_labels
+            .insert(self.power_dists.len(), label.into());
+        self.power_dists.push(dist);
+
+        self
+    }
+
+    /// Run the simulation once for each value of the parameter `t` in
+    /// `values`, using the mining power distribution given by `func(t)`. The
+    /// value of `t` appears in the results table in a column titled `title`.
+    /// Useful for structured sweeps, such as an attacker growing at the
+    /// expense of a single honest mining pool.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let results = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Selfish::new())
+    ///     .power_curve("Attacker Power", (0..=3).map(|i| i as f64 * 0.1), |t| {
+    ///         vec![0.5 - t, 0.5, t]
+    ///     })
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(results.num_configs(), 4);
+    /// ```
+    pub fn power_curve<T, I, F>(mut self, title: T, values: I, func: F) -> Self
+    where
+        T: Into<String>,
+        I: IntoIterator<Item = f64>,
+        F: Fn(f64) -> Vec<PowerValue>,
+    {
+        let title = title.into();
+        for t in values {
+            self.power_params
+                .insert(self.power_dists.len(), (title.clone(), t));
+            self.power_dists.push(PowerDistribution::SetValues(func(t)));
+        }
+
+        self
+    }
+
     /// Run the simulation using the mining power distribution described by
     /// `values`.
     pub fn power_values<I>(mut self, values: I) -> Self
@@ -123,6 +801,20 @@ impl SimulationBuilder {
         self
     }
 
+    /// Run the simulation using mining power proportional to the weights in
+    /// `values`, which are rescaled to sum to 1.0. See
+    /// [`PowerDistribution::SetValuesNormalized`].
+    pub fn power_weights<I>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = PowerValue>,
+    {
+        let weights = values.into_iter().collect();
+        self.power_dists
+            .push(PowerDistribution::SetValuesNormalized(weights));
+
+        self
+    }
+
     /// Run the simulation such that mining power is equally distributed
     /// between all miners (this is the default behavior).
     pub fn equal_power(mut self) -> Self {
@@ -156,51 +848,305 @@ impl SimulationBuilder {
         self
     }
 
+    /// Run the simulation once for each pair of power values in the cross
+    /// product of `values_a` and `values_b`, with the power of `miner_a` set to
+    /// the first value of the pair and the power of `miner_b` set to the
+    /// second. Mining power left over is distributed equally between all other
+    /// miners. Pairs whose values sum to more than 1.0 are skipped.
+    pub fn miner_power_grid<I, J>(
+        mut self,
+        miner_a: MinerId,
+        values_a: I,
+        miner_b: MinerId,
+        values_b: J,
+    ) -> Self
+    where
+        I: IntoIterator<Item = PowerValue>,
+        J: IntoIterator<Item = PowerValue>,
+    {
+        let values_b: Vec<_> = values_b.into_iter().collect();
+        for a in values_a {
+            let max_b = 1.0 - a + PowerDistribution::EPSILON_POWER;
+            for &b in values_b.iter().filter(|&&b| b <= max_b) {
+                self.power_dists.push(PowerDistribution::SetMiners(vec![
+                    (miner_a, a),
+                    (miner_b, b),
+                ]));
+            }
+        }
+
+        self
+    }
+
     /// Create a [`SimulationGroup`] from the specified parameters.
     pub fn build(self) -> Result<SimulationGroup, SimulationBuildError> {
         use SimulationBuildError::*;
 
         let SimulationBuilder {
+            block_capacity,
+            block_subsidy,
             blockchain,
+            common_random_numbers,
+            default_tie_breaker,
+            events,
+            finality,
+            gamma,
+            ghostdag,
             include_blockchain,
-            miners,
+            lightweight_output,
+            mut lineups,
+            mempool,
+            mut miner_labels,
+            mut power_dist_labels,
             mut power_dists,
+            mut power_params,
+            proof_of_stake,
+            record_proposers,
             repeat_all,
+            replay_proposers,
+            revenue_window,
+            rng,
             rounds,
+            seed,
+            sensitivities,
+            target_chain_length,
+            time_budget,
+            track_children,
+            uncle_rewards,
+            visibility,
             ..
         } = self;
 
-        if miners.is_empty() {
+        if lineups.iter().all(Vec::is_empty) {
             return Err(NoMinersGiven);
         }
 
+        if let Some(lineup) = lineups.iter().position(Vec::is_empty) {
+            return Err(EmptyLineup(lineup));
+        }
+
+        let num_miners = lineups[0].len();
+        if let Some((lineup, miners)) = lineups
+            .iter()
+            .enumerate()
+            .find(|(_, miners)| miners.len() != num_miners)
+        {
+            return Err(LineupSizeMismatch {
+                lineup,
+                expected: num_miners,
+                found: miners.len(),
+            });
+        }
+
+        miner_labels.resize_with(lineups.len(), BTreeMap::new);
+        for (lineup, labels) in miner_labels.iter().enumerate() {
+            let mut seen = HashSet::new();
+            if let Some(label) = labels.values().find(|l| !seen.insert(*l)) {
+                return Err(DuplicateMinerLabel {
+                    lineup,
+                    label: label.clone(),
+                });
+            }
+        }
+
         if power_dists.is_empty() {
             power_dists.push(PowerDistribution::Equal);
         }
 
         for power_dist in power_dists.iter() {
-            power_dist.validate(miners.len())?;
+            power_dist.validate(num_miners)?;
+        }
+
+        if let Some(gamma) = gamma {
+            if gamma.is_nan() || !(0.0..=1.0).contains(&gamma) {
+                return Err(BadGamma(gamma));
+            }
+        }
+
+        let block_subsidy = block_subsidy.unwrap_or(1.0);
+        if !block_subsidy.is_finite() || block_subsidy < 0.0 {
+            return Err(BadBlockSubsidy(block_subsidy));
+        }
+
+        let default_tie_breaker =
+            default_tie_breaker.or(gamma.map(TieBreaker::FavorLatestProb));
+        if let Some(tie_breaker) = default_tie_breaker.as_ref() {
+            for miner in lineups.iter_mut().flatten() {
+                miner.set_default_tie_breaker(tie_breaker.clone());
+            }
+        }
+
+        if let Some(mempool) = mempool.as_ref() {
+            mempool.validate()?;
+        }
+
+        if let Some(pos) = proof_of_stake.as_ref() {
+            if pos.slot_capacity == 0 {
+                return Err(ZeroSlotCapacity);
+            }
+            if let Some(fraction) = pos.slashing {
+                if !(0.0..=1.0).contains(&fraction) {
+                    return Err(BadSlashing(fraction));
+                }
+            }
+        }
+
+        if finality == Some(0) {
+            return Err(ZeroFinalityInterval);
+        }
+
+        if let Some(rewards) = uncle_rewards.as_ref() {
+            let fractions = rewards.uncle_rewards.iter();
+            for &fraction in fractions.chain([&rewards.nephew_reward]) {
+                if !fraction.is_finite() || fraction < 0.0 {
+                    return Err(BadUncleReward(fraction));
+                }
+            }
+        }
+
+        if let Some((window, step)) = revenue_window {
+            if window == 0 || step == 0 {
+                return Err(ZeroRevenueWindow);
+            }
         }
 
         let repeat_all = repeat_all.unwrap_or(NonZeroUsize::new(1).unwrap());
-        let rounds = rounds.unwrap_or(NonZeroUsize::new(1).unwrap());
+        let mut rounds = match (rounds, target_chain_length) {
+            (Some(rounds), _) => rounds,
+            (None, Some(_)) if replay_proposers.is_none() => {
+                return Err(UncappedTargetChainLength);
+            }
+            _ => NonZeroUsize::new(1).unwrap(),
+        };
+
+        if let Some(proposers) = replay_proposers.as_ref() {
+            if let Some(&miner_id) = proposers
+                .iter()
+                .find(|miner_id| !(1..=num_miners).contains(&miner_id.0))
+            {
+                return Err(ReplayBadMinerId(miner_id));
+            }
+
+            rounds = NonZeroUsize::new(proposers.len()).ok_or(ZeroRounds)?;
+        }
+
+        if let Some(&miner_id) = visibility
+            .keys()
+            .find(|miner_id| !(1..=num_miners).contains(&miner_id.0))
+        {
+            return Err(VisibilityBadMinerId(miner_id));
+        }
+
+        for config in sensitivities.iter() {
+            if !(1..=num_miners).contains(&config.miner.0) {
+                return Err(SensitivityBadMinerId(config.miner));
+            }
+            if !config.step.is_finite() || config.step <= 0.0 {
+                return Err(BadSensitivityStep(config.step));
+            }
+        }
+        let sensitivities = lineups
+            .iter()
+            .enumerate()
+            .map(|(lineup, miners)| {
+                sensitivities
+                    .iter()
+                    .map(|config| {
+                        config.pair(
+                            lineup,
+                            miners,
+                            default_tie_breaker.as_ref(),
+                        )
+                    })
+                    .collect::<Result<Arc<[_]>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let power_dist_labels = (0..power_dists.len())
+            .map(|i| power_dist_labels.remove(&i))
+            .collect();
+        let power_params = (0..power_dists.len())
+            .map(|i| power_params.remove(&i))
+            .collect();
+
+        let blockchain = match track_children {
+            Some(track) => {
+                let mut chain = blockchain.unwrap_or_default();
+                chain.track_children(track);
+                Some(chain)
+            }
+            None => blockchain,
+        };
 
         Ok(SimulationGroup {
+            block_capacity,
+            block_subsidy,
             blockchain,
+            common_random_numbers,
+            events,
+            finality,
+            ghostdag,
             include_blockchain,
-            miners,
+            lightweight_output,
+            lineups,
+            mempool,
+            miner_labels,
+            power_dist_labels,
             power_dists,
+            power_params,
+            proof_of_stake,
+            record_proposers,
             repeat_all,
+            replay_proposers: replay_proposers.map(Arc::from),
+            revenue_window,
+            rng,
             rounds,
+            seed,
+            sensitivities,
+            target_chain_length,
+            time_budget,
+            uncle_rewards,
+            visibility,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::miner::honest::Honest;
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{atomic::AtomicBool, Arc, Mutex},
+        time::Duration,
+    };
 
-    use super::SimulationBuilder;
+    use rand::rngs::mock::StepRng;
+
+    use crate::{
+        blockchain::{Block, BlockId, Blockchain},
+        mempool::{ArrivalProcess, FeeDistribution, FeeMarket, BLACKLIST_TAG},
+        miner::{
+            doublespend::DoubleSpend, equivocating::Equivocating,
+            honest::Honest, honestforking::HonestForking, ndeficit::NDeficit,
+            noop::Noop, selfish::Selfish, Action, Miner, MinerId,
+        },
+        results::{
+            honest_revenue, revenue_of, selfish_revenue, selfish_revenue_max,
+            selfish_revenue_min, selfish_threshold, Average, CellValue,
+            ColumnKind, Format, SkippedConfig,
+        },
+        stake::ProofOfStake,
+        transaction::Transaction,
+        uncle::UncleRewards,
+        view::{ChainView, Visibility},
+    };
+
+    use super::{
+        events::{EventSource, SimulationEvent},
+        snapshot::SnapshotRecorder,
+        threshold::{ThresholdError, ThresholdSearch},
+        Mempool, Parallelism, PowerDistribution, SimulationBuildError,
+        SimulationBuilder, SimulationError, SimulationOutput, TieBreaker,
+    };
 
     #[test]
     fn example_build() {
@@ -209,69 +1155,2734 @@ mod tests {
             .build()
             .expect("valid simulation build");
     }
-}
 
-/// Container for a group of simulations which run on the same set of miners.
-#[derive(Debug, Clone)]
-pub struct SimulationGroup {
-    blockchain: Option<Blockchain>,
-    include_blockchain: bool,
-    miners: Vec<Box<dyn Miner>>,
-    power_dists: Vec<PowerDistribution>,
-    repeat_all: NonZeroUsize,
-    rounds: NonZeroUsize,
-}
+    #[test]
+    fn lineup_size_mismatch() {
+        let result = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .next_lineup()
+            .add_miner(Honest::new())
+            .build();
 
-impl SimulationGroup {
+        assert!(matches!(
+            result,
+            Err(SimulationBuildError::LineupSizeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn replayed_proposers_reproduce_simulation() {
+        let builder = || {
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Honest::new())
+                .record_proposers()
+        };
+
+        let recorded = builder().rounds(100).build().unwrap().run_all();
+        let recorded = recorded.unwrap().data().pop().unwrap();
+        let proposers = recorded.proposers.clone().unwrap();
+
+        let replayed = builder().replay_proposers(proposers).build().unwrap();
+        let replayed = replayed.run_all().unwrap().data().pop().unwrap();
+
+        assert_eq!(replayed.rounds, 100);
+        assert_eq!(replayed.proposers, recorded.proposers);
+        assert_eq!(replayed.blocks_by_miner, recorded.blocks_by_miner);
+        assert_eq!(replayed.miner_stats, recorded.miner_stats);
+    }
+
+    #[test]
+    fn per_miner_block_count_columns() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .rounds(100)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .blocks_mined()
+            .blocks_on_chain()
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        let mut lines = table.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "Miner 1 Power,Miner 2 Power,\
+             Miner 1 Blocks Mined,Miner 2 Blocks Mined,\
+             Miner 1 Blocks On Chain,Miner 2 Blocks On Chain"
+        );
+
+        // Honest miners build one chain which contains every mined block
+        let counts: Vec<usize> = lines
+            .next()
+            .unwrap()
+            .split(',')
+            .skip(2)
+            .map(|count| count.parse().unwrap())
+            .collect();
+        assert_eq!(counts[0], counts[2]);
+        assert_eq!(counts[1], counts[3]);
+        assert_eq!(counts[0] + counts[1], 100);
+    }
+
+    #[test]
+    fn track_children_in_simulations() {
+        for track in [false, true] {
+            let output = SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .rounds(10)
+                .track_children(track)
+                .include_blockchain()
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .data()
+                .pop()
+                .unwrap();
+
+            let chain = output.blockchain.unwrap();
+            assert_eq!(chain.tracks_children(), track);
+            assert_eq!(chain[BlockId(0)].children.len(), track as usize);
+        }
+    }
+
+    #[test]
+    fn blockchain_memory_column() {
+        let results = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .rounds(100)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap();
+
+        let output = &results.clone().data()[0];
+        assert!(output.blockchain_memory > 100 * size_of::<BlockId>());
+
+        let table = results.blockchain_memory().format(Format::CSV).build();
+        let header = table.to_string();
+        assert!(header.starts_with("Miner 1 Power,Blockchain Memory (MiB)"));
+    }
+
+    #[test]
+    fn select_columns_for_specific_miners() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .rounds(10)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .strategy_name_for(MinerId(3))
+            .mining_power_for(MinerId(3))
+            .revenue_for(MinerId(3))
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        assert_eq!(
+            table.lines().next().unwrap(),
+            "Miner 3 Strategy,Miner 3 Power,Miner 3 Revenue"
+        );
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn results_to_record_batch() {
+        let batch = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .rounds(10)
+            .repeat_all(3)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .average(Average::Mean)
+            .strategy_names()
+            .revenue()
+            .build()
+            .to_record_batch()
+            .unwrap();
+
+        let schema = batch.schema();
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.num_columns(), 7);
+        assert_eq!(schema.field(0).name(), "Miner 1 Strategy");
+        assert_eq!(schema.field(6).name(), "Mean Of");
+    }
+
+    #[test]
+    fn revenue_vs_ideal_columns() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .rounds(100)
+            .power_values([0.3, 0.7])
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .revenue_for(MinerId(2))
+            .revenue_vs(MinerId(2), "Honest", honest_revenue)
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        let mut lines = table.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "Miner 1 Power,Miner 2 Power,Miner 2 Revenue,\
+             Miner 2 Revenue - Honest,Miner 2 Revenue Relative Error (Honest)"
+        );
+
+        let values: Vec<f64> = lines
+            .next()
+            .unwrap()
+            .split(',')
+            .map(|val| val.parse().unwrap())
+            .collect();
+        assert!((values[3] - (values[2] - 0.7)).abs() < 1e-5);
+        assert!((values[4] - values[3] / 0.7).abs() < 1e-5);
+    }
+
+    #[test]
+    fn closed_form_selfish_revenue() {
+        for gamma in [0.0, 0.25, 0.5, 0.75] {
+            let threshold = selfish_threshold(gamma);
+            let revenue = selfish_revenue(gamma)(threshold);
+            assert!((revenue - threshold).abs() < 1e-9);
+        }
+
+        let (min, max) = (
+            selfish_revenue_min(0.0..=1.0),
+            selfish_revenue_max(0.0..=1.0),
+        );
+        for a in [0.1, 0.3, 0.45] {
+            assert_eq!(min(a), selfish_revenue(0.0)(a));
+            assert_eq!(max(a), selfish_revenue(1.0)(a));
+        }
+    }
+
+    #[test]
+    fn raw_table_alongside_aggregates() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .rounds(10)
+            .repeat_all(3)
+            .power_values([0.5, 0.5])
+            .power_values([0.2, 0.8])
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .average(Average::Mean)
+            .rounds()
+            .include_raw()
+            .format(Format::CSV)
+            .build();
+
+        assert_eq!(table.to_string().lines().count(), 3);
+
+        let raw = table.raw().unwrap().to_string();
+        let mut lines = raw.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "Repeat,Miner 1 Power,Miner 2 Power,Simulated Rounds"
+        );
+        let repeats: Vec<_> = lines
+            .map(|line| line.split(',').next().unwrap().to_string())
+            .collect();
+        assert_eq!(repeats, ["0", "1", "2", "0", "1", "2"]);
+    }
+
+    #[test]
+    fn honest_miners_never_fork() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .rounds(100)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .forks()
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        assert_eq!(
+            table.lines().last().unwrap(),
+            "0.500000,0.500000,0,0.000000,0"
+        );
+    }
+
+    #[test]
+    fn selfish_mining_reorgs_longest_chain() {
+        let output = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .miner_power(MinerId(2), 0.4)
+            .rounds(1000)
+            .seed(7)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data()
+            .pop()
+            .unwrap();
+
+        assert!(!output.reorg_depths.is_empty());
+        assert!(output.reorg_depths.iter().all(|&depth| depth > 0));
+    }
+
+    #[test]
+    fn typed_rows_of_results_table() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .rounds(10)
+            .repeat_all(2)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .average(Average::Mean)
+            .strategy_names()
+            .rounds()
+            .build();
+
+        let rows: Vec<_> = table.rows().collect();
+        assert_eq!(
+            rows,
+            vec![vec![
+                (
+                    ColumnKind::MinerStrategyName(MinerId(1)),
+                    CellValue::Text("Honest".to_string())
+                ),
+                (ColumnKind::MiningPower(MinerId(1)), CellValue::Float(1.0)),
+                (ColumnKind::Rounds, CellValue::Float(10.0)),
+                (ColumnKind::AverageOf, CellValue::Integer(2)),
+            ]]
+        );
+
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            serde_json::to_string(&table).unwrap(),
+            "{\"columns\":[\
+             {\"title\":\"Miner 1 Strategy\",\"kind\":{\"MinerStrategyName\":1}},\
+             {\"title\":\"Miner 1 Power\",\"kind\":{\"MiningPower\":1}},\
+             {\"title\":\"Simulated Rounds\",\"kind\":\"Rounds\"},\
+             {\"title\":\"Mean Of\",\"kind\":\"AverageOf\"}],\
+             \"rows\":[[\"Honest\",1.0,10.0,2]]}"
+        );
+    }
+
+    #[test]
+    fn miner_power_grid_cross_product() {
+        let results = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .miner_power_grid(
+                MinerId::from(1),
+                [0.2, 0.4, 0.6],
+                MinerId::from(2),
+                [0.2, 0.4, 0.6],
+            )
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap();
+
+        // (0.6, 0.6) exceeds the total mining power
+        assert_eq!(results.num_configs(), 8);
+
+        let table = results.format(Format::CSV).build().to_string();
+        assert!(table.contains("0.600000,0.400000,0.000000"));
+        assert!(!table.contains("0.600000,0.600000"));
+    }
+
+    #[test]
+    fn one_hot_power_dist() {
+        let results = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .power_values([0.0, 1.0])
+            .rounds(50)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap();
+        let output = results.data().pop().unwrap();
+
+        assert_eq!(output.miner_stats[&MinerId::from(1)].blocks_mined, 0);
+        assert_eq!(output.miner_stats[&MinerId::from(2)].blocks_mined, 50);
+    }
+
+    #[test]
+    fn labeled_power_dists() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .labeled_power_dist("Even", PowerDistribution::Equal)
+            .power_values([0.25, 0.75])
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        assert_eq!(
+            table,
+            "Power Distribution,Miner 1 Power,Miner 2 Power\n\
+             Even,0.500000,0.500000\n\
+             ,0.250000,0.750000"
+        );
+    }
+
+    #[test]
+    fn power_curve_parameter_column() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .power_curve("t", [0.25, 0.5], |t| vec![t, 1.0 - t])
+            .power_values([0.5, 0.5])
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .format(Format::CSV)
+            .build()
+            .to_string();
+
+        assert_eq!(
+            table,
+            "t,Miner 1 Power,Miner 2 Power\n\
+             0.250000,0.250000,0.750000\n\
+             0.500000,0.500000,0.500000\n\
+             NaN,0.500000,0.500000"
+        );
+    }
+
+    #[test]
+    fn hide_power_columns() {
+        let results = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .format(Format::CSV)
+            .rounds()
+            .hide_power();
+
+        let table = results.clone().build().to_string();
+        assert_eq!(table.lines().next().unwrap(), "Simulated Rounds");
+
+        let table = results.mining_power_for(MinerId::from(2)).build();
+        let header = table.to_string();
+        assert_eq!(
+            header.lines().next().unwrap(),
+            "Miner 2 Power,Simulated Rounds"
+        );
+    }
+
+    #[test]
+    fn seeded_random_tie_breaking_is_reproducible() {
+        let run = || {
+            SimulationBuilder::new()
+                .add_miner(Honest::with_tie_breaker(TieBreaker::Random))
+                .add_miner(HonestForking::new(0.5))
+                .rounds(200)
+                .seed(3)
+                .include_blockchain()
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .data()
+                .pop()
+                .unwrap()
+        };
+
+        let (first, second) = (run(), run());
+        let parents = |output: &SimulationOutput| {
+            let chain = output.blockchain.as_ref().unwrap();
+            (1..=output.rounds)
+                .filter_map(|id| chain.get(BlockId::from(id)))
+                .map(|data| data.block.parent_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(first.miner_stats, second.miner_stats);
+        assert_eq!(parents(&first), parents(&second));
+    }
+
+    #[test]
+    fn default_tie_breaker_matches_gamma() {
+        let run = |builder: SimulationBuilder| {
+            builder
+                .add_miner(Honest::new())
+                .add_miner(Selfish::new())
+                .power_values([0.6, 0.4])
+                .rounds(500)
+                .seed(11)
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .data()
+                .pop()
+                .unwrap()
+                .miner_stats
+        };
+
+        let gamma = run(SimulationBuilder::new().gamma(1.0));
+        let default = run(SimulationBuilder::new()
+            .default_tie_breaker(TieBreaker::FavorLatestProb(1.0)));
+        let overridden = run(SimulationBuilder::new()
+            .gamma(0.0)
+            .default_tie_breaker(TieBreaker::FavorMiner(MinerId(2))));
+
+        assert_eq!(gamma, default);
+        assert_eq!(gamma, overridden);
+    }
+
+    #[test]
+    fn mempool_fills_published_blocks() {
+        let outputs = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .next_lineup()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .power_values([0.6, 0.4])
+            .mempool(Mempool::new(
+                ArrivalProcess::Rate(2.0),
+                FeeDistribution::Uniform(0.0, 1.0),
+            ))
+            .rounds(200)
+            .include_blockchain()
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data();
+
+        for output in outputs.iter() {
+            let chain = output.blockchain.as_ref().unwrap();
+            let txn_ids: Vec<_> = chain
+                .longest_chain()
+                .flat_map(|block_id| chain[block_id].block.txns.iter())
+                .map(|txn| txn.id)
+                .collect();
+            let unique: HashSet<_> = txn_ids.iter().collect();
+
+            assert_eq!(txn_ids.len(), unique.len());
+            assert!(txn_ids.len() <= 2 * output.rounds);
+        }
+
+        // Without forks, every transaction is confirmed by the next block
+        let honest = &outputs[0];
+        let chain = honest.blockchain.as_ref().unwrap();
+        assert!(chain
+            .longest_chain()
+            .filter(|&block_id| block_id != chain.genesis())
+            .all(|block_id| chain[block_id].block.txns.len() == 2));
+    }
+
+    #[test]
+    fn strategy_params_columns() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(NDeficit::new(1))
+            .next_lineup()
+            .add_miner(HonestForking::new(0.25))
+            .add_miner(NDeficit::new(2))
+            .rounds(10)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .strategy_params()
+            .build();
+
+        let titles: Vec<_> = table.titles().collect();
+        assert!(titles.contains(&"Miner 1 p"));
+        assert!(titles.contains(&"Miner 2 i"));
+
+        let values: Vec<_> = table
+            .rows()
+            .map(|row| {
+                let cell = |kind| {
+                    row.iter()
+                        .find(|(k, _)| *k == kind)
+                        .and_then(|(_, cell)| cell.as_f64())
+                        .unwrap()
+                };
+                (
+                    cell(ColumnKind::MinerParam(MinerId(1))),
+                    cell(ColumnKind::MinerParam(MinerId(2))),
+                )
+            })
+            .collect();
+        assert!(values[0].0.is_nan());
+        assert_eq!(values[0].1, 1.0);
+        assert_eq!(values[1], (0.25, 2.0));
+    }
+
+    #[test]
+    fn miner_labels_in_titles() {
+        let builder = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner_named("AttackerPool", Selfish::new())
+            .next_lineup()
+            .add_miner_named("HonestPool", Honest::new())
+            .add_miner_named("AttackerPool", NDeficit::new(1))
+            .rounds(10);
+        let results = builder.build().unwrap().run_all().unwrap();
+
+        let data = results.clone().data();
+        assert_eq!(data[0].miner_labels.get(&MinerId(1)), None);
+        assert_eq!(data[1].miner_labels[&MinerId(1)], "HonestPool");
+        assert_eq!(data[1].miner_labels[&MinerId(2)], "AttackerPool");
+
+        // Miner 1 is only labeled in one lineup
+        let table = results.all().build();
+        let titles: Vec<_> = table.titles().collect();
+        assert!(titles.contains(&"AttackerPool Revenue"));
+        assert!(titles.contains(&"AttackerPool Strategy"));
+        assert!(titles.contains(&"Miner 1 Revenue"));
+
+        let result = SimulationBuilder::new()
+            .add_miner_named("Pool", Honest::new())
+            .add_miner_named("Pool", Honest::new())
+            .build();
+        assert!(matches!(
+            result,
+            Err(SimulationBuildError::DuplicateMinerLabel { lineup: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn subsidy_and_fee_income() {
+        let income = |fee_market| {
+            let results = SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Honest::new())
+                .mempool(
+                    Mempool::new(
+                        ArrivalProcess::Rate(2.0),
+                        FeeDistribution::Constant(0.5),
+                    )
+                    .fee_market(fee_market),
+                )
+                .block_subsidy(2.0)
+                .rounds(100)
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap();
+            let output = results.clone().data().remove(0);
+            let blocks: f64 = output
+                .miner_stats
+                .values()
+                .map(|stats| stats.blocks_on_chain as f64)
+                .sum();
+            let subsidy: f64 = output
+                .miner_stats
+                .values()
+                .map(|stats| stats.subsidy_income)
+                .sum();
+            let fees: f64 = output
+                .miner_stats
+                .values()
+                .map(|stats| stats.fee_income)
+                .sum();
+
+            let table = results.income_for(MinerId(1)).build();
+            let row = table.rows().next().unwrap();
+            let cell = |kind| {
+                row.iter()
+                    .find(|(k, _)| *k == kind)
+                    .and_then(|(_, cell)| cell.as_f64())
+                    .unwrap()
+            };
+            assert_eq!(
+                cell(ColumnKind::MinerSubsidyIncome(MinerId(1))),
+                output.miner_stats[&MinerId(1)].subsidy_income
+            );
+            assert_eq!(
+                cell(ColumnKind::MinerFeeIncome(MinerId(1))),
+                output.miner_stats[&MinerId(1)].fee_income
+            );
+
+            (blocks, subsidy, fees)
+        };
+
+        // Each block includes 2 transactions paying 0.5
+        let (blocks, subsidy, fees) = income(FeeMarket::FirstPrice);
+        assert_eq!(blocks, 100.0);
+        assert_eq!(subsidy, 2.0 * blocks);
+        assert_eq!(fees, blocks);
+
+        // Blocks at the target size keep the base fee at 0.25
+        let (blocks, subsidy, fees) = income(FeeMarket::BaseFee {
+            initial: 0.25,
+            target: 2,
+        });
+        assert_eq!(subsidy, 2.0 * blocks);
+        assert_eq!(fees, 0.5 * blocks);
+
+        assert!(matches!(
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .block_subsidy(-1.0)
+                .build(),
+            Err(SimulationBuildError::BadBlockSubsidy(_))
+        ));
+    }
+
+    #[test]
+    fn ghostdag_rewards_blue_blocks() {
+        let run = |ghostdag: Option<usize>| {
+            let mut builder = SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Selfish::new())
+                .power_values([0.65, 0.35])
+                .rounds(3000)
+                .seed(4);
+            if let Some(k) = ghostdag {
+                builder = builder.ghostdag(k);
+            }
+
+            builder.build().unwrap().run_all().unwrap().data().remove(0)
+        };
+
+        // With a large k, every block is blue, so withholding blocks only
+        // delays the selfish miner's rewards
+        let chain = run(None);
+        let wide = run(Some(10));
+        assert!(chain.dag_order.is_none());
+        assert_eq!(wide.longest_chain_length, wide.blocks_published);
+        assert_eq!(
+            wide.dag_order.as_ref().unwrap().len(),
+            wide.blocks_published
+        );
+        assert!(
+            revenue_of(&MinerId(2), &wide) < revenue_of(&MinerId(2), &chain)
+        );
+
+        let narrow = run(Some(1));
+        let blue: usize = narrow
+            .miner_stats
+            .values()
+            .map(|stats| stats.blocks_on_chain)
+            .sum();
+        assert_eq!(blue + 1, narrow.longest_chain_length);
+        assert!(narrow.longest_chain_length < narrow.blocks_published);
+    }
+
+    #[test]
+    fn finality_limits_selfish_mining() {
+        let run = |finality: Option<usize>| {
+            let mut builder = SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Selfish::new())
+                .power_values([0.6, 0.4])
+                .include_blockchain()
+                .rounds(2000)
+                .seed(9);
+            if let Some(blocks) = finality {
+                builder = builder.finality(blocks);
+            }
+
+            builder.build().unwrap().run_all().unwrap().data().remove(0)
+        };
+
+        let unfinalized = run(None);
+        let finalized = run(Some(1));
+        let honest = finalized.miner_stats[&MinerId(1)];
+        let selfish = finalized.miner_stats[&MinerId(2)];
+        assert_eq!(honest.blocks_rejected, 0);
+        assert!(selfish.blocks_rejected > 0);
+        assert_eq!(unfinalized.miner_stats[&MinerId(2)].blocks_rejected, 0);
+        assert!(
+            revenue_of(&MinerId(2), &finalized)
+                < revenue_of(&MinerId(2), &unfinalized)
+        );
+
+        // Rejected blocks never reach the blockchain
+        let chain = finalized.blockchain.as_ref().unwrap();
+        assert_eq!(chain.checkpoint_interval(), Some(1));
+        assert_eq!(
+            Some(chain.last_checkpoint()),
+            chain.get_parent(chain.tip()[0])
+        );
+        assert_eq!(
+            chain.num_blocks() - 1,
+            honest.blocks_published + selfish.blocks_published
+        );
+
+        assert!(matches!(
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .finality(0)
+                .build(),
+            Err(SimulationBuildError::ZeroFinalityInterval)
+        ));
+    }
+
+    #[test]
+    fn uncle_rewards_income() {
+        let results = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .power_values([0.6, 0.4])
+            .uncle_rewards(UncleRewards::new())
+            .block_subsidy(2.0)
+            .rounds(1000)
+            .seed(5)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap();
+        let output = results.clone().data().remove(0);
+        let honest = output.miner_stats[&MinerId(1)];
+        let selfish = output.miner_stats[&MinerId(2)];
+
+        // Uncles earn between 2/8 and 7/8 of the subsidy, and nephews earn
+        // 1/32 of it for each uncle.
+        assert!(honest.uncles > 0);
+        assert!(honest.uncle_income >= 0.25 * 2.0 * honest.uncles as f64);
+        assert!(honest.uncle_income <= 0.875 * 2.0 * honest.uncles as f64);
+        let uncles = (honest.uncles + selfish.uncles) as f64;
+        let nephew_income = honest.nephew_income + selfish.nephew_income;
+        assert!((nephew_income - uncles / 16.0).abs() < 1e-9);
+
+        let table = results.uncle_income().reward_share().build();
+        let row = table.rows().next().unwrap();
+        let cell = |kind| {
+            row.iter()
+                .find(|(k, _)| *k == kind)
+                .and_then(|(_, cell)| cell.as_f64())
+                .unwrap()
+        };
+        assert_eq!(
+            cell(ColumnKind::MinerUncleIncome(MinerId(1))),
+            honest.uncle_income
+        );
+        assert_eq!(
+            cell(ColumnKind::MinerNephewIncome(MinerId(2))),
+            selfish.nephew_income
+        );
+        let share_1 = cell(ColumnKind::MinerRewardShare(MinerId(1)));
+        let share_2 = cell(ColumnKind::MinerRewardShare(MinerId(2)));
+        assert!((share_1 + share_2 - 1.0).abs() < 1e-9);
+
+        assert!(matches!(
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .uncle_rewards(UncleRewards::new().nephew_reward(f64::NAN))
+                .build(),
+            Err(SimulationBuildError::BadUncleReward(_))
+        ));
+    }
+
+    #[test]
+    fn inclusion_latency_grows_with_backlog() {
+        let latency = |capacity| {
+            let table = SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Honest::new())
+                .mempool(Mempool::new(
+                    ArrivalProcess::Rate(2.0),
+                    FeeDistribution::Constant(1.0),
+                ))
+                .block_capacity(capacity)
+                .rounds(100)
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .inclusion_latency()
+                .build();
+            let row = table.rows().next().unwrap();
+            let cell = |kind| {
+                row.iter()
+                    .find(|(k, _)| *k == kind)
+                    .and_then(|(_, cell)| cell.as_f64())
+                    .unwrap()
+            };
+
+            (
+                cell(ColumnKind::MeanInclusionLatency),
+                cell(ColumnKind::MedianInclusionLatency),
+            )
+        };
+
+        // Every transaction is included in its arrival round
+        assert_eq!(latency(2), (0.0, 0.0));
+
+        // Only half of the transactions fit, and the oldest are included first
+        let (mean, median) = latency(1);
+        assert!(mean > 20.0);
+        assert!(median > 20.0);
+    }
+
+    #[test]
+    fn transactions_are_replayable() {
+        let run = |mempool: Mempool, seed| {
+            let output = SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Honest::new())
+                .mempool(mempool)
+                .rounds(50)
+                .seed(seed)
+                .include_blockchain()
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .data()
+                .remove(0);
+            let chain = output.blockchain.as_ref().unwrap();
+            let txns: Vec<_> = chain
+                .longest_chain()
+                .flat_map(|block_id| chain[block_id].block.txns.clone())
+                .collect();
+
+            (output.txn_generator.unwrap(), txns)
+        };
+        let mempool = Mempool::new(
+            ArrivalProcess::Poisson(2.0),
+            FeeDistribution::Uniform(0.0, 1.0),
+        )
+        .random_whales(0.1, 10.0);
+
+        let (config, txns) = run(mempool.clone(), 7);
+        assert!(config.seed.is_some());
+        assert_eq!(run(mempool.clone(), 7).0, config);
+        assert_ne!(run(mempool, 8).0.seed, config.seed);
+
+        // Replaying the configuration reproduces the transactions, even if
+        // block proposers differ
+        let (replayed, replayed_txns) = run(Mempool::from(config.clone()), 8);
+        assert_eq!(replayed, config);
+        assert_eq!(replayed_txns, txns);
+    }
+
+    /// Honest miner which never includes the transactions matched by its
+    /// filter.
+    #[derive(Debug, Clone)]
+    struct Censor(Honest, fn(&Transaction) -> bool);
+
+    impl Miner for Censor {
+        fn name(&self) -> String {
+            "Censor".to_string()
+        }
+
+        fn id(&self) -> MinerId {
+            self.0.id()
+        }
+
+        fn set_id(&mut self, id: MinerId) {
+            self.0.set_id(id);
+        }
+
+        fn get_action(
+            &mut self,
+            chain: &ChainView,
+            block_mined: Option<BlockId>,
+        ) -> Action {
+            self.0.get_action(chain, block_mined)
+        }
+
+        fn select_txns(
+            &mut self,
+            mempool: &Mempool,
+            capacity: usize,
+            chain: &ChainView,
+            parent: BlockId,
+        ) -> Vec<Transaction> {
+            let mut txns = mempool.select(chain, parent, capacity);
+            txns.retain(|txn| !(self.1)(txn));
+
+            txns
+        }
+    }
+
+    #[test]
+    fn lifecycle_hooks_are_called() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        };
+
+        /// Honest miner which counts its simulations in state shared between
+        /// its copies.
+        #[derive(Debug, Clone, Default)]
+        struct Learner {
+            inner: Honest,
+            blocks: usize,
+            starts: Arc<AtomicUsize>,
+            revenue: Arc<Mutex<Vec<usize>>>,
+        }
+
+        impl Miner for Learner {
+            fn name(&self) -> String {
+                "Learner".to_string()
+            }
+
+            fn id(&self) -> MinerId {
+                self.inner.id()
+            }
+
+            fn set_id(&mut self, id: MinerId) {
+                self.inner.set_id(id);
+            }
+
+            fn get_action(
+                &mut self,
+                chain: &ChainView,
+                block_mined: Option<BlockId>,
+            ) -> Action {
+                self.blocks += usize::from(block_mined.is_some());
+                self.inner.get_action(chain, block_mined)
+            }
+
+            fn on_simulation_start(&mut self) {
+                assert_eq!(self.blocks, 0);
+                self.starts.fetch_add(1, Ordering::Relaxed);
+            }
+
+            fn on_simulation_end(&mut self, output: &SimulationOutput) {
+                let stats = output.miner_stats[&self.id()];
+                assert_eq!(stats.blocks_mined, self.blocks);
+                self.revenue.lock().unwrap().push(stats.blocks_on_chain);
+            }
+        }
+
+        let learner = Learner::default();
+        let results = SimulationBuilder::new()
+            .add_miner(learner.clone())
+            .add_miner(Honest::new())
+            .power_values([0.5, 0.5])
+            .power_values([0.2, 0.8])
+            .repeat_all(4)
+            .rounds(50)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap();
+
+        let mut revenue = learner.revenue.lock().unwrap().clone();
+        let mut expected: Vec<_> = results
+            .data()
+            .iter()
+            .map(|output| output.miner_stats[&MinerId(1)].blocks_on_chain)
+            .collect();
+        revenue.sort();
+        expected.sort();
+
+        assert_eq!(learner.starts.load(Ordering::Relaxed), 8);
+        assert_eq!(revenue, expected);
+    }
+
+    #[test]
+    fn miners_select_transactions() {
+        let output = SimulationBuilder::new()
+            .add_miner(Censor(Honest::new(), |txn| txn.fee < 0.5))
+            .add_miner(Honest::new())
+            .mempool(Mempool::new(
+                ArrivalProcess::Rate(1.0),
+                FeeDistribution::Uniform(0.0, 1.0),
+            ))
+            .rounds(100)
+            .include_blockchain()
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data()
+            .pop()
+            .unwrap();
+
+        let chain = output.blockchain.as_ref().unwrap();
+        let blocks_by = |miner_id| {
+            chain
+                .longest_chain()
+                .map(|block_id| &chain[block_id].block)
+                .filter(move |block| block.miner_id == miner_id)
+        };
+
+        assert!(blocks_by(MinerId(1))
+            .flat_map(|block| block.txns.iter())
+            .all(|txn| txn.fee >= 0.5));
+        assert!(blocks_by(MinerId(2))
+            .flat_map(|block| block.txns.iter())
+            .any(|txn| txn.fee < 0.5));
+    }
+
+    #[test]
+    fn blacklisted_transactions_are_censored() {
+        let table = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .next_lineup()
+            .add_miner(Censor(Honest::new(), |txn| {
+                txn.tag.as_deref() == Some(BLACKLIST_TAG)
+            }))
+            .add_miner(Honest::new())
+            .mempool(
+                Mempool::new(
+                    ArrivalProcess::Rate(1.0),
+                    FeeDistribution::Constant(1.0),
+                )
+                .blacklist(0.5),
+            )
+            .rounds(200)
+            .seed(3)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .censorship()
+            .inclusion_latency()
+            .build();
+
+        let stats: Vec<_> = table
+            .rows()
+            .map(|row| {
+                let cell = |kind| {
+                    row.iter()
+                        .find(|(k, _)| *k == kind)
+                        .and_then(|(_, cell)| cell.as_f64())
+                        .unwrap()
+                };
+
+                (
+                    cell(ColumnKind::BlacklistedConfirmed),
+                    cell(ColumnKind::BlacklistedMeanLatency),
+                    cell(ColumnKind::MeanInclusionLatency),
+                )
+            })
+            .collect();
+
+        // Honest miners include every transaction in its arrival round
+        let (honest_confirmed, honest_latency, _) = stats[0];
+        assert!(honest_confirmed > 50.0);
+        assert_eq!(honest_latency, 0.0);
+
+        // Blacklisted transactions wait for the honest miner
+        let (confirmed, latency, overall_latency) = stats[1];
+        assert!(confirmed > 50.0);
+        assert!(latency > 0.5);
+        assert!(overall_latency < latency);
+    }
+
+    #[test]
+    fn double_spend_success_rates() {
+        let success_rate = |attacker_power: f64, confirmations| {
+            let table = SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(DoubleSpend::new(7, confirmations).max_deficit(20))
+                .power_values([1.0 - attacker_power, attacker_power])
+                .mempool(
+                    Mempool::new(
+                        ArrivalProcess::Rate(1.0),
+                        FeeDistribution::Constant(0.1),
+                    )
+                    .double_spend(5, 7, 1.0),
+                )
+                .rounds(300)
+                .repeat_all(20)
+                .seed(5)
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .double_spend_success(confirmations)
+                .average(Average::Mean)
+                .build();
+
+            let row = table.rows().next().unwrap();
+            let kind = ColumnKind::DoubleSpendSuccess(confirmations);
+            let (_, cell) = row.iter().find(|(k, _)| *k == kind).unwrap();
+            cell.as_f64().unwrap()
+        };
+
+        assert_eq!(success_rate(0.1, 6), 0.0);
+        assert!(success_rate(0.7, 2) > 0.8);
+    }
+
+    #[test]
+    fn block_capacity_limits_transactions() {
+        let output = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .mempool(Mempool::new(
+                ArrivalProcess::Rate(3.0),
+                FeeDistribution::Exponential(1.0),
+            ))
+            .block_capacity(2)
+            .rounds(50)
+            .include_blockchain()
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data()
+            .pop()
+            .unwrap();
+
+        let chain = output.blockchain.as_ref().unwrap();
+        let confirmed: usize = chain
+            .longest_chain()
+            .map(|block_id| chain[block_id].block.txns.len())
+            .sum();
+
+        assert!(chain.longest_chain().all(|block_id| chain[block_id]
+            .block
+            .txns
+            .len()
+            <= 2));
+        assert_eq!(confirmed, 2 * output.rounds);
+    }
+
+    #[test]
+    fn revenue_series_windows() {
+        let output = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .rounds(100)
+            .revenue_series(10, 5)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data()
+            .pop()
+            .unwrap();
+
+        let series = output.revenue_series.unwrap();
+        let first = &series.revenue[&MinerId(1)];
+        let second = &series.revenue[&MinerId(2)];
+
+        assert_eq!(first.len(), 19);
+        for (a, b) in first.iter().zip(second) {
+            assert!((a + b - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn target_chain_length_stops_simulation() {
+        let output = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .target_chain_length(50)
+            .rounds(1000)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data()
+            .pop()
+            .unwrap();
+
+        assert_eq!(output.rounds, 50);
+        assert_eq!(output.longest_chain_length, 51);
+    }
+
+    #[test]
+    fn target_chain_length_requires_round_cap() {
+        let builder = || {
+            SimulationBuilder::new()
+                .add_miner(Noop::new())
+                .add_miner(Noop::new())
+                .target_chain_length(10)
+        };
+
+        assert!(matches!(
+            builder().build(),
+            Err(SimulationBuildError::UncappedTargetChainLength)
+        ));
+
+        let output = builder()
+            .rounds(100)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data()
+            .pop()
+            .unwrap();
+        assert_eq!(output.rounds, 100);
+        assert_eq!(output.longest_chain_length, 1);
+    }
+
+    #[test]
+    fn delayed_visibility_causes_forks() {
+        let run = |visibility| {
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Honest::new())
+                .visibility(MinerId(1), visibility)
+                .rounds(500)
+                .seed(7)
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .data()
+                .pop()
+                .unwrap()
+        };
+
+        let full = run(Visibility::Full);
+        assert_eq!(full.longest_chain_length, full.blocks_published);
+
+        let delayed = run(Visibility::Delayed(3));
+        assert!(delayed.longest_chain_length < delayed.blocks_published);
+        assert!(!delayed.fork_lengths.is_empty());
+
+        let result = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .visibility(MinerId(2), Visibility::Delayed(1))
+            .build();
+        assert!(matches!(
+            result,
+            Err(SimulationBuildError::VisibilityBadMinerId(MinerId(2)))
+        ));
+    }
+
+    #[test]
+    fn delayed_miners_select_txns_from_their_view() {
+        /// Honest miner which records whether every earlier block was visible
+        /// when it selected transactions.
+        #[derive(Debug, Clone, Default)]
+        struct Spy {
+            inner: Honest,
+            mined: usize,
+            saw_all: Arc<Mutex<Vec<bool>>>,
+        }
+
+        impl Miner for Spy {
+            fn name(&self) -> String {
+                "Spy".to_string()
+            }
+
+            fn id(&self) -> MinerId {
+                self.inner.id()
+            }
+
+            fn set_id(&mut self, id: MinerId) {
+                self.inner.set_id(id);
+            }
+
+            fn get_action(
+                &mut self,
+                chain: &ChainView,
+                block_mined: Option<BlockId>,
+            ) -> Action {
+                if let Some(BlockId(id)) = block_mined {
+                    self.mined = id;
+                }
+                self.inner.get_action(chain, block_mined)
+            }
+
+            fn select_txns(
+                &mut self,
+                mempool: &Mempool,
+                capacity: usize,
+                chain: &ChainView,
+                parent: BlockId,
+            ) -> Vec<Transaction> {
+                let saw_all =
+                    (1..self.mined).all(|id| chain.contains(BlockId(id)));
+                self.saw_all.lock().unwrap().push(saw_all);
+
+                mempool.select(chain, parent, capacity)
+            }
+        }
+
+        let run = |visibility| {
+            let spy = Spy::default();
+            SimulationBuilder::new()
+                .add_miner(spy.clone())
+                .add_miner(Honest::new())
+                .visibility(MinerId(1), visibility)
+                .mempool(Mempool::new(
+                    ArrivalProcess::Rate(1.0),
+                    FeeDistribution::Constant(1.0),
+                ))
+                .rounds(100)
+                .seed(7)
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap();
+
+            let saw_all = spy.saw_all.lock().unwrap().clone();
+            saw_all
+        };
+
+        let full = run(Visibility::Full);
+        assert!(!full.is_empty());
+        assert!(full.iter().all(|&saw_all| saw_all));
+
+        let delayed = run(Visibility::Delayed(3));
+        assert!(delayed.iter().any(|&saw_all| !saw_all));
+    }
+
+    #[test]
+    fn seeded_simulations_are_reproducible() {
+        let run = || {
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Honest::new())
+                .rounds(100)
+                .repeat_all(4)
+                .record_proposers()
+                .seed(7)
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .data()
+        };
+
+        let (first, second) = (run(), run());
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.seed, b.seed);
+            assert_eq!(a.proposers, b.proposers);
+        }
+        assert_ne!(first[0].proposers, first[1].proposers);
+    }
+
+    #[test]
+    fn common_random_numbers_couple_sweep_points() {
+        let data = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .miner_power_iter(MinerId(2), [0.3, 0.35])
+            .rounds(500)
+            .repeat_all(3)
+            .record_proposers()
+            .common_random_numbers()
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data();
+
+        let (low, high) = data.split_at(3);
+        assert_ne!(low[0].seed, low[1].seed);
+        for (a, b) in low.iter().zip(high) {
+            assert_eq!(a.seed, b.seed);
+
+            // Any round won at lower mining power is also won at higher power
+            let (a, b) = (a.proposers.as_ref(), b.proposers.as_ref());
+            for (a, b) in a.unwrap().iter().zip(b.unwrap()) {
+                assert!(*a == MinerId(1) || *b == MinerId(2));
+            }
+        }
+    }
+
+    #[test]
+    fn revenue_sensitivities() {
+        let results = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(HonestForking::new(0.5))
+            .miner_power(MinerId(2), 0.3)
+            .revenue_sensitivity(MinerId(2), 0.05)
+            .param_sensitivity(MinerId(2), "p", 0.25, HonestForking::new)
+            .rounds(2000)
+            .repeat_all(4)
+            .seed(9)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap();
+
+        for output in results.clone().data() {
+            let [power, param] = &output.sensitivities[..] else {
+                panic!("expected two sensitivities");
+            };
+            assert_eq!(
+                (power.param.as_str(), param.param.as_str()),
+                ("Power", "p")
+            );
+            assert!((power.low - 0.25).abs() < 1e-9);
+            assert_eq!((param.low, param.high), (0.25, 0.75));
+            assert!(param.derivative.is_finite());
+        }
+
+        // Honest revenue grows with mining power at a rate of roughly 1
+        let table = results.build();
+        let column = table
+            .titles()
+            .position(|title| title == "Miner 2 Revenue Sensitivity (Power)")
+            .unwrap();
+        let derivative = table.rows().next().unwrap()[column].1.as_f64();
+        let derivative = derivative.unwrap();
+        assert!((derivative - 1.0).abs() < 0.3, "{derivative}");
+        assert!(table
+            .titles()
+            .any(|title| title == "Miner 2 Revenue Sensitivity (p)"));
+
+        let build = |miner, step| {
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Honest::new())
+                .param_sensitivity(miner, "p", step, HonestForking::new)
+                .build()
+        };
+        assert!(matches!(
+            build(MinerId(3), 0.1),
+            Err(SimulationBuildError::SensitivityBadMinerId(MinerId(3)))
+        ));
+        assert!(matches!(
+            build(MinerId(1), 0.0),
+            Err(SimulationBuildError::BadSensitivityStep(_))
+        ));
+        assert!(matches!(
+            build(MinerId(2), 0.1),
+            Err(SimulationBuildError::SensitivityUnknownParam { .. })
+        ));
+    }
+
+    #[test]
+    fn events_reach_subscribers() {
+        #[derive(Debug, Default, Clone, Copy, PartialEq)]
+        struct Counts {
+            published: usize,
+            reorgs: usize,
+            reorg_depth: usize,
+            rounds: usize,
+            finished: usize,
+        }
+
+        let counts =
+            Arc::new(Mutex::new(HashMap::<EventSource, Counts>::new()));
+        let subscriber = counts.clone();
+        let data = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .miner_power_iter(MinerId(2), [0.3, 0.45])
+            .revenue_sensitivity(MinerId(2), 0.05)
+            .rounds(500)
+            .repeat_all(2)
+            .subscribe(move |source, event: &SimulationEvent| {
+                let mut counts = subscriber.lock().unwrap();
+                let counts = counts.entry(source).or_default();
+                match *event {
+                    SimulationEvent::BlockPublished { block, .. } => {
+                        assert!(block.parent_id.is_some());
+                        counts.published += 1;
+                    }
+                    SimulationEvent::ReorgOccurred { depth, .. } => {
+                        counts.reorgs += 1;
+                        counts.reorg_depth += depth;
+                    }
+                    SimulationEvent::RoundCompleted { round, .. } => {
+                        counts.rounds += 1;
+                        assert_eq!(round, counts.rounds);
+                    }
+                    SimulationEvent::SimulationFinished { output } => {
+                        assert_eq!(output.sensitivities.len(), 1);
+                        counts.finished += 1;
+                    }
+                }
+            })
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data();
+
+        // Paired simulations used to estimate sensitivities emit no events
+        let counts = counts.lock().unwrap();
+        assert_eq!(counts.len(), data.len());
+        for (i, output) in data.iter().enumerate() {
+            let source = EventSource {
+                config: i / 2,
+                seed: output.seed,
+            };
+            let expected = Counts {
+                published: output.blocks_published - 1,
+                reorgs: output.reorg_depths.len(),
+                reorg_depth: output.reorg_depths.iter().sum(),
+                rounds: output.rounds,
+                finished: 1,
+            };
+            assert_eq!(counts[&source], expected);
+        }
+        assert!(counts.values().any(|counts| counts.reorgs > 0));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_events_are_emitted() {
+        use std::{
+            fmt::Debug,
+            sync::atomic::{AtomicU64, Ordering},
+        };
+
+        use tracing::{
+            field::{Field, Visit},
+            span, Event, Metadata,
+        };
+
+        /// Counts events by message, and the spans created.
+        #[derive(Default)]
+        struct Recorder {
+            messages: Mutex<HashMap<String, usize>>,
+            spans: AtomicU64,
+        }
+
+        struct Message(String);
+
+        impl Visit for Message {
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                if field.name() == "message" {
+                    self.0 = format!("{:?}", value);
+                }
+            }
+        }
+
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(
+                    self.spans.fetch_add(1, Ordering::Relaxed) + 1,
+                )
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut message = Message(String::new());
+                event.record(&mut message);
+                *self.messages.lock().unwrap().entry(message.0).or_default() +=
+                    1;
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let data = tracing::subscriber::with_default(recorder.clone(), || {
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(NDeficit::new(2))
+                .miner_power(MinerId(2), 0.4)
+                .rounds(1000)
+                .repeat_all(2)
+                .build()
+                .unwrap()
+                .run_all_sequential()
+                .unwrap()
+                .data()
+        });
+
+        let messages = recorder.messages.lock().unwrap();
+        let published: usize =
+            data.iter().map(|output| output.blocks_published - 1).sum();
+        let reorgs: usize =
+            data.iter().map(|output| output.reorg_depths.len()).sum();
+        assert_eq!(recorder.spans.load(Ordering::Relaxed), 2);
+        assert_eq!(messages["block published"], published);
+        assert_eq!(messages["reorg occurred"], reorgs);
+        assert_eq!(messages["simulation finished"], 2);
+        assert!(messages["capitulated"] > 0);
+    }
+
+    #[test]
+    fn snapshots_replay_blockchain() {
+        let recorder = SnapshotRecorder::new().interval(25);
+        let output = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .miner_power(MinerId(2), 0.4)
+            .rounds(510)
+            .include_blockchain()
+            .subscribe(recorder.clone())
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data()
+            .pop()
+            .unwrap();
+        let chain = output.blockchain.unwrap();
+
+        let recordings = recorder.recordings();
+        assert_eq!(recordings.len(), 1);
+        let recording = &recordings[0];
+        assert_eq!(recording.source.seed, output.seed);
+        assert!(recording.finished);
+
+        let mut replayed = Blockchain::new();
+        for (i, frame) in recording.frames.iter().enumerate() {
+            assert!(frame.round % 25 == 0 || frame.round == 510);
+            assert!(i == 0 || frame.round > recording.frames[i - 1].round);
+            for &(id, parent, miner) in frame.blocks.iter() {
+                replayed
+                    .publish(Block {
+                        id,
+                        parent_id: Some(parent),
+                        miner_id: miner,
+                        txns: vec![],
+                    })
+                    .unwrap();
+            }
+            assert!(replayed.tip().contains(&frame.tip));
+        }
+        assert_eq!(replayed.num_blocks(), chain.num_blocks());
+        assert_eq!(recording.frames.last().unwrap().tip, chain.tip()[0]);
+
+        let mut json = vec![];
+        recorder.write_to(&mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(json.lines().count(), recording.frames.len() + 1);
+        assert!(json.starts_with(&format!(
+            "{{\"config\":0,\"seed\":{}}}\n{{\"round\":25,",
+            output.seed
+        )));
+    }
+
+    #[test]
+    fn threshold_search() {
+        let selfish = || {
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Selfish::new())
+                .rounds(3000)
+        };
+        let threshold = ThresholdSearch::new(MinerId(2), selfish)
+            .range(0.15, 0.48)
+            .tolerance(0.02)
+            .seed(4)
+            .run()
+            .unwrap();
+
+        let expected = selfish_threshold(0.0);
+        assert!((threshold.alpha - expected).abs() < 0.05, "{threshold:?}");
+        assert!(threshold.high - threshold.low <= 0.02);
+        assert!(threshold.probes.iter().all(|probe| probe.repeats <= 100));
+        assert!(threshold.simulations() >= 2 * 10);
+
+        let honest = || {
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Honest::new())
+                .rounds(500)
+        };
+        assert!(matches!(
+            ThresholdSearch::new(MinerId(2), honest)
+                .max_repeats(20)
+                .seed(4)
+                .run(),
+            Err(ThresholdError::NeverProfitable(_))
+        ));
+        assert!(matches!(
+            ThresholdSearch::new(MinerId(2), honest)
+                .range(0.6, 0.2)
+                .run(),
+            Err(ThresholdError::BadRange(..))
+        ));
+        assert!(matches!(
+            ThresholdSearch::new(MinerId(2), honest).batch_size(1).run(),
+            Err(ThresholdError::BadBatchSize)
+        ));
+    }
+
+    #[test]
+    fn reset_miners_match_new_miners() {
+        let strategies: [Box<dyn Miner>; 3] = [
+            Box::new(Selfish::new()),
+            Box::new(NDeficit::new(2)),
+            Box::new(DoubleSpend::new(0, 2)),
+        ];
+
+        for strategy in strategies {
+            let builder = || {
+                SimulationBuilder::new()
+                    .add_miner(Honest::new())
+                    .add_boxed_miner(strategy.clone())
+                    .miner_power(MinerId(2), 0.4)
+                    .rounds(500)
+            };
+
+            // Every simulation after the first reuses the same miners
+            let reused = builder()
+                .repeat_all(3)
+                .record_proposers()
+                .seed(5)
+                .build()
+                .unwrap()
+                .run_all_sequential()
+                .unwrap()
+                .data()
+                .pop()
+                .unwrap();
+            let new = builder()
+                .replay_proposers(reused.proposers.unwrap())
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .data()
+                .pop()
+                .unwrap();
+
+            assert_eq!(reused.miner_stats, new.miner_stats);
+            assert_eq!(reused.blocks_by_miner, new.blocks_by_miner);
+        }
+    }
+
+    #[test]
+    fn sequential_run_matches_parallel_run() {
+        let sim = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .rounds(100)
+            .repeat_all(4)
+            .record_proposers()
+            .seed(11)
+            .build()
+            .unwrap();
+
+        let parallel = sim.clone().run_all().unwrap().data();
+        let sequential = sim.clone().run_all_sequential().unwrap().data();
+        for (a, b) in parallel.iter().zip(sequential.iter()) {
+            assert_eq!(a.proposers, b.proposers);
+        }
+
+        let parallelism = Parallelism::new().threads(2).chunk_size(3);
+        let chunked = sim.clone().run_all_with(&parallelism).unwrap().data();
+        let grouped = sim
+            .run_all_with(&parallelism.group_repeats())
+            .unwrap()
+            .data();
+        for outputs in [chunked, grouped] {
+            assert_eq!(outputs.len(), sequential.len());
+            for (a, b) in outputs.iter().zip(sequential.iter()) {
+                assert_eq!(a.proposers, b.proposers);
+            }
+        }
+    }
+
+    #[test]
+    fn mock_rng_always_picks_first_miner() {
+        let output = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .rounds(10)
+            .rng_fn(|_| StepRng::new(0, 0))
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data()
+            .pop()
+            .unwrap();
+
+        assert_eq!(output.miner_stats[&MinerId(1)].blocks_on_chain, 10);
+        assert_eq!(output.miner_stats[&MinerId(2)].blocks_mined, 0);
+    }
+
+    #[test]
+    fn initial_blocks_do_not_count_as_revenue() {
+        /// Honest miner which skips the rounds of the initial blockchain's
+        /// block IDs.
+        #[derive(Debug, Clone)]
+        struct Late(Honest);
+
+        impl Miner for Late {
+            fn name(&self) -> String {
+                "Late".to_string()
+            }
+
+            fn id(&self) -> MinerId {
+                self.0.id()
+            }
+
+            fn set_id(&mut self, id: MinerId) {
+                self.0.set_id(id);
+            }
+
+            fn get_action(
+                &mut self,
+                chain: &ChainView,
+                block_mined: Option<BlockId>,
+            ) -> Action {
+                match block_mined {
+                    Some(BlockId(id)) if id <= 3 => Action::Wait,
+                    _ => self.0.get_action(chain, block_mined),
+                }
+            }
+        }
+
+        let mut initial = Blockchain::new();
+        for id in 1..=3 {
+            let block = Block {
+                id: BlockId(id),
+                parent_id: Some(BlockId(id - 1)),
+                miner_id: MinerId(1),
+                txns: vec![],
+            };
+            initial.publish(block).unwrap();
+        }
+
+        let output = SimulationBuilder::new()
+            .add_miner(Late(Honest::new()))
+            .add_miner(Late(Honest::new()))
+            .blockchain(initial)
+            .rounds(10)
+            .rng_fn(|_| StepRng::new(0, 0))
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data()
+            .pop()
+            .unwrap();
+
+        assert_eq!(output.longest_chain_length, 11);
+        assert_eq!(output.miner_stats[&MinerId(1)].blocks_on_chain, 7);
+        assert_eq!(revenue_of(&MinerId(1), &output), 7.0 / 11.0);
+    }
+
+    #[cfg(feature = "timing")]
+    #[test]
+    fn phase_timings_cover_each_miner() {
+        let results = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .rounds(1000)
+            .repeat_all(2)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap();
+
+        let timings = results.phase_timings();
+        assert_eq!(timings.get_action.len(), 2);
+        assert!(timings.get_action[&MinerId(2)] > Duration::ZERO);
+        assert!(timings.publish > Duration::ZERO);
+        assert_eq!(timings.to_string().lines().count(), 6);
+
+        let data = results.data();
+        let total: super::PhaseTimings =
+            data.iter().map(|output| &output.timings).sum();
+        assert_eq!(total, timings);
+    }
+
+    #[test]
+    fn gamma_out_of_range() {
+        let result = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .gamma(1.5)
+            .build();
+
+        assert!(matches!(result, Err(SimulationBuildError::BadGamma(_))));
+    }
+
+    #[test]
+    fn exhausted_time_budget_skips_configs() {
+        let group = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .next_lineup()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .power_values([0.5, 0.5])
+            .power_values([0.25, 0.75])
+            .time_budget(Duration::ZERO)
+            .build()
+            .unwrap();
+
+        let skipped = |config, power_dist| SkippedConfig {
+            config,
+            lineup: config / 2,
+            power_dist: PowerDistribution::SetValues(power_dist),
+        };
+        let expected = [
+            skipped(0, vec![0.5, 0.5]),
+            skipped(1, vec![0.25, 0.75]),
+            skipped(2, vec![0.5, 0.5]),
+            skipped(3, vec![0.25, 0.75]),
+        ];
+
+        let partitions = group.clone().run_partitioned().unwrap();
+        for (i, (_, results)) in partitions.iter().enumerate() {
+            assert_eq!(results.skipped_configs(), [expected[i].clone()]);
+        }
+
+        let results = group.run_all().unwrap();
+        assert_eq!(results.skipped_configs(), expected);
+        assert_eq!(results.clone().all().build().skipped_configs(), expected);
+
+        let table = results
+            .revenue_for(MinerId::from(2))
+            .format(Format::CSV)
+            .build();
+        assert_eq!(
+            table.to_string(),
+            "Miner 1 Power,Miner 2 Power,Miner 2 Revenue"
+        );
+    }
+
+    #[test]
+    fn errors_are_not_reported_as_skipped_configs() {
+        /// Miner which takes a while to publish a block too large for any
+        /// simulation.
+        #[derive(Debug, Clone, Default)]
+        struct Slow(MinerId);
+
+        impl Miner for Slow {
+            fn name(&self) -> String {
+                "Slow".to_string()
+            }
+
+            fn id(&self) -> MinerId {
+                self.0
+            }
+
+            fn set_id(&mut self, id: MinerId) {
+                self.0 = id;
+            }
+
+            fn get_action(
+                &mut self,
+                chain: &ChainView,
+                block_mined: Option<BlockId>,
+            ) -> Action {
+                std::thread::sleep(Duration::from_millis(20));
+                match block_mined {
+                    Some(id) => Action::Publish(Block {
+                        id,
+                        parent_id: Some(chain.tip()[0]),
+                        miner_id: self.0,
+                        txns: vec![Transaction::new(0.into(), 1.0).size(2)],
+                    }),
+                    None => Action::Wait,
+                }
+            }
+        }
+
+        // The first repeat fails, after which the time budget is exhausted
+        let result = SimulationBuilder::new()
+            .add_miner(Slow::default())
+            .block_capacity(1)
+            .repeat_all(2)
+            .time_budget(Duration::from_millis(10))
+            .build()
+            .unwrap()
+            .run_all_sequential();
+
+        assert!(matches!(
+            result,
+            Err(SimulationError::BlockOverCapacity { .. })
+        ));
+    }
+
+    #[test]
+    fn cancelled_run_skips_configs() {
+        let results = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .repeat_all(3)
+            .build()
+            .unwrap()
+            .run_all_cancellable(&AtomicBool::new(true))
+            .unwrap();
+
+        assert_eq!(results.skipped_configs().len(), 1);
+    }
+
+    #[test]
+    fn partitioned_by_power_dist() {
+        let partitions = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .power_values([0.5, 0.5])
+            .power_values([0.25, 0.75])
+            .power_values([0.5, 0.5])
+            .repeat_all(3)
+            .build()
+            .unwrap()
+            .run_partitioned()
+            .unwrap();
+
+        assert_eq!(partitions.len(), 2);
+        assert_eq!(partitions[0].1.clone().data().len(), 6);
+        assert_eq!(partitions[1].1.clone().data().len(), 3);
+    }
+
+    #[test]
+    fn partitioned_by_lineup() {
+        let partitions = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .next_lineup()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .power_values([0.5, 0.5])
+            .power_values([0.25, 0.75])
+            .power_values([0.5, 0.5])
+            .repeat_all(2)
+            .build()
+            .unwrap()
+            .run_partitioned()
+            .unwrap();
+
+        assert_eq!(partitions.len(), 4);
+        for (i, (dist, results)) in partitions.iter().enumerate() {
+            let data = results.clone().data();
+            let selfish = data
+                .iter()
+                .flat_map(|output| output.miners.values())
+                .any(|name| name == "Selfish");
+
+            assert_eq!(*dist, partitions[i % 2].0);
+            assert_eq!(data.len(), [4, 2][i % 2]);
+            assert_eq!(selfish, i >= 2);
+        }
+    }
+
+    /// Miner which publishes invalid blocks for proof-of-stake simulations:
+    /// either a block with the first ID of the next slot, or two blocks of the
+    /// same slot where one extends the other.
+    #[derive(Debug, Clone)]
+    struct SlotAbuser {
+        id: MinerId,
+        chained: bool,
+    }
+
+    impl Miner for SlotAbuser {
+        fn name(&self) -> String {
+            "SlotAbuser".to_string()
+        }
+
+        fn id(&self) -> MinerId {
+            self.id
+        }
+
+        fn set_id(&mut self, id: MinerId) {
+            self.id = id;
+        }
+
+        fn get_action(
+            &mut self,
+            chain: &ChainView,
+            block_mined: Option<BlockId>,
+        ) -> Action {
+            let Some(BlockId(id)) = block_mined else {
+                return Action::Wait;
+            };
+            let tip = chain.tip()[0];
+
+            if self.chained {
+                Action::PublishSet(vec![
+                    Block {
+                        id: BlockId(id),
+                        parent_id: Some(tip),
+                        miner_id: self.id,
+                        txns: vec![],
+                    },
+                    Block {
+                        id: BlockId(id + 1),
+                        parent_id: Some(BlockId(id)),
+                        miner_id: self.id,
+                        txns: vec![],
+                    },
+                ])
+            } else {
+                Action::Publish(Block {
+                    id: BlockId(id + 2),
+                    parent_id: Some(tip),
+                    miner_id: self.id,
+                    txns: vec![],
+                })
+            }
+        }
+    }
+
+    #[test]
+    fn proof_of_stake_counts_equivocations() {
+        let data = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Equivocating::new(1))
+            .proof_of_stake(ProofOfStake::new())
+            .rounds(500)
+            .seed(7)
+            .build()
+            .unwrap()
+            .run_all()
+            .unwrap()
+            .data();
+
+        let honest = &data[0].miner_stats[&MinerId(1)];
+        let equivocating = &data[0].miner_stats[&MinerId(2)];
+        assert_eq!(honest.equivocations, 0);
+        assert!(equivocating.equivocations > 0);
+        assert_eq!(
+            equivocating.blocks_published,
+            equivocating.blocks_mined + equivocating.equivocations
+        );
+        assert_eq!(equivocating.slashed, 0.0);
+    }
+
+    #[test]
+    fn proof_of_stake_slashing_burns_stake() {
+        let run = |pos: ProofOfStake| {
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Equivocating::new(1))
+                .proof_of_stake(pos)
+                .rounds(2000)
+                .seed(11)
+                .build()
+                .unwrap()
+                .run_all()
+                .unwrap()
+                .data()
+                .remove(0)
+                .miner_stats[&MinerId(2)]
+        };
+
+        let unslashed = run(ProofOfStake::new());
+        let slashed = run(ProofOfStake::new().slashing(0.5));
+        assert!(slashed.slashed > 0.9 && slashed.slashed < 1.0);
+        assert!(slashed.blocks_mined < unslashed.blocks_mined / 10);
+
+        // Burning the whole stake stops the miner from proposing after it is
+        // first caught
+        let burned = run(ProofOfStake::new().slashing(1.0));
+        assert_eq!(burned.slashed, 1.0);
+        assert_eq!(burned.equivocations, 1);
+    }
+
+    #[test]
+    fn proof_of_stake_with_unit_slots_matches_proof_of_work() {
+        let run = |pos: Option<ProofOfStake>| {
+            let mut builder = SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Selfish::new())
+                .power_values([0.6, 0.4])
+                .rounds(300)
+                .seed(3);
+            if let Some(pos) = pos {
+                builder = builder.proof_of_stake(pos);
+            }
+
+            builder.build().unwrap().run_all().unwrap().data().remove(0)
+        };
+
+        let pow = run(None);
+        let pos = run(Some(ProofOfStake::new().slot_capacity(1)));
+        assert_eq!(pow.miner_stats, pos.miner_stats);
+    }
+
+    #[test]
+    fn proof_of_stake_config_is_validated() {
+        let result = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .proof_of_stake(ProofOfStake::new().slot_capacity(0))
+            .build();
+        assert!(matches!(
+            result,
+            Err(SimulationBuildError::ZeroSlotCapacity)
+        ));
+
+        let result = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .proof_of_stake(ProofOfStake::new().slashing(1.5))
+            .build();
+        assert!(matches!(result, Err(SimulationBuildError::BadSlashing(_))));
+    }
+
+    #[test]
+    fn proof_of_stake_rejects_invalid_slots() {
+        let run = |chained: bool| {
+            SimulationBuilder::new()
+                .add_miner(SlotAbuser {
+                    id: MinerId::default(),
+                    chained,
+                })
+                .proof_of_stake(ProofOfStake::new().slot_capacity(2))
+                .rounds(10)
+                .build()
+                .unwrap()
+                .run_all()
+        };
+
+        assert!(matches!(
+            run(false),
+            Err(SimulationError::FutureSlot { .. })
+        ));
+        assert!(matches!(
+            run(true),
+            Err(SimulationError::SameSlotParent { .. })
+        ));
+
+        let stake = ProofOfStake::new().slot_capacity(2);
+        assert_eq!(stake.first_block(3), BlockId(5));
+        assert_eq!(stake.slot(BlockId(6)), 3);
+        assert_eq!(stake.slot(BlockId(0)), 0);
+    }
+}
+
+/// Container for a group of simulations which run on the same lineups of
+/// miners.
+#[derive(Debug, Clone)]
+pub struct SimulationGroup {
+    block_capacity: Option<usize>,
+    block_subsidy: f64,
+    blockchain: Option<Blockchain>,
+    common_random_numbers: bool,
+    events: EventBus,
+    finality: Option<usize>,
+    ghostdag: Option<usize>,
+    include_blockchain: bool,
+    lightweight_output: bool,
+    lineups: Vec<Vec<Box<dyn Miner>>>,
+    mempool: Option<Mempool>,
+    /// Label of each labeled miner, by lineup.
+    miner_labels: Vec<BTreeMap<MinerId, String>>,
+    /// Label of each power distribution, if any.
+    power_dist_labels: Vec<Option<String>>,
+    power_dists: Vec<PowerDistribution>,
+    /// Title and value of the parameter which produced each power
+    /// distribution, if any.
+    power_params: Vec<Option<(String, f64)>>,
+    proof_of_stake: Option<ProofOfStake>,
+    record_proposers: bool,
+    repeat_all: NonZeroUsize,
+    replay_proposers: Option<Arc<[MinerId]>>,
+    revenue_window: Option<(usize, usize)>,
+    rng: Option<RngFactory>,
+    rounds: NonZeroUsize,
+    seed: Option<u64>,
+    /// Pairs of simulations which estimate each sensitivity, by lineup.
+    sensitivities: Vec<Arc<[SensitivityPair]>>,
+    target_chain_length: Option<NonZeroUsize>,
+    time_budget: Option<Duration>,
+    uncle_rewards: Option<UncleRewards>,
+    visibility: BTreeMap<MinerId, Visibility>,
+}
+
+impl SimulationGroup {
     /// Returns the builder for this struct.
     pub fn builder() -> SimulationBuilder {
         SimulationBuilder::new()
     }
 
-    /// Runs all configured simulations.
-    pub fn run_all(self) -> Result<ResultsBuilder, SimulationError> {
+    /// Runs all configured simulations.
+    pub fn run_all(self) -> Result<ResultsBuilder, SimulationError> {
+        self.run_all_with(&Parallelism::default())
+    }
+
+    /// Runs all configured simulations, splitting them between threads as
+    /// configured by `parallelism`. Useful when simulations vary greatly in
+    /// length, e.g. with [`SimulationBuilder::target_chain_length`].
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// let results = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Selfish::new())
+    ///     .repeat_all(10)
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all_with(&Parallelism::new().threads(2).group_repeats())
+    ///     .unwrap();
+    /// ```
+    pub fn run_all_with(
+        self,
+        parallelism: &Parallelism,
+    ) -> Result<ResultsBuilder, SimulationError> {
+        let repeat_all = self.repeat_all;
+        let num_miners = self.num_miners();
+        let (data, skipped) = self.run(None, Some(parallelism), None)?;
+
+        Ok(ResultsBuilder::new(data, repeat_all, num_miners, skipped))
+    }
+
+    /// Runs all configured simulations, calling `observer` with the index of
+    /// each simulation's configuration and its output as soon as the
+    /// simulation finishes. Configurations are indexed in the order
+    /// described in [`ResultsBuilder::data`], and simulations may finish in
+    /// any order when running in parallel. Useful for reporting progress
+    /// during long sweeps.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// use mining_sim::prelude::*;
+    ///
+    /// let finished = AtomicUsize::new(0);
+    ///
+    /// let results = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .add_miner(Selfish::new())
+    ///     .repeat_all(10)
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all_observed(|_, _| {
+    ///         finished.fetch_add(1, Ordering::Relaxed);
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(finished.into_inner(), 10);
+    /// ```
+    pub fn run_all_observed<F>(
+        self,
+        observer: F,
+    ) -> Result<ResultsBuilder, SimulationError>
+    where
+        F: Fn(usize, &SimulationOutput) + Sync,
+    {
+        let repeat_all = self.repeat_all;
+        let num_miners = self.num_miners();
+        let (data, skipped) =
+            self.run(None, Some(&Parallelism::default()), Some(&observer))?;
+
+        Ok(ResultsBuilder::new(data, repeat_all, num_miners, skipped))
+    }
+
+    /// Returns the number of distinct simulation configurations in this
+    /// group, each of which is run once per repeat.
+    pub fn num_configs(&self) -> usize {
+        self.lineups.len() * self.power_dists.len()
+    }
+
+    /// Returns the number of times each configuration is repeated.
+    pub fn repeats(&self) -> usize {
+        self.repeat_all.get()
+    }
+
+    /// Returns the number of miners in each lineup.
+    pub fn num_miners(&self) -> usize {
+        self.lineups[0].len()
+    }
+
+    /// Runs all configured simulations one at a time, in the order they were
+    /// configured, even if the `rayon` feature is enabled. Useful for
+    /// debugging strategies with `println!` or logging, and for profiling
+    /// memory usage deterministically.
+    pub fn run_all_sequential(self) -> Result<ResultsBuilder, SimulationError> {
+        let repeat_all = self.repeat_all;
+        let num_miners = self.num_miners();
+        let (data, skipped) = self.run(None, None, None)?;
+
+        Ok(ResultsBuilder::new(data, repeat_all, num_miners, skipped))
+    }
+
+    /// Runs all configured simulations until `cancel` is set to `true`, after
+    /// which no new simulations are started and running simulations stop
+    /// early. Useful for handling Ctrl-C or stopping work from a GUI.
+    ///
+    /// As with [`SimulationBuilder::time_budget`], only configurations for
+    /// which every repeat ran to completion are reported, and the
+    /// configurations left out can be read using
+    /// [`ResultsBuilder::skipped_configs`].
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::atomic::AtomicBool;
+    ///
+    /// use mining_sim::prelude::*;
+    ///
+    /// let cancel = AtomicBool::new(false);
+    ///
+    /// let results = SimulationBuilder::new()
+    ///     .add_miner(Honest::new())
+    ///     .rounds(1000)
+    ///     .build()
+    ///     .unwrap()
+    ///     .run_all_cancellable(&cancel)
+    ///     .unwrap();
+    /// ```
+    pub fn run_all_cancellable(
+        self,
+        cancel: &AtomicBool,
+    ) -> Result<ResultsBuilder, SimulationError> {
+        let repeat_all = self.repeat_all;
+        let num_miners = self.num_miners();
+        let (data, skipped) =
+            self.run(Some(cancel), Some(&Parallelism::default()), None)?;
+
+        Ok(ResultsBuilder::new(data, repeat_all, num_miners, skipped))
+    }
+
+    /// Runs all configured simulations, and partitions the results by
+    /// lineup and [`PowerDistribution`] so that each configured distribution
+    /// can be analyzed independently. Partitions are given for each lineup
+    /// in turn (see [`SimulationBuilder::next_lineup`]), in the order that
+    /// their distributions were added to the [`SimulationBuilder`], and
+    /// identical distributions share a partition within each lineup.
+    pub fn run_partitioned(
+        self,
+    ) -> Result<Vec<(PowerDistribution, ResultsBuilder)>, SimulationError> {
+        let repeat_all = self.repeat_all;
+        let num_miners = self.num_miners();
+        let num_configs = self.num_configs();
+        let power_dists = self.power_dists.clone();
+
+        let mut partitions: Vec<(usize, PowerDistribution, Vec<_>, Vec<_>)> =
+            vec![];
+        for lineup in 0..self.lineups.len() {
+            for power_dist in power_dists.iter() {
+                if !partitions.iter().any(|(other, dist, _, _)| {
+                    *other == lineup && dist == power_dist
+                }) {
+                    partitions.push((
+                        lineup,
+                        power_dist.clone(),
+                        vec![],
+                        vec![],
+                    ));
+                }
+            }
+        }
+
+        let (data, skipped) =
+            self.run(None, Some(&Parallelism::default()), None)?;
+        let mut data = data.into_iter();
+        let mut skipped = skipped.into_iter().peekable();
+        for config in 0..num_configs {
+            let lineup = config / power_dists.len();
+            let power_dist = &power_dists[config % power_dists.len()];
+            let (_, _, outputs, partition_skipped) = partitions
+                .iter_mut()
+                .find(|(other, dist, _, _)| {
+                    *other == lineup && dist == power_dist
+                })
+                .expect("configuration was partitioned");
+
+            if let Some(config) = skipped.next_if(|s| s.config == config) {
+                partition_skipped.push(config);
+            } else {
+                outputs.extend(data.by_ref().take(repeat_all.get()));
+            }
+        }
+
+        Ok(partitions
+            .into_iter()
+            .map(|(_, dist, outputs, skipped)| {
+                (
+                    dist,
+                    ResultsBuilder::new(
+                        outputs, repeat_all, num_miners, skipped,
+                    ),
+                )
+            })
+            .collect())
+    }
+
+    /// Runs all configured simulations, returning the outputs of each
+    /// configuration which completed, as well as the configurations which
+    /// were skipped, in ascending order. Simulations are
+    /// run in parallel as configured by `parallelism`, or one at a time if it
+    /// is `None` or `rayon` is disabled.
+    ///
+    /// `observer` is called with the index of each simulation's
+    /// configuration and its output as soon as it finishes.
+    #[cfg_attr(
+        any(not(feature = "rayon"), target_arch = "wasm32"),
+        allow(unused_variables)
+    )]
+    fn run(
+        self,
+        cancel: Option<&AtomicBool>,
+        parallelism: Option<&Parallelism>,
+        observer: Option<&Observer<'_>>,
+    ) -> Result<(Vec<SimulationOutput>, Vec<SkippedConfig>), SimulationError>
+    {
         let SimulationGroup {
+            block_capacity,
+            block_subsidy,
             blockchain,
+            common_random_numbers,
+            events,
+            finality,
+            ghostdag,
             include_blockchain,
-            miners,
+            lightweight_output,
+            lineups,
+            mempool,
+            miner_labels,
+            power_dist_labels,
             power_dists,
+            power_params,
+            proof_of_stake,
+            record_proposers,
             repeat_all,
+            replay_proposers,
+            revenue_window,
+            rng,
             rounds,
+            seed,
+            sensitivities,
+            target_chain_length,
+            time_budget,
+            uncle_rewards,
+            visibility,
         } = self;
 
-        let blockchain = blockchain.unwrap_or_default();
+        let blockchain =
+            Arc::new(BlockchainPool::new(blockchain.unwrap_or_default()));
+        let events = Arc::new(events);
+        let start = Instant::now();
+        let run_within_budget = |sim: Simulation| match time_budget {
+            Some(budget) if start.elapsed() >= budget => None,
+            _ => {
+                let config = sim.config;
+                let output = sim.run_paired(cancel).transpose();
+                if let (Some(observer), Some(Ok(output))) = (observer, &output)
+                {
+                    observer(config, output);
+                }
 
-        #[cfg(feature = "rayon")]
-        let outputs: Result<_, _> = power_dists
-            .into_par_iter()
-            .map(|power_dist| Simulation {
-                blockchain: blockchain.clone(),
-                include_blockchain,
-                miners: miners.clone(),
-                power_dist,
-                rounds: rounds.get(),
-            })
-            // Clone each simulation repeat_all times
-            .flat_map(|sim| vec![sim; repeat_all.get()])
-            .map(|sim| sim.run())
-            .collect();
+                output
+            }
+        };
 
-        #[cfg(not(feature = "rayon"))]
-        let outputs: Result<_, _> = power_dists
+        // Miners are cloned as needed by each simulation, rather than once
+        // per simulation up front
+        let lineups: Vec<_> = lineups
             .into_iter()
-            .map(|power_dist| Simulation {
+            .map(|miners| Arc::new(MinerPool::new(miners)))
+            .collect();
+        let lineups = lineups
+            .iter()
+            .zip(miner_labels.iter())
+            .zip(sensitivities.iter());
+        let configs = lineups.flat_map(|((miners, labels), sensitivities)| {
+            (0..power_dists.len()).map(|i| Simulation {
+                block_capacity,
+                block_subsidy,
+                config: 0,
                 blockchain: blockchain.clone(),
+                events: events.clone(),
+                finality,
+                ghostdag,
                 include_blockchain,
+                lightweight_output,
+                mempool: mempool.clone(),
+                miner_labels: labels.clone(),
                 miners: miners.clone(),
-                power_dist,
+                power_dist: power_dists[i].clone(),
+                power_dist_label: power_dist_labels[i].clone(),
+                power_param: power_params[i].clone(),
+                proof_of_stake,
+                record_proposers,
+                replay_proposers: replay_proposers.clone(),
+                revenue_window,
+                rng: rng.clone(),
                 rounds: rounds.get(),
+                seed: 0,
+                sensitivities: sensitivities.clone(),
+                target_chain_length: target_chain_length.map(NonZeroUsize::get),
+                uncle_rewards: uncle_rewards.clone(),
+                visibility: visibility.clone(),
             })
+        });
+
+        // Seeds are assigned before running simulations in parallel, so that
+        // they do not depend on the order of execution
+        let mut seeder = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let common_seeds: Option<Vec<u64>> = common_random_numbers
+            .then(|| (0..repeat_all.get()).map(|_| seeder.gen()).collect());
+        let sims: Vec<_> = configs
             // Clone each simulation repeat_all times
-            .flat_map(|sim| vec![sim; repeat_all.get()])
-            .map(|sim| sim.run())
+            .enumerate()
+            .flat_map(|(config, sim)| {
+                vec![Simulation { config, ..sim }; repeat_all.get()]
+            })
+            .enumerate()
+            .map(|(i, sim)| Simulation {
+                seed: match common_seeds.as_ref() {
+                    Some(seeds) => seeds[i % repeat_all.get()],
+                    None => seeder.gen(),
+                },
+                ..sim
+            })
             .collect();
 
-        Ok(ResultsBuilder::new(outputs?, repeat_all))
+        #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+        let outputs: Vec<_> = match parallelism {
+            Some(parallelism) => {
+                parallelism.run(sims, repeat_all.get(), run_within_budget)?
+            }
+            None => sims.into_iter().map(run_within_budget).collect(),
+        };
+
+        #[cfg(any(not(feature = "rayon"), target_arch = "wasm32"))]
+        let outputs: Vec<_> = sims.into_iter().map(run_within_budget).collect();
+
+        // Only keep configurations for which every repeat was run
+        let mut data = Vec::with_capacity(outputs.len());
+        let mut skipped = vec![];
+        let mut outputs = outputs.into_iter();
+        for config in 0.. {
+            let repeats: Vec<_> =
+                outputs.by_ref().take(repeat_all.get()).collect();
+
+            if repeats.is_empty() {
+                break;
+            }
+
+            if repeats.iter().all(Option::is_some) {
+                for output in repeats.into_iter().flatten() {
+                    data.push(output?);
+                }
+            } else if let Some(Some(Err(error))) = repeats
+                .into_iter()
+                .find(|output| matches!(output, Some(Err(_))))
+            {
+                // Errors are reported even if another repeat was cut short
+                return Err(error);
+            } else {
+                skipped.push(SkippedConfig {
+                    config,
+                    lineup: config / power_dists.len(),
+                    power_dist: power_dists[config % power_dists.len()].clone(),
+                });
+            }
+        }
+
+        Ok((data, skipped))
     }
 }
 
@@ -282,96 +3893,1366 @@ impl SimulationGroup {
 /// given order.
 #[derive(Debug, Clone)]
 struct Simulation {
-    blockchain: Blockchain,
+    block_capacity: Option<usize>,
+    block_subsidy: f64,
+    /// Index of this simulation's configuration, in the order described in
+    /// [`ResultsBuilder::data`].
+    config: usize,
+    /// Provides the initial blockchain, shared by all simulations of a group.
+    blockchain: Arc<BlockchainPool>,
+    /// Subscribers to events, shared by all simulations of a group.
+    events: Arc<EventBus>,
+    finality: Option<usize>,
+    ghostdag: Option<usize>,
     include_blockchain: bool,
-    miners: Vec<Box<dyn Miner>>,
+    lightweight_output: bool,
+    mempool: Option<Mempool>,
+    miner_labels: BTreeMap<MinerId, String>,
+    /// Provides the lineup of miners, shared by all simulations of the
+    /// lineup.
+    miners: Arc<MinerPool>,
     power_dist: PowerDistribution,
+    power_dist_label: Option<String>,
+    power_param: Option<(String, f64)>,
+    proof_of_stake: Option<ProofOfStake>,
+    record_proposers: bool,
+    replay_proposers: Option<Arc<[MinerId]>>,
+    revenue_window: Option<(usize, usize)>,
+    rng: Option<RngFactory>,
     rounds: usize,
+    seed: u64,
+    sensitivities: Arc<[SensitivityPair]>,
+    target_chain_length: Option<usize>,
+    uncle_rewards: Option<UncleRewards>,
+    visibility: BTreeMap<MinerId, Visibility>,
 }
 
 /// Contains the output data from a simulation.
 #[derive(Debug, Clone)]
 pub struct SimulationOutput {
     pub blockchain: Option<Blockchain>,
-    pub blocks_by_miner: HashMap<MinerId, Vec<BlockId>>,
+    /// Estimated peak memory used by the blockchain in bytes. See
+    /// [`Blockchain::memory_usage`].
+    pub blockchain_memory: usize,
+    /// Inclusion latency of each blacklisted transaction on the longest
+    /// chain. See [`Mempool::blacklisted_latencies`]. Empty if the simulation
+    /// has no [`Mempool`].
+    pub blacklisted_latencies: Vec<usize>,
+    /// IDs of the blocks published by each miner. Not present if
+    /// [`SimulationBuilder::lightweight_output`] was called.
+    pub blocks_by_miner: Option<HashMap<MinerId, Vec<BlockId>, BlockHasher>>,
     pub blocks_published: usize,
-    pub longest_chain: HashSet<BlockId>,
+    /// IDs of every block in the order given by the GHOSTDAG rule. Only
+    /// present in [block-DAG](SimulationBuilder::ghostdag) simulations, if
+    /// [`SimulationBuilder::lightweight_output`] was not called.
+    pub dag_order: Option<Vec<BlockId>>,
+    /// Outcome of each double-spend attempt scheduled with
+    /// [`Mempool::double_spend`]. Empty if the simulation has no
+    /// [`Mempool`].
+    pub double_spends: Vec<DoubleSpendOutcome>,
+    /// Length of each fork off of the longest chain. See
+    /// [`Blockchain::fork_lengths`].
+    pub fork_lengths: Vec<usize>,
+    /// Inclusion latency of each transaction on the longest chain. See
+    /// [`Mempool::inclusion_latencies`]. Empty if the simulation has no
+    /// [`Mempool`].
+    pub inclusion_latencies: Vec<usize>,
+    /// IDs of the blocks on the longest chain. Not present if
+    /// [`SimulationBuilder::lightweight_output`] was called.
+    pub longest_chain: Option<BlockSet>,
+    /// Number of blocks on the longest chain, including the genesis block.
+    /// Counts the blue blocks instead in
+    /// [block-DAG](SimulationBuilder::ghostdag) simulations.
+    pub longest_chain_length: usize,
+    /// Block statistics of each miner.
+    pub miner_stats: HashMap<MinerId, MinerStats>,
     pub miners: HashMap<MinerId, String>,
+    /// Label of each miner added with [`SimulationBuilder::add_miner_named`].
+    /// Miners without a label are not included.
+    pub miner_labels: HashMap<MinerId, String>,
+    /// Strategy parameters of each miner. See [`Miner::params`].
+    pub miner_params: HashMap<MinerId, Vec<(String, f64)>>,
     pub power_dist: PowerDistribution,
+    /// Label given to the power distribution with
+    /// [`SimulationBuilder::labeled_power_dist`], if any.
+    pub power_dist_label: Option<String>,
+    /// Title and value of the parameter passed to
+    /// [`SimulationBuilder::power_curve`] to produce the power distribution,
+    /// if any.
+    pub power_param: Option<(String, f64)>,
+    /// Proposer of each round, in order. Only present if
+    /// [`SimulationBuilder::record_proposers`] was called.
+    pub proposers: Option<Vec<MinerId>>,
+    /// Depth of each reorganization of the longest chain, in order. The depth
+    /// of a reorganization is the number of blocks which were on the longest
+    /// chain before it and are not on the longest chain after it.
+    pub reorg_depths: Vec<usize>,
+    /// Only present if [`SimulationBuilder::revenue_series`] was called.
+    pub revenue_series: Option<RevenueSeries>,
+    /// Number of rounds which were run.
     pub rounds: usize,
+    /// Seed of the simulation's random number generator.
+    pub seed: u64,
+    /// Estimated derivatives of revenue. Empty unless
+    /// [`SimulationBuilder::revenue_sensitivity`] or
+    /// [`SimulationBuilder::param_sensitivity`] was called.
+    pub sensitivities: Vec<Sensitivity>,
+    /// Time spent in each phase of the simulation. Requires the `timing`
+    /// feature.
+    #[cfg(feature = "timing")]
+    pub timings: PhaseTimings,
+    /// Configuration of the simulation's transactions, including the seed
+    /// used to generate them. Not present if the simulation has no
+    /// [`Mempool`].
+    pub txn_generator: Option<GeneratorConfig>,
+}
+
+/// Block statistics of a miner in a simulation.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MinerStats {
+    /// Number of rounds in which the miner was the block proposer.
+    pub blocks_mined: usize,
+    /// Number of blocks published by the miner, not counting
+    /// [rejected](MinerStats::blocks_rejected) blocks.
+    pub blocks_published: usize,
+    /// Number of blocks published by the miner during the simulation which
+    /// are on the longest chain, or which are blue in
+    /// [block-DAG](SimulationBuilder::ghostdag) simulations.
+    pub blocks_on_chain: usize,
+    /// Number of blocks the miner tried to publish which were rejected for
+    /// not descending from the last [finalized](SimulationBuilder::finality)
+    /// checkpoint.
+    pub blocks_rejected: usize,
+    /// Number of blocks published by the miner for a
+    /// [proof-of-stake](SimulationBuilder::proof_of_stake) slot which already
+    /// had a published block.
+    pub equivocations: usize,
+    /// Fraction of the miner's stake burned by
+    /// [slashing](ProofOfStake::slashing).
+    pub slashed: f64,
+    /// Total [block subsidy](SimulationBuilder::block_subsidy) of the miner's
+    /// blocks on the longest chain.
+    pub subsidy_income: f64,
+    /// Total transaction fees paid to the miner by its blocks on the longest
+    /// chain, after any base fees are burned. See
+    /// [`Mempool::miner_fees`].
+    pub fee_income: f64,
+    /// Number of blocks published by the miner which were referenced as
+    /// [uncles](SimulationBuilder::uncle_rewards).
+    pub uncles: usize,
+    /// Total [uncle rewards](SimulationBuilder::uncle_rewards) of the miner's
+    /// blocks which were referenced as uncles.
+    pub uncle_income: f64,
+    /// Total [uncle rewards](SimulationBuilder::uncle_rewards) of the miner's
+    /// blocks for referencing uncles.
+    pub nephew_income: f64,
+}
+
+/// Estimated derivative of a miner's revenue with respect to a parameter,
+/// from a pair of simulations which share a seed. See
+/// [`SimulationBuilder::revenue_sensitivity`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensitivity {
+    pub miner: MinerId,
+    /// `"Power"` for mining power, or the name of a strategy parameter.
+    pub param: String,
+    /// Value of the parameter in the lower paired simulation.
+    pub low: f64,
+    /// Value of the parameter in the higher paired simulation.
+    pub high: f64,
+    /// Difference in the miner's revenue between the paired simulations,
+    /// divided by `high - low`.
+    pub derivative: f64,
+}
+
+/// Time spent in each phase of one or more simulations. Adding two
+/// [`PhaseTimings`] combines their times, so the timings of a set of
+/// simulations can be totaled with [`Iterator::sum`]. Displays as a CSV table
+/// with one row per phase.
+///
+/// Requires the `timing` feature.
+#[cfg(feature = "timing")]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PhaseTimings {
+    /// Time spent choosing the block proposer of each round.
+    pub proposer_sampling: Duration,
+    /// Time spent in [`Miner::get_action`] by each miner.
+    pub get_action: HashMap<MinerId, Duration>,
+    /// Time spent adding published blocks to the blockchain with
+    /// [`Blockchain::publish`].
+    pub publish: Duration,
+    /// Time spent computing the [`SimulationOutput`] once all rounds were
+    /// run.
+    pub results: Duration,
+}
+
+#[cfg(feature = "timing")]
+impl PhaseTimings {
+    /// Total time spent in all phases.
+    pub fn total(&self) -> Duration {
+        self.proposer_sampling
+            + self.get_action.values().sum::<Duration>()
+            + self.publish
+            + self.results
+    }
+
+    fn phase_mut(&mut self, phase: Phase) -> &mut Duration {
+        match phase {
+            Phase::ProposerSampling => &mut self.proposer_sampling,
+            Phase::GetAction(miner_id) => {
+                self.get_action.entry(miner_id).or_default()
+            }
+            Phase::Publish => &mut self.publish,
+            Phase::Results => &mut self.results,
+        }
+    }
+}
+
+#[cfg(feature = "timing")]
+impl std::ops::AddAssign<&PhaseTimings> for PhaseTimings {
+    fn add_assign(&mut self, rhs: &PhaseTimings) {
+        self.proposer_sampling += rhs.proposer_sampling;
+        for (&miner_id, &time) in rhs.get_action.iter() {
+            *self.get_action.entry(miner_id).or_default() += time;
+        }
+        self.publish += rhs.publish;
+        self.results += rhs.results;
+    }
+}
+
+#[cfg(feature = "timing")]
+impl<'a> std::iter::Sum<&'a PhaseTimings> for PhaseTimings {
+    fn sum<I: Iterator<Item = &'a PhaseTimings>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut total, timings| {
+            total += timings;
+            total
+        })
+    }
+}
+
+#[cfg(feature = "timing")]
+impl std::fmt::Display for PhaseTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.total().as_secs_f64();
+        let mut get_action: Vec<_> = self.get_action.iter().collect();
+        get_action.sort_unstable();
+
+        let phases =
+            [("Proposer Sampling".to_string(), self.proposer_sampling)]
+                .into_iter()
+                .chain(get_action.into_iter().map(|(miner_id, &time)| {
+                    (format!("Miner {miner_id} get_action"), time)
+                }))
+                .chain([
+                    ("Publish".to_string(), self.publish),
+                    ("Results".to_string(), self.results),
+                ]);
+
+        write!(f, "Phase,Seconds,Share")?;
+        for (phase, time) in phases {
+            let share = match total > 0.0 {
+                true => time.as_secs_f64() / total,
+                false => 0.0,
+            };
+            write!(
+                f,
+                "\n{},{:.3$},{:.3$}",
+                phase,
+                time.as_secs_f64(),
+                share,
+                crate::results::F64_DISPLAY_DIGITS
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Phase of a simulation timed by a [`PhaseTimer`].
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "timing"), allow(dead_code))]
+enum Phase {
+    ProposerSampling,
+    GetAction(MinerId),
+    Publish,
+    Results,
+}
+
+/// Records the time spent in each [`Phase`] of a simulation. Does nothing
+/// unless the `timing` feature is enabled, in which case it produces
+/// [`PhaseTimings`].
+#[derive(Debug, Default)]
+struct PhaseTimer {
+    #[cfg(feature = "timing")]
+    timings: PhaseTimings,
+}
+
+impl PhaseTimer {
+    /// Marks the start of a phase, to be passed to [`PhaseTimer::stop`].
+    #[inline]
+    fn start(&self) -> Option<Instant> {
+        #[cfg(feature = "timing")]
+        return Some(Instant::now());
+        #[cfg(not(feature = "timing"))]
+        return None;
+    }
+
+    /// Adds the time since `start` to `phase`.
+    #[inline]
+    #[cfg_attr(not(feature = "timing"), allow(unused_variables))]
+    fn stop(&mut self, phase: Phase, start: Option<Instant>) {
+        #[cfg(feature = "timing")]
+        if let Some(start) = start {
+            *self.timings.phase_mut(phase) += start.elapsed();
+        }
+    }
+}
+
+/// Revenue of each miner over sliding windows of simulation rounds.
+///
+/// Window `i` covers rounds `i * step + 1` through `i * step + window`, and
+/// only windows which end before the simulation does are included. The
+/// revenue of a miner in a window is the fraction of longest chain blocks
+/// mined during the window which belong to that miner, or `NaN` if no
+/// longest chain blocks were mined during the window.
+#[derive(Debug, Clone)]
+pub struct RevenueSeries {
+    /// Number of rounds in each window.
+    pub window: usize,
+    /// Number of rounds between the starts of consecutive windows.
+    pub step: usize,
+    /// Revenue of each miner in each window, in order.
+    pub revenue: HashMap<MinerId, Vec<f64>>,
+}
+
+impl RevenueSeries {
+    /// Computes the revenue series of the longest chain of `chain`, where
+    /// each round has `slot_capacity` block IDs.
+    fn new(
+        chain: &Blockchain,
+        miners: &[Box<dyn Miner>],
+        rounds: usize,
+        slot_capacity: usize,
+        window: usize,
+        step: usize,
+    ) -> Self {
+        // Miner of the longest chain block mined in each round, if any
+        let mut lc_by_round = vec![None; rounds + 1];
+        for block_id in chain.longest_chain() {
+            let round = stake::slot(block_id, slot_capacity);
+            if let Some(slot) = lc_by_round.get_mut(round) {
+                *slot = Some(chain[block_id].block.miner_id);
+            }
+        }
+        lc_by_round[0] = None;
+
+        let mut revenue: HashMap<_, Vec<_>> =
+            miners.iter().map(|m| (m.id(), vec![])).collect();
+        let mut counts: HashMap<MinerId, usize> = HashMap::new();
+        let mut total = 0;
+
+        // Counts cover the rounds in lo+1..=hi
+        let (mut lo, mut hi) = (0, 0);
+        for start in (0..).step_by(step) {
+            let end = start + window;
+            if end > rounds {
+                break;
+            }
+
+            if start >= hi {
+                counts.clear();
+                total = 0;
+                (lo, hi) = (start, start);
+            }
+            while lo < start {
+                lo += 1;
+                if let Some(miner_id) = lc_by_round[lo] {
+                    *counts.get_mut(&miner_id).unwrap() -= 1;
+                    total -= 1;
+                }
+            }
+            while hi < end {
+                hi += 1;
+                if let Some(miner_id) = lc_by_round[hi] {
+                    *counts.entry(miner_id).or_default() += 1;
+                    total += 1;
+                }
+            }
+
+            for (miner_id, series) in revenue.iter_mut() {
+                let count = counts.get(miner_id).copied().unwrap_or_default();
+                series.push(count as f64 / total as f64);
+            }
+        }
+
+        Self {
+            window,
+            step,
+            revenue,
+        }
+    }
+}
+
+/// Controls how [`SimulationGroup::run_all_with`] splits simulations between
+/// threads. By default, simulations run on the global `rayon` thread pool,
+/// which splits them adaptively.
+///
+/// Has no effect unless the `rayon` feature is enabled.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    any(not(feature = "rayon"), target_arch = "wasm32"),
+    allow(dead_code)
+)]
+pub struct Parallelism {
+    chunk_size: usize,
+    group_repeats: bool,
+    threads: Option<usize>,
+}
+
+impl Parallelism {
+    /// Create a new [`Parallelism`] with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run simulations on a new pool of `threads` threads instead of the
+    /// global `rayon` thread pool. If `threads` is 0, `rayon` chooses the
+    /// number of threads.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+
+        self
+    }
+
+    /// Hand simulations to threads in chunks of at least `size`, rather than
+    /// letting `rayon` split them down to single simulations. Larger chunks
+    /// reduce scheduling overhead when there are many short simulations.
+    /// With [`Parallelism::group_repeats`], chunks are counted in
+    /// configurations.
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size;
+
+        self
+    }
+
+    /// Run all repeats of each configuration on the same thread, one after
+    /// another, so that a slow configuration holds up a single thread
+    /// instead of being spread across all of them.
+    pub fn group_repeats(mut self) -> Self {
+        self.group_repeats = true;
+
+        self
+    }
+
+    /// Runs `f` on each of `sims` in parallel, where each configuration is
+    /// repeated `repeats` times, and returns the results in order.
+    #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+    fn run<F, T>(
+        &self,
+        sims: Vec<Simulation>,
+        repeats: usize,
+        f: F,
+    ) -> Result<Vec<T>, SimulationError>
+    where
+        F: Fn(Simulation) -> T + Sync,
+        T: Send,
+    {
+        let min_len = self.chunk_size.max(1);
+        let run = || -> Vec<T> {
+            match self.group_repeats {
+                true => {
+                    let mut sims = sims.into_iter();
+                    let configs: Vec<Vec<_>> = std::iter::from_fn(|| {
+                        let config: Vec<_> =
+                            sims.by_ref().take(repeats).collect();
+                        (!config.is_empty()).then_some(config)
+                    })
+                    .collect();
+
+                    configs
+                        .into_par_iter()
+                        .with_min_len(min_len)
+                        .flat_map_iter(|config| config.into_iter().map(&f))
+                        .collect()
+                }
+                false => {
+                    sims.into_par_iter().with_min_len(min_len).map(&f).collect()
+                }
+            }
+        };
+
+        match self.threads {
+            Some(threads) => {
+                let pool = rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()?;
+
+                Ok(pool.install(run))
+            }
+            None => Ok(run()),
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum SimulationError {
     #[error("block could not be published")]
     BlockPublishingError(#[from] BlockPublishingError),
+    #[error("block could not be added to the block DAG")]
+    DagError(#[from] DagError),
+    #[error("block {block} has size {size}, over the capacity {capacity}")]
+    BlockOverCapacity {
+        block: BlockId,
+        size: usize,
+        capacity: usize,
+    },
     #[error("invalid mining power distribution")]
     PowerDistributionError(#[from] PowerDistributionError),
     #[error("could not create rand::distributions::WeightedIndex")]
     WeightedIndexError(#[from] WeightedError),
+    #[error("block {block} was published in round {round}, before its slot")]
+    FutureSlot { block: BlockId, round: usize },
+    #[error("block {block} points to block {parent} of the same slot")]
+    SameSlotParent { block: BlockId, parent: BlockId },
+    #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+    #[error("could not build thread pool")]
+    ThreadPoolError(#[from] rayon::ThreadPoolBuildError),
 }
 
 impl Simulation {
-    /// Executes the configured simulation.
-    fn run(self) -> Result<SimulationOutput, SimulationError> {
+    /// Number of rounds between checks for cancellation.
+    const CANCEL_CHECK_INTERVAL: usize = 1 << 10;
+
+    /// Executes the configured simulation, followed by the paired
+    /// simulations of each of its sensitivities. Returns `None` if any of
+    /// them was cancelled before it could finish.
+    fn run_paired(
+        self,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<Option<SimulationOutput>, SimulationError> {
+        let events = self.events.clone();
+        let source = self.event_source();
+        let output = match self.sensitivities.is_empty() {
+            true => self.run(cancel)?,
+            false => self.run_sensitivities(cancel)?,
+        };
+
+        if let Some(output) = output.as_ref() {
+            if !events.is_empty() {
+                events.emit(
+                    source,
+                    SimulationEvent::SimulationFinished { output },
+                );
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Identifies this simulation in the events it emits.
+    fn event_source(&self) -> EventSource {
+        EventSource {
+            config: self.config,
+            seed: self.seed,
+        }
+    }
+
+    /// Executes the configured simulation and the paired simulations of
+    /// each of its sensitivities.
+    fn run_sensitivities(
+        self,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<Option<SimulationOutput>, SimulationError> {
+        // Only the revenue of paired simulations is needed, and their events
+        // are not reported
+        let pairs = self.sensitivities.clone();
+        let paired = Simulation {
+            events: Arc::default(),
+            include_blockchain: false,
+            lightweight_output: true,
+            record_proposers: false,
+            revenue_window: None,
+            sensitivities: Arc::new([]),
+            ..self.clone()
+        };
+
+        let Some(mut output) = self.run(cancel)? else {
+            return Ok(None);
+        };
+        for pair in pairs.iter() {
+            match pair.estimate(&paired, cancel)? {
+                Some(sensitivity) => output.sensitivities.push(sensitivity),
+                None => return Ok(None),
+            }
+        }
+
+        Ok(Some(output))
+    }
+
+    /// Executes the configured simulation. Returns `None` if the simulation
+    /// was cancelled before it could finish.
+    fn run(
+        self,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<Option<SimulationOutput>, SimulationError> {
+        let event_source = self.event_source();
         let Simulation {
-            mut blockchain,
+            block_capacity,
+            block_subsidy,
+            config: _,
+            blockchain: pool,
+            events,
+            finality,
+            ghostdag,
             include_blockchain,
-            mut miners,
+            lightweight_output,
+            mut mempool,
+            miner_labels,
+            miners: miner_pool,
             power_dist,
-            rounds,
+            power_dist_label,
+            power_param,
+            proof_of_stake,
+            record_proposers,
+            replay_proposers,
+            revenue_window,
+            rng,
+            rounds: max_rounds,
+            seed,
+            sensitivities: _,
+            target_chain_length,
+            uncle_rewards,
+            visibility,
         } = self;
 
-        let mut blocks_by_miner: HashMap<_, Vec<_>> = HashMap::new();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "simulation",
+            config = event_source.config,
+            seed,
+            rounds = max_rounds
+        )
+        .entered();
+
+        let mut blockchain = pool.take();
+        if finality.is_some() {
+            blockchain.finalize_every(finality);
+        }
+        let mut dag = ghostdag.map(|_| BlockDag::from_chain(&blockchain));
+        // Blocks referenced by each block of a block-DAG simulation, besides
+        // its parent, from the round in which it was mined
+        let mut dag_refs: HashMap<BlockId, Vec<BlockId>> = HashMap::new();
+        let mut miners = miner_pool.take();
+        let mut blocks_by_miner =
+            (!lightweight_output).then(HashMap::<_, Vec<_>, _>::default);
+        let mut miner_stats: HashMap<_, MinerStats> = miners
+            .iter()
+            .map(|m| (m.id(), MinerStats::default()))
+            .collect();
+        let mut proposers = record_proposers.then(Vec::new);
+        let mut reorgs = ReorgTracker::new(&blockchain);
+        let mut views = ViewTracker::new(&blockchain, &visibility);
+        let mut timer = PhaseTimer::default();
+        let mut rounds = 0;
+
+        let mut rng = match rng {
+            Some(factory) => factory.call(seed),
+            None => Box::new(StdRng::seed_from_u64(seed)),
+        };
+        for miner in miners.iter_mut() {
+            miner.set_rng_seed(rng.gen());
+            miner.on_simulation_start();
+        }
+        let mut txn_rng = mempool.as_mut().map(|mempool| {
+            mempool.update(&blockchain);
+            mempool.generator(seed)
+        });
 
         // Safety: power distributions are validated during the simulation
         // build process
         let power_values = unsafe { power_dist.values_unchecked(miners.len()) };
-        let gamma = WeightedIndex::new(power_values)?
-            .sample_iter(rand::thread_rng())
-            .enumerate()
-            .map(|(round, proposer)| (round + 1, MinerId(proposer + 1)))
-            .take(self.rounds);
+        let mut source = match replay_proposers {
+            Some(replayed) => ProposerSource::Replay(replayed),
+            // One-hot distributions always choose the same proposer
+            None => match power_values.iter().position(|&power| power == 1.0) {
+                Some(index) => ProposerSource::Fixed(MinerId(index + 1)),
+                None => ProposerSource::Sample(WeightedIndex::new(
+                    power_values.iter(),
+                )?),
+            },
+        };
+
+        // Stake of each miner, and number of blocks published for each slot
+        // of a proof-of-stake simulation
+        let slot_capacity = proof_of_stake.map_or(1, |pos| pos.slot_capacity);
+        let mut stakes = power_values;
+        let mut slot_blocks: HashMap<usize, usize> = HashMap::new();
+        // Blocks rejected for conflicting with a finalized checkpoint
+        let mut rejected = HashSet::new();
+
+        let cancelled =
+            || cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+
+        for round in 1..=max_rounds {
+            if round % Self::CANCEL_CHECK_INTERVAL == 1 && cancelled() {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(round, "simulation cancelled");
+                return Ok(None);
+            }
+
+            let start = timer.start();
+            let proposer = source.proposer(round, &mut rng);
+            timer.stop(Phase::ProposerSampling, start);
+            if let Some(proposers) = proposers.as_mut() {
+                proposers.push(proposer);
+            }
+            miner_stats.get_mut(&proposer).unwrap().blocks_mined += 1;
+
+            if let (Some(mempool), Some(txn_rng)) =
+                (mempool.as_mut(), txn_rng.as_mut())
+            {
+                mempool.arrive(round, txn_rng);
+            }
 
-        for (round, proposer) in gamma {
             for m in miners.iter_mut() {
                 let miner_id = m.id();
 
-                let block_mined =
-                    (proposer == miner_id).then_some(BlockId(round));
+                let block_mined = (proposer == miner_id)
+                    .then(|| stake::first_block(round, slot_capacity));
 
-                let blocks_published =
-                    match m.get_action(&blockchain, block_mined) {
-                        Action::Wait => vec![],
-                        Action::Publish(block) => vec![block],
-                        Action::PublishSet(blocks) => blocks,
-                    };
+                let view = views.view(miner_id, round, &blockchain);
+                let start = timer.start();
+                let action = m.get_action(&view, block_mined);
+                timer.stop(Phase::GetAction(miner_id), start);
+                if let (Some(dag), Some(block_id)) = (dag.as_ref(), block_mined)
+                {
+                    let tips: Vec<_> = dag
+                        .tips()
+                        .iter()
+                        .copied()
+                        .filter(|&tip| view.contains(tip))
+                        .collect();
+                    dag_refs
+                        .insert(block_id, m.dag_refs(&view, &tips, block_id));
+                }
+                let blocks_published = match action {
+                    Action::Wait => vec![],
+                    Action::Publish(block) => vec![block],
+                    Action::PublishSet(blocks) => blocks,
+                };
 
-                for block in blocks_published {
+                for mut block in blocks_published {
                     assert_eq!(
                         block.miner_id, miner_id,
                         "Miner {} published block with wrong MinerId",
                         miner_id
                     );
+                    if proof_of_stake.is_some() {
+                        check_slot(&block, round, slot_capacity)?;
+                    }
+                    if finality.is_some()
+                        && conflicts_with_checkpoint(
+                            &block,
+                            &blockchain,
+                            &rejected,
+                        )
+                    {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(
+                            round,
+                            block = %block.id,
+                            checkpoint = %blockchain.last_checkpoint(),
+                            "block conflicts with checkpoint"
+                        );
+                        rejected.insert(block.id);
+                        miner_stats
+                            .get_mut(&miner_id)
+                            .unwrap()
+                            .blocks_rejected += 1;
+                        continue;
+                    }
+                    let stats = miner_stats.get_mut(&miner_id).unwrap();
+                    stats.blocks_published += 1;
+
+                    if let Some(blocks_by_miner) = blocks_by_miner.as_mut() {
+                        blocks_by_miner
+                            .entry(miner_id)
+                            .or_default()
+                            .push(block.id);
+                    }
+                    if let (Some(mempool), Some(parent_id)) =
+                        (mempool.as_ref(), block.parent_id)
+                    {
+                        if block.txns.is_empty() {
+                            // Rebuilt for each block, since the miner sees
+                            // the blocks it published earlier in the round
+                            let view = views.view(miner_id, round, &blockchain);
+                            block.txns = m.select_txns(
+                                mempool,
+                                block_capacity.unwrap_or(usize::MAX),
+                                &view,
+                                parent_id,
+                            );
+                        }
+                    }
 
-                    blocks_by_miner.entry(miner_id).or_default().push(block.id);
+                    if let Some(capacity) = block_capacity {
+                        let size = block.txns.iter().map(|txn| txn.size).sum();
+                        if size > capacity {
+                            return Err(SimulationError::BlockOverCapacity {
+                                block: block.id,
+                                size,
+                                capacity,
+                            });
+                        }
+                    }
+
+                    let block_id = block.id;
+                    views.publish(&block, round);
+                    let start = timer.start();
                     blockchain.publish(block)?;
+                    if let Some(dag) = dag.as_mut() {
+                        let refs =
+                            dag_refs.remove(&block_id).unwrap_or_default();
+                        let parent = blockchain[block_id].block.parent_id;
+                        dag.insert(
+                            block_id,
+                            miner_id,
+                            parent.into_iter().chain(refs),
+                        )?;
+                    }
+                    timer.stop(Phase::Publish, start);
+                    if let Some(pos) = proof_of_stake.as_ref() {
+                        let slot = stake::slot(block_id, slot_capacity);
+                        let published = slot_blocks.entry(slot).or_default();
+                        *published += 1;
+                        if *published > 1 {
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!(
+                                round,
+                                slot,
+                                miner = %miner_id,
+                                "equivocation detected"
+                            );
+                            let stats = miner_stats.get_mut(&miner_id).unwrap();
+                            stats.equivocations += 1;
+                            if let (2, Some(fraction)) =
+                                (*published, pos.slashing)
+                            {
+                                stats.slashed +=
+                                    (1.0 - stats.slashed) * fraction;
+                                source.slash(
+                                    &mut stakes,
+                                    miner_id,
+                                    fraction,
+                                )?;
+                            }
+                        }
+                    }
+                    let old_tip = reorgs.tip;
+                    let reorg_depth = reorgs.update(&blockchain);
+                    #[cfg(feature = "tracing")]
+                    {
+                        let block = &blockchain[block_id];
+                        tracing::trace!(
+                            round,
+                            block = %block_id,
+                            parent = ?block.block.parent_id.map(|id| id.get()),
+                            height = block.height,
+                            miner = %miner_id,
+                            "block published"
+                        );
+                        if let Some(depth) = reorg_depth {
+                            tracing::debug!(
+                                round,
+                                depth,
+                                old_tip = %old_tip,
+                                new_tip = %reorgs.tip,
+                                "reorg occurred"
+                            );
+                        }
+                    }
+                    if let Some(mempool) = mempool.as_mut() {
+                        mempool.publish(&blockchain, block_id);
+                    }
+
+                    if !events.is_empty() {
+                        let block = &blockchain[block_id].block;
+                        events.emit(
+                            event_source,
+                            SimulationEvent::BlockPublished { round, block },
+                        );
+                        if let Some(depth) = reorg_depth {
+                            events.emit(
+                                event_source,
+                                SimulationEvent::ReorgOccurred {
+                                    round,
+                                    depth,
+                                    old_tip,
+                                    new_tip: reorgs.tip,
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+
+            if !events.is_empty() {
+                events.emit(
+                    event_source,
+                    SimulationEvent::RoundCompleted {
+                        round,
+                        proposer,
+                        blockchain: &blockchain,
+                    },
+                );
+            }
+
+            rounds = round;
+            if target_chain_length
+                .is_some_and(|length| blockchain.max_height() >= length)
+            {
+                break;
+            }
+        }
+
+        let results_start = timer.start();
+        let revenue_series = revenue_window.map(|(window, step)| {
+            RevenueSeries::new(
+                &blockchain,
+                &miners,
+                rounds,
+                slot_capacity,
+                window,
+                step,
+            )
+        });
+
+        // Block-DAG simulations reward the blue blocks instead of the longest
+        // chain
+        let mut dag_order = None;
+        let rewarded: Vec<BlockId> = match (dag.as_ref(), ghostdag) {
+            (Some(dag), Some(k)) => {
+                let ordering = dag.ghostdag(k);
+                let blue = ordering
+                    .order
+                    .iter()
+                    .copied()
+                    .filter(|&id| ordering.is_blue(id))
+                    .collect();
+                dag_order = (!lightweight_output).then_some(ordering.order);
+                blue
+            }
+            _ => blockchain.longest_chain().collect(),
+        };
+
+        // Blocks of the initial blockchain count towards the length of the
+        // longest chain, but not towards any miner's rewards
+        let mut longest_chain_length = 0;
+        for block_id in rewarded {
+            longest_chain_length += 1;
+            if pool.initial().contains(block_id) {
+                continue;
+            }
+
+            let block = &blockchain[block_id].block;
+            if let Some(stats) = miner_stats.get_mut(&block.miner_id) {
+                stats.blocks_on_chain += 1;
+                stats.subsidy_income += block_subsidy;
+                stats.fee_income += mempool
+                    .as_ref()
+                    .map_or(0.0, |mempool| mempool.miner_fees(block));
+            }
+        }
+
+        if let Some(rewards) = uncle_rewards.as_ref() {
+            for uncle in rewards.uncles(&blockchain) {
+                let fraction =
+                    rewards.uncle_reward_at(uncle.distance).unwrap_or_default();
+                let uncle_miner = blockchain[uncle.block].block.miner_id;
+                if let Some(stats) = miner_stats.get_mut(&uncle_miner) {
+                    stats.uncles += 1;
+                    stats.uncle_income += fraction * block_subsidy;
+                }
+
+                let nephew_miner = blockchain[uncle.nephew].block.miner_id;
+                if let Some(stats) = miner_stats.get_mut(&nephew_miner) {
+                    stats.nephew_income +=
+                        rewards.nephew_reward * block_subsidy;
                 }
             }
         }
 
+        let blacklisted_latencies = mempool
+            .as_ref()
+            .map(Mempool::blacklisted_latencies)
+            .unwrap_or_default();
         let blocks_published = blockchain.num_blocks();
-        let longest_chain = HashSet::from_iter(blockchain.longest_chain());
-        let miners = miners.into_iter().map(|m| (m.id(), m.name())).collect();
+        let double_spends = mempool
+            .as_ref()
+            .map(Mempool::double_spend_outcomes)
+            .unwrap_or_default();
+        let blockchain_memory = blockchain.memory_usage();
+        let fork_lengths = blockchain.fork_lengths();
+        let inclusion_latencies = mempool
+            .as_ref()
+            .map(Mempool::inclusion_latencies)
+            .unwrap_or_default();
+        let txn_generator = mempool.as_ref().map(Mempool::generator_config);
+        let longest_chain = (!lightweight_output)
+            .then(|| BlockSet::from_iter(blockchain.longest_chain()));
+        let miner_names = miners.iter().map(|m| (m.id(), m.name())).collect();
+        let miner_params =
+            miners.iter().map(|m| (m.id(), m.params())).collect();
 
-        Ok(SimulationOutput {
-            blockchain: include_blockchain.then_some(blockchain),
+        let blockchain = match include_blockchain {
+            true => Some(blockchain),
+            false => {
+                pool.put(blockchain);
+                None
+            }
+        };
+        timer.stop(Phase::Results, results_start);
+
+        let output = SimulationOutput {
+            blockchain,
+            blockchain_memory,
+            blacklisted_latencies,
             blocks_by_miner,
             blocks_published,
+            dag_order,
+            double_spends,
+            fork_lengths,
+            inclusion_latencies,
             longest_chain,
-            miners,
+            longest_chain_length,
+            miner_stats,
+            miners: miner_names,
+            miner_labels: miner_labels.into_iter().collect(),
+            miner_params,
             power_dist,
+            power_dist_label,
+            power_param,
+            proposers,
+            reorg_depths: reorgs.depths,
+            revenue_series,
+            rounds,
+            seed,
+            sensitivities: vec![],
+            #[cfg(feature = "timing")]
+            timings: timer.timings,
+            txn_generator,
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
             rounds,
+            blocks_published,
+            longest_chain_length,
+            reorgs = output.reorg_depths.len(),
+            "simulation finished"
+        );
+        for miner in miners.iter_mut() {
+            miner.on_simulation_end(&output);
+        }
+        miner_pool.put(miners);
+
+        Ok(Some(output))
+    }
+}
+
+/// Records the reorganizations of the longest chain during a simulation.
+struct ReorgTracker {
+    /// Tip of the longest chain as of the last update.
+    tip: BlockId,
+    /// Depth of each reorganization, in order.
+    depths: Vec<usize>,
+}
+
+impl ReorgTracker {
+    fn new(chain: &Blockchain) -> Self {
+        Self {
+            tip: chain.tip()[0],
+            depths: vec![],
+        }
+    }
+
+    /// Checks whether the longest chain was reorganized since the last
+    /// update, and returns the depth of the reorganization if so. Must be
+    /// called after every block is published.
+    #[inline]
+    fn update(&mut self, chain: &Blockchain) -> Option<usize> {
+        let tip = chain.tip()[0];
+        if tip == self.tip {
+            return None;
+        }
+
+        let ancestor = chain.common_ancestor(tip, self.tip).unwrap();
+        let depth = (ancestor != self.tip)
+            .then(|| chain[self.tip].height - chain[ancestor].height);
+        if let Some(depth) = depth {
+            self.depths.push(depth);
+        }
+        self.tip = tip;
+
+        depth
+    }
+}
+
+/// Returns true if `block` extends a block of `chain` which does not descend
+/// from its last checkpoint, or a block in `rejected`.
+fn conflicts_with_checkpoint(
+    block: &Block,
+    chain: &Blockchain,
+    rejected: &HashSet<BlockId>,
+) -> bool {
+    block.parent_id.is_some_and(|parent| {
+        rejected.contains(&parent)
+            || (chain.contains(parent)
+                && !chain.descends_from_checkpoint(parent))
+    })
+}
+
+/// Checks that `block`, published in `round` of a proof-of-stake simulation,
+/// belongs to a slot which has started, and does not point to a block of the
+/// same slot.
+fn check_slot(
+    block: &Block,
+    round: usize,
+    slot_capacity: usize,
+) -> Result<(), SimulationError> {
+    let slot = stake::slot(block.id, slot_capacity);
+    if slot > round {
+        return Err(SimulationError::FutureSlot {
+            block: block.id,
+            round,
+        });
+    }
+    if let Some(parent) = block.parent_id {
+        if stake::slot(parent, slot_capacity) == slot {
+            return Err(SimulationError::SameSlotParent {
+                block: block.id,
+                parent,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Determines the block proposer of each simulation round.
+enum ProposerSource {
+    /// Replay a recorded sequence of proposers.
+    Replay(Arc<[MinerId]>),
+    /// Sample proposers according to their mining power.
+    Sample(WeightedIndex<PowerValue>),
+    /// Give every round to the only miner with mining power.
+    Fixed(MinerId),
+}
+
+impl ProposerSource {
+    /// Returns the proposer of `round`, which starts from 1.
+    #[inline]
+    fn proposer<R: Rng + ?Sized>(&self, round: usize, rng: &mut R) -> MinerId {
+        match self {
+            Self::Replay(replayed) => replayed[round - 1],
+            Self::Sample(weights) => MinerId(weights.sample(rng) + 1),
+            Self::Fixed(miner_id) => *miner_id,
+        }
+    }
+
+    /// Burns `fraction` of the stake of `miner`, given in `stakes`. Only
+    /// affects later proposers if they are sampled.
+    fn slash(
+        &mut self,
+        stakes: &mut [PowerValue],
+        miner: MinerId,
+        fraction: f64,
+    ) -> Result<(), WeightedError> {
+        let index = miner.0 - 1;
+        stakes[index] *= 1.0 - fraction;
+        match self {
+            Self::Sample(weights) => {
+                weights.update_weights(&[(index, &stakes[index])])
+            }
+            Self::Replay(_) | Self::Fixed(_) => Ok(()),
+        }
+    }
+}
+
+/// Parameter varied by a [`SensitivityConfig`].
+#[derive(Debug, Clone)]
+enum SensitivityParam {
+    Power,
+    /// Named strategy parameter, and a function which creates the miner
+    /// from its value.
+    Strategy(String, WrapFunc<f64, Box<dyn Miner>>),
+}
+
+/// A sensitivity requested from a [`SimulationBuilder`].
+#[derive(Debug, Clone)]
+struct SensitivityConfig {
+    miner: MinerId,
+    param: SensitivityParam,
+    step: f64,
+}
+
+impl SensitivityConfig {
+    /// Prepares the paired simulations of this sensitivity for the miners of
+    /// a lineup.
+    fn pair(
+        &self,
+        lineup: usize,
+        miners: &[Box<dyn Miner>],
+        tie_breaker: Option<&TieBreaker>,
+    ) -> Result<SensitivityPair, SimulationBuildError> {
+        let index = self.miner.0 - 1;
+        let (param, func) = match &self.param {
+            SensitivityParam::Power => {
+                return Ok(SensitivityPair::Power {
+                    miner: self.miner,
+                    num_miners: miners.len(),
+                    step: self.step,
+                });
+            }
+            SensitivityParam::Strategy(param, func) => (param, func),
+        };
+
+        let value = miners[index]
+            .params()
+            .into_iter()
+            .find(|(name, _)| name == param)
+            .map(|(_, value)| value)
+            .ok_or_else(|| SimulationBuildError::SensitivityUnknownParam {
+                lineup,
+                miner: self.miner,
+                param: param.clone(),
+            })?;
+        let (low, high) = (value - self.step, value + self.step);
+
+        let pools = [low, high].map(|value| {
+            let mut miner = func.call(value);
+            miner.set_id(self.miner);
+            if let Some(tie_breaker) = tie_breaker {
+                miner.set_default_tie_breaker(tie_breaker.clone());
+            }
+
+            let mut lineup = miners.to_vec();
+            lineup[index] = miner;
+            Arc::new(MinerPool::new(lineup))
+        });
+
+        Ok(SensitivityPair::Param {
+            miner: self.miner,
+            param: param.clone(),
+            low,
+            high,
+            pools,
         })
     }
 }
+
+/// Pair of simulations which estimate the derivative of a miner's revenue,
+/// prepared for one lineup.
+#[derive(Debug)]
+enum SensitivityPair {
+    /// Shift the miner's mining power down and up by `step`.
+    Power {
+        miner: MinerId,
+        num_miners: usize,
+        step: PowerValue,
+    },
+    /// Replace the miner with copies whose parameter is `low` and `high`.
+    Param {
+        miner: MinerId,
+        param: String,
+        low: f64,
+        high: f64,
+        pools: [Arc<MinerPool>; 2],
+    },
+}
+
+impl SensitivityPair {
+    /// Runs the pair of simulations as variations of `sim`. Returns `None`
+    /// if either was cancelled before it could finish.
+    fn estimate(
+        &self,
+        sim: &Simulation,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<Option<Sensitivity>, SimulationError> {
+        let (miner, param, low, high, sims) = match self {
+            Self::Power {
+                miner,
+                num_miners,
+                step,
+            } => {
+                // Safety: power distributions are validated during the
+                // simulation build process
+                let values =
+                    unsafe { sim.power_dist.values_unchecked(*num_miners) };
+                let power = values[miner.0 - 1];
+                let (low, high) =
+                    ((power - step).max(0.0), (power + step).min(1.0));
+                let sims = [low, high].map(|power| Simulation {
+                    power_dist: shift_power(&values, *miner, power),
+                    ..sim.clone()
+                });
+
+                (*miner, "Power".to_string(), low, high, sims)
+            }
+            Self::Param {
+                miner,
+                param,
+                low,
+                high,
+                pools,
+            } => {
+                let sims = pools.clone().map(|miners| Simulation {
+                    miners,
+                    ..sim.clone()
+                });
+
+                (*miner, param.clone(), *low, *high, sims)
+            }
+        };
+
+        let mut revenues = [0.0; 2];
+        for (revenue, sim) in revenues.iter_mut().zip(sims) {
+            match sim.run(cancel)? {
+                Some(output) => *revenue = revenue_of(&miner, &output),
+                None => return Ok(None),
+            }
+        }
+
+        Ok(Some(Sensitivity {
+            miner,
+            param,
+            low,
+            high,
+            derivative: (revenues[1] - revenues[0]) / (high - low),
+        }))
+    }
+}
+
+/// Sets the mining power of `miner` to `power`, scaling the power of the
+/// other miners so that the total is unchanged.
+fn shift_power(
+    values: &[PowerValue],
+    miner: MinerId,
+    power: PowerValue,
+) -> PowerDistribution {
+    let index = miner.0 - 1;
+    let rest = 1.0 - values[index];
+    let others = (values.len() - 1) as PowerValue;
+
+    let values = values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| match i == index {
+            true => power,
+            false if rest > 0.0 => value * (1.0 - power) / rest,
+            false => (1.0 - power) / others,
+        })
+        .collect();
+
+    PowerDistribution::SetValues(values)
+}
@@ -0,0 +1,413 @@
+/*!
+Terminal dashboard for monitoring long-running simulation sweeps
+
+Requires the `tui` feature, and is not available on WebAssembly.
+
+A [`Dashboard`] runs a [`SimulationGroup`] while drawing its progress in the
+terminal with [`ratatui`](https://docs.rs/ratatui/0.29): the number of
+repeats finished for each configuration, a running estimate of one miner's
+revenue in each configuration, and the estimated time until the sweep is
+done. Pressing `q`, `Esc`, or `Ctrl-C` cancels the sweep as with
+[`SimulationGroup::run_all_cancellable`].
+
+## Example
+```no_run
+use mining_sim::{prelude::*, simulation::dashboard::Dashboard};
+
+let sim = SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(Selfish::new())
+    .miner_power_iter(MinerId::from(2), (0..=50).percent())
+    .rounds(1_000_000)
+    .repeat_all(100)
+    .build()
+    .unwrap();
+
+let results = Dashboard::new(MinerId::from(2)).run(sim).unwrap();
+```
+*/
+
+use std::{
+    io,
+    sync::{atomic::AtomicBool, atomic::Ordering, Mutex},
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    widgets::{Block, Gauge, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    miner::MinerId,
+    results::{revenue_of, ResultsBuilder},
+    simulation::{
+        Parallelism, SimulationError, SimulationGroup, SimulationOutput,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DashboardError {
+    #[error("could not draw to the terminal")]
+    IoError(#[from] io::Error),
+    #[error("could not run simulations")]
+    SimulationError(#[from] SimulationError),
+}
+
+/// Runs a [`SimulationGroup`] while displaying its progress in the terminal.
+/// See the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct Dashboard {
+    miner: MinerId,
+    parallelism: Parallelism,
+    refresh: Duration,
+}
+
+impl Dashboard {
+    /// Create a dashboard which tracks the revenue of the miner with ID
+    /// `miner`.
+    pub fn new(miner: MinerId) -> Self {
+        Self {
+            miner,
+            parallelism: Parallelism::default(),
+            refresh: Duration::from_millis(250),
+        }
+    }
+
+    /// Split simulations between threads as configured by `parallelism`. See
+    /// [`SimulationGroup::run_all_with`].
+    pub fn parallelism(mut self, parallelism: Parallelism) -> Self {
+        self.parallelism = parallelism;
+
+        self
+    }
+
+    /// Redraw the dashboard every `interval`. Defaults to 250 milliseconds.
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh = interval;
+
+        self
+    }
+
+    /// Runs all simulations in `group`, drawing the dashboard until they
+    /// finish or are cancelled. As with
+    /// [`SimulationGroup::run_all_cancellable`], only configurations for
+    /// which every repeat ran to completion are reported.
+    pub fn run(
+        &self,
+        group: SimulationGroup,
+    ) -> Result<ResultsBuilder, DashboardError> {
+        let repeat_all = group.repeat_all;
+        let num_miners = group.num_miners();
+        let progress =
+            Mutex::new(Progress::new(group.num_configs(), group.repeats()));
+        let cancel = AtomicBool::new(false);
+        let observer = |config: usize, output: &SimulationOutput| {
+            let mut progress = progress.lock().unwrap();
+            progress.record(config, self.miner, output);
+        };
+
+        let mut terminal = ratatui::try_init()?;
+        let (drawn, outputs) = std::thread::scope(|scope| {
+            let sims = scope.spawn(|| {
+                group.run(
+                    Some(&cancel),
+                    Some(&self.parallelism),
+                    Some(&observer),
+                )
+            });
+
+            let mut table = TableState::default();
+            let drawn = loop {
+                let progress = progress.lock().unwrap();
+                table.select(progress.current_config());
+                let drawn = terminal
+                    .draw(|frame| progress.draw(frame, &mut table))
+                    .map(|_| ());
+                drop(progress);
+
+                if drawn.is_err() || sims.is_finished() {
+                    break drawn;
+                }
+                match self.poll_cancel() {
+                    Ok(true) => cancel.store(true, Ordering::Relaxed),
+                    Ok(false) => {}
+                    Err(e) => {
+                        cancel.store(true, Ordering::Relaxed);
+                        break Err(e);
+                    }
+                }
+            };
+
+            (drawn, sims.join())
+        });
+        ratatui::try_restore()?;
+
+        let (data, skipped) = match outputs {
+            Ok(outputs) => outputs?,
+            Err(panic) => std::panic::resume_unwind(panic),
+        };
+        drawn?;
+
+        Ok(ResultsBuilder::new(data, repeat_all, num_miners, skipped))
+    }
+
+    /// Waits up to the refresh interval for a key press, and returns whether
+    /// it was a request to cancel.
+    fn poll_cancel(&self) -> io::Result<bool> {
+        if !event::poll(self.refresh)? {
+            return Ok(false);
+        }
+
+        Ok(match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+                    || (key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL))
+            }
+            _ => false,
+        })
+    }
+}
+
+/// Progress of a single configuration.
+#[derive(Debug, Default, Clone)]
+struct ConfigProgress {
+    /// Strategy and mining power of the tracked miner, once known.
+    label: Option<(String, f64)>,
+    finished: usize,
+    revenue_sum: f64,
+    revenue_sq_sum: f64,
+}
+
+impl ConfigProgress {
+    /// Returns the mean revenue and its standard error, if any repeats have
+    /// finished.
+    fn revenue(&self) -> Option<(f64, f64)> {
+        if self.finished == 0 {
+            return None;
+        }
+
+        let n = self.finished as f64;
+        let mean = self.revenue_sum / n;
+        let std_err = match self.finished {
+            1 => f64::NAN,
+            _ => {
+                let var = (self.revenue_sq_sum - n * mean * mean) / (n - 1.0);
+                (var.max(0.0) / n).sqrt()
+            }
+        };
+
+        Some((mean, std_err))
+    }
+}
+
+/// Progress of a [`SimulationGroup`] being run by a [`Dashboard`].
+#[derive(Debug, Clone)]
+struct Progress {
+    configs: Vec<ConfigProgress>,
+    repeats: usize,
+    finished: usize,
+    start: Instant,
+}
+
+impl Progress {
+    fn new(num_configs: usize, repeats: usize) -> Self {
+        Self {
+            configs: vec![ConfigProgress::default(); num_configs],
+            repeats,
+            finished: 0,
+            start: Instant::now(),
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.configs.len() * self.repeats
+    }
+
+    fn record(
+        &mut self,
+        config: usize,
+        miner: MinerId,
+        output: &SimulationOutput,
+    ) {
+        let progress = &mut self.configs[config];
+        if progress.label.is_none() {
+            let strategy = output.miners.get(&miner).cloned();
+            // Safety: power distributions are validated during the build
+            // step of the simulation pipeline
+            let power = unsafe {
+                output
+                    .power_dist
+                    .power_of_unchecked(miner, output.miners.len())
+            };
+            progress.label = Some((strategy.unwrap_or_default(), power));
+        }
+
+        let revenue = revenue_of(&miner, output);
+        progress.finished += 1;
+        progress.revenue_sum += revenue;
+        progress.revenue_sq_sum += revenue * revenue;
+        self.finished += 1;
+    }
+
+    /// Returns the first configuration which has not finished, which the
+    /// table is scrolled to.
+    fn current_config(&self) -> Option<usize> {
+        self.configs
+            .iter()
+            .position(|config| config.finished < self.repeats)
+    }
+
+    /// Estimated time until every simulation has finished, assuming the
+    /// remaining simulations take as long as the finished ones on average.
+    fn eta(&self) -> Option<Duration> {
+        (self.finished > 0).then(|| {
+            let remaining = (self.total() - self.finished) as f64;
+            self.start
+                .elapsed()
+                .mul_f64(remaining / self.finished as f64)
+        })
+    }
+
+    fn draw(&self, frame: &mut Frame, table: &mut TableState) {
+        let [gauge_area, status_area, table_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+
+        let ratio = match self.total() {
+            0 => 1.0,
+            total => self.finished as f64 / total as f64,
+        };
+        let gauge = Gauge::default()
+            .block(Block::bordered().title(" Simulations "))
+            .label(format!("{}/{}", self.finished, self.total()))
+            .ratio(ratio);
+        frame.render_widget(gauge, gauge_area);
+
+        let eta = self.eta().map_or("-".to_string(), format_duration);
+        let status = format!(
+            " Elapsed {}  |  ETA {}  |  Press q to cancel",
+            format_duration(self.start.elapsed()),
+            eta
+        );
+        frame.render_widget(Paragraph::new(status), status_area);
+
+        let header =
+            Row::new(["Config", "Strategy", "Power", "Done", "Revenue"])
+                .style(Style::default().add_modifier(Modifier::BOLD));
+        let rows = self.configs.iter().enumerate().map(|(i, config)| {
+            let (strategy, power) = match &config.label {
+                Some((strategy, power)) => {
+                    (strategy.clone(), format!("{:.4}", power))
+                }
+                None => (String::new(), String::new()),
+            };
+            let revenue = match config.revenue() {
+                Some((mean, std_err)) if std_err.is_finite() => {
+                    format!("{:.4} ± {:.4}", mean, std_err)
+                }
+                Some((mean, _)) => format!("{:.4}", mean),
+                None => String::new(),
+            };
+
+            Row::new([
+                i.to_string(),
+                strategy,
+                power,
+                format!("{}/{}", config.finished, self.repeats),
+                revenue,
+            ])
+        });
+        let widths = [
+            Constraint::Length(8),
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Length(20),
+        ];
+        let table_widget = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered().title(" Configurations "))
+            .row_highlight_style(Style::default().add_modifier(Modifier::BOLD));
+        frame.render_stateful_widget(table_widget, table_area, table);
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ratatui::{backend::TestBackend, widgets::TableState, Terminal};
+
+    use super::{format_duration, Progress};
+    use crate::{
+        miner::{honest::Honest, selfish::Selfish, MinerId},
+        simulation::SimulationBuilder,
+    };
+
+    #[test]
+    fn dashboard_tracks_progress() {
+        let progress = std::sync::Mutex::new(Progress::new(2, 3));
+        let results = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .miner_power_iter(MinerId(2), [0.2, 0.4])
+            .rounds(500)
+            .repeat_all(3)
+            .build()
+            .unwrap()
+            .run_all_observed(|config, output| {
+                progress.lock().unwrap().record(config, MinerId(2), output)
+            })
+            .unwrap();
+        let progress = progress.into_inner().unwrap();
+
+        assert_eq!(progress.finished, 6);
+        assert_eq!(progress.current_config(), None);
+        assert_eq!(progress.eta(), Some(Duration::ZERO));
+        for (config, power) in progress.configs.iter().zip([0.2, 0.4]) {
+            assert_eq!(config.finished, 3);
+            assert_eq!(config.label, Some(("Selfish".to_string(), power)));
+        }
+        let mean = results
+            .revenue_points(MinerId(2))
+            .iter()
+            .take(3)
+            .map(|(_, revenue)| revenue / 3.0)
+            .sum::<f64>();
+        let (revenue, _) = progress.configs[0].revenue().unwrap();
+        assert!((revenue - mean).abs() < 1e-12);
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal
+            .draw(|frame| progress.draw(frame, &mut TableState::default()))
+            .unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("6/6"));
+        assert!(screen.contains("Selfish"));
+        assert!(screen.contains("0.4000"));
+    }
+
+    #[test]
+    fn durations_are_formatted() {
+        assert_eq!(format_duration(Duration::from_secs(3723)), "01:02:03");
+    }
+}
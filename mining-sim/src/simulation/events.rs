@@ -0,0 +1,135 @@
+/*!
+Typed stream of events emitted while simulations run
+
+Subscribers registered with
+[`SimulationBuilder::subscribe`](crate::simulation::SimulationBuilder::subscribe)
+receive a [`SimulationEvent`] whenever a block is published, the longest
+chain is reorganized, a round is completed, or a simulation finishes, along
+with the [`EventSource`] identifying the simulation. Logging, animation, and
+custom metrics can all be built on the same stream.
+
+Simulations may run in parallel, so subscribers must be thread-safe, and
+events from different simulations may be interleaved. Events from a single
+simulation are delivered in order, on the thread running it.
+
+## Example
+```
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use mining_sim::{prelude::*, simulation::events::SimulationEvent};
+
+let reorgs = Arc::new(AtomicUsize::new(0));
+let counter = reorgs.clone();
+
+SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(Selfish::new())
+    .miner_power(MinerId::from(2), 0.4)
+    .rounds(1000)
+    .subscribe(move |_, event: &SimulationEvent| {
+        if let SimulationEvent::ReorgOccurred { .. } = event {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    })
+    .build()
+    .unwrap()
+    .run_all()
+    .unwrap();
+
+assert!(reorgs.load(Ordering::Relaxed) > 0);
+```
+*/
+
+use std::sync::Arc;
+
+use crate::{
+    blockchain::{Block, BlockId, Blockchain},
+    miner::MinerId,
+    simulation::SimulationOutput,
+};
+
+/// Identifies the simulation which emitted an event. Ordered by configuration
+/// index, and then by seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EventSource {
+    /// Index of the simulation's configuration, in the order described in
+    /// [`ResultsBuilder::data`](crate::results::ResultsBuilder::data).
+    pub config: usize,
+    /// Seed of the simulation, as in [`SimulationOutput::seed`].
+    pub seed: u64,
+}
+
+/// An event emitted during a simulation.
+#[derive(Debug, Clone, Copy)]
+pub enum SimulationEvent<'a> {
+    /// A block was published to the blockchain.
+    BlockPublished { round: usize, block: &'a Block },
+    /// Publishing a block moved the tip of the longest chain off of the
+    /// previous tip's branch, abandoning `depth` blocks.
+    ReorgOccurred {
+        round: usize,
+        depth: usize,
+        old_tip: BlockId,
+        new_tip: BlockId,
+    },
+    /// Every miner has acted in a round.
+    RoundCompleted {
+        round: usize,
+        proposer: MinerId,
+        blockchain: &'a Blockchain,
+    },
+    /// A simulation finished, and was not cancelled.
+    SimulationFinished { output: &'a SimulationOutput },
+}
+
+/// Receives the events of running simulations.
+///
+/// Implemented for any thread-safe closure taking an [`EventSource`] and a
+/// [`SimulationEvent`].
+pub trait Subscriber: Send + Sync {
+    fn on_event(&self, source: EventSource, event: &SimulationEvent<'_>);
+}
+
+impl<F> Subscriber for F
+where
+    F: Fn(EventSource, &SimulationEvent<'_>) + Send + Sync,
+{
+    fn on_event(&self, source: EventSource, event: &SimulationEvent<'_>) {
+        self(source, event)
+    }
+}
+
+/// Delivers events to each registered [`Subscriber`], in the order they were
+/// registered.
+#[derive(Clone, Default)]
+pub(crate) struct EventBus {
+    subscribers: Vec<Arc<dyn Subscriber>>,
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+impl EventBus {
+    pub(crate) fn subscribe(&mut self, subscriber: Arc<dyn Subscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Returns true if no subscribers are registered, in which case events
+    /// need not be constructed.
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+
+    pub(crate) fn emit(&self, source: EventSource, event: SimulationEvent<'_>) {
+        for subscriber in self.subscribers.iter() {
+            subscriber.on_event(source, &event);
+        }
+    }
+}
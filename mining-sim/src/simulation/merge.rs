@@ -0,0 +1,483 @@
+/*!
+Simulating two chains which share a merge-mining miner
+
+In merged mining, a miner searches for blocks on a primary and an auxiliary
+chain with the same work, so that its luck on the two chains is correlated. A
+[`MergeMining`] experiment simulates both chains side by side, each with its
+own lineup of miners configured by a [`SimulationBuilder`], and designates a
+merge miner on each chain. Every round, a single uniform draw decides whether
+the merge miner proposes on each chain, so that with mining power `a` on the
+primary chain and `b` on the auxiliary chain, it proposes on both chains in a
+`min(a, b)` fraction of rounds. The other proposers of each chain are sampled
+independently by mining power. Each chain is then simulated with
+[`SimulationBuilder::replay_proposers`].
+
+The merge miner may use a different strategy on each chain, such as honest
+mining on the primary chain and selfish mining on the auxiliary chain. Its
+revenue is reported per chain and across both chains, where the income of
+each chain is valued by its
+[`block_subsidy`](SimulationBuilder::block_subsidy) and transaction fees, so
+the relative value of the chains' coins can be set through each chain's
+block subsidy.
+
+## Example
+```
+use mining_sim::{prelude::*, simulation::merge::MergeMining};
+
+let results = MergeMining::new(
+    || SimulationBuilder::new().add_miner(Honest::new()).add_miner(Honest::new()),
+    || {
+        SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Selfish::new())
+            .block_subsidy(0.1)
+    },
+)
+.power_values(vec![0.6, 0.4], vec![0.6, 0.4])
+.rounds(2000)
+.repeats(4)
+.seed(1)
+.run()
+.unwrap();
+
+let revenue = results.mean_revenue();
+assert!(revenue.auxiliary > revenue.primary);
+assert!(revenue.primary < revenue.combined);
+assert!(revenue.combined < revenue.auxiliary);
+```
+*/
+
+use std::fmt;
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    Rng, SeedableRng,
+};
+
+use crate::{
+    miner::MinerId,
+    power_dist::{PowerDistribution, PowerValue},
+    results::revenue_of,
+    simulation::{
+        SimulationBuildError, SimulationBuilder, SimulationError,
+        SimulationOutput,
+    },
+};
+
+/// One of the chains of a [`MergeMining`] experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MergedChain {
+    Primary,
+    Auxiliary,
+}
+
+impl fmt::Display for MergedChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Primary => write!(f, "primary"),
+            Self::Auxiliary => write!(f, "auxiliary"),
+        }
+    }
+}
+
+/// Error returned by [`MergeMining::run`].
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    #[error("power values of the {0} chain were not given")]
+    MissingPowerValues(MergedChain),
+    #[error("power values of the {0} chain do not form a valid distribution")]
+    BadPowerValues(MergedChain),
+    #[error("merge miner {1} is not a miner of the {0} chain")]
+    BadMergeMiner(MergedChain, MinerId),
+    #[error("number of rounds must be at least 1")]
+    ZeroRounds,
+    #[error("could not build simulations")]
+    SimulationBuildError(#[from] SimulationBuildError),
+    #[error("could not run simulations")]
+    SimulationError(#[from] SimulationError),
+}
+
+/// Revenue of the merge miner in a [`MergeMining`] experiment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MergeRevenue {
+    /// Fraction of the blocks on the primary chain's longest chain which
+    /// were mined by the merge miner, as in
+    /// [`ResultsBuilder::revenue`](crate::results::ResultsBuilder::revenue).
+    pub primary: f64,
+    /// Revenue on the auxiliary chain, as for `primary`.
+    pub auxiliary: f64,
+    /// Merge miner's share of the total income of both chains, counting
+    /// block subsidies and transaction fees.
+    pub combined: f64,
+}
+
+/// Outputs of one repeat of a [`MergeMining`] experiment.
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    pub primary: SimulationOutput,
+    pub auxiliary: SimulationOutput,
+    pub revenue: MergeRevenue,
+}
+
+/// Result of [`MergeMining::run`].
+#[derive(Debug, Clone)]
+pub struct MergeResults {
+    /// Outcome of each repeat, in order.
+    pub outcomes: Vec<MergeOutcome>,
+}
+
+impl MergeResults {
+    /// Returns the mean revenue of the merge miner over every repeat.
+    pub fn mean_revenue(&self) -> MergeRevenue {
+        let n = self.outcomes.len() as f64;
+        let mean = |f: fn(&MergeRevenue) -> f64| {
+            self.outcomes.iter().map(|o| f(&o.revenue)).sum::<f64>() / n
+        };
+
+        MergeRevenue {
+            primary: mean(|r| r.primary),
+            auxiliary: mean(|r| r.auxiliary),
+            combined: mean(|r| r.combined),
+        }
+    }
+}
+
+/// Simulates a primary and an auxiliary chain which share a merge miner. See
+/// the [module docs](self).
+pub struct MergeMining<F, G> {
+    make_primary: F,
+    make_auxiliary: G,
+    power_values: [Option<Vec<PowerValue>>; 2],
+    merge_miners: [Option<MinerId>; 2],
+    rounds: usize,
+    repeats: usize,
+    seed: Option<u64>,
+}
+
+impl<F, G> fmt::Debug for MergeMining<F, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MergeMining")
+            .field("power_values", &self.power_values)
+            .field("merge_miners", &self.merge_miners)
+            .field("rounds", &self.rounds)
+            .field("repeats", &self.repeats)
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+impl<F, G> MergeMining<F, G>
+where
+    F: Fn() -> SimulationBuilder,
+    G: Fn() -> SimulationBuilder,
+{
+    /// Creates an experiment on the primary chain built by `make_primary`
+    /// and the auxiliary chain built by `make_auxiliary`. Each function is
+    /// called once per repeat, and should return a builder with the miners
+    /// and any other options configured, but not the mining power, number
+    /// of rounds, or seed, which are set by the experiment.
+    pub fn new(make_primary: F, make_auxiliary: G) -> Self {
+        Self {
+            make_primary,
+            make_auxiliary,
+            power_values: [None, None],
+            merge_miners: [None, None],
+            rounds: 1,
+            repeats: 1,
+            seed: None,
+        }
+    }
+
+    /// Sets the mining power of each miner on the primary and auxiliary
+    /// chains, in order of [`MinerId`]. Required.
+    pub fn power_values(
+        mut self,
+        primary: Vec<PowerValue>,
+        auxiliary: Vec<PowerValue>,
+    ) -> Self {
+        self.power_values = [Some(primary), Some(auxiliary)];
+
+        self
+    }
+
+    /// Sets the IDs of the merge miner on the primary and auxiliary chains.
+    /// Defaults to the last miner of each chain.
+    pub fn merge_miner(mut self, primary: MinerId, auxiliary: MinerId) -> Self {
+        self.merge_miners = [Some(primary), Some(auxiliary)];
+
+        self
+    }
+
+    /// Sets the number of rounds simulated on both chains. Defaults to 1.
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+
+        self
+    }
+
+    /// Sets the number of times the experiment is repeated with different
+    /// proposers. Defaults to 1.
+    pub fn repeats(mut self, repeats: usize) -> Self {
+        self.repeats = repeats;
+
+        self
+    }
+
+    /// Seeds the proposers and simulations of every repeat
+    /// deterministically from `seed`. See [`SimulationBuilder::seed`].
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+
+        self
+    }
+
+    /// Runs each repeat of the experiment in turn.
+    pub fn run(&self) -> Result<MergeResults, MergeError> {
+        let chains = [MergedChain::Primary, MergedChain::Auxiliary]
+            .map(|chain| self.sampler(chain));
+        let [primary, auxiliary] = chains;
+        let (primary, auxiliary) = (primary?, auxiliary?);
+        if self.rounds == 0 {
+            return Err(MergeError::ZeroRounds);
+        }
+
+        let mut seeder = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        let mut outcomes = Vec::with_capacity(self.repeats);
+        for _ in 0..self.repeats {
+            let mut rng = StdRng::seed_from_u64(seeder.gen());
+            let (mut primary_proposers, mut auxiliary_proposers) = (
+                Vec::with_capacity(self.rounds),
+                Vec::with_capacity(self.rounds),
+            );
+            for _ in 0..self.rounds {
+                // The same draw decides whether the merge miner proposes on
+                // either chain
+                let luck: f64 = rng.gen();
+                primary_proposers.push(primary.proposer(luck, &mut rng));
+                auxiliary_proposers.push(auxiliary.proposer(luck, &mut rng));
+            }
+
+            let primary_output = primary.run(
+                (self.make_primary)(),
+                primary_proposers,
+                seeder.gen(),
+            )?;
+            let auxiliary_output = auxiliary.run(
+                (self.make_auxiliary)(),
+                auxiliary_proposers,
+                seeder.gen(),
+            )?;
+
+            let income = |output: &SimulationOutput, miner: Option<MinerId>| {
+                output
+                    .miner_stats
+                    .iter()
+                    .filter(|(&id, _)| miner.is_none_or(|miner| miner == id))
+                    .map(|(_, stats)| stats.subsidy_income + stats.fee_income)
+                    .sum::<f64>()
+            };
+            let merge_income = income(&primary_output, Some(primary.miner))
+                + income(&auxiliary_output, Some(auxiliary.miner));
+            let total_income =
+                income(&primary_output, None) + income(&auxiliary_output, None);
+
+            let revenue = MergeRevenue {
+                primary: revenue_of(&primary.miner, &primary_output),
+                auxiliary: revenue_of(&auxiliary.miner, &auxiliary_output),
+                combined: match total_income > 0.0 {
+                    true => merge_income / total_income,
+                    false => 0.0,
+                },
+            };
+            outcomes.push(MergeOutcome {
+                primary: primary_output,
+                auxiliary: auxiliary_output,
+                revenue,
+            });
+        }
+
+        Ok(MergeResults { outcomes })
+    }
+
+    /// Validates the configuration of `chain`, and returns its proposer
+    /// sampler.
+    fn sampler(&self, chain: MergedChain) -> Result<ChainSampler, MergeError> {
+        let index = chain as usize;
+        let power_values = self.power_values[index]
+            .clone()
+            .ok_or(MergeError::MissingPowerValues(chain))?;
+        let num_miners = power_values.len();
+        if !PowerDistribution::SetValues(power_values.clone())
+            .is_valid(num_miners)
+        {
+            return Err(MergeError::BadPowerValues(chain));
+        }
+
+        let miner = self.merge_miners[index].unwrap_or(MinerId(num_miners));
+        if !(1..=num_miners).contains(&miner.0) {
+            return Err(MergeError::BadMergeMiner(chain, miner));
+        }
+
+        let power = power_values[miner.0 - 1];
+        let mut others = power_values.clone();
+        others[miner.0 - 1] = 0.0;
+
+        Ok(ChainSampler {
+            miner,
+            power,
+            // Fails only if the merge miner has all of the mining power, in
+            // which case other miners are never sampled
+            others: WeightedIndex::new(others).ok(),
+            power_values,
+        })
+    }
+}
+
+/// Samples the proposers of one chain of a [`MergeMining`] experiment.
+#[derive(Debug)]
+struct ChainSampler {
+    /// ID of the merge miner.
+    miner: MinerId,
+    /// Mining power of the merge miner.
+    power: PowerValue,
+    /// Distribution of the other miners' mining power.
+    others: Option<WeightedIndex<PowerValue>>,
+    power_values: Vec<PowerValue>,
+}
+
+impl ChainSampler {
+    /// Returns the proposer of a round in which the merge miner's luck was
+    /// `luck`, drawn uniformly from `0.0..1.0`.
+    fn proposer<R: Rng + ?Sized>(&self, luck: f64, rng: &mut R) -> MinerId {
+        match (luck < self.power, self.others.as_ref()) {
+            (false, Some(others)) => MinerId(others.sample(rng) + 1),
+            _ => self.miner,
+        }
+    }
+
+    fn run(
+        &self,
+        builder: SimulationBuilder,
+        proposers: Vec<MinerId>,
+        seed: u64,
+    ) -> Result<SimulationOutput, MergeError> {
+        let mut data = builder
+            .power_values(self.power_values.clone())
+            .replay_proposers(proposers)
+            .seed(seed)
+            .build()?
+            .run_all()?
+            .data();
+
+        Ok(data.pop().expect("one simulation is run per chain"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MergeError, MergeMining, MergedChain};
+    use crate::{
+        miner::{honest::Honest, selfish::Selfish, MinerId},
+        simulation::SimulationBuilder,
+    };
+
+    fn honest_pair() -> SimulationBuilder {
+        SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_miner(Honest::new())
+            .record_proposers()
+    }
+
+    #[test]
+    fn merge_miner_luck_is_shared() {
+        let results = MergeMining::new(honest_pair, || {
+            honest_pair().add_miner(Honest::new())
+        })
+        .power_values(vec![0.7, 0.3], vec![0.2, 0.4, 0.4])
+        .merge_miner(MinerId::from(2), MinerId::from(3))
+        .rounds(5000)
+        .repeats(2)
+        .seed(1)
+        .run()
+        .unwrap();
+        assert_eq!(results.outcomes.len(), 2);
+
+        for outcome in results.outcomes.iter() {
+            let primary = outcome.primary.proposers.as_ref().unwrap();
+            let auxiliary = outcome.auxiliary.proposers.as_ref().unwrap();
+            assert_eq!(primary.len(), 5000);
+
+            // The merge miner has less power on the primary chain, so each of
+            // its primary blocks comes with an auxiliary block
+            let mut both = 0;
+            for (p, a) in primary.iter().zip(auxiliary.iter()) {
+                if p.get() == 2 {
+                    assert_eq!(a.get(), 3);
+                    both += 1;
+                }
+            }
+            assert!((both as f64 / 5000.0 - 0.3).abs() < 0.03);
+        }
+
+        let revenue = results.mean_revenue();
+        assert!((revenue.primary - 0.3).abs() < 0.03);
+        assert!((revenue.auxiliary - 0.4).abs() < 0.03);
+        assert!((revenue.combined - 0.35).abs() < 0.03);
+    }
+
+    #[test]
+    fn withholding_on_one_chain() {
+        let results = MergeMining::new(honest_pair, || {
+            SimulationBuilder::new()
+                .add_miner(Honest::new())
+                .add_miner(Selfish::new())
+                .block_subsidy(3.0)
+        })
+        .power_values(vec![0.6, 0.4], vec![0.6, 0.4])
+        .rounds(5000)
+        .repeats(4)
+        .seed(2)
+        .run()
+        .unwrap();
+
+        let revenue = results.mean_revenue();
+        assert!((revenue.primary - 0.4).abs() < 0.03);
+        assert!(revenue.auxiliary > 0.45);
+        assert!(revenue.primary < revenue.combined);
+        assert!(revenue.combined < revenue.auxiliary);
+    }
+
+    #[test]
+    fn bad_configs() {
+        let experiment = || MergeMining::new(honest_pair, honest_pair);
+
+        assert!(matches!(
+            experiment().run(),
+            Err(MergeError::MissingPowerValues(MergedChain::Primary))
+        ));
+        assert!(matches!(
+            experiment()
+                .power_values(vec![0.5, 0.5], vec![0.5, 0.6])
+                .run(),
+            Err(MergeError::BadPowerValues(MergedChain::Auxiliary))
+        ));
+        assert!(matches!(
+            experiment()
+                .power_values(vec![0.5, 0.5], vec![0.5, 0.5])
+                .merge_miner(MinerId::from(3), MinerId::from(1))
+                .run(),
+            Err(MergeError::BadMergeMiner(MergedChain::Primary, _))
+        ));
+        assert!(matches!(
+            experiment()
+                .power_values(vec![0.5, 0.5], vec![0.5, 0.5, 0.0])
+                .run(),
+            Err(MergeError::SimulationBuildError(_))
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+/*!
+Recording the growth of the block tree for animation
+
+A [`SnapshotRecorder`] subscribes to the [events](super::events) of running
+simulations, and records each simulation's blockchain as a series of
+[`ChainFrame`]s, one per configurable interval of rounds. Rather than
+repeating the whole tree, each frame holds only the blocks published since
+the previous frame, along with the tip of the longest chain, so that the
+evolution of forks during an attack can be replayed or stepped through by
+external visualization tools.
+
+Recordings are written with [`SnapshotRecorder::write_to`] as
+[JSON Lines](https://jsonlines.org). Each recording starts with a line
+identifying its simulation, followed by one line per frame, where each block
+is given as an `[id, parent, miner]` triple:
+
+```text
+{"config":0,"seed":559256596868823998}
+{"round":10,"tip":10,"blocks":[[1,0,1],[3,1,1],[2,1,2],[4,2,2],[8,4,1],[5,4,2],[10,8,1],[6,5,2]]}
+{"round":20,"tip":20,"blocks":[[11,10,1],[7,6,2],[9,7,2],[13,9,1],[12,9,2],[14,12,2],[15,14,1],[16,15,1],[18,16,1],[17,16,2],[19,17,2],[20,19,1]]}
+```
+
+## Example
+```
+use mining_sim::{prelude::*, simulation::snapshot::SnapshotRecorder};
+
+let recorder = SnapshotRecorder::new().interval(10);
+
+SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(Selfish::new())
+    .miner_power(MinerId::from(2), 0.4)
+    .rounds(100)
+    .subscribe(recorder.clone())
+    .build()
+    .unwrap()
+    .run_all()
+    .unwrap();
+
+let recordings = recorder.recordings();
+assert_eq!(recordings.len(), 1);
+assert!(recordings[0].frames.len() <= 10);
+
+let mut json = vec![];
+recorder.write_to(&mut json).unwrap();
+```
+*/
+
+use std::{
+    collections::BTreeMap,
+    io,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    blockchain::BlockId,
+    miner::MinerId,
+    simulation::events::{EventSource, SimulationEvent, Subscriber},
+};
+
+/// Blocks published during an interval of rounds, and the state of the
+/// longest chain at its end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainFrame {
+    /// Last round of the interval.
+    pub round: usize,
+    /// Tip of the longest chain at the end of `round`.
+    pub tip: BlockId,
+    /// `(id, parent, miner)` of each block published during the interval,
+    /// in the order they were published.
+    pub blocks: Vec<(BlockId, BlockId, MinerId)>,
+}
+
+/// The frames recorded for a single simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainRecording {
+    pub source: EventSource,
+    /// Frames in round order. Intervals in which no blocks were published
+    /// are left out.
+    pub frames: Vec<ChainFrame>,
+    /// Whether the simulation ran to completion. Simulations which are
+    /// cancelled are missing the frames of their last interval.
+    pub finished: bool,
+}
+
+impl ChainRecording {
+    /// Writes this recording as JSON Lines. See the
+    /// [module docs](self) for the format.
+    pub fn write_to<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "{{\"config\":{},\"seed\":{}}}",
+            self.source.config, self.source.seed
+        )?;
+
+        for frame in self.frames.iter() {
+            write!(
+                writer,
+                "{{\"round\":{},\"tip\":{},\"blocks\":[",
+                frame.round, frame.tip
+            )?;
+            for (i, (id, parent, miner)) in frame.blocks.iter().enumerate() {
+                let sep = if i == 0 { "" } else { "," };
+                write!(writer, "{}[{},{},{}]", sep, id, parent, miner)?;
+            }
+            writeln!(writer, "]}}")?;
+        }
+
+        Ok(())
+    }
+
+    fn push_frame(&mut self, pending: &mut Pending) {
+        if pending.blocks.is_empty() {
+            return;
+        }
+
+        self.frames.push(ChainFrame {
+            round: pending.round,
+            tip: pending.tip,
+            blocks: std::mem::take(&mut pending.blocks),
+        });
+    }
+}
+
+/// State of the frame currently being recorded for a simulation.
+#[derive(Debug, Default)]
+struct Pending {
+    round: usize,
+    tip: BlockId,
+    blocks: Vec<(BlockId, BlockId, MinerId)>,
+}
+
+/// Records the block tree of each simulation it is subscribed to. See the
+/// [module docs](self).
+///
+/// Clones share the same recordings, so a clone can be passed to
+/// [`SimulationBuilder::subscribe`](crate::simulation::SimulationBuilder::subscribe)
+/// and the recordings read from the original.
+#[derive(Debug, Clone)]
+pub struct SnapshotRecorder {
+    interval: usize,
+    recordings: Arc<Mutex<BTreeMap<EventSource, (ChainRecording, Pending)>>>,
+}
+
+impl Default for SnapshotRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnapshotRecorder {
+    /// Creates a recorder which records a frame every round.
+    pub fn new() -> Self {
+        Self {
+            interval: 1,
+            recordings: Arc::default(),
+        }
+    }
+
+    /// Record a frame every `rounds` rounds, and at the end of each
+    /// simulation.
+    ///
+    /// # Panics
+    /// Panics if `rounds` is 0.
+    pub fn interval(mut self, rounds: usize) -> Self {
+        assert!(rounds > 0, "snapshot interval must be at least one round");
+        self.interval = rounds;
+
+        self
+    }
+
+    /// Returns the recording of each simulation, ordered by configuration
+    /// index and then by seed.
+    pub fn recordings(&self) -> Vec<ChainRecording> {
+        self.recordings
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(recording, _)| recording.clone())
+            .collect()
+    }
+
+    /// Discards every recording.
+    pub fn clear(&self) {
+        self.recordings.lock().unwrap().clear();
+    }
+
+    /// Writes each of [`SnapshotRecorder::recordings`] as JSON Lines, one
+    /// after the other.
+    pub fn write_to<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        for recording in self.recordings() {
+            recording.write_to(&mut writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Subscriber for SnapshotRecorder {
+    fn on_event(&self, source: EventSource, event: &SimulationEvent<'_>) {
+        let mut recordings = self.recordings.lock().unwrap();
+        let (recording, pending) =
+            recordings.entry(source).or_insert_with(|| {
+                let recording = ChainRecording {
+                    source,
+                    frames: vec![],
+                    finished: false,
+                };
+
+                (recording, Pending::default())
+            });
+
+        match *event {
+            SimulationEvent::BlockPublished { block, .. } => {
+                let parent = block.parent_id.unwrap_or_default();
+                pending.blocks.push((block.id, parent, block.miner_id));
+            }
+            SimulationEvent::RoundCompleted {
+                round, blockchain, ..
+            } => {
+                pending.round = round;
+                pending.tip = blockchain.tip()[0];
+                if round % self.interval == 0 {
+                    recording.push_frame(pending);
+                }
+            }
+            SimulationEvent::SimulationFinished { .. } => {
+                recording.push_frame(pending);
+                recording.finished = true;
+            }
+            SimulationEvent::ReorgOccurred { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,289 @@
+/*!
+Adaptive search for the mining power at which a strategy becomes profitable
+
+A [`ThresholdSearch`] bisects over the mining power of one miner to find the
+smallest power at which its simulated revenue exceeds
+[`honest_revenue`](crate::results::honest_revenue), i.e. its mining power.
+Rather than running a fixed grid of power values, each probed power value is
+simulated in batches of repeats until a t-test decides whether the strategy
+is profitable there, so that power values far from the threshold are settled
+with few simulations.
+
+## Example
+```
+use mining_sim::prelude::*;
+
+let threshold = ThresholdSearch::new(MinerId::from(2), || {
+    SimulationBuilder::new()
+        .add_miner(Honest::new())
+        .add_miner(Selfish::new())
+        .gamma(0.5)
+        .rounds(2000)
+})
+.range(0.1, 0.45)
+.tolerance(0.05)
+.seed(1)
+.run()
+.unwrap();
+
+// Eyal and Sirer's threshold for gamma = 0.5 is 1/4
+assert!((threshold.alpha - 0.25).abs() < 0.1);
+```
+*/
+
+use crate::{
+    miner::MinerId,
+    power_dist::PowerValue,
+    simulation::{SimulationBuildError, SimulationBuilder, SimulationError},
+    utils::t_test_p_value,
+};
+
+/// Error returned by [`ThresholdSearch::run`].
+#[derive(Debug, thiserror::Error)]
+pub enum ThresholdError {
+    #[error("search range {0}..={1} is not within 0.0..=1.0")]
+    BadRange(PowerValue, PowerValue),
+    #[error("tolerance {0} is not a finite positive number")]
+    BadTolerance(f64),
+    #[error("confidence level {0} is not in the range 0.0..1.0")]
+    BadConfidence(f64),
+    #[error("batch size must be at least 2, and at most the maximum repeats")]
+    BadBatchSize,
+    #[error("strategy is already profitable with mining power {0}")]
+    AlwaysProfitable(PowerValue),
+    #[error("strategy is not profitable with mining power {0}")]
+    NeverProfitable(PowerValue),
+    #[error("could not build simulations")]
+    SimulationBuildError(#[from] SimulationBuildError),
+    #[error("could not run simulations")]
+    SimulationError(#[from] SimulationError),
+}
+
+/// Searches for the smallest mining power at which the miner with a given ID
+/// earns more than its honest revenue. See the [module docs](self).
+pub struct ThresholdSearch<F> {
+    miner: MinerId,
+    make_builder: F,
+    low: PowerValue,
+    high: PowerValue,
+    tolerance: f64,
+    confidence: f64,
+    batch_size: usize,
+    max_repeats: usize,
+    seed: Option<u64>,
+}
+
+impl<F> std::fmt::Debug for ThresholdSearch<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ThresholdSearch")
+            .field("miner", &self.miner)
+            .field("low", &self.low)
+            .field("high", &self.high)
+            .field("tolerance", &self.tolerance)
+            .field("confidence", &self.confidence)
+            .field("batch_size", &self.batch_size)
+            .field("max_repeats", &self.max_repeats)
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+/// Result of simulating one power value during a [`ThresholdSearch`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    /// Mining power of the searched miner.
+    pub alpha: PowerValue,
+    /// Mean revenue of the searched miner, minus its honest revenue.
+    pub excess_revenue: f64,
+    /// p-value of a t-test of the hypothesis that the excess revenue is 0.
+    pub p_value: f64,
+    /// Number of simulations run.
+    pub repeats: usize,
+    /// Whether the excess revenue was found to be significantly positive.
+    pub profitable: bool,
+}
+
+/// Result of a [`ThresholdSearch`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Threshold {
+    /// Estimated threshold, the midpoint of `low` and `high`.
+    pub alpha: PowerValue,
+    /// Largest probed power at which the strategy was not profitable.
+    pub low: PowerValue,
+    /// Smallest probed power at which the strategy was profitable.
+    pub high: PowerValue,
+    /// Every probed power value, in the order they were probed.
+    pub probes: Vec<Probe>,
+}
+
+impl Threshold {
+    /// Total number of simulations run during the search.
+    pub fn simulations(&self) -> usize {
+        self.probes.iter().map(|probe| probe.repeats).sum()
+    }
+}
+
+impl<F> ThresholdSearch<F>
+where
+    F: Fn() -> SimulationBuilder,
+{
+    /// Creates a search over the mining power of the miner with ID `miner`.
+    /// `make_builder` is called once per batch of simulations, and should
+    /// return a builder with the miners and any other options configured,
+    /// but not the mining power or number of repeats, which are set by the
+    /// search.
+    pub fn new(miner: MinerId, make_builder: F) -> Self {
+        Self {
+            miner,
+            make_builder,
+            low: 0.05,
+            high: 0.5,
+            tolerance: 0.01,
+            confidence: 0.95,
+            batch_size: 10,
+            max_repeats: 100,
+            seed: None,
+        }
+    }
+
+    /// Search for the threshold between `low` and `high`, which must bracket
+    /// it. Defaults to `0.05..=0.5`.
+    pub fn range(mut self, low: PowerValue, high: PowerValue) -> Self {
+        self.low = low;
+        self.high = high;
+
+        self
+    }
+
+    /// Stop once the threshold is known to within `tolerance`. Defaults to
+    /// `0.01`.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+
+        self
+    }
+
+    /// Confidence level of the test of whether the strategy is profitable at
+    /// each probed power value. Defaults to `0.95`.
+    pub fn confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence;
+
+        self
+    }
+
+    /// Run simulations in batches of `size` repeats. Defaults to 10.
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size;
+
+        self
+    }
+
+    /// Treat the strategy as unprofitable at a power value if profit cannot
+    /// be shown after `repeats` simulations. Defaults to 100.
+    pub fn max_repeats(mut self, repeats: usize) -> Self {
+        self.max_repeats = repeats;
+
+        self
+    }
+
+    /// Seed each batch of simulations deterministically from `seed`. See
+    /// [`SimulationBuilder::seed`].
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+
+        self
+    }
+
+    /// Runs the search. Both ends of the range are probed first, and the
+    /// strategy must be unprofitable at the low end and profitable at the
+    /// high end.
+    pub fn run(&self) -> Result<Threshold, ThresholdError> {
+        self.validate()?;
+
+        let mut probes = vec![];
+        let (mut low, mut high) = (self.low, self.high);
+
+        if self.probe(low, &mut probes)? {
+            return Err(ThresholdError::AlwaysProfitable(low));
+        }
+        if !self.probe(high, &mut probes)? {
+            return Err(ThresholdError::NeverProfitable(high));
+        }
+
+        while high - low > self.tolerance {
+            let mid = 0.5 * (low + high);
+            match self.probe(mid, &mut probes)? {
+                true => high = mid,
+                false => low = mid,
+            }
+        }
+
+        Ok(Threshold {
+            alpha: 0.5 * (low + high),
+            low,
+            high,
+            probes,
+        })
+    }
+
+    fn validate(&self) -> Result<(), ThresholdError> {
+        if !(0.0 <= self.low && self.low < self.high && self.high <= 1.0) {
+            return Err(ThresholdError::BadRange(self.low, self.high));
+        }
+        if !(self.tolerance.is_finite() && self.tolerance > 0.0) {
+            return Err(ThresholdError::BadTolerance(self.tolerance));
+        }
+        if !(0.0..1.0).contains(&self.confidence) {
+            return Err(ThresholdError::BadConfidence(self.confidence));
+        }
+        if self.batch_size < 2 || self.batch_size > self.max_repeats {
+            return Err(ThresholdError::BadBatchSize);
+        }
+
+        Ok(())
+    }
+
+    /// Simulates mining power `alpha` in batches until the excess revenue is
+    /// significantly positive or negative, or the maximum number of repeats
+    /// is reached, and returns whether it was significantly positive.
+    fn probe(
+        &self,
+        alpha: PowerValue,
+        probes: &mut Vec<Probe>,
+    ) -> Result<bool, ThresholdError> {
+        let significance = 1.0 - self.confidence;
+        let mut excess = vec![];
+
+        let probe = loop {
+            let mut builder = (self.make_builder)()
+                .miner_power(self.miner, alpha)
+                .repeat_all(self.batch_size);
+            if let Some(seed) = self.seed {
+                let batch = probes.len() * self.max_repeats + excess.len();
+                builder = builder.seed(seed.wrapping_add(batch as u64));
+            }
+
+            let points = builder.build()?.run_all()?.revenue_points(self.miner);
+            excess
+                .extend(points.iter().map(|(power, revenue)| revenue - power));
+
+            let mean = excess.iter().sum::<f64>() / excess.len() as f64;
+            let p_value = t_test_p_value(&excess, 0.0);
+            let decided = p_value < significance;
+            if decided || excess.len() + self.batch_size > self.max_repeats {
+                break Probe {
+                    alpha,
+                    excess_revenue: mean,
+                    p_value,
+                    repeats: excess.len(),
+                    profitable: decided && mean > 0.0,
+                };
+            }
+        };
+
+        let profitable = probe.profitable;
+        probes.push(probe);
+
+        Ok(profitable)
+    }
+}
@@ -0,0 +1,116 @@
+/*!
+Proof-of-stake round structure
+
+By default, each simulation round models the discovery of a single
+proof-of-work block. Passing a [`ProofOfStake`] to
+[`SimulationBuilder::proof_of_stake`](crate::simulation::SimulationBuilder::proof_of_stake)
+instead treats each round as a slot, whose proposer is selected with
+probability proportional to its stake, given by the simulation's power
+distribution. Since proposing costs nothing, the proposer of a slot may
+publish up to [`ProofOfStake::slot_capacity`] blocks for it, each extending
+a different block. Publishing more than one block for a slot is an
+*equivocation*, which is counted in
+[`MinerStats::equivocations`](crate::simulation::MinerStats::equivocations)
+and can optionally be slashed.
+
+Each slot reserves a range of consecutive block IDs, so that block IDs stay
+unique and increase with the slot of each block. The `block_mined` passed
+to [`Miner::get_action`](crate::miner::Miner::get_action) is the first ID of
+the slot, and the proposer may use the following `slot_capacity - 1` IDs for
+equivocating blocks. With a slot capacity of 1, block IDs are the same as in
+proof-of-work simulations. During a proof-of-stake simulation, it is an
+error to publish a block with the ID of a future slot, or a block which
+points to a block of the same slot.
+
+## Example
+```
+use mining_sim::prelude::*;
+
+let data = SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(Equivocating::new(1))
+    .proof_of_stake(ProofOfStake::new().slot_capacity(4).slashing(0.1))
+    .rounds(1000)
+    .build()
+    .unwrap()
+    .run_all()
+    .unwrap()
+    .data();
+
+let stats = data[0].miner_stats[&MinerId::from(2)];
+assert!(stats.equivocations > 0);
+assert!(stats.slashed > 0.0);
+```
+*/
+
+use crate::blockchain::BlockId;
+
+/// Configures proof-of-stake simulations. See the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProofOfStake {
+    pub(crate) slot_capacity: usize,
+    pub(crate) slashing: Option<f64>,
+}
+
+impl Default for ProofOfStake {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProofOfStake {
+    pub const DEFAULT_SLOT_CAPACITY: usize = 4;
+
+    /// Creates a configuration where each slot holds up to
+    /// [`ProofOfStake::DEFAULT_SLOT_CAPACITY`] blocks, without slashing.
+    pub fn new() -> Self {
+        Self {
+            slot_capacity: Self::DEFAULT_SLOT_CAPACITY,
+            slashing: None,
+        }
+    }
+
+    /// Let the proposer of each slot publish up to `blocks` blocks for it.
+    /// Must be at least 1, which rules out equivocation.
+    pub fn slot_capacity(mut self, blocks: usize) -> Self {
+        self.slot_capacity = blocks;
+
+        self
+    }
+
+    /// Slash a miner whenever it is caught equivocating, by burning
+    /// `fraction` of its remaining stake. Equivocation is caught once a
+    /// second block of a slot is published, and each slot is slashed at most
+    /// once. Slashing lowers the probability that the miner proposes later
+    /// slots, unless the proposers are
+    /// [replayed](crate::simulation::SimulationBuilder::replay_proposers).
+    /// `fraction` must be in the range `0.0..=1.0`, where `1.0` burns the
+    /// miner's whole stake so that it never proposes again.
+    pub fn slashing(mut self, fraction: f64) -> Self {
+        self.slashing = Some(fraction);
+
+        self
+    }
+
+    /// Returns the first block ID reserved for the slot of `round`.
+    pub fn first_block(&self, round: usize) -> BlockId {
+        first_block(round, self.slot_capacity)
+    }
+
+    /// Returns the round of the slot which `block` belongs to, or 0 for the
+    /// genesis block.
+    pub fn slot(&self, block: BlockId) -> usize {
+        slot(block, self.slot_capacity)
+    }
+}
+
+pub(crate) fn first_block(round: usize, slot_capacity: usize) -> BlockId {
+    BlockId(round.saturating_sub(1) * slot_capacity + 1)
+}
+
+pub(crate) fn slot(block: BlockId, slot_capacity: usize) -> usize {
+    match block.0 {
+        0 => 0,
+        id => (id - 1) / slot_capacity + 1,
+    }
+}
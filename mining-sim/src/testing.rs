@@ -0,0 +1,308 @@
+/*!
+Helpers for verifying mining strategies against known revenue values
+
+A [`GoldenTest`] runs a strategy against an [`Honest`] miner at a set of
+[`GoldenPoint`]s, each giving the strategy's mining power `alpha`, `gamma`,
+the number of rounds, and the seed, and checks that the strategy's revenue
+is within a tolerance of the value predicted by a closed form. Presets are
+given for the results a correct simulator must reproduce:
+
+- [`GoldenTest::honest`]: honest miners earn their mining power.
+- [`GoldenTest::selfish`]: selfish mining earns Eyal and Sirer's
+  [`selfish_revenue`].
+- [`GoldenTest::nsm`]: the Nothing-At-Stake miner earns Weinberg and
+  Ferreira's [`nsm_revenue`].
+
+Random blockchains and power distributions for fuzzing strategies are
+given by [`generators`].
+
+## Example
+```
+use mining_sim::testing::{GoldenPoint, GoldenTest};
+
+GoldenTest::selfish()
+    .point(GoldenPoint::new(0.3, 0.5).rounds(20_000).seed(7))
+    .tolerance(0.02)
+    .assert();
+```
+*/
+
+use std::{fmt::Display, sync::Arc};
+
+use crate::{
+    miner::{
+        honest::Honest, ndeficit::NDeficit, selfish::Selfish, Miner, MinerId,
+    },
+    power_dist::PowerValue,
+    results::{honest_revenue, nsm_revenue, selfish_revenue},
+    simulation::{SimulationBuildError, SimulationBuilder, SimulationError},
+};
+
+pub mod generators;
+
+/// Expected revenue of a strategy, given its mining power `alpha` and
+/// `gamma`.
+pub type ExpectedRevenueFn = Arc<dyn Fn(PowerValue, f64) -> f64 + Send + Sync>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum GoldenError {
+    #[error("no points were added")]
+    NoPoints,
+    #[error("could not build simulation")]
+    SimulationBuildError(#[from] SimulationBuildError),
+    #[error("could not run simulation")]
+    SimulationError(#[from] SimulationError),
+}
+
+/// Configuration of a single simulation run by a [`GoldenTest`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoldenPoint {
+    /// Mining power of the tested strategy.
+    pub alpha: PowerValue,
+    /// See [`SimulationBuilder::gamma`].
+    pub gamma: f64,
+    pub rounds: usize,
+    pub seed: u64,
+}
+
+impl GoldenPoint {
+    /// Number of rounds simulated by default.
+    pub const DEFAULT_ROUNDS: usize = 100_000;
+
+    /// Create a point with the given `alpha` and `gamma`, which runs for
+    /// [`GoldenPoint::DEFAULT_ROUNDS`] rounds with seed 0.
+    pub fn new(alpha: PowerValue, gamma: f64) -> Self {
+        Self {
+            alpha,
+            gamma,
+            rounds: Self::DEFAULT_ROUNDS,
+            seed: 0,
+        }
+    }
+
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+
+        self
+    }
+}
+
+/// Outcome of simulating a [`GoldenPoint`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoldenCheck {
+    pub point: GoldenPoint,
+    /// Revenue predicted by the closed form.
+    pub expected: f64,
+    /// Simulated revenue of the tested strategy.
+    pub revenue: f64,
+    /// Whether the simulated revenue was within the tolerance of the
+    /// expected revenue.
+    pub passed: bool,
+}
+
+impl Display for GoldenCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let GoldenPoint {
+            alpha,
+            gamma,
+            rounds,
+            seed,
+        } = self.point;
+
+        write!(
+            f,
+            "alpha {}, gamma {}, {} rounds, seed {}: revenue {} (expected {})",
+            alpha, gamma, rounds, seed, self.revenue, self.expected
+        )
+    }
+}
+
+/// Checks the simulated revenue of a strategy against a closed form. See the
+/// [module docs](self).
+#[derive(Clone)]
+pub struct GoldenTest {
+    strategy: Box<dyn Miner>,
+    expected: ExpectedRevenueFn,
+    points: Vec<GoldenPoint>,
+    tolerance: f64,
+}
+
+impl std::fmt::Debug for GoldenTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GoldenTest")
+            .field("strategy", &self.strategy)
+            .field("points", &self.points)
+            .field("tolerance", &self.tolerance)
+            .finish()
+    }
+}
+
+impl GoldenTest {
+    /// Create a test of `strategy`, whose revenue should be
+    /// `expected(alpha, gamma)`.
+    pub fn new<M, F>(strategy: M, expected: F) -> Self
+    where
+        M: Miner + 'static,
+        F: Fn(PowerValue, f64) -> f64 + Send + Sync + 'static,
+    {
+        Self {
+            strategy: Box::new(strategy),
+            expected: Arc::new(expected),
+            points: vec![],
+            tolerance: 0.01,
+        }
+    }
+
+    /// Test that an [`Honest`] miner earns [`honest_revenue`].
+    pub fn honest() -> Self {
+        Self::new(Honest::new(), |alpha, _| honest_revenue(alpha))
+    }
+
+    /// Test that a [`Selfish`] miner earns [`selfish_revenue`].
+    pub fn selfish() -> Self {
+        Self::new(Selfish::new(), |alpha, gamma| selfish_revenue(gamma)(alpha))
+    }
+
+    /// Test that a 2-Deficit miner, the Nothing-At-Stake miner, earns
+    /// [`nsm_revenue`]. The closed form does not depend on `gamma`.
+    pub fn nsm() -> Self {
+        Self::new(NDeficit::new(2), |alpha, _| nsm_revenue(alpha))
+    }
+
+    /// Simulate the strategy at `point`.
+    pub fn point(mut self, point: GoldenPoint) -> Self {
+        self.points.push(point);
+
+        self
+    }
+
+    /// Simulate the strategy at each of `points`.
+    pub fn points<I>(mut self, points: I) -> Self
+    where
+        I: IntoIterator<Item = GoldenPoint>,
+    {
+        self.points.extend(points);
+
+        self
+    }
+
+    /// Largest allowed absolute difference between simulated and expected
+    /// revenue. Defaults to `0.01`.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+
+        self
+    }
+
+    /// Simulates the strategy at each point, in the order they were added.
+    pub fn run(&self) -> Result<Vec<GoldenCheck>, GoldenError> {
+        if self.points.is_empty() {
+            return Err(GoldenError::NoPoints);
+        }
+
+        self.points.iter().map(|point| self.check(*point)).collect()
+    }
+
+    /// Simulates the strategy at each point.
+    ///
+    /// # Panics
+    /// Panics if any simulated revenue is not within the tolerance of the
+    /// expected revenue, listing each point which failed, or if the
+    /// simulations could not be run.
+    pub fn assert(&self) {
+        let checks = match self.run() {
+            Ok(checks) => checks,
+            Err(e) => {
+                panic!("{} could not be run: {}", self.strategy.name(), e)
+            }
+        };
+
+        let failed: Vec<_> = checks
+            .iter()
+            .filter(|check| !check.passed)
+            .map(GoldenCheck::to_string)
+            .collect();
+        assert!(
+            failed.is_empty(),
+            "{} revenue not within {} of expected revenue at:\n{}",
+            self.strategy.name(),
+            self.tolerance,
+            failed.join("\n")
+        );
+    }
+
+    fn check(&self, point: GoldenPoint) -> Result<GoldenCheck, GoldenError> {
+        let attacker = MinerId(2);
+        let revenue = SimulationBuilder::new()
+            .add_miner(Honest::new())
+            .add_boxed_miner(self.strategy.clone())
+            .miner_power(attacker, point.alpha)
+            .gamma(point.gamma)
+            .rounds(point.rounds)
+            .seed(point.seed)
+            .lightweight_output()
+            .build()?
+            .run_all_sequential()?
+            .revenue_points(attacker)[0]
+            .1;
+        let expected = (self.expected)(point.alpha, point.gamma);
+
+        Ok(GoldenCheck {
+            point,
+            expected,
+            revenue,
+            passed: (revenue - expected).abs() <= self.tolerance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GoldenError, GoldenPoint, GoldenTest};
+    use crate::miner::noop::Noop;
+
+    fn points() -> impl Iterator<Item = GoldenPoint> {
+        [(0.1, 0.0), (0.25, 0.5), (0.4, 1.0)]
+            .into_iter()
+            .map(|(alpha, gamma)| GoldenPoint::new(alpha, gamma).rounds(50_000))
+    }
+
+    #[test]
+    fn honest_revenue_matches_power() {
+        GoldenTest::honest().points(points()).assert();
+    }
+
+    #[test]
+    fn selfish_revenue_matches_closed_form() {
+        GoldenTest::selfish().points(points()).assert();
+    }
+
+    #[test]
+    fn nsm_revenue_matches_closed_form() {
+        GoldenTest::nsm()
+            .points(points().map(|point| point.seed(3)))
+            .assert();
+    }
+
+    #[test]
+    fn failing_points_are_reported() {
+        let checks = GoldenTest::new(Noop::new(), |alpha, _| alpha)
+            .point(GoldenPoint::new(0.3, 0.0).rounds(100))
+            .run()
+            .unwrap();
+        assert_eq!(checks.len(), 1);
+        assert_eq!((checks[0].revenue, checks[0].expected), (0.0, 0.3));
+        assert!(!checks[0].passed);
+
+        assert!(matches!(
+            GoldenTest::honest().run(),
+            Err(GoldenError::NoPoints)
+        ));
+    }
+}
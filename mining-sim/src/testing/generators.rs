@@ -0,0 +1,346 @@
+/*!
+Generators of random blockchains and power distributions for fuzzing
+strategies
+
+Strategy authors can check that a [`Miner`](crate::miner::Miner) behaves
+sensibly in arbitrary chain states, such as states containing forks it would
+never create itself, by calling
+[`get_action`](crate::miner::Miner::get_action) with a
+[`ChainView::full`](crate::view::ChainView::full) of a generated chain.
+
+Each generated [`Block`] points to one of the [`ChainShape::fork_window`]
+blocks published before it, and is mined by one of [`ChainShape::miners`]
+miners with IDs starting at 1. Block IDs increase from 1 in publishing order,
+so every generated sequence of blocks can be published to a [`Blockchain`]
+in order.
+
+The `random_*` functions draw from any [`Rng`]. For use with
+[`quickcheck`](https://docs.rs/quickcheck/1), seed a generator from an
+arbitrary `u64`. If the `proptest` feature is enabled, [`blocks`],
+[`blockchain`], [`power_values`], and [`power_distribution`] return
+[`proptest`](https://docs.rs/proptest/1) strategies which shrink towards
+short chains without forks.
+
+## Example
+```
+use mining_sim::{
+    miner::{honest::Honest, Action, Miner, MinerId},
+    testing::generators::{random_blockchain, ChainShape},
+    blockchain::{BlockId, ChainQuery},
+    view::ChainView,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+let mut rng = StdRng::seed_from_u64(0);
+for _ in 0..100 {
+    let chain = random_blockchain(&mut rng, ChainShape::new(3), 20);
+
+    let mut miner = Honest::new();
+    miner.set_id(MinerId::from(1));
+    let mined = BlockId::from(chain.num_blocks());
+    match miner.get_action(&ChainView::full(&chain), Some(mined)) {
+        Action::Publish(block) => {
+            assert!(chain.tip().contains(&block.parent_id.unwrap()))
+        }
+        action => panic!("unexpected action {:?}", action),
+    }
+}
+```
+*/
+
+use rand::Rng;
+
+use crate::{
+    blockchain::{Block, BlockId, Blockchain},
+    miner::MinerId,
+    power_dist::{PowerDistribution, PowerValue},
+};
+
+#[cfg(feature = "proptest")]
+use proptest::{
+    collection::{vec, SizeRange},
+    strategy::Strategy,
+};
+
+/// Describes the blocks generated by this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainShape {
+    /// Number of miners, with IDs `1..=miners`, which mine blocks.
+    pub miners: usize,
+    /// Number of most recently published blocks which a block may point to.
+    pub fork_window: usize,
+}
+
+impl ChainShape {
+    /// Creates a shape with blocks mined by `miners` miners, and a fork
+    /// window of 3.
+    ///
+    /// # Panics
+    /// Panics if `miners` is 0.
+    pub fn new(miners: usize) -> Self {
+        assert!(miners > 0, "blocks must be mined by at least one miner");
+
+        Self {
+            miners,
+            fork_window: 3,
+        }
+    }
+
+    /// Let each block point to one of the `window` blocks published before
+    /// it. A window of 1 generates a single chain without forks.
+    ///
+    /// # Panics
+    /// Panics if `window` is 0.
+    pub fn fork_window(mut self, window: usize) -> Self {
+        assert!(window > 0, "fork window must contain at least one block");
+        self.fork_window = window;
+
+        self
+    }
+}
+
+impl Default for ChainShape {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+/// Returns `len` random blocks, which can be published in order to a new
+/// [`Blockchain`].
+pub fn random_blocks<R: Rng + ?Sized>(
+    rng: &mut R,
+    shape: ChainShape,
+    len: usize,
+) -> Vec<Block> {
+    let choices: Vec<_> = (0..len)
+        .map(|_| {
+            (
+                rng.gen_range(0..shape.fork_window),
+                rng.gen_range(1..=shape.miners),
+            )
+        })
+        .collect();
+
+    blocks_from_choices(choices)
+}
+
+/// Returns a blockchain containing the genesis block and `len` random blocks.
+pub fn random_blockchain<R: Rng + ?Sized>(
+    rng: &mut R,
+    shape: ChainShape,
+    len: usize,
+) -> Blockchain {
+    blockchain_from_blocks(random_blocks(rng, shape, len))
+}
+
+/// Returns random power values for `miners` miners, which sum to 1.
+pub fn random_power_values<R: Rng + ?Sized>(
+    rng: &mut R,
+    miners: usize,
+) -> Vec<PowerValue> {
+    normalize((0..miners).map(|_| rng.gen_range(0.0..=1.0)).collect())
+}
+
+/// Returns a valid [`PowerDistribution`] over `miners` miners, with random
+/// power values.
+pub fn random_power_distribution<R: Rng + ?Sized>(
+    rng: &mut R,
+    miners: usize,
+) -> PowerDistribution {
+    PowerDistribution::SetValues(random_power_values(rng, miners))
+}
+
+/// Strategy generating sequences of blocks whose lengths are in `len`, as
+/// in [`random_blocks`].
+#[cfg(feature = "proptest")]
+pub fn blocks(
+    shape: ChainShape,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<Block>> {
+    vec((0..shape.fork_window, 1..=shape.miners), len)
+        .prop_map(blocks_from_choices)
+}
+
+/// Strategy generating blockchains with a number of blocks other than the
+/// genesis block in `len`, as in [`random_blockchain`].
+///
+/// ## Example
+/// ```
+/// use mining_sim::{
+///     blockchain::ChainQuery,
+///     testing::generators::{blockchain, ChainShape},
+/// };
+/// use proptest::prelude::*;
+///
+/// proptest!(|(chain in blockchain(ChainShape::new(2), 0..50))| {
+///     prop_assert!(chain.max_height() < chain.num_blocks());
+/// });
+/// ```
+#[cfg(feature = "proptest")]
+pub fn blockchain(
+    shape: ChainShape,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = Blockchain> {
+    blocks(shape, len).prop_map(blockchain_from_blocks)
+}
+
+/// Strategy generating power values for `miners` miners, as in
+/// [`random_power_values`].
+#[cfg(feature = "proptest")]
+pub fn power_values(miners: usize) -> impl Strategy<Value = Vec<PowerValue>> {
+    vec(0.0..=1.0, miners).prop_map(normalize)
+}
+
+/// Strategy generating power distributions over `miners` miners, as in
+/// [`random_power_distribution`].
+#[cfg(feature = "proptest")]
+pub fn power_distribution(
+    miners: usize,
+) -> impl Strategy<Value = PowerDistribution> {
+    power_values(miners).prop_map(PowerDistribution::SetValues)
+}
+
+/// Builds blocks from `(offset, miner)` pairs, where the block with ID `i`
+/// points to the block with ID `i - 1 - offset`, or to the genesis block if
+/// that is negative.
+fn blocks_from_choices(choices: Vec<(usize, usize)>) -> Vec<Block> {
+    choices
+        .into_iter()
+        .zip(1..)
+        .map(|((offset, miner), id)| Block {
+            id: BlockId(id),
+            parent_id: Some(BlockId((id - 1).saturating_sub(offset))),
+            miner_id: MinerId(miner),
+            txns: vec![],
+        })
+        .collect()
+}
+
+fn blockchain_from_blocks(blocks: Vec<Block>) -> Blockchain {
+    let mut chain = Blockchain::new();
+    for block in blocks {
+        chain
+            .publish(block)
+            .expect("generated blocks point to earlier blocks");
+    }
+
+    chain
+}
+
+/// Rescales `weights` to sum to 1, or returns equal values if they are all 0.
+fn normalize(weights: Vec<PowerValue>) -> Vec<PowerValue> {
+    let total: PowerValue = weights.iter().sum();
+    match total > 0.0 {
+        true => weights.iter().map(|weight| weight / total).collect(),
+        false => vec![1.0 / weights.len() as PowerValue; weights.len()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{
+        random_blockchain, random_blocks, random_power_distribution, ChainShape,
+    };
+    use crate::{
+        blockchain::{BlockId, Blockchain},
+        miner::{
+            doublespend::DoubleSpend, honest::Honest,
+            honestforking::HonestForking, ndeficit::NDeficit,
+            ndeficiteager::NDeficitEager, noop::Noop, selfish::Selfish, Action,
+            Miner, MinerId,
+        },
+        view::ChainView,
+    };
+
+    fn strategies() -> Vec<Box<dyn Miner>> {
+        vec![
+            Box::new(Honest::new()),
+            Box::new(HonestForking::new(0.5)),
+            Box::new(Selfish::new()),
+            Box::new(NDeficit::new(2)),
+            Box::new(NDeficitEager::new(2)),
+            Box::new(DoubleSpend::new(0, 2)),
+            Box::new(Noop::new()),
+        ]
+    }
+
+    /// Gives a fresh copy of each strategy the next block on `chain`, and
+    /// checks that the blocks it publishes are accepted.
+    fn check_strategies(chain: &Blockchain, miner: MinerId) {
+        let mined = BlockId::from(chain.num_blocks());
+
+        for mut strategy in strategies() {
+            strategy.set_id(miner);
+            let blocks = match strategy
+                .get_action(&ChainView::full(chain), Some(mined))
+            {
+                Action::Wait => vec![],
+                Action::Publish(block) => vec![block],
+                Action::PublishSet(blocks) => blocks,
+            };
+
+            let mut chain = {
+                let mut copy = Blockchain::new();
+                copy.extend_from(chain).unwrap();
+                copy
+            };
+            for block in blocks {
+                assert_eq!(block.miner_id, miner, "{}", strategy.name());
+                assert!(
+                    chain.publish(block).is_ok(),
+                    "{} published an invalid block",
+                    strategy.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generated_chains_are_valid() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let shape = ChainShape::new(3).fork_window(4);
+
+        for len in 0..50 {
+            let blocks = random_blocks(&mut rng, shape, len);
+            assert_eq!(blocks.len(), len);
+            for (block, id) in blocks.iter().zip(1..) {
+                let parent = block.parent_id.unwrap().get();
+                assert!(parent < id && id - parent <= 4);
+                assert!((1..=3).contains(&block.miner_id.get()));
+            }
+
+            let chain = random_blockchain(&mut rng, shape, len);
+            assert_eq!(chain.num_blocks(), len + 1);
+
+            let dist = random_power_distribution(&mut rng, 3);
+            assert!(dist.is_valid(3), "{:?}", dist);
+        }
+
+        let chain = random_blockchain(&mut rng, shape.fork_window(1), 10);
+        assert_eq!(chain.max_height(), 10);
+    }
+
+    #[test]
+    fn strategies_act_on_random_chains() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for len in 0..200 {
+            let chain = random_blockchain(&mut rng, ChainShape::new(2), len);
+            check_strategies(&chain, MinerId::from(2));
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn strategies_act_on_arbitrary_chains(
+            chain in super::blockchain(ChainShape::new(3), 0..100),
+            dist in super::power_distribution(3),
+        ) {
+            proptest::prop_assert!(dist.is_valid(3));
+            check_strategies(&chain, MinerId::from(3));
+        }
+    }
+}
@@ -1,17 +1,26 @@
 //! Describing tie-breaking behavior in miner strategies
 
-use rand::{seq::SliceRandom, Rng};
+use std::{collections::HashSet, sync::Arc};
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    seq::SliceRandom,
+    Rng,
+};
 
 use crate::{
-    blockchain::{BlockId, Blockchain},
+    blockchain::{Block, BlockId, ChainQuery},
     miner::MinerId,
 };
 
 /// Breaks ties between multiple blocks of at the tip of a blockchain's longest
 /// chain.
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub enum TieBreaker {
-    /// Use the block published in the earliest round.
+    /// Use the block published in the earliest round. Given a miner's
+    /// [`ChainView`](crate::view::ChainView), this is the block the miner saw
+    /// first, which differs from global publication order when the miner's
+    /// [`Visibility`](crate::view::Visibility) delays or hides blocks.
     #[default]
     EarliestPublished,
     /// Use the earliest block published by the specified miner, if such a block
@@ -21,21 +30,172 @@ pub enum TieBreaker {
     /// specified miner, if such a block exists. Otherwise, use the earliest
     /// block published by any *other* miner.
     FavorMinerProb(MinerId, f64),
+    /// Use the highest block published by the specified miner at most the
+    /// given number of blocks below the tip of the longest chain, preferring
+    /// the earliest such block at each height. If no such block exists, use
+    /// the earliest block at the tip. With a depth of `0`, this is equivalent
+    /// to [`TieBreaker::FavorMiner`].
+    ///
+    /// Allows stubborn strategies to keep mining on their own fork after
+    /// falling behind the longest chain.
+    FavorMinerFork(MinerId, usize),
+    /// Use the earliest block published by any of the specified miners, if
+    /// such a block exists. Otherwise, use the earliest block published by
+    /// any miner. Models a coalition of colluding miners.
+    FavorAny(HashSet<MinerId>),
+    /// With the given probability, use the earliest block published by any of
+    /// the specified miners, if such a block exists. Otherwise, use the
+    /// earliest block published by a miner outside of the set.
+    FavorAnyProb(HashSet<MinerId>, f64),
+    /// With the given probability, use a block picked uniformly at random from
+    /// every block at the tip except the earliest published. Otherwise, use
+    /// the block published in the earliest round.
+    ///
+    /// During a race, the blocks published after the first are typically
+    /// attackers', so this variant models the propagation advantage `gamma`
+    /// of [Eyal and Sirer](https://doi.org/10.48550/arXiv.1311.0243) without
+    /// naming the attacking miner.
+    FavorLatestProb(f64),
+    /// Use a block picked at random, with probability proportional to the
+    /// propagation weight of the miner which published it. The weight of
+    /// miner `i` is element `i - 1` of the given vector, and miners without a
+    /// weight have weight `0.0`. If every block at the tip has weight `0.0`,
+    /// use the block published in the earliest round.
+    ///
+    /// This is a finer-grained version of the propagation advantage modeled
+    /// by [`TieBreaker::FavorMinerProb`], in which every miner's blocks reach
+    /// the network at a different rate.
+    PropagationWeights(Vec<f64>),
     /// Use a block picked uniformly at random.
     Random,
+    /// Use the block with the smallest deterministic pseudo-hash of its
+    /// [`BlockId`], parent, and miner. The result does not depend on the
+    /// order in which blocks were published, which models protocols that
+    /// break ties by block hash instead of by first-seen order.
+    ByBlockHashLike,
+    /// Use the block chosen by a user-defined rule. See
+    /// [`TieBreaker::custom`].
+    Custom(CustomTieBreaker),
+}
+
+/// Chooses a block from the tip of a blockchain's longest chain.
+type TieBreakFn = dyn Fn(&[BlockId], &dyn ChainQuery) -> BlockId + Send + Sync;
+
+/// Named, user-defined tie-breaking rule used by [`TieBreaker::Custom`].
+/// Rules are compared by name.
+#[derive(Clone)]
+pub struct CustomTieBreaker {
+    name: String,
+    func: Arc<TieBreakFn>,
+}
+
+impl CustomTieBreaker {
+    /// Returns the name of this rule.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl std::fmt::Debug for CustomTieBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<CustomTieBreaker: {}>", self.name)
+    }
+}
+
+impl PartialEq for CustomTieBreaker {
+    fn eq(&self, other: &Self) -> bool {
+        self.name.eq(&other.name)
+    }
 }
 
 impl TieBreaker {
+    /// Creates a [`TieBreaker::Custom`] rule named `name`. `func` is given the
+    /// blocks at the tip of the longest chain (or at the height passed to
+    /// [`TieBreaker::choose_at`]), in order of publication, along with the
+    /// blockchain, and must return one of the given blocks.
+    ///
+    /// # Panics
+    /// Choosing a block with the returned rule panics if `func` returns a
+    /// block which is not one of the given blocks.
+    ///
+    /// # Example
+    /// ```
+    /// use mining_sim::prelude::*;
+    ///
+    /// // Favor the block with the highest ID
+    /// let tie_breaker = TieBreaker::custom("Highest ID", |tip, _chain| {
+    ///     *tip.iter().max().unwrap()
+    /// });
+    /// let miner = Honest::with_tie_breaker(tie_breaker);
+    /// ```
+    pub fn custom<N, F>(name: N, func: F) -> Self
+    where
+        N: Into<String>,
+        F: Fn(&[BlockId], &dyn ChainQuery) -> BlockId + Send + Sync + 'static,
+    {
+        Self::Custom(CustomTieBreaker {
+            name: name.into(),
+            func: Arc::new(func),
+        })
+    }
+
     /// Returns the block at the tip of the longest chain in `blockchain`,
-    /// according to the given tie-breaking rule.
-    pub fn choose(&self, blockchain: &Blockchain) -> BlockId {
+    /// according to the given tie-breaking rule. Randomness is drawn from the
+    /// thread-local random number generator, so the result cannot be
+    /// reproduced by seeding a simulation.
+    #[deprecated(
+        note = "use `choose_with` and a seeded random number generator"
+    )]
+    pub fn choose(&self, blockchain: &dyn ChainQuery) -> BlockId {
+        self.choose_with(&mut rand::thread_rng(), blockchain)
+    }
+
+    /// Returns the block at the tip of the longest chain in `blockchain`,
+    /// according to the given tie-breaking rule. Randomized rules draw from
+    /// `rng`, such as the generator seeded by
+    /// [`Miner::set_rng_seed`](crate::miner::Miner::set_rng_seed).
+    ///
+    /// The candidates are the blocks of [`ChainQuery::tip`], so chains with
+    /// other fork-choice rules break ties among their own heads.
+    pub fn choose_with<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        blockchain: &dyn ChainQuery,
+    ) -> BlockId {
         let tip = blockchain.tip();
-        let mut rng = rand::thread_rng();
+        let height = blockchain[tip[0]].height;
+
+        self.choose_among(rng, blockchain, tip, height)
+    }
 
+    /// Returns the block among those at `height` in `blockchain` chosen by the
+    /// given tie-breaking rule, as if the blocks at `height` were the tip of
+    /// the longest chain. Rules which search below the tip, such as
+    /// [`TieBreaker::FavorMinerFork`], search below `height` instead. Returns
+    /// [`None`] if no blocks are at `height`.
+    pub fn choose_at<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        blockchain: &dyn ChainQuery,
+        height: usize,
+    ) -> Option<BlockId> {
+        let blocks = blockchain.at_height(height)?;
+
+        Some(self.choose_among(rng, blockchain, blocks, height))
+    }
+
+    /// Chooses one of `blocks`, which are at `height` in `blockchain`.
+    fn choose_among<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        blockchain: &dyn ChainQuery,
+        blocks: &[BlockId],
+        height: usize,
+    ) -> BlockId {
         match &self {
-            Self::EarliestPublished => tip[0],
+            Self::EarliestPublished => blocks[0],
             Self::FavorMiner(miner_id) => {
-                let block_id = tip
+                let block_id = blocks
                     .iter()
                     .find(|&block_id| {
                         blockchain[block_id].block.miner_id.eq(miner_id)
@@ -44,7 +204,7 @@ impl TieBreaker {
 
                 match block_id {
                     Some(block_id) => block_id,
-                    None => tip[0],
+                    None => blocks[0],
                 }
             }
             Self::FavorMinerProb(miner_id, prob) => {
@@ -53,13 +213,13 @@ impl TieBreaker {
                     "tie breaker probability must be between 0 and 1"
                 );
 
-                let favored = tip
+                let favored = blocks
                     .iter()
                     .find(|&block_id| {
                         blockchain[block_id].block.miner_id.eq(miner_id)
                     })
                     .copied();
-                let not_favored = tip
+                let not_favored = blocks
                     .iter()
                     .find(|&block_id| {
                         blockchain[block_id].block.miner_id.ne(miner_id)
@@ -76,11 +236,296 @@ impl TieBreaker {
                         }
                     }
                     (None, None) => {
-                        unreachable!("blockchain tip cannot be empty")
+                        unreachable!("blockchain heights cannot be empty")
                     }
                 }
             }
-            Self::Random => *tip.choose(&mut rng).unwrap(),
+            Self::FavorMinerFork(miner_id, depth) => {
+                let min_height = height.saturating_sub(*depth);
+
+                (min_height..=height)
+                    .rev()
+                    .filter_map(|height| blockchain.at_height(height))
+                    .find_map(|candidates| {
+                        candidates.iter().find(|&block_id| {
+                            blockchain[block_id].block.miner_id.eq(miner_id)
+                        })
+                    })
+                    .copied()
+                    .unwrap_or(blocks[0])
+            }
+            Self::FavorAny(miner_ids) => blocks
+                .iter()
+                .find(|&block_id| {
+                    miner_ids.contains(&blockchain[block_id].block.miner_id)
+                })
+                .copied()
+                .unwrap_or(blocks[0]),
+            Self::FavorAnyProb(miner_ids, prob) => {
+                assert!(
+                    (0.0..=1.0).contains(prob),
+                    "tie breaker probability must be between 0 and 1"
+                );
+
+                let in_set = |block_id: &&BlockId| {
+                    miner_ids.contains(&blockchain[*block_id].block.miner_id)
+                };
+                let favored = blocks.iter().find(in_set).copied();
+                let not_favored =
+                    blocks.iter().find(|block_id| !in_set(block_id)).copied();
+
+                match (favored, not_favored) {
+                    (Some(block_id), None) | (None, Some(block_id)) => block_id,
+                    (Some(favored), Some(not_favored)) => {
+                        if rng.gen_bool(*prob) {
+                            favored
+                        } else {
+                            not_favored
+                        }
+                    }
+                    (None, None) => {
+                        unreachable!("blockchain heights cannot be empty")
+                    }
+                }
+            }
+            Self::FavorLatestProb(prob) => {
+                assert!(
+                    (0.0..=1.0).contains(prob),
+                    "tie breaker probability must be between 0 and 1"
+                );
+
+                match blocks.split_first() {
+                    Some((_, later))
+                        if !later.is_empty() && rng.gen_bool(*prob) =>
+                    {
+                        *later.choose(rng).unwrap()
+                    }
+                    _ => blocks[0],
+                }
+            }
+            Self::PropagationWeights(weights) => {
+                if blocks.len() == 1 {
+                    return blocks[0];
+                }
+
+                let block_weights = blocks.iter().map(|block_id| {
+                    let miner_id = blockchain[block_id].block.miner_id;
+                    miner_id
+                        .0
+                        .checked_sub(1)
+                        .and_then(|i| weights.get(i))
+                        .copied()
+                        .unwrap_or_default()
+                });
+
+                match WeightedIndex::new(block_weights) {
+                    Ok(dist) => blocks[dist.sample(rng)],
+                    Err(_) => blocks[0],
+                }
+            }
+            Self::Random => *blocks.choose(rng).unwrap(),
+            Self::ByBlockHashLike => *blocks
+                .iter()
+                .min_by_key(|&block_id| {
+                    pseudo_hash(&blockchain[block_id].block)
+                })
+                .unwrap(),
+            Self::Custom(rule) => {
+                let block_id = (rule.func)(blocks, blockchain);
+                assert!(
+                    blocks.contains(&block_id),
+                    "tie breaker {:?} chose a block outside of the given blocks",
+                    rule.name
+                );
+
+                block_id
+            }
+        }
+    }
+}
+
+/// Deterministic stand-in for the hash of `block`, computed by mixing its
+/// ID, parent ID, and miner ID with the SplitMix64 finalizer.
+fn pseudo_hash(block: &Block) -> u64 {
+    let mix = |mut z: u64| {
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    };
+    let parent_id = block.parent_id.map_or(0, |id| id.0 as u64 + 1);
+
+    [block.id.0 as u64, parent_id, block.miner_id.0 as u64]
+        .into_iter()
+        .fold(0x9e3779b97f4a7c15, |hash, x| mix(hash ^ x))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::TieBreaker;
+    use crate::{
+        blockchain::{Block, BlockId, Blockchain},
+        miner::MinerId,
+    };
+
+    /// Returns a blockchain whose tip has one block from each of the given
+    /// miners, in order.
+    fn tied_chain(miners: &[usize]) -> Blockchain {
+        let mut chain = Blockchain::new();
+        for (i, &miner) in miners.iter().enumerate() {
+            chain
+                .publish(Block {
+                    id: BlockId(i + 1),
+                    parent_id: Some(BlockId(0)),
+                    miner_id: MinerId(miner),
+                    txns: vec![],
+                })
+                .unwrap();
+        }
+
+        chain
+    }
+
+    #[test]
+    fn favor_miner_fork_tie_breaker() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut chain = tied_chain(&[1, 2]);
+        for (id, parent) in [(3, 2), (4, 3)] {
+            chain
+                .publish(Block {
+                    id: BlockId(id),
+                    parent_id: Some(BlockId(parent)),
+                    miner_id: MinerId(2),
+                    txns: vec![],
+                })
+                .unwrap();
+        }
+
+        let mut choose = |depth| {
+            let tie_breaker = TieBreaker::FavorMinerFork(MinerId(1), depth);
+            tie_breaker.choose_with(&mut rng, &chain)
+        };
+
+        assert_eq!(choose(0), BlockId(4));
+        assert_eq!(choose(1), BlockId(4));
+        assert_eq!(choose(2), BlockId(1));
+    }
+
+    #[test]
+    fn choose_below_tip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut chain = tied_chain(&[1, 2, 3]);
+        chain
+            .publish(Block {
+                id: BlockId(4),
+                parent_id: Some(BlockId(1)),
+                miner_id: MinerId(1),
+                txns: vec![],
+            })
+            .unwrap();
+
+        let tie_breaker = TieBreaker::FavorMiner(MinerId(2));
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(4));
+        assert_eq!(
+            tie_breaker.choose_at(&mut rng, &chain, 1),
+            Some(BlockId(2))
+        );
+        assert_eq!(
+            tie_breaker.choose_at(&mut rng, &chain, 2),
+            Some(BlockId(4))
+        );
+        assert_eq!(tie_breaker.choose_at(&mut rng, &chain, 3), None);
+    }
+
+    #[test]
+    fn favor_any_tie_breaker() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let chain = tied_chain(&[1, 2, 3]);
+        let coalition = HashSet::from([MinerId(3), MinerId(2)]);
+
+        let tie_breaker = TieBreaker::FavorAny(coalition.clone());
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(2));
+
+        let tie_breaker = TieBreaker::FavorAnyProb(coalition.clone(), 1.0);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(2));
+
+        let tie_breaker = TieBreaker::FavorAnyProb(coalition, 0.0);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(1));
+
+        let outsiders = HashSet::from([MinerId(4)]);
+        let tie_breaker = TieBreaker::FavorAny(outsiders);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(1));
+    }
+
+    #[test]
+    fn favor_latest_prob_tie_breaker() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let chain = tied_chain(&[1, 2, 3]);
+
+        let tie_breaker = TieBreaker::FavorLatestProb(0.0);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(1));
+
+        let tie_breaker = TieBreaker::FavorLatestProb(1.0);
+        let chosen: HashSet<_> = (0..100)
+            .map(|_| tie_breaker.choose_with(&mut rng, &chain))
+            .collect();
+        assert_eq!(chosen, HashSet::from([BlockId(2), BlockId(3)]));
+
+        let chain = tied_chain(&[1]);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(1));
+    }
+
+    #[test]
+    fn propagation_weights_tie_breaker() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let chain = tied_chain(&[1, 2, 3]);
+
+        let tie_breaker = TieBreaker::PropagationWeights(vec![0.0, 0.0, 2.0]);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(3));
+
+        let tie_breaker = TieBreaker::PropagationWeights(vec![0.0, 1.0]);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(2));
+
+        let tie_breaker = TieBreaker::PropagationWeights(vec![]);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(1));
+    }
+
+    #[test]
+    fn block_hash_tie_breaker() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let chain = tied_chain(&[1, 2, 3, 4]);
+        let mut reversed = Blockchain::new();
+        for id in (1..=4).rev() {
+            reversed.publish(chain[BlockId(id)].block.clone()).unwrap();
         }
+
+        let tie_breaker = TieBreaker::ByBlockHashLike;
+        let block_id = tie_breaker.choose_with(&mut rng, &chain);
+
+        assert_eq!(tie_breaker.choose_with(&mut rng, &reversed), block_id);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), block_id);
+    }
+
+    #[test]
+    fn custom_tie_breaker() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let chain = tied_chain(&[1, 2, 3]);
+        let last = TieBreaker::custom("Last", |tip, _| tip[tip.len() - 1]);
+
+        assert_eq!(last.choose_with(&mut rng, &chain), BlockId(3));
+        assert_eq!(last, TieBreaker::custom("Last", |tip, _| tip[0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "chose a block outside of the given blocks")]
+    fn custom_tie_breaker_outside_blocks() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let chain = tied_chain(&[1, 2, 3]);
+        let genesis = TieBreaker::custom("Genesis", |_, chain| chain.genesis());
+
+        genesis.choose_with(&mut rng, &chain);
     }
 }
@@ -1,5 +1,125 @@
-//! Describing transactions on the chain (currently unimplemented)
+//! Describing transactions on the chain
 
 /// Describes a transaction on the blockchain.
-#[derive(Debug, Clone)]
-pub struct Transaction;
+///
+/// ## Example
+/// ```
+/// use mining_sim::transaction::{Transaction, TransactionId};
+///
+/// let txn = Transaction::new(TransactionId::from(1), 0.5)
+///     .size(2)
+///     .arrival_round(10)
+///     .tag("exchange");
+///
+/// assert_eq!(txn.size, 2);
+/// assert_eq!(txn.tag.as_deref(), Some("exchange"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transaction {
+    /// Unique identifier of this transaction.
+    pub id: TransactionId,
+    /// Fee paid to the miner of the block which includes this transaction.
+    pub fee: f64,
+    /// Size of this transaction, counted against the capacity of the block
+    /// which includes it.
+    pub size: usize,
+    /// Identifier of the coin spent by this transaction, if any. Transactions
+    /// spending the same input conflict, so at most one of them can be
+    /// included on any branch of the blockchain.
+    pub input: Option<usize>,
+    /// Simulation round in which this transaction entered the
+    /// [`Mempool`](crate::mempool::Mempool).
+    pub arrival_round: usize,
+    /// Optional label used to group transactions in analyses, such as
+    /// `"whale"` for the transactions added by
+    /// [`Mempool::whale`](crate::mempool::Mempool::whale).
+    pub tag: Option<String>,
+}
+
+impl Transaction {
+    /// Creates a new untagged transaction with ID `id` paying `fee`, which
+    /// has size 1, spends no input, and arrived in round 0.
+    pub fn new(id: TransactionId, fee: f64) -> Self {
+        Self {
+            id,
+            fee,
+            size: 1,
+            input: None,
+            arrival_round: 0,
+            tag: None,
+        }
+    }
+
+    /// Set the size of this transaction.
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+
+        self
+    }
+
+    /// Set the identifier of the coin spent by this transaction.
+    pub fn input(mut self, input: usize) -> Self {
+        self.input = Some(input);
+
+        self
+    }
+
+    /// Set the round in which this transaction arrived.
+    pub fn arrival_round(mut self, round: usize) -> Self {
+        self.arrival_round = round;
+
+        self
+    }
+
+    /// Set the label of this transaction.
+    pub fn tag<T: Into<String>>(mut self, tag: T) -> Self {
+        self.tag = Some(tag.into());
+
+        self
+    }
+}
+
+/// Unique identifier of a [`Transaction`]. Corresponds to a [`usize`].
+#[repr(transparent)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct TransactionId(pub(crate) usize);
+
+impl TransactionId {
+    /// Returns the [`usize`] corresponding to this [`TransactionId`].
+    pub fn get(&self) -> usize {
+        self.0
+    }
+}
+
+impl From<usize> for TransactionId {
+    fn from(value: usize) -> Self {
+        Self(value)
+    }
+}
+
+impl std::fmt::Display for TransactionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "serde")]
+    #[test]
+    fn transaction_round_trip() {
+        use super::{Transaction, TransactionId};
+
+        let txn = Transaction::new(TransactionId(7), 2.5)
+            .input(3)
+            .arrival_round(4)
+            .tag("payment");
+        let json = serde_json::to_string(&txn).unwrap();
+
+        assert!(json.contains(r#""id":7"#));
+        assert_eq!(serde_json::from_str::<Transaction>(&json).unwrap(), txn);
+    }
+}
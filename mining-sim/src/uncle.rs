@@ -0,0 +1,248 @@
+/*!
+Ethereum-style uncle rewards
+
+In proof-of-work Ethereum, blocks which lost a fork could still be referenced
+as *uncles* by later blocks on the longest chain, which are called their
+*nephews*. The miner of an uncle was paid a fraction of the block subsidy
+which shrinks with the distance between the uncle and its nephew, and the
+miner of the nephew was paid a small inclusion reward for each uncle it
+referenced. Since uncle rewards soften the cost of losing forks, they change
+the profitability of strategies like selfish mining.
+
+Passing an [`UncleRewards`] to
+[`SimulationBuilder::uncle_rewards`](crate::simulation::SimulationBuilder::uncle_rewards)
+pays these rewards at the end of each simulation, in
+[`MinerStats::uncle_income`](crate::simulation::MinerStats::uncle_income) and
+[`MinerStats::nephew_income`](crate::simulation::MinerStats::nephew_income).
+Blocks don't carry uncle references, so uncles are assigned from the final
+block tree instead: each block on the longest chain references the oldest
+eligible uncles, up to [`UncleRewards::max_uncles`] of them. A block is an
+eligible uncle of a nephew if it is not on the longest chain, its parent is,
+it was mined before the nephew, it is at most
+[`UncleRewards::max_distance`] blocks below the nephew, and no earlier nephew
+has referenced it.
+
+## Example
+```
+use mining_sim::prelude::*;
+
+let data = SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(Selfish::new())
+    .power_values([0.7, 0.3])
+    .uncle_rewards(UncleRewards::new())
+    .rounds(1000)
+    .build()
+    .unwrap()
+    .run_all()
+    .unwrap()
+    .data();
+
+let honest = data[0].miner_stats[&MinerId::from(1)];
+assert!(honest.uncles > 0);
+assert!(honest.uncle_income > 0.0);
+```
+*/
+
+use std::collections::HashSet;
+
+use crate::blockchain::{BlockId, Blockchain};
+
+/// Configures the uncle rewards of a simulation. See the
+/// [module docs](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct UncleRewards {
+    pub(crate) uncle_rewards: Vec<f64>,
+    pub(crate) nephew_reward: f64,
+    pub(crate) max_uncles: usize,
+}
+
+impl Default for UncleRewards {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UncleRewards {
+    /// Creates the reward rules used by Ethereum from its launch until the
+    /// merge: an uncle at distance `d` from its nephew earns `(8 - d) / 8` of
+    /// the block subsidy for `d` up to 6, and each nephew earns `1 / 32` of
+    /// the block subsidy for each of its at most 2 uncles.
+    pub fn new() -> Self {
+        Self {
+            uncle_rewards: (1..=6).map(|d| (8 - d) as f64 / 8.0).collect(),
+            nephew_reward: 1.0 / 32.0,
+            max_uncles: 2,
+        }
+    }
+
+    /// Set the fraction of the block subsidy paid to the miner of an uncle,
+    /// by distance from its nephew, starting at distance 1. The number of
+    /// fractions given is the maximum distance of an uncle from its nephew.
+    /// Each fraction must be finite and non-negative.
+    pub fn uncle_reward<I>(mut self, fractions: I) -> Self
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        self.uncle_rewards = fractions.into_iter().collect();
+
+        self
+    }
+
+    /// Set the fraction of the block subsidy paid to the miner of a nephew
+    /// for each uncle it references. Must be finite and non-negative.
+    pub fn nephew_reward(mut self, fraction: f64) -> Self {
+        self.nephew_reward = fraction;
+
+        self
+    }
+
+    /// Let each block on the longest chain reference up to `uncles` uncles.
+    pub fn max_uncles(mut self, uncles: usize) -> Self {
+        self.max_uncles = uncles;
+
+        self
+    }
+
+    /// Returns the greatest distance at which an uncle can be referenced.
+    pub fn max_distance(&self) -> usize {
+        self.uncle_rewards.len()
+    }
+
+    /// Returns the fraction of the block subsidy paid to the miner of an
+    /// uncle at `distance` from its nephew, or [`None`] if the distance is
+    /// out of range.
+    pub fn uncle_reward_at(&self, distance: usize) -> Option<f64> {
+        distance
+            .checked_sub(1)
+            .and_then(|i| self.uncle_rewards.get(i))
+            .copied()
+    }
+
+    /// Returns the fraction of the block subsidy paid to the miner of a
+    /// nephew for each uncle it references.
+    pub fn nephew_reward_fraction(&self) -> f64 {
+        self.nephew_reward
+    }
+
+    /// Returns the uncles referenced by the blocks on the longest chain of
+    /// `chain`, in order of nephew height.
+    pub fn uncles(&self, chain: &Blockchain) -> Vec<Uncle> {
+        let mut canonical: Vec<BlockId> = chain.longest_chain().collect();
+        canonical.reverse();
+
+        let mut referenced = HashSet::new();
+        let mut uncles = vec![];
+        for (height, &nephew) in canonical.iter().enumerate().skip(1) {
+            let lowest = height.saturating_sub(self.max_distance()).max(1);
+            let candidates = (lowest..height).flat_map(|uncle_height| {
+                let parent = canonical[uncle_height - 1];
+                chain
+                    .at_height(uncle_height)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(move |&&id| chain.get_parent(id) == Some(parent))
+                    .map(move |&id| (id, height - uncle_height))
+            });
+
+            let mut count = 0;
+            for (id, distance) in candidates {
+                if count == self.max_uncles {
+                    break;
+                }
+                if id < nephew
+                    && id != canonical[height - distance]
+                    && referenced.insert(id)
+                {
+                    uncles.push(Uncle {
+                        block: id,
+                        nephew,
+                        distance,
+                    });
+                    count += 1;
+                }
+            }
+        }
+
+        uncles
+    }
+}
+
+/// Block off the longest chain which is referenced by a block on it. See
+/// [`UncleRewards::uncles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uncle {
+    pub block: BlockId,
+    pub nephew: BlockId,
+    /// Difference between the heights of the nephew and the uncle.
+    pub distance: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        blockchain::{Block, BlockId, Blockchain},
+        miner::MinerId,
+    };
+
+    use super::{Uncle, UncleRewards};
+
+    fn publish(chain: &mut Blockchain, id: usize, parent: usize) {
+        chain
+            .publish(Block {
+                id: BlockId(id),
+                parent_id: Some(BlockId(parent)),
+                miner_id: MinerId(1),
+                txns: vec![],
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn ethereum_rewards() {
+        let rewards = UncleRewards::new();
+        assert_eq!(rewards.max_distance(), 6);
+        assert_eq!(rewards.uncle_reward_at(0), None);
+        assert_eq!(rewards.uncle_reward_at(1), Some(0.875));
+        assert_eq!(rewards.uncle_reward_at(6), Some(0.25));
+        assert_eq!(rewards.uncle_reward_at(7), None);
+        assert_eq!(rewards.nephew_reward_fraction(), 1.0 / 32.0);
+    }
+
+    #[test]
+    fn uncles_follow_eligibility_rules() {
+        // 0 - 1 - 2 - 6 - 7 - 8 - 9 - 10 - 11
+        //     |\   \
+        //     | 3   12
+        //     4  \
+        //         5
+        let mut chain = Blockchain::new();
+        for (id, parent) in [(1, 0), (2, 1), (3, 1), (4, 1), (5, 3), (6, 2)] {
+            publish(&mut chain, id, parent);
+        }
+        for id in 7..=11 {
+            publish(&mut chain, id, id - 1);
+        }
+        publish(&mut chain, 12, 2);
+
+        // Block 5 is not a child of the longest chain, and block 12 was mined
+        // after every block on the longest chain.
+        let uncle = |block, nephew, distance| Uncle {
+            block: BlockId(block),
+            nephew: BlockId(nephew),
+            distance,
+        };
+        assert_eq!(
+            UncleRewards::new().uncles(&chain),
+            [uncle(3, 6, 1), uncle(4, 6, 1)]
+        );
+        assert_eq!(
+            UncleRewards::new().max_uncles(1).uncles(&chain),
+            [uncle(3, 6, 1), uncle(4, 7, 2)]
+        );
+
+        // Block 4 is too far below block 7 to be referenced by it.
+        let rewards = UncleRewards::new().uncle_reward([0.5]).max_uncles(1);
+        assert_eq!(rewards.uncles(&chain), [uncle(3, 6, 1)]);
+    }
+}
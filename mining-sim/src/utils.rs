@@ -2,7 +2,6 @@
 
 /// Uses a name string to turn any pure `Fn(Input) -> Output` into a sized,
 /// sortable, hashable, clone-able, and thread-safe datatype.
-#[derive(Clone)]
 pub struct WrapFunc<Input, Output> {
     name: String,
     func: std::sync::Arc<dyn Fn(Input) -> Output + Send + Sync + 'static>,
@@ -38,6 +37,15 @@ impl<I, J> WrapFunc<I, J> {
     }
 }
 
+impl<I, J> Clone for WrapFunc<I, J> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            func: self.func.clone(),
+        }
+    }
+}
+
 impl<I, J> std::fmt::Debug for WrapFunc<I, J> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "<WrappedFunc: {}>", self.name)
@@ -54,7 +62,7 @@ impl<I, J> Eq for WrapFunc<I, J> {}
 
 impl<I, J> PartialOrd for WrapFunc<I, J> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.name.cmp(&other.name))
+        Some(self.cmp(other))
     }
 }
 
@@ -88,3 +96,182 @@ pub fn median_of_floats(values: &mut [f64]) -> f64 {
         nth_float(values, mid)
     }
 }
+
+/// Returns the `q`-quantile of `values`, where `q` is between 0.0 and 1.0,
+/// linearly interpolating between adjacent ranks.
+pub fn quantile_of_floats(values: &mut [f64], q: f64) -> f64 {
+    debug_assert!(!values.is_empty(), "quantile of empty vec");
+
+    let pos = q * (values.len() - 1) as f64;
+    let rank = pos.floor() as usize;
+    let lower = nth_float(values, rank);
+
+    if rank + 1 < values.len() {
+        let upper = nth_float(values, rank + 1);
+        lower + (upper - lower) * (pos - rank as f64)
+    } else {
+        lower
+    }
+}
+
+/// Returns the two-sided p-value of a one-sample Student's t-test of the
+/// hypothesis that `samples` are drawn from a distribution with the given
+/// `mean`. Returns NaN if there are fewer than two samples.
+pub fn t_test_p_value(samples: &[f64], mean: f64) -> f64 {
+    if samples.len() < 2 {
+        return f64::NAN;
+    }
+
+    let n = samples.len() as f64;
+    let sample_mean = samples.iter().sum::<f64>() / n;
+    let sum_sq: f64 = samples.iter().map(|v| (v - sample_mean).powi(2)).sum();
+    let std_err = (sum_sq / (n - 1.0) / n).sqrt();
+
+    if std_err == 0.0 {
+        return match sample_mean == mean {
+            true => 1.0,
+            false => 0.0,
+        };
+    }
+
+    let t = (sample_mean - mean) / std_err;
+    let df = n - 1.0;
+
+    regularized_incomplete_beta(df / (df + t * t), 0.5 * df, 0.5)
+}
+
+/// Natural logarithm of the gamma function, computed with the Lanczos
+/// approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |acc, (i, c)| {
+            acc + c / (x + 1.0 + i as f64)
+        });
+
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Regularized incomplete beta function I<sub>x</sub>(a, b).
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b)
+        + a * x.ln()
+        + b * (1.0 - x).ln())
+    .exp();
+
+    // The continued fraction converges quickly for x < (a + 1) / (a + b + 2)
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Continued fraction for the incomplete beta function, evaluated with the
+/// modified Lentz method.
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 300;
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+
+    let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut result = d;
+
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+
+        let numerator = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + numerator * d);
+        c = clamp(1.0 + numerator / c);
+        result *= d * c;
+
+        let numerator =
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + numerator * d);
+        c = clamp(1.0 + numerator / c);
+        let delta = d * c;
+        result *= delta;
+
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+
+    result
+}
+
+/// Returns the Gini coefficient of `values`, which must be non-negative. The
+/// coefficient is 0 when all values are equal, and approaches 1 as a single
+/// value dominates the total. Returns 0 if all values are 0.
+pub fn gini_of_floats(values: &mut [f64]) -> f64 {
+    debug_assert!(!values.is_empty(), "gini coefficient of empty vec");
+
+    let total: f64 = values.iter().sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    values.sort_by(|a, b| a.total_cmp(b));
+
+    let n = values.len() as f64;
+    let weighted: f64 = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (i + 1) as f64 * value)
+        .sum();
+
+    2.0 * weighted / (n * total) - (n + 1.0) / n
+}
+
+/// Solves a linear system given as an augmented matrix with Gaussian
+/// elimination, or returns `None` if it is singular, i.e. if a pivot is
+/// smaller than `epsilon` in magnitude.
+pub fn solve_linear(
+    mut matrix: Vec<Vec<f64>>,
+    epsilon: f64,
+) -> Option<Vec<f64>> {
+    let n = matrix.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&a, &b| {
+            matrix[a][col].abs().total_cmp(&matrix[b][col].abs())
+        })?;
+        if matrix[pivot][col].abs() < epsilon {
+            return None;
+        }
+        matrix.swap(col, pivot);
+
+        let pivot_row = matrix[col].clone();
+        for (i, row) in matrix.iter_mut().enumerate() {
+            if i != col {
+                let factor = row[col] / pivot_row[col];
+                for (x, pivot_x) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *x -= factor * pivot_x;
+                }
+            }
+        }
+    }
+
+    Some((0..n).map(|i| matrix[i][n] / matrix[i][i]).collect())
+}
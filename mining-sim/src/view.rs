@@ -0,0 +1,527 @@
+/*!
+Restricting the blocks each miner can see
+
+By default, every miner sees every block as soon as it is published. Giving a
+miner a [`Visibility`] other than [`Visibility::Full`] with
+[`SimulationBuilder::visibility`](crate::simulation::SimulationBuilder::visibility)
+models partial-information games, such as miners with slow network
+connections or miners eclipsed by an attacker.
+
+## Example
+Delaying the blocks seen by an honest miner, so that a selfish miner wins more
+races:
+```
+use mining_sim::prelude::*;
+
+let sim = SimulationBuilder::new()
+    .add_miner(Honest::new())
+    .add_miner(Selfish::new())
+    .visibility(MinerId::from(1), Visibility::Delayed(2))
+    .build()
+    .unwrap();
+```
+*/
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    ops::{Deref, Index},
+};
+
+use crate::{
+    blockchain::{
+        Ancestors, Block, BlockData, BlockId, Blockchain, ChainQuery,
+    },
+    miner::MinerId,
+};
+
+/// Determines which blocks published by other miners a miner can see. A miner
+/// always sees its own blocks as soon as it publishes them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum Visibility {
+    /// See every block as soon as it is published.
+    #[default]
+    Full,
+    /// See each block the given number of rounds after it is published.
+    Delayed(usize),
+    /// Only see blocks published by the given miners. Blocks which extend a
+    /// block the miner cannot see are never seen.
+    Eclipsed(Vec<MinerId>),
+}
+
+/// The [`Visibility::Full`] policy, used by [`ChainView::full`].
+static FULL: Visibility = Visibility::Full;
+
+/// A miner's view of the simulation's blockchain, passed to
+/// [`Miner::get_action`](crate::miner::Miner::get_action).
+///
+/// Implements [`ChainQuery`] for, and dereferences to, the chain containing
+/// the blocks the miner can see under its [`Visibility`]. Under
+/// [`Visibility::Full`], this is the simulation's blockchain itself.
+/// Otherwise, blocks are published to the view as the miner sees them, so
+/// the blocks at each height are in the miner's first-seen order. Any
+/// [`ChainQuery`] can be viewed, so strategies written against a view work
+/// with other chain structures.
+#[derive(Debug, Clone, Copy)]
+pub struct ChainView<'a> {
+    chain: &'a dyn ChainQuery,
+    visibility: &'a Visibility,
+}
+
+impl<'a> ChainView<'a> {
+    /// Creates a view of `chain` which contains the blocks seen by a miner
+    /// with the given [`Visibility`].
+    pub fn new(chain: &'a dyn ChainQuery, visibility: &'a Visibility) -> Self {
+        Self { chain, visibility }
+    }
+
+    /// Creates a view of every block in `chain`. Useful for calling
+    /// [`Miner::get_action`](crate::miner::Miner::get_action) outside of a
+    /// simulation.
+    pub fn full(chain: &'a dyn ChainQuery) -> Self {
+        Self::new(chain, &FULL)
+    }
+
+    /// Returns the chain seen through this view.
+    pub fn chain(&self) -> &'a dyn ChainQuery {
+        self.chain
+    }
+
+    /// Returns the [`Visibility`] of the miner which owns this view.
+    pub fn visibility(&self) -> &'a Visibility {
+        self.visibility
+    }
+}
+
+impl<'a> Deref for ChainView<'a> {
+    type Target = dyn ChainQuery + 'a;
+
+    fn deref(&self) -> &Self::Target {
+        self.chain
+    }
+}
+
+impl ChainQuery for ChainView<'_> {
+    fn genesis(&self) -> BlockId {
+        self.chain.genesis()
+    }
+
+    fn get(&self, id: BlockId) -> Option<&BlockData> {
+        self.chain.get(id)
+    }
+
+    fn at_height(&self, height: usize) -> Option<&[BlockId]> {
+        self.chain.at_height(height)
+    }
+
+    fn max_height(&self) -> usize {
+        self.chain.max_height()
+    }
+
+    fn num_blocks(&self) -> usize {
+        self.chain.num_blocks()
+    }
+
+    fn tip(&self) -> &[BlockId] {
+        self.chain.tip()
+    }
+
+    fn ancestors_of(&self, id: BlockId) -> Ancestors<'_> {
+        self.chain.ancestors_of(id)
+    }
+
+    fn contains(&self, id: BlockId) -> bool {
+        self.chain.contains(id)
+    }
+
+    fn get_parent(&self, id: BlockId) -> Option<BlockId> {
+        self.chain.get_parent(id)
+    }
+
+    fn height(&self, id: BlockId) -> Option<usize> {
+        self.chain.height(id)
+    }
+
+    fn common_ancestor(&self, a: BlockId, b: BlockId) -> Option<BlockId> {
+        self.chain.common_ancestor(a, b)
+    }
+
+    fn weight(&self, id: BlockId) -> Option<usize> {
+        self.chain.weight(id)
+    }
+
+    fn last_checkpoint(&self) -> BlockId {
+        self.chain.last_checkpoint()
+    }
+
+    fn longest_chain(&self) -> Ancestors<'_> {
+        self.chain.longest_chain()
+    }
+}
+
+impl Index<BlockId> for ChainView<'_> {
+    type Output = BlockData;
+
+    fn index(&self, index: BlockId) -> &Self::Output {
+        &self.chain[index]
+    }
+}
+
+impl Index<&BlockId> for ChainView<'_> {
+    type Output = BlockData;
+
+    fn index(&self, index: &BlockId) -> &Self::Output {
+        &self.chain[index]
+    }
+}
+
+/// Blockchain seen by a miner without [`Visibility::Full`].
+#[derive(Debug)]
+struct RestrictedView {
+    visibility: Visibility,
+    chain: Blockchain,
+    /// Blocks published by other miners, with the round in which they become
+    /// visible, in order of publication.
+    queue: VecDeque<(usize, Block)>,
+}
+
+impl RestrictedView {
+    /// Publishes `block` to this view if its parent is visible.
+    fn deliver(&mut self, block: Block) {
+        let parent_visible = block
+            .parent_id
+            .is_some_and(|parent| self.chain.contains(parent));
+        if parent_visible && !self.chain.contains(block.id) {
+            // Views do not enforce checkpoints, and the block was valid on
+            // the simulation's blockchain
+            self.chain
+                .publish(block)
+                .expect("visible blocks can be published to a view");
+        }
+    }
+}
+
+/// Maintains the blockchain seen by each miner without [`Visibility::Full`]
+/// during a simulation.
+#[derive(Debug)]
+pub(crate) struct ViewTracker {
+    views: HashMap<MinerId, RestrictedView>,
+}
+
+impl ViewTracker {
+    /// Creates views of `chain` for each miner with a restricted visibility.
+    pub(crate) fn new(
+        chain: &Blockchain,
+        visibility: &BTreeMap<MinerId, Visibility>,
+    ) -> Self {
+        let views = visibility
+            .iter()
+            .filter(|(_, visibility)| **visibility != Visibility::Full)
+            .map(|(&miner_id, visibility)| {
+                // Views follow the checkpoints of `chain` rather than
+                // finalizing blocks themselves
+                let mut view_chain = chain.clone();
+                view_chain.follow_checkpoint(chain.last_checkpoint());
+                let view = RestrictedView {
+                    visibility: visibility.clone(),
+                    chain: view_chain,
+                    queue: VecDeque::new(),
+                };
+                (miner_id, view)
+            })
+            .collect();
+
+        Self { views }
+    }
+
+    /// Returns the view of the miner with ID `miner_id` in `round`, where
+    /// `chain` is the simulation's blockchain.
+    pub(crate) fn view<'a>(
+        &'a mut self,
+        miner_id: MinerId,
+        round: usize,
+        chain: &'a Blockchain,
+    ) -> ChainView<'a> {
+        let Some(view) = self.views.get_mut(&miner_id) else {
+            return ChainView::full(chain);
+        };
+
+        while view
+            .queue
+            .front()
+            .is_some_and(|(visible, _)| *visible <= round)
+        {
+            let (_, block) = view.queue.pop_front().unwrap();
+            view.deliver(block);
+        }
+        let checkpoint = chain.last_checkpoint();
+        if view.chain.contains(checkpoint) {
+            view.chain.follow_checkpoint(checkpoint);
+        }
+
+        ChainView::new(&view.chain, &view.visibility)
+    }
+
+    /// Routes `block`, published to the simulation's blockchain in `round`,
+    /// to each restricted view. Must be called after every block is
+    /// published.
+    pub(crate) fn publish(&mut self, block: &Block, round: usize) {
+        for (&miner_id, view) in self.views.iter_mut() {
+            if block.miner_id == miner_id {
+                view.deliver(block.clone());
+                continue;
+            }
+
+            match &view.visibility {
+                Visibility::Full => view.deliver(block.clone()),
+                Visibility::Delayed(delay) => {
+                    view.queue.push_back((
+                        round.saturating_add(*delay),
+                        block.clone(),
+                    ));
+                }
+                Visibility::Eclipsed(visible) => {
+                    if visible.contains(&block.miner_id) {
+                        view.deliver(block.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        ops::Index,
+    };
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{ChainView, ViewTracker, Visibility};
+    use crate::{
+        blockchain::{
+            Ancestors, Block, BlockData, BlockId, Blockchain, ChainQuery,
+        },
+        miner::{honest::Honest, Action, Miner, MinerId},
+        tie_breaker::TieBreaker,
+    };
+
+    /// Blockchain whose tip is chosen by GHOST, which descends from genesis
+    /// into the child with the most descendants.
+    #[derive(Debug)]
+    struct Ghost {
+        chain: Blockchain,
+        tip: Vec<BlockId>,
+        weights: HashMap<BlockId, usize>,
+    }
+
+    impl Ghost {
+        fn new(chain: Blockchain) -> Self {
+            let mut weights = HashMap::new();
+            for height in (0..=chain.max_height()).rev() {
+                for &id in chain.at_height(height).unwrap() {
+                    let weight = *weights.entry(id).or_insert(1);
+                    if let Some(parent) = chain.get_parent(id) {
+                        *weights.entry(parent).or_insert(1) += weight;
+                    }
+                }
+            }
+
+            let mut head = chain.genesis();
+            while let Some(&child) = chain
+                .at_height(chain[head].height + 1)
+                .unwrap_or_default()
+                .iter()
+                .filter(|&&id| chain.get_parent(id) == Some(head))
+                .max_by_key(|&id| (weights[id], std::cmp::Reverse(*id)))
+            {
+                head = child;
+            }
+
+            Self {
+                chain,
+                tip: vec![head],
+                weights,
+            }
+        }
+    }
+
+    impl ChainQuery for Ghost {
+        fn genesis(&self) -> BlockId {
+            self.chain.genesis()
+        }
+
+        fn get(&self, id: BlockId) -> Option<&BlockData> {
+            self.chain.get(id)
+        }
+
+        fn at_height(&self, height: usize) -> Option<&[BlockId]> {
+            self.chain.at_height(height)
+        }
+
+        fn max_height(&self) -> usize {
+            self.chain.max_height()
+        }
+
+        fn num_blocks(&self) -> usize {
+            self.chain.num_blocks()
+        }
+
+        fn tip(&self) -> &[BlockId] {
+            &self.tip
+        }
+
+        fn ancestors_of(&self, id: BlockId) -> Ancestors<'_> {
+            Ancestors::new(self, id)
+        }
+
+        fn weight(&self, id: BlockId) -> Option<usize> {
+            self.weights.get(&id).copied()
+        }
+    }
+
+    impl Index<BlockId> for Ghost {
+        type Output = BlockData;
+
+        fn index(&self, index: BlockId) -> &Self::Output {
+            &self.chain[index]
+        }
+    }
+
+    impl Index<&BlockId> for Ghost {
+        type Output = BlockData;
+
+        fn index(&self, index: &BlockId) -> &Self::Output {
+            &self.chain[index]
+        }
+    }
+
+    #[test]
+    fn strategies_follow_chain_query_tip() {
+        // Block 3 extends block 1 to form the longest chain, but blocks 4 and
+        // 5 make the subtree of block 2 heavier
+        let mut chain = Blockchain::new();
+        for (id, parent) in [(1, 0), (2, 0), (3, 1), (4, 2), (5, 2)] {
+            let block = Block {
+                id: BlockId(id),
+                parent_id: Some(BlockId(parent)),
+                miner_id: MinerId(2),
+                txns: vec![],
+            };
+            chain.publish(block).unwrap();
+        }
+
+        let parent_of_next = |chain: &dyn ChainQuery| {
+            let mut honest = Honest::new();
+            honest.set_id(MinerId(1));
+            match honest.get_action(&ChainView::full(chain), Some(BlockId(6))) {
+                Action::Publish(block) => block.parent_id.unwrap(),
+                action => panic!("unexpected action {action:?}"),
+            }
+        };
+
+        assert_eq!(parent_of_next(&chain), BlockId(3));
+
+        let ghost = Ghost::new(chain);
+        assert_eq!(ghost.weight(BlockId(2)), Some(3));
+        assert_eq!(ghost.weight(BlockId(1)), Some(2));
+        assert_eq!(parent_of_next(&ghost), BlockId(4));
+
+        let ancestors: Vec<_> = ghost.longest_chain().collect();
+        assert_eq!(ancestors, vec![BlockId(4), BlockId(2), BlockId(0)]);
+    }
+
+    #[test]
+    fn restricted_views() {
+        let mut chain = Blockchain::new();
+        let visibility = BTreeMap::from([
+            (MinerId(1), Visibility::Delayed(2)),
+            (MinerId(2), Visibility::Eclipsed(vec![MinerId(3)])),
+            (MinerId(4), Visibility::Full),
+        ]);
+        let mut views = ViewTracker::new(&chain, &visibility);
+
+        // Miner 3 publishes block 1, miner 4 extends it with block 2, and
+        // miner 2 publishes block 3 on genesis
+        for (id, parent, miner) in [(1, 0, 3), (2, 1, 4), (3, 0, 2)] {
+            let block = Block {
+                id: BlockId(id),
+                parent_id: Some(BlockId(parent)),
+                miner_id: MinerId(miner),
+                txns: vec![],
+            };
+            views.publish(&block, id);
+            chain.publish(block).unwrap();
+        }
+
+        let seen = |views: &mut ViewTracker, miner, round| {
+            let view = views.view(MinerId(miner), round, &chain);
+            (0..=3)
+                .filter(|&id| view.contains(BlockId(id)))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(seen(&mut views, 4, 3), vec![0, 1, 2, 3]);
+        assert_eq!(seen(&mut views, 2, 3), vec![0, 1, 3]);
+        assert_eq!(seen(&mut views, 1, 3), vec![0, 1]);
+        assert_eq!(seen(&mut views, 1, 5), vec![0, 1, 2, 3]);
+        assert!(!views.views.contains_key(&MinerId(4)));
+    }
+
+    #[test]
+    fn views_follow_checkpoints() {
+        let mut chain = Blockchain::new();
+        chain.finalize_every(Some(1));
+        let visibility = BTreeMap::from([(MinerId(1), Visibility::Delayed(2))]);
+        let mut views = ViewTracker::new(&chain, &visibility);
+
+        for id in 1..=2 {
+            let block = Block {
+                id: BlockId(id),
+                parent_id: Some(BlockId(id - 1)),
+                miner_id: MinerId(2),
+                txns: vec![],
+            };
+            views.publish(&block, id);
+            chain.publish(block).unwrap();
+        }
+        assert_eq!(chain.last_checkpoint(), BlockId(1));
+
+        // The checkpoint is followed once it is visible, even though the view
+        // would not have finalized it yet
+        let view = views.view(MinerId(1), 2, &chain);
+        assert_eq!(view.last_checkpoint(), Blockchain::GENESIS_ID);
+        let view = views.view(MinerId(1), 3, &chain);
+        assert_eq!(view.last_checkpoint(), BlockId(1));
+        assert_eq!(views.views[&MinerId(1)].chain.checkpoint_interval(), None);
+    }
+
+    #[test]
+    fn ties_follow_first_seen_order() {
+        let mut chain = Blockchain::new();
+        let visibility = BTreeMap::from([(MinerId(1), Visibility::Delayed(2))]);
+        let mut views = ViewTracker::new(&chain, &visibility);
+
+        // Miner 2 publishes block 1 before miner 1 publishes block 2, but
+        // miner 1 sees its own block first
+        for (id, miner) in [(1, 2), (2, 1)] {
+            let block = Block {
+                id: BlockId(id),
+                parent_id: Some(chain.genesis()),
+                miner_id: MinerId(miner),
+                txns: vec![],
+            };
+            views.publish(&block, id);
+            chain.publish(block).unwrap();
+        }
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let tie_breaker = TieBreaker::EarliestPublished;
+        let view = views.view(MinerId(1), 3, &chain);
+
+        assert_eq!(view.tip(), &[BlockId(2), BlockId(1)]);
+        assert_eq!(tie_breaker.choose_with(&mut rng, &view), BlockId(2));
+        assert_eq!(tie_breaker.choose_with(&mut rng, &chain), BlockId(1));
+    }
+}
@@ -7,11 +7,9 @@ const GAMMA: f64 = 0.40;
 
 fn main() -> Result<()> {
     let sim = SimulationBuilder::new()
-        .add_miner(Honest::with_tie_breaker(TieBreaker::FavorMinerProb(
-            MinerId::from(2),
-            GAMMA,
-        )))
+        .add_miner(Honest::new())
         .add_miner(NDeficitEager::new(1))
+        .gamma(GAMMA)
         .rounds(100000)
         .miner_power(MinerId::from(2), 0.45)
         .repeat_all(20)
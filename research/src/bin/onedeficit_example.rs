@@ -5,12 +5,10 @@ const GAMMA: f64 = 0.5;
 
 fn main() -> Result<()> {
     let sim = SimulationBuilder::new()
-        .add_miner(Honest::with_tie_breaker(TieBreaker::FavorMinerProb(
-            MinerId::from(2),
-            GAMMA,
-        )))
+        .add_miner(Honest::new())
         .add_miner(NDeficit::new(1))
         .miner_power_iter(MinerId::from(2), (0..=50).percent())
+        .gamma(GAMMA)
         .rounds(10000)
         .repeat_all(200)
         .build()?;